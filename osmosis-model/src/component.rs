@@ -4,6 +4,8 @@
 
 use vstd::prelude::*;
 
+use crate::kind::*;
+
 verus!
 {
 
@@ -23,33 +25,64 @@ impl ProtectionDomain {
     }
 }
 
-/// Resource
-pub ghost struct Resource {
-    pub rtype: ResourceType,
+/// Stable identifier of a Resource, independent of its attributes
+pub type ResourceId = nat;
+
+/// Resource attributes
+pub ghost struct Resource<K: ResourceKind> {
+    pub rtype: ResourceType<K>,
     pub val: nat,
+    pub size: nat,
+    pub align: nat,
 }
 
-impl Resource {
+impl<K: ResourceKind> Resource<K> {
     /// Obtains the type of the Resource
-    pub open spec fn rtype(&self) -> ResourceType {
+    pub open spec fn rtype(&self) -> ResourceType<K> {
         self.rtype
     }
 
-    /// Obtains the identifying value of the Resource
+    /// Obtains the identifying value of the Resource, the first of the vals it covers
     pub open spec fn val(&self) -> nat {
         self.val
     }
+
+    /// Obtains the number of consecutive vals covered by the Resource
+    pub open spec fn size(&self) -> nat {
+        self.size
+    }
+
+    /// Obtains the alignment of the first val covered by the Resource
+    pub open spec fn align(&self) -> nat {
+        self.align
+    }
+
+    /// Obtains the vals covered by the Resource
+    pub open spec fn range(&self) -> Set<nat> {
+        Set::new(|v: nat| self.val <= v < self.val + self.size)
+    }
+
+    pub open spec fn well_formed(&self) -> bool {
+        // A Resource covers at least one val
+        &&& self.size() > 0
+        // The first val must be aligned
+        &&& self.align() > 0
+        &&& self.val() % self.align() == 0
+    }
 }
 
+/// Stable identifier of a ResourceSpace, independent of the vals it manages
+pub type SpaceId = nat;
+
 /// Resource Space
-pub ghost struct ResourceSpace {
-    pub rtype: ResourceType,
+pub ghost struct ResourceSpace<K: ResourceKind> {
+    pub rtype: ResourceType<K>,
     pub vals: Set<nat>,
 }
 
-impl ResourceSpace {
+impl<K: ResourceKind> ResourceSpace<K> {
     /// Obtains the type of the ResourceSpace
-    pub open spec fn rtype(&self) -> ResourceType {
+    pub open spec fn rtype(&self) -> ResourceType<K> {
         self.rtype
     }
 
@@ -60,30 +93,80 @@ impl ResourceSpace {
 }
 
 /// Resource Type
-pub ghost enum ResourceType {
-    Virtual(nat),
-    Physical(nat),
+///
+/// Virtual and Physical Resources carry a kind of the [K] declaration, which says which of them
+/// exist physically, which may be mapped onto which, and which are delivered to a handler.
+///
+/// Time stays outside the declaration: it is neither mapped nor delivered but admitted against
+/// the scheduling period of its core, and its type names that core so that the periods of
+/// distinct cores are told apart.
+pub ghost enum ResourceType<K: ResourceKind> {
+    Virtual(K),
+    Physical(K),
+    /// Time on the identified core, whose vals are the slots of that core's scheduling period
+    Time(nat),
+}
+
+impl<K: ResourceKind> ResourceType<K> {
+    /// Whether the type is declared: only kinds which exist physically have physical Resources
+    pub open spec fn declared(self) -> bool {
+        self is Physical ==> self->Physical_0.physical()
+    }
+
+    /// Whether Resources of this type are delivered to a handler, as declared by their kind
+    pub open spec fn delivered(self) -> bool {
+        self is Physical && self->Physical_0.delivered()
+    }
+
+    /// Whether a node of this type may be mapped onto a node of type [other], as declared by
+    /// their kinds. Physical nodes never map onto virtual ones, and time is never mapped.
+    pub open spec fn may_map_onto(self, other: ResourceType<K>) -> bool {
+        match (self, other) {
+            (ResourceType::Virtual(k), ResourceType::Virtual(l)) => k.maps_onto(l),
+            (ResourceType::Virtual(k), ResourceType::Physical(l)) => k.maps_onto(l),
+            (ResourceType::Physical(k), ResourceType::Physical(l)) => k.maps_onto(l),
+            _ => false,
+        }
+    }
+
+    /// Whether a Resource of this type can be retyped into the backing of a ResourceSpace of
+    /// type [other]. Only physical Resources back the metadata of virtual spaces, such as page
+    /// tables or capability tables, and only those the space may be mapped onto.
+    pub open spec fn can_retype_into(self, other: ResourceType<K>) -> bool {
+        self is Physical && other is Virtual && other.may_map_onto(self)
+    }
 }
 
 pub ghost enum ResourceLike {
-    Resource { res: Resource },
-    Space { space: ResourceSpace },
+    Resource { res: ResourceId },
+    Space { space: SpaceId },
 }
 
 impl ResourceLike {
-    /// Obtains the type of the ResourceLike
-    pub open spec fn rtype(&self) -> ResourceType {
+    /// Obtains the type of the ResourceLike, looking up resources and spaces by their id
+    pub open spec fn rtype<K: ResourceKind>(&self, resources: Map<ResourceId, Resource<K>>, spaces: Map<SpaceId, ResourceSpace<K>>) -> ResourceType<K> {
         match self {
-            ResourceLike::Resource { res } => res.rtype(),
-            ResourceLike::Space { space } => space.rtype(),
+            ResourceLike::Resource { res } => resources[*res].rtype(),
+            ResourceLike::Space { space } => spaces[*space].rtype(),
         }
     }
 }
 
+/// Access right conveyed by a HoldEdge
+pub ghost enum Right {
+    Read,
+    Write,
+    Execute,
+    Grant,
+    Map,
+    Destroy,
+}
+
 /// Hold edge
 pub ghost struct HoldEdge {
     pub src: ProtectionDomain,
     pub dst: ResourceLike,
+    pub rights: Set<Right>,
 }
 
 impl HoldEdge {
@@ -94,59 +177,263 @@ impl HoldEdge {
     pub open spec fn dst(&self) -> ResourceLike {
         self.dst
     }
+
+    /// Obtains the rights the src ProtectionDomain holds the dst with
+    pub open spec fn rights(&self) -> Set<Right> {
+        self.rights
+    }
+}
+
+/// Terms under which a hold edge is lent: the [lender] ProtectionDomain may reclaim it once the
+/// [expiry] epoch is reached
+pub ghost struct Lease {
+    pub lender: ProtectionDomain,
+    pub expiry: nat,
+}
+
+impl Lease {
+    pub open spec fn lender(&self) -> ProtectionDomain {
+        self.lender
+    }
+
+    /// Obtains the epoch from which the lease may be reclaimed
+    pub open spec fn expiry(&self) -> nat {
+        self.expiry
+    }
+}
+
+/// Cacheability of a mapping
+pub ghost enum CacheAttribute {
+    WriteBack,
+    WriteThrough,
+    WriteCombining,
+    Uncached,
+}
+
+/// Translation of the vals of a mapping's src onto the vals of its dst
+pub ghost enum Translation {
+    Offset { offset: int },
+    Function { f: spec_fn(nat) -> nat },
+}
+
+impl Translation {
+    /// The translation which maps every val onto itself
+    pub open spec fn identity() -> Translation {
+        Translation::Offset { offset: 0 }
+    }
+
+    /// Translates a val of the src of a mapping into a val of its dst
+    pub open spec fn apply(self, v: nat) -> int {
+        match (self) {
+            Translation::Offset { offset } => v + offset,
+            Translation::Function { f } => f(v) as int,
+        }
+    }
+}
+
+/// Attributes of a mapping
+pub ghost struct MapAttributes {
+    /// The access permitted through the mapping
+    pub perms: Set<Right>,
+    /// The cacheability of accesses through the mapping
+    pub cache: CacheAttribute,
+    /// How vals of the src are translated onto vals of the dst
+    pub translation: Translation,
+    /// Whether the dst is shared copy-on-write, and gets copied before it is written through the
+    /// mapping
+    pub cow: bool,
+    /// Whether the vals are only backed once first touched through the mapping, which is then
+    /// narrowed down to the vals not touched yet
+    pub lazy: bool,
 }
 
 /// Map edge
 pub ghost enum MapEdge {
-    SpaceBacking { sb_src: ResourceSpace, sb_dst: Resource },
-    SpaceMap { sm_src: ResourceSpace, sm_dst: ResourceSpace },
-    ResourceMap { rm_src: Resource, rm_dst: Resource },
+    SpaceBacking { sb_src: SpaceId, sb_dst: ResourceId },
+    /// Translates the [sm_vals] part of the vals of its src, so that several SpaceMaps may fan
+    /// out of one space, such as device memory embedded in physical memory
+    SpaceMap { sm_src: SpaceId, sm_dst: SpaceId, sm_vals: Set<nat>, sm_attrs: MapAttributes },
+    ResourceMap { rm_src: ResourceId, rm_dst: ResourceId, rm_attrs: MapAttributes },
+    /// Delivers the interrupt line [dl_src] to the ProtectionDomain [dl_dst] which handles it.
+    /// The handler is not a node, so a delivery has no dst node and the node-based map helpers
+    /// leave it out
+    Delivery { dl_src: ResourceId, dl_dst: ProtectionDomain },
 }
 
 impl MapEdge {
-    pub open spec fn well_formed(self) -> bool {
-        //  Physical src can't map to a Virtual dst
+    /// Obtains the node which is being mapped
+    pub open spec fn src_node(self) -> ResourceLike {
+        match (self) {
+            MapEdge::SpaceBacking { sb_src, .. } => ResourceLike::Space { space: sb_src },
+            MapEdge::SpaceMap { sm_src, .. } => ResourceLike::Space { space: sm_src },
+            MapEdge::ResourceMap { rm_src, .. } => ResourceLike::Resource { res: rm_src },
+            MapEdge::Delivery { dl_src, .. } => ResourceLike::Resource { res: dl_src },
+        }
+    }
+
+    /// Obtains the node which is being mapped onto, a delivery has none
+    pub open spec fn dst_node(self) -> ResourceLike
+        recommends !(self is Delivery)
+    {
         match (self) {
-            MapEdge::SpaceBacking { sb_src, sb_dst } => sb_src.rtype() is Physical ==> sb_dst.rtype() is Physical,
-            MapEdge::SpaceMap { sm_src, sm_dst } => sm_src.rtype() is Physical ==> sm_dst.rtype() is Physical,
-            MapEdge::ResourceMap { rm_src, rm_dst } => rm_src.rtype() is Physical ==> rm_dst.rtype() is Physical,
+            MapEdge::SpaceBacking { sb_dst, .. } => ResourceLike::Resource { res: sb_dst },
+            MapEdge::SpaceMap { sm_dst, .. } => ResourceLike::Space { space: sm_dst },
+            MapEdge::ResourceMap { rm_dst, .. } => ResourceLike::Resource { res: rm_dst },
+            MapEdge::Delivery { .. } => arbitrary(),
+        }
+    }
+
+    /// Whether the mapping maps a node onto [n]
+    pub open spec fn maps_onto(self, n: ResourceLike) -> bool {
+        !(self is Delivery) && self.dst_node() == n
+    }
+
+    /// Whether the mapping maps the node [n] or maps a node onto it, a delivery touches none
+    pub open spec fn touches(self, n: ResourceLike) -> bool {
+        !(self is Delivery) && (self.src_node() == n || self.dst_node() == n)
+    }
+
+    /// Obtains the access permitted through the mapping, a backing or a delivery permits none
+    /// by itself
+    pub open spec fn perms(self) -> Set<Right> {
+        match (self) {
+            MapEdge::SpaceBacking { .. } => Set::empty(),
+            MapEdge::SpaceMap { sm_attrs, .. } => sm_attrs.perms,
+            MapEdge::ResourceMap { rm_attrs, .. } => rm_attrs.perms,
+            MapEdge::Delivery { .. } => Set::empty(),
+        }
+    }
+
+    /// Whether the mapping translates the val [v] of the space [s]
+    pub open spec fn covers(self, s: SpaceId, v: nat) -> bool {
+        self is SpaceMap && self->sm_src == s && self->sm_vals.contains(v)
+    }
+
+    /// The SpaceMap narrowed down to all of its vals but [v]
+    pub open spec fn without(self, v: nat) -> MapEdge {
+        MapEdge::SpaceMap { sm_src: self->sm_src, sm_dst: self->sm_dst, sm_vals: self->sm_vals.remove(v), sm_attrs: self->sm_attrs }
+    }
+
+    /// The SpaceMap narrowed down to all of its vals but those of [vals]
+    pub open spec fn without_vals(self, vals: Set<nat>) -> MapEdge {
+        MapEdge::SpaceMap { sm_src: self->sm_src, sm_dst: self->sm_dst, sm_vals: self->sm_vals.difference(vals), sm_attrs: self->sm_attrs }
+    }
+
+    /// The eager, private SpaceMap which translates only the val [v] of the src of the SpaceMap
+    /// onto the val [w] of [dst], with the same access and cacheability
+    pub open spec fn single(self, v: nat, dst: SpaceId, w: nat) -> MapEdge {
+        MapEdge::SpaceMap {
+            sm_src: self->sm_src,
+            sm_dst: dst,
+            sm_vals: Set::empty().insert(v),
+            sm_attrs: MapAttributes {
+                perms: self->sm_attrs.perms,
+                cache: self->sm_attrs.cache,
+                translation: Translation::Offset { offset: w - v },
+                cow: false,
+                lazy: false,
+            },
+        }
+    }
+
+    pub open spec fn well_formed<K: ResourceKind>(self, resources: Map<ResourceId, Resource<K>>, spaces: Map<SpaceId, ResourceSpace<K>>) -> bool {
+        // The src must be of a type which may be mapped onto the type of the dst
+        match (self) {
+            MapEdge::SpaceBacking { sb_src, sb_dst } => spaces[sb_src].rtype().may_map_onto(resources[sb_dst].rtype()),
+            MapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } => {
+                &&& spaces[sm_src].rtype().may_map_onto(spaces[sm_dst].rtype())
+                // Only vals of the src are translated, and each must translate into a val of the dst
+                &&& sm_vals.subset_of(spaces[sm_src].vals())
+                &&& forall |v: nat| #[trigger] sm_vals.contains(v) ==> {
+                    &&& 0 <= sm_attrs.translation.apply(v)
+                    &&& spaces[sm_dst].vals().contains(sm_attrs.translation.apply(v) as nat)
+                }
+            },
+            MapEdge::ResourceMap { rm_src, rm_dst, rm_attrs } => {
+                &&& resources[rm_src].rtype().may_map_onto(resources[rm_dst].rtype())
+                // The val of the src must translate into the val of the dst
+                &&& rm_attrs.translation.apply(resources[rm_src].val()) == resources[rm_dst].val() as int
+            },
+            // Only the Resources of a kind declared to be delivered are
+            MapEdge::Delivery { dl_src, .. } => resources[dl_src].rtype().delivered(),
         }
     }
 }
 
+/// A device, modelled as a ProtectionDomain whose accesses are translated by an IOMMU
+pub ghost struct Device {
+    /// The ProtectionDomain which drives the device
+    pub driver: ProtectionDomain,
+    /// The ResourceSpace through which every access of the device goes
+    pub iommu: SpaceId,
+}
+
+impl Device {
+    pub open spec fn driver(&self) -> ProtectionDomain {
+        self.driver
+    }
+
+    pub open spec fn iommu(&self) -> SpaceId {
+        self.iommu
+    }
+}
+
 /// Subset edge
 pub ghost struct SubsetEdge {
-    pub src: Resource,
-    pub dst: ResourceSpace,
+    pub src: ResourceId,
+    pub dst: SpaceId,
 }
 
 impl SubsetEdge {
-    pub open spec fn src(&self) -> Resource {
+    pub open spec fn src(&self) -> ResourceId {
         self.src
     }
 
-    pub open spec fn dst(&self) -> ResourceSpace {
+    pub open spec fn dst(&self) -> SpaceId {
         self.dst
     }
 
-    pub open spec fn well_formed(self) -> bool {
-        // The val of the src Resource must be managed by the space that it subsets
-        &&& self.dst().vals().contains(
-            self.src().val()
-        )
+    pub open spec fn well_formed<K: ResourceKind>(self, resources: Map<ResourceId, Resource<K>>, spaces: Map<SpaceId, ResourceSpace<K>>) -> bool {
+        // The whole range of the src Resource must be managed by the space that it subsets
+        &&& resources[self.src()].range().subset_of(spaces[self.dst()].vals())
         // The src and dst must share a type
-        &&& self.src().rtype() == self.dst().rtype()
+        &&& resources[self.src()].rtype() == spaces[self.dst()].rtype()
+    }
+}
+
+/// Partition edge
+pub ghost struct PartitionEdge {
+    pub src: SpaceId,
+    pub dst: SpaceId,
+}
+
+impl PartitionEdge {
+    /// Obtains the child ResourceSpace which was carved out of dst
+    pub open spec fn src(&self) -> SpaceId {
+        self.src
+    }
+
+    /// Obtains the parent ResourceSpace from which src was carved
+    pub open spec fn dst(&self) -> SpaceId {
+        self.dst
+    }
+
+    pub open spec fn well_formed<K: ResourceKind>(self, spaces: Map<SpaceId, ResourceSpace<K>>) -> bool {
+        // The vals of the src ResourceSpace must be managed by the space it was carved from
+        &&& spaces[self.src()].vals().subset_of(spaces[self.dst()].vals())
+        // The src and dst must share a type
+        &&& spaces[self.src()].rtype() == spaces[self.dst()].rtype()
     }
 }
 
 /// Request edge
-pub ghost struct RequestEdge {
+pub ghost struct RequestEdge<K: ResourceKind> {
     pub src: ProtectionDomain,
     pub dst: ProtectionDomain,
-    pub rtype: ResourceType,
+    pub rtype: ResourceType<K>,
 }
 
-impl RequestEdge {
+impl<K: ResourceKind> RequestEdge<K> {
     pub open spec fn src(&self) -> ProtectionDomain {
         self.src
     }
@@ -155,9 +442,76 @@ impl RequestEdge {
         self.dst
     }
 
-    pub open spec fn rtype(&self) -> ResourceType {
+    pub open spec fn rtype(&self) -> ResourceType<K> {
+        self.rtype
+    }
+}
+
+/// Stable identifier of a Request
+pub type RequestId = nat;
+
+/// Where a Request is in its lifecycle
+pub ghost enum RequestStatus {
+    /// Issued by the client, and not answered by the server yet, which handed the [handed]
+    /// Resources over to the client so far
+    Pending { handed: Set<ResourceId> },
+    /// Fulfilled by the server, which handed the [granted] Resources over to the client
+    Granted { granted: Set<ResourceId> },
+    /// Refused by the server
+    Denied,
+}
+
+/// A request by the [client] ProtectionDomain for [count] Resources of type [rtype] from the
+/// [server] ProtectionDomain
+pub ghost struct Request<K: ResourceKind> {
+    pub client: ProtectionDomain,
+    pub server: ProtectionDomain,
+    pub rtype: ResourceType<K>,
+    pub count: nat,
+    pub status: RequestStatus,
+}
+
+impl<K: ResourceKind> Request<K> {
+    pub open spec fn client(&self) -> ProtectionDomain {
+        self.client
+    }
+
+    pub open spec fn server(&self) -> ProtectionDomain {
+        self.server
+    }
+
+    pub open spec fn rtype(&self) -> ResourceType<K> {
         self.rtype
     }
+
+    /// Obtains the number of Resources which were requested
+    pub open spec fn count(&self) -> nat {
+        self.count
+    }
+
+    pub open spec fn status(&self) -> RequestStatus {
+        self.status
+    }
+
+    /// Obtains the RequestEdge permitting the client to make the Request
+    pub open spec fn edge(&self) -> RequestEdge<K> {
+        RequestEdge { src: self.client, dst: self.server, rtype: self.rtype }
+    }
+
+    /// Obtains the Resources the server handed over to the client for the Request, which a
+    /// denied Request no longer accounts for
+    pub open spec fn granted(&self) -> Set<ResourceId> {
+        match self.status {
+            RequestStatus::Pending { handed } => handed,
+            RequestStatus::Granted { granted } => granted,
+            RequestStatus::Denied => Set::empty(),
+        }
+    }
+
+    /// The Request with its status replaced by [status]
+    pub open spec fn with_status(self, status: RequestStatus) -> Request<K> {
+        Request { client: self.client, server: self.server, rtype: self.rtype, count: self.count, status }
+    }
 }
 
 } // verus!
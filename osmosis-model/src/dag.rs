@@ -7,21 +7,751 @@ use vstd::prelude::*;
 use vstd::set::Set;
 
 use crate::component::*;
-use crate::utils::set_map_finite_preserving;
+use crate::kind::*;
+use crate::utils::{set_map_finite_preserving, set_sum, lemma_set_sum_insert, lemma_set_sum_remove, lemma_set_sum_congruent, lemma_set_sum_subset,
+    lemma_set_sum_disjoint_subsets, lemma_set_sum_union, lemma_set_sum_parts, nat_range, lemma_nat_range_len, nat_interval, lemma_nat_interval_len, lemma_union_finite};
+use vstd::set_lib::lemma_len_subset;
 
 verus!
 {
 
+// Resource accounting:
+
+/// Whether [pd] holds the Resource [r] through any of [holds]
+pub open spec fn holds_resource(holds: Set<HoldEdge>, pd: ProtectionDomain, r: ResourceId) -> bool {
+    exists |he: HoldEdge| #[trigger] holds.contains(he) && he.src() == pd && he.dst() == (ResourceLike::Resource { res: r })
+}
+
+/// The Resources of type [rtype] which [pd] holds
+pub open spec fn held_resources<K: ResourceKind>(resources: Map<ResourceId, Resource<K>>, holds: Set<HoldEdge>, pd: ProtectionDomain, rtype: ResourceType<K>) -> Set<ResourceId> {
+    resources.dom().filter(|r: ResourceId| resources[r].rtype() == rtype && holds_resource(holds, pd, r))
+}
+
+/// The size of each Resource
+pub open spec fn resource_size<K: ResourceKind>(resources: Map<ResourceId, Resource<K>>) -> spec_fn(ResourceId) -> nat {
+    |r: ResourceId| resources[r].size()
+}
+
+/// The total size of the Resources of type [rtype] which [pd] holds. A Resource held by several
+/// ProtectionDomains counts against the quota of each of them.
+pub open spec fn usage<K: ResourceKind>(resources: Map<ResourceId, Resource<K>>, holds: Set<HoldEdge>, pd: ProtectionDomain, rtype: ResourceType<K>) -> nat {
+    set_sum(held_resources(resources, holds, pd, rtype), resource_size(resources))
+}
+
+/// The number of vals managed by each ResourceSpace, which for a time reservation is its budget
+pub open spec fn space_budget<K: ResourceKind>(spaces: Map<SpaceId, ResourceSpace<K>>) -> spec_fn(SpaceId) -> nat {
+    |s: SpaceId| spaces[s].vals().len()
+}
+
+/// Holding a subset of the Resources, at the same sizes, never increases usage
+proof fn lemma_usage_subset<K: ResourceKind>(
+    pre_resources: Map<ResourceId, Resource<K>>, pre_holds: Set<HoldEdge>,
+    post_resources: Map<ResourceId, Resource<K>>, post_holds: Set<HoldEdge>,
+    pd: ProtectionDomain, rtype: ResourceType<K>,
+)
+    requires
+        pre_resources.dom().finite(),
+        held_resources(post_resources, post_holds, pd, rtype).subset_of(held_resources(pre_resources, pre_holds, pd, rtype)),
+        forall |r: ResourceId| #[trigger] held_resources(post_resources, post_holds, pd, rtype).contains(r) ==>
+            post_resources[r].size() == pre_resources[r].size(),
+    ensures
+        usage(post_resources, post_holds, pd, rtype) <= usage(pre_resources, pre_holds, pd, rtype),
+{
+    let post_held = held_resources(post_resources, post_holds, pd, rtype);
+    let pre_held = held_resources(pre_resources, pre_holds, pd, rtype);
+    lemma_set_sum_congruent(post_held, resource_size(post_resources), resource_size(pre_resources));
+    lemma_set_sum_subset(post_held, pre_held, resource_size(pre_resources));
+}
+
+/// Coming to hold one more Resource [r] adds exactly its size to the usage
+proof fn lemma_usage_insert<K: ResourceKind>(
+    pre_resources: Map<ResourceId, Resource<K>>, pre_holds: Set<HoldEdge>,
+    post_resources: Map<ResourceId, Resource<K>>, post_holds: Set<HoldEdge>,
+    pd: ProtectionDomain, rtype: ResourceType<K>, r: ResourceId,
+)
+    requires
+        pre_resources.dom().finite(),
+        held_resources(post_resources, post_holds, pd, rtype) == held_resources(pre_resources, pre_holds, pd, rtype).insert(r),
+        !held_resources(pre_resources, pre_holds, pd, rtype).contains(r),
+        forall |x: ResourceId| #[trigger] held_resources(pre_resources, pre_holds, pd, rtype).contains(x) ==>
+            post_resources[x].size() == pre_resources[x].size(),
+    ensures
+        usage(post_resources, post_holds, pd, rtype) == post_resources[r].size() + usage(pre_resources, pre_holds, pd, rtype),
+{
+    let pre_held = held_resources(pre_resources, pre_holds, pd, rtype);
+    lemma_set_sum_insert(pre_held, r, resource_size(post_resources));
+    lemma_set_sum_congruent(pre_held, resource_size(post_resources), resource_size(pre_resources));
+}
+
+/// If the only node any ProtectionDomain comes to hold is [dst], received by [to], then usage
+/// only grows by the size of [dst], and only for [to] if it did not hold [dst] before
+proof fn lemma_usage_receive_hold<K: ResourceKind>(
+    resources: Map<ResourceId, Resource<K>>, pre_holds: Set<HoldEdge>, post_holds: Set<HoldEdge>,
+    to: ProtectionDomain, dst: ResourceLike, pd: ProtectionDomain, rtype: ResourceType<K>,
+)
+    requires
+        resources.dom().finite(),
+        forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post_holds, p, r) ==>
+            holds_resource(pre_holds, p, r) || (p == to && dst == (ResourceLike::Resource { res: r })),
+    ensures
+        ({
+            let gains = pd == to && dst is Resource && !holds_resource(pre_holds, to, dst->res)
+                && resources.contains_key(dst->res) && resources[dst->res].rtype() == rtype;
+            usage(resources, post_holds, pd, rtype)
+                <= usage(resources, pre_holds, pd, rtype) + if (gains) { resources[dst->res].size() } else { 0 }
+        }),
+{
+    let pre_held = held_resources(resources, pre_holds, pd, rtype);
+    let post_held = held_resources(resources, post_holds, pd, rtype);
+    if (pd == to && dst is Resource && !holds_resource(pre_holds, to, dst->res)
+        && resources.contains_key(dst->res) && resources[dst->res].rtype() == rtype) {
+        assert(post_held.subset_of(pre_held.insert(dst->res)));
+        lemma_set_sum_subset(post_held, pre_held.insert(dst->res), resource_size(resources));
+        lemma_set_sum_insert(pre_held, dst->res, resource_size(resources));
+    } else {
+        assert(post_held.subset_of(pre_held));
+        lemma_set_sum_subset(post_held, pre_held, resource_size(resources));
+    }
+}
+
+/// Whether [pd] holds the ResourceSpace [s] through any of [holds]
+pub open spec fn holds_space(holds: Set<HoldEdge>, pd: ProtectionDomain, s: SpaceId) -> bool {
+    exists |he: HoldEdge| #[trigger] holds.contains(he) && he.src() == pd && he.dst() == (ResourceLike::Space { space: s })
+}
+
+/// Whether the ResourceSpace [s] was carved out of another one
+pub open spec fn carved_out(partitions: Set<PartitionEdge>, s: SpaceId) -> bool {
+    exists |pe: PartitionEdge| #[trigger] partitions.contains(pe) && pe.src() == s
+}
+
+/// The carved out ResourceSpaces of type [rtype] which [pd] holds
+pub open spec fn held_reservations<K: ResourceKind>(
+    spaces: Map<SpaceId, ResourceSpace<K>>, partitions: Set<PartitionEdge>, holds: Set<HoldEdge>,
+    pd: ProtectionDomain, rtype: ResourceType<K>,
+) -> Set<SpaceId> {
+    spaces.dom().filter(|s: SpaceId| spaces[s].rtype() == rtype && carved_out(partitions, s) && holds_space(holds, pd, s))
+}
+
+/// The total budget of the carved out ResourceSpaces of type [rtype] which [pd] holds. A space
+/// split off another reserves vals of its parent, so it counts against the quota of each of its
+/// holders like a Resource of its budget would.
+pub open spec fn reserved<K: ResourceKind>(
+    spaces: Map<SpaceId, ResourceSpace<K>>, partitions: Set<PartitionEdge>, holds: Set<HoldEdge>,
+    pd: ProtectionDomain, rtype: ResourceType<K>,
+) -> nat {
+    set_sum(held_reservations(spaces, partitions, holds, pd, rtype), space_budget(spaces))
+}
+
+/// Holding a subset of the reservations, at the same budgets, never increases what is reserved
+proof fn lemma_reserved_subset<K: ResourceKind>(
+    pre_spaces: Map<SpaceId, ResourceSpace<K>>, pre_partitions: Set<PartitionEdge>, pre_holds: Set<HoldEdge>,
+    post_spaces: Map<SpaceId, ResourceSpace<K>>, post_partitions: Set<PartitionEdge>, post_holds: Set<HoldEdge>,
+    pd: ProtectionDomain, rtype: ResourceType<K>,
+)
+    requires
+        pre_spaces.dom().finite(),
+        held_reservations(post_spaces, post_partitions, post_holds, pd, rtype)
+            .subset_of(held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype)),
+        forall |s: SpaceId| #[trigger] held_reservations(post_spaces, post_partitions, post_holds, pd, rtype).contains(s) ==>
+            post_spaces[s].vals().len() == pre_spaces[s].vals().len(),
+    ensures
+        reserved(post_spaces, post_partitions, post_holds, pd, rtype) <= reserved(pre_spaces, pre_partitions, pre_holds, pd, rtype),
+{
+    let post_held = held_reservations(post_spaces, post_partitions, post_holds, pd, rtype);
+    let pre_held = held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype);
+    lemma_len_subset(pre_held, pre_spaces.dom());
+    lemma_set_sum_congruent(post_held, space_budget(post_spaces), space_budget(pre_spaces));
+    lemma_set_sum_subset(post_held, pre_held, space_budget(pre_spaces));
+}
+
+/// If every reservation [pd] holds after a step was held before at the same budget, except
+/// [changed] when [pd] is [to], then what is reserved only moves by the budgets of [changed]
+proof fn lemma_reserved_change<K: ResourceKind>(
+    pre_spaces: Map<SpaceId, ResourceSpace<K>>, pre_partitions: Set<PartitionEdge>, pre_holds: Set<HoldEdge>,
+    post_spaces: Map<SpaceId, ResourceSpace<K>>, post_partitions: Set<PartitionEdge>, post_holds: Set<HoldEdge>,
+    to: ProtectionDomain, changed: Option<SpaceId>, pd: ProtectionDomain, rtype: ResourceType<K>,
+)
+    requires
+        pre_spaces.dom().finite(),
+        forall |s: SpaceId| #[trigger] held_reservations(post_spaces, post_partitions, post_holds, pd, rtype).contains(s) ==> {
+            ||| held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype).contains(s)
+                && post_spaces[s].vals().len() == pre_spaces[s].vals().len()
+            ||| pd == to && changed == Some(s)
+        },
+    ensures
+        ({
+            let pre_held = held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype);
+            let post_held = held_reservations(post_spaces, post_partitions, post_holds, pd, rtype);
+            reserved(post_spaces, post_partitions, post_holds, pd, rtype)
+                + (if (pd == to && changed is Some && pre_held.contains(changed->Some_0)) { pre_spaces[changed->Some_0].vals().len() } else { 0 })
+                <= reserved(pre_spaces, pre_partitions, pre_holds, pd, rtype)
+                + (if (pd == to && changed is Some && post_held.contains(changed->Some_0)) { post_spaces[changed->Some_0].vals().len() } else { 0 })
+        }),
+{
+    let pre_held = held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype);
+    let post_held = held_reservations(post_spaces, post_partitions, post_holds, pd, rtype);
+    let moved = pd == to && changed is Some;
+    let pre_kept = if (moved) { pre_held.remove(changed->Some_0) } else { pre_held };
+    let post_kept = if (moved) { post_held.remove(changed->Some_0) } else { post_held };
+    lemma_len_subset(pre_held, pre_spaces.dom());
+    assert(post_kept.subset_of(pre_kept));
+    lemma_len_subset(pre_kept, pre_held);
+    lemma_set_sum_congruent(post_kept, space_budget(post_spaces), space_budget(pre_spaces));
+    lemma_set_sum_subset(post_kept, pre_kept, space_budget(pre_spaces));
+    if (moved) {
+        let c = changed->Some_0;
+        if (post_held.contains(c)) {
+            assert(post_held.subset_of(pre_held.insert(c)));
+            lemma_len_subset(post_held, pre_held.insert(c));
+            lemma_set_sum_remove(post_held, c, space_budget(post_spaces));
+        } else {
+            assert(post_kept =~= post_held);
+        }
+        if (pre_held.contains(c)) {
+            lemma_set_sum_remove(pre_held, c, space_budget(pre_spaces));
+        } else {
+            assert(pre_kept =~= pre_held);
+        }
+    }
+}
+
+/// If the only space any ProtectionDomain comes to hold is [dst], received by [to], and the only
+/// carved out space which was not one before at the same budget is [dst], held by nobody before,
+/// then what is reserved only grows by the budget of [dst], and only for [to]
+proof fn lemma_reserved_receive_hold<K: ResourceKind>(
+    pre_spaces: Map<SpaceId, ResourceSpace<K>>, pre_partitions: Set<PartitionEdge>, pre_holds: Set<HoldEdge>,
+    post_spaces: Map<SpaceId, ResourceSpace<K>>, post_partitions: Set<PartitionEdge>, post_holds: Set<HoldEdge>,
+    to: ProtectionDomain, dst: ResourceLike, pd: ProtectionDomain, rtype: ResourceType<K>,
+)
+    requires
+        pre_spaces.dom().finite(),
+        forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post_holds, p, s) ==>
+            holds_space(pre_holds, p, s) || (p == to && dst == (ResourceLike::Space { space: s })),
+        forall |s: SpaceId| #[trigger] post_spaces.contains_key(s) && carved_out(post_partitions, s) ==> {
+            ||| pre_spaces.contains_key(s) && carved_out(pre_partitions, s) && post_spaces[s] == pre_spaces[s]
+            ||| dst == (ResourceLike::Space { space: s }) && forall |p: ProtectionDomain| !#[trigger] holds_space(pre_holds, p, s)
+        },
+    ensures
+        ({
+            let gains = pd == to && dst is Space && !holds_space(pre_holds, to, dst->space)
+                && held_reservations(post_spaces, post_partitions, post_holds, pd, rtype).contains(dst->space);
+            reserved(post_spaces, post_partitions, post_holds, pd, rtype)
+                <= reserved(pre_spaces, pre_partitions, pre_holds, pd, rtype) + if (gains) { post_spaces[dst->space].vals().len() } else { 0 }
+        }),
+{
+    let pre_held = held_reservations(pre_spaces, pre_partitions, pre_holds, pd, rtype);
+    let post_held = held_reservations(post_spaces, post_partitions, post_holds, pd, rtype);
+    let changed = if (dst is Space) { Some(dst->space) } else { None };
+    assert forall |s: SpaceId| #[trigger] post_held.contains(s) implies {
+        ||| pre_held.contains(s) && post_spaces[s].vals().len() == pre_spaces[s].vals().len()
+        ||| pd == to && changed == Some(s)
+    } by {
+        assert(holds_space(post_holds, pd, s));
+        assert(post_spaces.contains_key(s) && carved_out(post_partitions, s));
+    }
+    lemma_reserved_change(pre_spaces, pre_partitions, pre_holds, post_spaces, post_partitions, post_holds, to, changed, pd, rtype);
+    if (pd == to && dst is Space && holds_space(pre_holds, to, dst->space) && post_held.contains(dst->space)) {
+        assert(post_spaces.contains_key(dst->space) && carved_out(post_partitions, dst->space));
+        assert(pre_held.contains(dst->space));
+    }
+}
+
+// Partitions:
+
+/// Whether the ResourceSpace [s] is [a], or was carved out of it through at most [fuel] partition
+/// edges
+pub open spec fn carved_within(partitions: Set<PartitionEdge>, s: SpaceId, a: SpaceId, fuel: nat) -> bool
+    decreases fuel
+{
+    ||| s == a
+    ||| fuel > 0 && exists |pe: PartitionEdge| #[trigger] partitions.contains(pe) && pe.src() == s
+        && carved_within(partitions, pe.dst(), a, (fuel - 1) as nat)
+}
+
+/// Whether the ResourceSpace [s] is [a], or was carved out of it, directly or not
+pub open spec fn carved_from(partitions: Set<PartitionEdge>, s: SpaceId, a: SpaceId) -> bool {
+    exists |fuel: nat| #[trigger] carved_within(partitions, s, a, fuel)
+}
+
+/// A space carved out of another manages a subset of its vals, and is of the same type
+proof fn lemma_carved_within_vals<K: ResourceKind>(spaces: Map<SpaceId, ResourceSpace<K>>, partitions: Set<PartitionEdge>, s: SpaceId, a: SpaceId, fuel: nat)
+    requires
+        forall |pe: PartitionEdge| partitions.contains(pe) ==> #[trigger] pe.well_formed(spaces),
+        carved_within(partitions, s, a, fuel),
+    ensures
+        spaces[s].vals().subset_of(spaces[a].vals()),
+        spaces[s].rtype() == spaces[a].rtype(),
+    decreases fuel
+{
+    if (s != a) {
+        let pe = choose |pe: PartitionEdge| #[trigger] partitions.contains(pe) && pe.src() == s
+            && carved_within(partitions, pe.dst(), a, (fuel - 1) as nat);
+        assert(pe.well_formed(spaces));
+        lemma_carved_within_vals(spaces, partitions, pe.dst(), a, (fuel - 1) as nat);
+    }
+}
+
+/// Adding partition edges keeps every space carved out of what it was carved out of
+proof fn lemma_carved_within_grows(pre: Set<PartitionEdge>, post: Set<PartitionEdge>, s: SpaceId, a: SpaceId, fuel: nat)
+    requires
+        pre.subset_of(post),
+        carved_within(pre, s, a, fuel),
+    ensures
+        carved_within(post, s, a, fuel),
+    decreases fuel
+{
+    if (s != a) {
+        let pe = choose |pe: PartitionEdge| #[trigger] pre.contains(pe) && pe.src() == s
+            && carved_within(pre, pe.dst(), a, (fuel - 1) as nat);
+        lemma_carved_within_grows(pre, post, pe.dst(), a, (fuel - 1) as nat);
+        assert(post.contains(pe));
+    }
+}
+
+/// Removing the partition edges of a space without children keeps every other space carved out
+/// of what it was carved out of
+proof fn lemma_carved_within_kept(pre: Set<PartitionEdge>, post: Set<PartitionEdge>, leaf: SpaceId, s: SpaceId, a: SpaceId, fuel: nat)
+    requires
+        forall |pe: PartitionEdge| #[trigger] pre.contains(pe) && pe.src() != leaf ==> post.contains(pe),
+        forall |pe: PartitionEdge| #[trigger] pre.contains(pe) ==> pe.dst() != leaf,
+        s != leaf,
+        carved_within(pre, s, a, fuel),
+    ensures
+        carved_within(post, s, a, fuel),
+    decreases fuel
+{
+    if (s != a) {
+        let pe = choose |pe: PartitionEdge| #[trigger] pre.contains(pe) && pe.src() == s
+            && carved_within(pre, pe.dst(), a, (fuel - 1) as nat);
+        lemma_carved_within_kept(pre, post, leaf, pe.dst(), a, (fuel - 1) as nat);
+        assert(post.contains(pe));
+    }
+}
+
+/// A space carved out of the dst of [pe] is carved out of its src too
+proof fn lemma_carved_within_extend(partitions: Set<PartitionEdge>, pe: PartitionEdge, a: SpaceId, fuel: nat)
+    requires
+        partitions.contains(pe),
+        carved_within(partitions, pe.dst(), a, fuel),
+    ensures
+        carved_within(partitions, pe.src(), a, fuel + 1),
+{
+    assert(carved_within(partitions, pe.dst(), a, ((fuel + 1) - 1) as nat));
+}
+
+/// A space carved out of [a] which is not [a] itself was carved out of one of its children
+proof fn lemma_carved_within_child(partitions: Set<PartitionEdge>, s: SpaceId, a: SpaceId, fuel: nat) -> (d: SpaceId)
+    requires
+        carved_within(partitions, s, a, fuel),
+        s != a,
+    ensures
+        fuel > 0,
+        partitions.contains(PartitionEdge { src: d, dst: a }),
+        carved_within(partitions, s, d, (fuel - 1) as nat),
+    decreases fuel
+{
+    let pe = choose |pe: PartitionEdge| #[trigger] partitions.contains(pe) && pe.src() == s
+        && carved_within(partitions, pe.dst(), a, (fuel - 1) as nat);
+    if (pe.dst() == a) {
+        assert(pe == (PartitionEdge { src: s, dst: a }));
+        s
+    } else {
+        let d = lemma_carved_within_child(partitions, pe.dst(), a, (fuel - 1) as nat);
+        lemma_carved_within_extend(partitions, pe, d, (fuel - 2) as nat);
+        d
+    }
+}
+
+/// Carving [child] out of [parent] leaves it disjoint from the physical spaces of its type which
+/// neither were carved out of it, nor is it carved out of
+proof fn lemma_split_child_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, parent: SpaceId, child: SpaceId, vals: Set<nat>, x: SpaceId)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(parent),
+        !pre.spaces.contains_key(child),
+        pre.spaces[parent].rtype() is Physical,
+        vals.subset_of(pre.spaces[parent].vals()),
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == parent ==>
+            pre.spaces[pe.src()].vals().disjoint(vals),
+        post.partitions == pre.partitions.insert(PartitionEdge { src: child, dst: parent }),
+        pre.spaces.contains_key(x),
+        pre.spaces[x].rtype() == pre.spaces[parent].rtype(),
+        !carved_from(post.partitions, child, x),
+        !carved_from(post.partitions, x, child),
+    ensures
+        vals.disjoint(pre.spaces[x].vals()),
+        pre.spaces[x].vals().disjoint(vals),
+{
+    let edge = PartitionEdge { src: child, dst: parent };
+    assert(pre.partitions.subset_of(post.partitions));
+    if (x == parent) {
+        assert(carved_within(post.partitions, parent, x, 0));
+        lemma_carved_within_extend(post.partitions, edge, x, 0);
+    } else if (carved_from(pre.partitions, parent, x)) {
+        // x is an ancestor of the parent, and so of the child too
+        let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, parent, x, fuel);
+        lemma_carved_within_grows(pre.partitions, post.partitions, parent, x, fuel);
+        lemma_carved_within_extend(post.partitions, edge, x, fuel);
+    } else if (carved_from(pre.partitions, x, parent)) {
+        // x lies within a sibling of the child
+        let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, x, parent, fuel);
+        let d = lemma_carved_within_child(pre.partitions, x, parent, fuel);
+        lemma_carved_within_vals(pre.spaces, pre.partitions, x, d, (fuel - 1) as nat);
+        assert(pre.spaces[d].vals().disjoint(vals));
+    } else {
+        // x shares no val with the parent, which manages those of the child
+        assert(pre.spaces[x].vals().disjoint(pre.spaces[parent].vals()));
+    }
+}
+
+/// Carving [child] out of [parent] keeps physical spaces of the same type disjoint, unless one
+/// was carved out of the other
+proof fn lemma_split_keeps_spaces_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, parent: SpaceId, child: SpaceId, vals: Set<nat>)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(parent),
+        !pre.spaces.contains_key(child),
+        vals.subset_of(pre.spaces[parent].vals()),
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == parent ==>
+            pre.spaces[pe.src()].vals().disjoint(vals),
+        post.spaces == pre.spaces.insert(child, ResourceSpace { rtype: pre.spaces[parent].rtype(), vals }),
+        post.partitions == pre.partitions.insert(PartitionEdge { src: child, dst: parent }),
+    ensures
+        post.spaces_disjoint(),
+{
+    assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2) && s1 != s2
+        && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+        && !carved_from(post.partitions, s1, s2) && !carved_from(post.partitions, s2, s1)
+        implies post.spaces[s1].vals().disjoint(post.spaces[s2].vals()) by {
+        if (s1 == child) {
+            lemma_split_child_disjoint(pre, post, parent, child, vals, s2);
+        } else if (s2 == child) {
+            lemma_split_child_disjoint(pre, post, parent, child, vals, s1);
+        } else {
+            assert(pre.partitions.subset_of(post.partitions));
+            if (carved_from(pre.partitions, s1, s2)) {
+                let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, s1, s2, fuel);
+                lemma_carved_within_grows(pre.partitions, post.partitions, s1, s2, fuel);
+            }
+            if (carved_from(pre.partitions, s2, s1)) {
+                let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, s2, s1, fuel);
+                lemma_carved_within_grows(pre.partitions, post.partitions, s2, s1, fuel);
+            }
+        }
+    }
+}
+
+/// Resizing [space] to [vals] within the partitioning leaves it disjoint from the physical spaces
+/// of its type which neither were carved out of it, nor is it carved out of
+proof fn lemma_resized_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, space: SpaceId, vals: Set<nat>, x: SpaceId)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(space),
+        pre.spaces[space].rtype() is Physical,
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space ==> {
+            &&& vals.subset_of(pre.spaces[pe.dst()].vals())
+            &&& forall |sib: PartitionEdge| #[trigger] pre.partitions.contains(sib) && sib.dst() == pe.dst() && sib.src() != space ==>
+                pre.spaces[sib.src()].vals().disjoint(vals)
+        },
+        pre.spaces[space].rtype() is Physical
+            && (forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space)
+            ==> vals.subset_of(pre.spaces[space].vals()),
+        pre.spaces.contains_key(x),
+        x != space,
+        pre.spaces[x].rtype() == pre.spaces[space].rtype(),
+        !carved_from(pre.partitions, space, x),
+        !carved_from(pre.partitions, x, space),
+    ensures
+        vals.disjoint(pre.spaces[x].vals()),
+        pre.spaces[x].vals().disjoint(vals),
+{
+    if (carved_out(pre.partitions, space)) {
+        let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space;
+        let parent = pe.dst();
+        assert(pe.well_formed(pre.spaces));
+        if (x == parent) {
+            assert(carved_within(pre.partitions, parent, x, 0));
+            lemma_carved_within_extend(pre.partitions, pe, x, 0);
+        } else if (carved_from(pre.partitions, parent, x)) {
+            // x is an ancestor of the parent, and so of the space too
+            let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, parent, x, fuel);
+            lemma_carved_within_extend(pre.partitions, pe, x, fuel);
+        } else if (carved_from(pre.partitions, x, parent)) {
+            // x lies within a sibling of the space
+            let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, x, parent, fuel);
+            let d = lemma_carved_within_child(pre.partitions, x, parent, fuel);
+            if (d == space) {
+                assert(carved_from(pre.partitions, x, space));
+            }
+            lemma_carved_within_vals(pre.spaces, pre.partitions, x, d, (fuel - 1) as nat);
+            assert(pre.spaces[d].vals().disjoint(vals));
+        } else {
+            // x shares no val with the parent, which manages the new vals
+            assert(pre.spaces[x].vals().disjoint(pre.spaces[parent].vals()));
+        }
+    } else {
+        // A physical space which was not carved out of another only ever shrinks
+        assert(forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space);
+        assert(pre.spaces[x].vals().disjoint(pre.spaces[space].vals()));
+    }
+}
+
+/// Resizing [space] to [vals] within the partitioning keeps physical spaces of the same type
+/// disjoint, unless one was carved out of the other
+proof fn lemma_resize_keeps_spaces_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, space: SpaceId, vals: Set<nat>)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(space),
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space ==> {
+            &&& vals.subset_of(pre.spaces[pe.dst()].vals())
+            &&& forall |sib: PartitionEdge| #[trigger] pre.partitions.contains(sib) && sib.dst() == pe.dst() && sib.src() != space ==>
+                pre.spaces[sib.src()].vals().disjoint(vals)
+        },
+        pre.spaces[space].rtype() is Physical
+            && (forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space)
+            ==> vals.subset_of(pre.spaces[space].vals()),
+        post.spaces == pre.spaces.insert(space, ResourceSpace { rtype: pre.spaces[space].rtype(), vals }),
+        post.partitions == pre.partitions,
+    ensures
+        post.spaces_disjoint(),
+{
+    assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2) && s1 != s2
+        && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+        && !carved_from(post.partitions, s1, s2) && !carved_from(post.partitions, s2, s1)
+        implies post.spaces[s1].vals().disjoint(post.spaces[s2].vals()) by {
+        if (s1 == space) {
+            lemma_resized_disjoint(pre, space, vals, s2);
+        } else if (s2 == space) {
+            lemma_resized_disjoint(pre, space, vals, s1);
+        }
+    }
+}
+
+/// Merging [other] into its sibling [keep] keeps physical spaces of the same type disjoint,
+/// unless one was carved out of the other
+proof fn lemma_merge_keeps_spaces_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, parent: SpaceId, keep: SpaceId, other: SpaceId)
+    requires
+        pre.invariant(),
+        keep != other,
+        pre.partitions.contains(PartitionEdge { src: keep, dst: parent }),
+        pre.partitions.contains(PartitionEdge { src: other, dst: parent }),
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.dst() != other,
+        post.spaces == pre.spaces.remove(other).insert(keep, ResourceSpace {
+            rtype: pre.spaces[keep].rtype(),
+            vals: pre.spaces[keep].vals().union(pre.spaces[other].vals()),
+        }),
+        post.partitions == pre.partitions.remove(PartitionEdge { src: other, dst: parent }),
+    ensures
+        post.spaces_disjoint(),
+{
+    let gone = PartitionEdge { src: other, dst: parent };
+    let kept = PartitionEdge { src: keep, dst: parent };
+    assert(kept.well_formed(pre.spaces) && gone.well_formed(pre.spaces));
+    assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2) && s1 != s2
+        && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+        && !carved_from(post.partitions, s1, s2) && !carved_from(post.partitions, s2, s1)
+        implies post.spaces[s1].vals().disjoint(post.spaces[s2].vals()) by {
+        // Only the partition edge of other, which has no children, is gone
+        if (carved_from(pre.partitions, s1, s2)) {
+            let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, s1, s2, fuel);
+            lemma_carved_within_kept(pre.partitions, post.partitions, other, s1, s2, fuel);
+        }
+        if (carved_from(pre.partitions, s2, s1)) {
+            let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, s2, s1, fuel);
+            lemma_carved_within_kept(pre.partitions, post.partitions, other, s2, s1, fuel);
+        }
+        if (s1 == keep || s2 == keep) {
+            let x = if (s1 == keep) { s2 } else { s1 };
+            // Neither is other carved out of x, which would make keep carved out of it too, nor
+            // is x carved out of other, which has no children
+            if (carved_from(pre.partitions, other, x)) {
+                let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, other, x, fuel);
+                let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == other
+                    && carved_within(pre.partitions, pe.dst(), x, (fuel - 1) as nat);
+                assert(pe == gone);
+                lemma_carved_within_extend(pre.partitions, kept, x, (fuel - 1) as nat);
+                lemma_carved_within_kept(pre.partitions, post.partitions, other, keep, x, fuel);
+            }
+            if (carved_from(pre.partitions, x, other)) {
+                let fuel = choose |fuel: nat| #[trigger] carved_within(pre.partitions, x, other, fuel);
+                lemma_carved_within_child(pre.partitions, x, other, fuel);
+            }
+            assert(pre.spaces[x].vals().disjoint(pre.spaces[keep].vals()));
+            assert(pre.spaces[x].vals().disjoint(pre.spaces[other].vals()));
+        }
+    }
+}
+
+// Provenance:
+
+impl<K: ResourceKind> OsmosisDAG::Step<K> {
+    /// Whether the step created the node [n]
+    pub open spec fn creates(self, n: ResourceLike) -> bool {
+        match self {
+            OsmosisDAG::Step::create_resource(_, id, ..) => n == (ResourceLike::Resource { res: id }),
+            OsmosisDAG::Step::demand_fault(_, _, _, backing) => n == (ResourceLike::Resource { res: backing }),
+            OsmosisDAG::Step::retype(_, _, space, ..) => n == (ResourceLike::Space { space }),
+            OsmosisDAG::Step::create_resource_space(_, space, ..) => n == (ResourceLike::Space { space }),
+            OsmosisDAG::Step::split_space(_, _, child, ..) => n == (ResourceLike::Space { space: child }),
+            OsmosisDAG::Step::create_budget(_, _, budget, ..) => n == (ResourceLike::Space { space: budget }),
+            _ => false,
+        }
+    }
+
+    /// Obtains the ProtectionDomain which created a node, for a step which created one
+    pub open spec fn creator(self) -> ProtectionDomain {
+        match self {
+            OsmosisDAG::Step::create_resource(pd, ..) => pd,
+            OsmosisDAG::Step::demand_fault(pd, ..) => pd,
+            OsmosisDAG::Step::retype(pd, ..) => pd,
+            OsmosisDAG::Step::create_resource_space(pd, ..) => pd,
+            OsmosisDAG::Step::split_space(pd, ..) => pd,
+            OsmosisDAG::Step::create_budget(pd, ..) => pd,
+            _ => arbitrary(),
+        }
+    }
+
+    /// Obtains the node a node was created out of, for a step which created one: the space a
+    /// Resource was allocated or faulted in from or a space was carved out of, the Resource a
+    /// space was retyped from, the node a new virtual space is mapped onto, or the time space a
+    /// reservation was admitted through
+    pub open spec fn source(self) -> ResourceLike {
+        match self {
+            OsmosisDAG::Step::create_resource(_, _, _, space, _) => ResourceLike::Space { space },
+            OsmosisDAG::Step::demand_fault(_, me, ..) => me.dst_node(),
+            OsmosisDAG::Step::retype(_, res, ..) => ResourceLike::Resource { res },
+            OsmosisDAG::Step::create_resource_space(_, _, _, me, ..) => me.dst_node(),
+            OsmosisDAG::Step::split_space(_, parent, ..) => ResourceLike::Space { space: parent },
+            OsmosisDAG::Step::create_budget(_, source, ..) => ResourceLike::Space { space: source },
+            _ => arbitrary(),
+        }
+    }
+
+    /// Whether the step handed a hold on the node [n] from one ProtectionDomain to another
+    pub open spec fn hands_over(self, n: ResourceLike) -> bool {
+        match self {
+            OsmosisDAG::Step::share_hold(_, _, he, _) => n == he.dst(),
+            OsmosisDAG::Step::grant_hold(_, _, he, _) => n == he.dst(),
+            OsmosisDAG::Step::fulfil_request(_, _, he, _) => n == he.dst(),
+            OsmosisDAG::Step::lend_hold(_, _, he, ..) => n == he.dst(),
+            OsmosisDAG::Step::assign_device(_, _, iommu, _) => n == (ResourceLike::Space { space: iommu }),
+            OsmosisDAG::Step::route_interrupt(_, irq, ..) => n == (ResourceLike::Resource { res: irq }),
+            _ => false,
+        }
+    }
+
+    /// Obtains the ProtectionDomain which handed a hold over, for a step which handed one over
+    pub open spec fn giver(self) -> ProtectionDomain {
+        match self {
+            OsmosisDAG::Step::share_hold(pd, ..) => pd,
+            OsmosisDAG::Step::grant_hold(pd, ..) => pd,
+            OsmosisDAG::Step::fulfil_request(_, _, he, _) => he.src(),
+            OsmosisDAG::Step::lend_hold(lender, ..) => lender,
+            OsmosisDAG::Step::assign_device(driver, ..) => driver,
+            OsmosisDAG::Step::route_interrupt(pd, ..) => pd,
+            _ => arbitrary(),
+        }
+    }
+
+    /// Obtains the ProtectionDomain which received a hold, for a step which handed one over
+    pub open spec fn receiver(self) -> ProtectionDomain {
+        match self {
+            OsmosisDAG::Step::share_hold(_, to, ..) => to,
+            OsmosisDAG::Step::grant_hold(_, to, ..) => to,
+            OsmosisDAG::Step::fulfil_request(_, client, ..) => client,
+            OsmosisDAG::Step::lend_hold(_, borrower, ..) => borrower,
+            OsmosisDAG::Step::assign_device(_, device, ..) => device,
+            OsmosisDAG::Step::route_interrupt(_, _, handler, _) => handler,
+            _ => arbitrary(),
+        }
+    }
+}
+
+/// The index of the last step of [history] which created the node [n], or -1 if none did. Ids
+/// of destroyed nodes may be reused, so the lineage of a node starts there
+pub open spec fn last_creation<K: ResourceKind>(history: Seq<OsmosisDAG::Step<K>>, n: ResourceLike) -> int
+    decreases history.len()
+{
+    if (history.len() == 0) {
+        -1
+    } else if (history.last().creates(n)) {
+        history.len() - 1
+    } else {
+        last_creation(history.drop_last(), n)
+    }
+}
+
+/// Whether [pd] came to hold [n] through the steps of [history], where [root] holds every node
+/// which was never created: [pd] is [root] and [n] was never created, [pd] last created [n] out
+/// of a node it came to hold before, or [n] was handed over to [pd] since it was last created by
+/// a ProtectionDomain which came to hold it before
+pub open spec fn traces_back<K: ResourceKind>(history: Seq<OsmosisDAG::Step<K>>, n: ResourceLike, pd: ProtectionDomain, root: ProtectionDomain, fuel: nat) -> bool
+    decreases fuel
+{
+    let created = last_creation(history, n);
+    ||| created == -1 && pd == root
+    ||| fuel > 0 && created >= 0 && history[created].creator() == pd
+        && traces_back(history.take(created), history[created].source(), pd, root, (fuel - 1) as nat)
+    ||| fuel > 0 && exists |i: int| created < i < history.len() && #[trigger] history[i].hands_over(n)
+        && history[i].receiver() == pd && traces_back(history.take(i), n, history[i].giver(), root, (fuel - 1) as nat)
+}
+
+/// The last creation of [n] is a step of [history] which created it, if there is one
+proof fn lemma_last_creation_bounded<K: ResourceKind>(history: Seq<OsmosisDAG::Step<K>>, n: ResourceLike)
+    ensures
+        -1 <= last_creation(history, n) < history.len(),
+        last_creation(history, n) >= 0 ==> history[last_creation(history, n)].creates(n),
+    decreases history.len()
+{
+    if (history.len() > 0 && !history.last().creates(n)) {
+        lemma_last_creation_bounded(history.drop_last(), n);
+    }
+}
+
+/// Recording the step [l] moves the last creation of [n] onto it exactly when it creates [n]
+proof fn lemma_last_creation_push<K: ResourceKind>(history: Seq<OsmosisDAG::Step<K>>, l: OsmosisDAG::Step<K>, n: ResourceLike)
+    ensures
+        -1 <= last_creation(history, n) < history.len(),
+        last_creation(history.push(l), n) == if (l.creates(n)) { history.len() as int } else { last_creation(history, n) },
+{
+    assert(history.push(l).drop_last() == history);
+    lemma_last_creation_bounded(history, n);
+}
+
+/// Recording a step which does not create [n] keeps every way [pd] came to hold [n]
+proof fn lemma_traces_back_push<K: ResourceKind>(history: Seq<OsmosisDAG::Step<K>>, l: OsmosisDAG::Step<K>, n: ResourceLike, pd: ProtectionDomain, root: ProtectionDomain, fuel: nat)
+    requires
+        traces_back(history, n, pd, root, fuel),
+        !l.creates(n),
+    ensures
+        traces_back(history.push(l), n, pd, root, fuel),
+{
+    let longer = history.push(l);
+    let created = last_creation(history, n);
+    lemma_last_creation_push(history, l, n);
+    if (fuel > 0 && created >= 0) {
+        assert(longer[created] == history[created]);
+        assert(longer.take(created) == history.take(created));
+    }
+    if (fuel > 0 && exists |i: int| created < i < history.len() && #[trigger] history[i].hands_over(n)
+        && history[i].receiver() == pd && traces_back(history.take(i), n, history[i].giver(), root, (fuel - 1) as nat)) {
+        let i = choose |i: int| created < i < history.len() && #[trigger] history[i].hands_over(n)
+            && history[i].receiver() == pd && traces_back(history.take(i), n, history[i].giver(), root, (fuel - 1) as nat);
+        assert(longer[i] == history[i]);
+        assert(longer.take(i) == history.take(i));
+    }
+}
+
 state_machine! 
 {
-    OsmosisDAG {
+    OsmosisDAG<K: ResourceKind> {
         fields {
             /// The protection domains of the Osmosis DAG
             pub domains: Set<ProtectionDomain>,
-            /// The resources of the Osmosis DAG
-            pub resources: Set<Resource>,
-            /// The resource spaces of the Osmosis DAG
-            pub spaces: Set<ResourceSpace>,
+            /// The resources of the Osmosis DAG, keyed by their stable id
+            pub resources: Map<ResourceId, Resource<K>>,
+            /// The resource spaces of the Osmosis DAG, keyed by their stable id
+            pub spaces: Map<SpaceId, ResourceSpace<K>>,
             /// The Hold edges of the Osmosis DAG
             pub holds: Set<HoldEdge>,
             /// The Map edges of the Osmosis DAG
@@ -29,7 +759,40 @@ state_machine!
             /// The Subset edges of the Osmosis DAG
             pub subsets: Set<SubsetEdge>,
             /// The Request edges of the Osmosis DAG
-            pub requests: Set<RequestEdge>,
+            pub requests: Set<RequestEdge<K>>,
+            /// The Partition edges of the Osmosis DAG
+            pub partitions: Set<PartitionEdge>,
+            /// The total size of each ResourceType a ProtectionDomain may hold
+            pub quotas: Map<(ProtectionDomain, ResourceType<K>), nat>,
+            /// The ProtectionDomain each ProtectionDomain was created by
+            pub parents: Map<ProtectionDomain, ProtectionDomain>,
+            /// The number of slots in the scheduling period of each core
+            pub periods: Map<nat, nat>,
+            /// The devices of the Osmosis DAG, keyed by their ProtectionDomain
+            pub devices: Map<ProtectionDomain, Device>,
+            /// The Resources which were retyped, and the ResourceSpace each of them backs
+            pub retyped: Map<ResourceId, SpaceId>,
+            /// The physical ResourceSpaces set up in initialize
+            pub initial_spaces: Map<SpaceId, ResourceSpace<K>>,
+            /// The size of the metadata that managing a val of a ResourceSpace of each type costs,
+            /// a type without an entry costs nothing
+            pub metadata_costs: Map<ResourceType<K>, nat>,
+            /// The Resources charged for managing each ResourceSpace
+            pub metadata: Map<SpaceId, Set<ResourceId>>,
+            /// The Requests which were issued and are not closed yet, keyed by their stable id
+            pub issued: Map<RequestId, Request<K>>,
+            /// The current epoch, against which leases expire
+            pub epoch: nat,
+            /// The hold edges which were lent, and the terms each of them was lent under
+            pub leases: Map<HoldEdge, Lease>,
+            /// The hold edge each derived hold edge was derived from, a forest whose roots are the
+            /// hold edges which were not derived from another
+            pub derived_from: Map<HoldEdge, HoldEdge>,
+            /// The depth of each derived hold edge in the forest of derivations, which grows along
+            /// every derivation
+            pub depth: Map<HoldEdge, nat>,
+            /// The steps taken since initialize, in the order they were taken
+            pub history: Seq<Step<K>>,
         }
 
         // Invariants:
@@ -43,13 +806,13 @@ state_machine!
         /// The model must have a finite number of resources
         #[invariant]
         pub open spec fn resources_is_finite(&self) -> bool {
-            self.resources.finite()
+            self.resources.dom().finite()
         }
 
         /// The model must have a finite number of resource spaces
         #[invariant]
         pub open spec fn spaces_is_finite(&self) -> bool {
-            self.spaces.finite()
+            self.spaces.dom().finite()
         }
 
         /// The model must have a finite number of hold edges
@@ -63,22 +826,22 @@ state_machine!
         pub open spec fn hold_nodes_in_graph(&self) -> bool {
             forall |e: HoldEdge| #[trigger] self.holds.contains(e) ==> {
                 &&& self.domains.contains(e.src())
-                &&& e.dst() is Resource ==> self.resources.contains(e.dst()->res)
-                &&& e.dst() is Space ==> self.spaces.contains(e.dst()->space)
+                &&& e.dst() is Resource ==> self.resources.contains_key(e.dst()->res)
+                &&& e.dst() is Space ==> self.spaces.contains_key(e.dst()->space)
             }
         }
 
         /// There must be at least one hold edge to each resource in the graph
         #[invariant]
         pub open spec fn hold_edge_to_each_resource(&self) -> bool {
-            forall |r: Resource|   self.resources.contains(r) ==>
+            forall |r: ResourceId|   self.resources.contains_key(r) ==>
                 exists |e: HoldEdge| #[trigger]  self.holds.contains(e) && e.dst() is Resource && e.dst()->res == r
         }
 
         /// There must be at least one hold edge to each resource space in the graph
         #[invariant]
         pub open spec fn hold_edge_to_each_space(&self) -> bool {
-            forall |s: ResourceSpace|  self.spaces.contains(s) ==>
+            forall |s: SpaceId|  self.spaces.contains_key(s) ==>
                 exists |e: HoldEdge| #[trigger] self.holds.contains(e) && e.dst() is Space && e.dst()->space == s
         }
 
@@ -91,27 +854,31 @@ state_machine!
         /// Map edges must be well formed
         #[invariant]
         pub open spec fn map_edges_well_formed(&self) -> bool {
-            forall |me: MapEdge| self.maps.contains(me) ==> #[trigger] me.well_formed()
+            forall |me: MapEdge| self.maps.contains(me) ==> #[trigger] me.well_formed(self.resources, self.spaces)
         }
 
         /// The map edges must be between nodes in the grpah
         #[invariant]
         pub open spec fn map_nodes_in_graph(&self) -> bool {
             forall |e: MapEdge|  #[trigger] self.maps.contains(e) ==> {
-                &&& e is SpaceBacking ==> self.spaces.contains(e->sb_src) && self.resources.contains(e->sb_dst)
-                &&& e is SpaceMap ==> self.spaces.contains(e->sm_src) && self.spaces.contains(e->sm_dst)
-                &&& e is ResourceMap ==> self.resources.contains(e->rm_src) && self.resources.contains(e->rm_dst)
+                &&& e is SpaceBacking ==> self.spaces.contains_key(e->sb_src) && self.resources.contains_key(e->sb_dst)
+                &&& e is SpaceMap ==> self.spaces.contains_key(e->sm_src) && self.spaces.contains_key(e->sm_dst)
+                &&& e is ResourceMap ==> self.resources.contains_key(e->rm_src) && self.resources.contains_key(e->rm_dst)
+                &&& e is Delivery ==> self.resources.contains_key(e->dl_src)
             }
         }
 
-        /// Every resource space in the model needs to map to either a resource or another space
+        /// Every resource space in the model needs to map to either a resource or another space,
+        /// or be carved out of a space which does
         #[invariant]
         pub open spec fn spaces_are_mapped(&self) -> bool {
-            forall |s: ResourceSpace|  #[trigger] self.spaces.contains(s) && s.rtype() is Virtual ==>
-                exists |e: MapEdge| #[trigger] self.maps.contains(e) && ({
+            forall |s: SpaceId|  #[trigger] self.spaces.contains_key(s) && self.spaces[s].rtype() is Virtual ==> {
+                ||| exists |e: MapEdge| #[trigger] self.maps.contains(e) && ({
                     ||| e is SpaceBacking && e->sb_src == s
                     ||| e is SpaceMap && e->sm_src == s
                 })
+                ||| exists |p: PartitionEdge| #[trigger] self.partitions.contains(p) && p.src() == s
+            }
         }
 
         /// Note (2025-02-12)
@@ -163,25 +930,39 @@ state_machine!
         /// Subset edges must be well formed
         #[invariant]
         pub open spec fn subset_edges_well_formed(&self) -> bool {
-            forall |se: SubsetEdge| self.subsets.contains(se) ==> #[trigger] se.well_formed()
+            forall |se: SubsetEdge| self.subsets.contains(se) ==> #[trigger] se.well_formed(self.resources, self.spaces)
         }
 
         /// Subset edges must be between nodes in the graph
         #[invariant]
         pub open spec fn subset_nodes_in_graph(&self) -> bool {
             forall |e: SubsetEdge| #[trigger] self.subsets.contains(e) ==> {
-                &&& self.resources.contains(e.src())
-                &&& self.spaces.contains(e.dst())
+                &&& self.resources.contains_key(e.src())
+                &&& self.spaces.contains_key(e.dst())
             }
         }
 
         /// All resource nodes in the graph must be the source in a subset edge
         #[invariant]
         pub open spec fn resources_are_subset(&self) -> bool {
-            forall |r: Resource| self.resources.contains(r) ==>
+            forall |r: ResourceId| self.resources.contains_key(r) ==>
                 exists |e: SubsetEdge| self.subsets.contains(e) && #[trigger] e.src() == r
         }
 
+        /// Resources must be well formed
+        #[invariant]
+        pub open spec fn resources_well_formed(&self) -> bool {
+            forall |r: ResourceId| self.resources.contains_key(r) ==> #[trigger] self.resources[r].well_formed()
+        }
+
+        /// Live resources subset from the same space never overlap
+        #[invariant]
+        pub open spec fn subset_ranges_disjoint(&self) -> bool {
+            forall |e1: SubsetEdge, e2: SubsetEdge|
+                #[trigger] self.subsets.contains(e1) && #[trigger] self.subsets.contains(e2) && e1.dst() == e2.dst()
+                && e1.src() != e2.src() ==> self.resources[e1.src()].range().disjoint(self.resources[e2.src()].range())
+        }
+
         #[invariant]
         pub open spec fn subset_src_are_unique(&self) -> bool {
             forall |e1: SubsetEdge, e2: SubsetEdge| 
@@ -194,27 +975,323 @@ state_machine!
             self.requests.finite()
         }
 
+        /// The model must have a finite number of partition edges
+        #[invariant]
+        pub open spec fn partitions_is_finite(&self) -> bool {
+            self.partitions.finite()
+        }
+
+        /// Partition edges must be well formed
+        #[invariant]
+        pub open spec fn partition_edges_well_formed(&self) -> bool {
+            forall |pe: PartitionEdge| self.partitions.contains(pe) ==> #[trigger] pe.well_formed(self.spaces)
+        }
+
+        /// Partition edges must be between nodes in the graph
+        #[invariant]
+        pub open spec fn partition_nodes_in_graph(&self) -> bool {
+            forall |e: PartitionEdge| #[trigger] self.partitions.contains(e) ==> {
+                &&& self.spaces.contains_key(e.src())
+                &&& self.spaces.contains_key(e.dst())
+            }
+        }
+
+        /// A resource space is carved out of at most one parent space
+        #[invariant]
+        pub open spec fn partition_src_are_unique(&self) -> bool {
+            forall |e1: PartitionEdge, e2: PartitionEdge|
+                #[trigger] self.partitions.contains(e1) && #[trigger] self.partitions.contains(e2) && e1.src() == e2.src() ==> e1.dst() == e2.dst()
+        }
+
+        /// The spaces carved out of the same parent never share a value
+        #[invariant]
+        pub open spec fn partition_siblings_disjoint(&self) -> bool {
+            forall |e1: PartitionEdge, e2: PartitionEdge|
+                #[trigger] self.partitions.contains(e1) && #[trigger] self.partitions.contains(e2)
+                && e1.dst() == e2.dst() && e1.src() != e2.src() ==> self.spaces[e1.src()].vals().disjoint(self.spaces[e2.src()].vals())
+        }
+
+        /// A parent space never allocates a value which was carved out into one of its children
+        #[invariant]
+        pub open spec fn partition_excludes_parent_allocations(&self) -> bool {
+            forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] self.partitions.contains(pe) && #[trigger] self.subsets.contains(se) && se.dst() == pe.dst()
+                ==> self.spaces[pe.src()].vals().disjoint(self.resources[se.src()].range())
+        }
+
+        /// Time reservations are on a core with a scheduling period, and only cover slots of it
+        #[invariant]
+        pub open spec fn time_spaces_within_period(&self) -> bool {
+            forall |s: SpaceId| #[trigger] self.spaces.contains_key(s) && self.spaces[s].rtype() is Time ==> {
+                &&& self.periods.contains_key(self.spaces[s].rtype()->Time_0)
+                &&& self.spaces[s].vals().subset_of(self.period_slots(self.spaces[s].rtype()->Time_0))
+            }
+        }
+
+        /// Devices can only ever reach what their driver can reach
+        #[invariant]
+        pub open spec fn devices_confined(&self) -> bool {
+            forall |dev: ProtectionDomain| #[trigger] self.devices.contains_key(dev) ==> self.device_confined(dev)
+        }
+
+        /// No two devices share an IOMMU space
+        #[invariant]
+        pub open spec fn device_iommus_unique(&self) -> bool {
+            forall |d1: ProtectionDomain, d2: ProtectionDomain|
+                #[trigger] self.devices.contains_key(d1) && #[trigger] self.devices.contains_key(d2) && d1 != d2
+                ==> self.devices[d1].iommu() != self.devices[d2].iommu()
+        }
+
+        /// Delivery edges must be to a ProtectionDomain in the graph
+        #[invariant]
+        pub open spec fn delivery_nodes_in_graph(&self) -> bool {
+            forall |me: MapEdge| #[trigger] self.maps.contains(me) && me is Delivery ==> self.domains.contains(me->dl_dst)
+        }
+
+        /// A ProtectionDomain only handles the interrupt lines it holds
+        #[invariant]
+        pub open spec fn handlers_hold_interrupts(&self) -> bool {
+            forall |me: MapEdge| #[trigger] self.maps.contains(me) && me is Delivery ==> holds_resource(self.holds, me->dl_dst, me->dl_src)
+        }
+
+        /// Each interrupt line is routed to at most one handler at a time
+        #[invariant]
+        pub open spec fn interrupts_routed_once(&self) -> bool {
+            forall |m1: MapEdge, m2: MapEdge|
+                #[trigger] self.maps.contains(m1) && #[trigger] self.maps.contains(m2) && m1 is Delivery && m2 is Delivery
+                && m1->dl_src == m2->dl_src ==> m1 == m2
+        }
+
+        /// A retyped Resource backs the ResourceSpace it was retyped into, which is of a type it can
+        /// be retyped into
+        #[invariant]
+        pub open spec fn retyped_nodes_in_graph(&self) -> bool {
+            forall |r: ResourceId| #[trigger] self.retyped.contains_key(r) ==> {
+                &&& self.resources.contains_key(r)
+                &&& self.spaces.contains_key(self.retyped[r])
+                &&& self.resources[r].rtype().can_retype_into(self.spaces[self.retyped[r]].rtype())
+                &&& self.maps.contains(MapEdge::SpaceBacking { sb_src: self.retyped[r], sb_dst: r })
+            }
+        }
+
+        /// A retyped Resource is never mapped as plain memory: its backing is its only map edge
+        #[invariant]
+        pub open spec fn retyped_resources_exclusive(&self) -> bool {
+            forall |r: ResourceId, me: MapEdge| #[trigger] self.retyped.contains_key(r) && #[trigger] self.maps.contains(me)
+                && me.touches(ResourceLike::Resource { res: r })
+                ==> me == (MapEdge::SpaceBacking { sb_src: self.retyped[r], sb_dst: r })
+        }
+
+        /// Finitely many physical ResourceSpaces, each with finitely many vals, are set up in
+        /// initialize
+        #[invariant]
+        pub open spec fn initial_spaces_finite(&self) -> bool {
+            &&& self.initial_spaces.dom().finite()
+            &&& forall |s: SpaceId| #[trigger] self.initial_spaces.contains_key(s) ==> self.initial_spaces[s].vals().finite()
+        }
+
+        /// Physical ResourceSpaces only ever manage physical vals set up in initialize
+        #[invariant]
+        pub open spec fn physical_spaces_within_initial(&self) -> bool {
+            forall |s: SpaceId| #[trigger] self.spaces.contains_key(s) && self.spaces[s].rtype() is Physical ==>
+                self.spaces[s].vals().subset_of(self.physical_vals(self.spaces[s].rtype()))
+        }
+
+        /// Live physical Resources of the same type never overlap, whichever space they were
+        /// allocated from
+        #[invariant]
+        pub open spec fn physical_ranges_disjoint(&self) -> bool {
+            forall |r1: ResourceId, r2: ResourceId|
+                #[trigger] self.resources.contains_key(r1) && #[trigger] self.resources.contains_key(r2) && r1 != r2
+                && self.resources[r1].rtype() is Physical && self.resources[r1].rtype() == self.resources[r2].rtype()
+                ==> self.resources[r1].range().disjoint(self.resources[r2].range())
+        }
+
+        /// Physical spaces of the same type never share a val, unless one was carved, directly or
+        /// not, out of the other
+        #[invariant]
+        pub open spec fn spaces_disjoint(&self) -> bool {
+            forall |s1: SpaceId, s2: SpaceId| #[trigger] self.spaces.contains_key(s1) && #[trigger] self.spaces.contains_key(s2) && s1 != s2
+                && self.spaces[s1].rtype() is Physical && self.spaces[s1].rtype() == self.spaces[s2].rtype()
+                && !carved_from(self.partitions, s1, s2) && !carved_from(self.partitions, s2, s1)
+                ==> self.spaces[s1].vals().disjoint(self.spaces[s2].vals())
+        }
+
+        /// The metadata of a ResourceSpace is made up of a finite number of physical Resources, and
+        /// only spaces which were not carved out of another are charged for it
+        #[invariant]
+        pub open spec fn metadata_nodes_in_graph(&self) -> bool {
+            &&& forall |s: SpaceId| #[trigger] self.metadata.contains_key(s) ==> {
+                &&& self.spaces.contains_key(s)
+                &&& self.metadata[s].finite()
+                &&& forall |r: ResourceId| #[trigger] self.metadata[s].contains(r) ==>
+                    self.resources.contains_key(r) && self.resources[r].rtype() is Physical
+            }
+            &&& forall |pe: PartitionEdge| #[trigger] self.partitions.contains(pe) ==> !self.metadata.contains_key(pe.src())
+        }
+
+        /// The metadata charged for a ResourceSpace covers the cost of managing its vals
+        #[invariant]
+        pub open spec fn metadata_covers_cost(&self) -> bool {
+            forall |s: SpaceId| #[trigger] self.metadata.contains_key(s) ==>
+                self.metadata_cost(self.spaces[s].rtype(), self.spaces[s].vals()) <= set_sum(self.metadata[s], resource_size(self.resources))
+        }
+
+        /// Metadata is charged to a single ResourceSpace, and is never mapped
+        #[invariant]
+        pub open spec fn metadata_exclusive(&self) -> bool {
+            &&& forall |s1: SpaceId, s2: SpaceId, r: ResourceId|
+                #[trigger] self.metadata.contains_key(s1) && #[trigger] self.metadata.contains_key(s2)
+                && #[trigger] self.metadata[s1].contains(r) && self.metadata[s2].contains(r) ==> s1 == s2
+            &&& forall |r: ResourceId| #[trigger] self.is_metadata(r) ==> !self.is_mapped(ResourceLike::Resource { res: r })
+        }
+
+        /// The model must have a finite number of issued requests
+        #[invariant]
+        pub open spec fn issued_is_finite(&self) -> bool {
+            self.issued.dom().finite()
+        }
+
+        /// A pending Request is made over a RequestEdge of the graph
+        #[invariant]
+        pub open spec fn pending_requests_over_edges(&self) -> bool {
+            forall |id: RequestId| #[trigger] self.is_pending(id) ==> self.requests.contains(self.issued[id].edge())
+        }
+
+        /// A fulfilled Request granted as many Resources of the requested type as were asked for,
+        /// and a pending one fewer. Its client holds all of them until it closes the Request, and
+        /// no other Request accounts for any of them.
+        #[invariant]
+        pub open spec fn granted_requests_held(&self) -> bool {
+            &&& forall |id: RequestId| #[trigger] self.issued.contains_key(id) && self.issued[id].status() is Granted ==> {
+                &&& self.issued[id].granted().finite()
+                &&& self.issued[id].granted().len() == self.issued[id].count()
+            }
+            &&& forall |id: RequestId| #[trigger] self.is_pending(id) ==> {
+                &&& self.issued[id].granted().finite()
+                &&& self.issued[id].granted().len() < self.issued[id].count()
+            }
+            &&& forall |id: RequestId, r: ResourceId| #[trigger] self.granted_by(id, r) ==> {
+                &&& self.resources.contains_key(r)
+                &&& self.resources[r].rtype() == self.issued[id].rtype()
+                &&& holds_resource(self.holds, self.issued[id].client(), r)
+            }
+            &&& forall |i1: RequestId, i2: RequestId, r: ResourceId| #[trigger] self.granted_by(i1, r) && #[trigger] self.granted_by(i2, r) ==> i1 == i2
+        }
+
+        /// Lent Resources only reach the borrower through the lease
+        #[invariant]
+        pub open spec fn leases_well_formed(&self) -> bool {
+            forall |he: HoldEdge| #[trigger] self.leases.contains_key(he) ==> self.lease_well_formed(he)
+        }
+
+        /// A derived hold edge is derived from a shallower hold edge on the same node, and both are
+        /// in the graph, so following derivations never loops back
+        #[invariant]
+        pub open spec fn derivation_forest(&self) -> bool {
+            forall |he: HoldEdge| #[trigger] self.derived_from.contains_key(he) ==> {
+                &&& self.holds.contains(he)
+                &&& self.holds.contains(self.derived_from[he])
+                &&& self.derived_from[he].dst() == he.dst()
+                &&& self.rank(self.derived_from[he]) < self.rank(he)
+            }
+        }
+
+        /// Every node which was not set up in initialize was created by a recorded step
+        #[invariant]
+        pub open spec fn created_nodes_recorded(&self) -> bool {
+            forall |n: ResourceLike| #[trigger] self.node_in_graph(n) && last_creation(self.history, n) == -1 ==>
+                n is Space && self.initial_spaces.contains_key(n->space)
+        }
+
+        /// Every hold edge traces back through the history to the initial ProtectionDomain
+        #[invariant]
+        pub open spec fn holds_traced(&self) -> bool {
+            forall |he: HoldEdge| #[trigger] self.holds.contains(he) ==> self.traced(he.dst(), he.src())
+        }
+
+        /// A space may fan out onto several spaces through its SpaceMaps, such as device memory
+        /// embedded in physical memory, each of which translates a disjoint part of its vals
+        #[invariant]
+        pub open spec fn translations_disjoint(&self) -> bool {
+            forall |m1: MapEdge, m2: MapEdge|
+                #[trigger] self.maps.contains(m1) && #[trigger] self.maps.contains(m2) && m1 is SpaceMap && m2 is SpaceMap
+                && m1->sm_src == m2->sm_src && m1 != m2 ==> m1->sm_vals.disjoint(m2->sm_vals)
+        }
+
+        /// The SpaceMaps fanning out of a space translate every one of its vals between them, so
+        /// with map_edges_well_formed the union of their vals is exactly the vals of the space
+        #[invariant]
+        pub open spec fn translations_cover(&self) -> bool {
+            forall |me: MapEdge, v: nat|
+                #[trigger] self.maps.contains(me) && me is SpaceMap && #[trigger] self.spaces[me->sm_src].vals().contains(v)
+                ==> exists |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(me->sm_src, v)
+        }
+
+        /// No ProtectionDomain is ever charged more for a ResourceType than its quota allows, for
+        /// the Resources and the reservations of that type it holds
+        #[invariant]
+        pub open spec fn usage_within_quota(&self) -> bool {
+            forall |pd: ProtectionDomain, rtype: ResourceType<K>|
+                #[trigger] self.charged(pd, rtype) <= self.quota(pd, rtype)
+        }
+
 
         // Initalize:
 
         init! {
-            initialize(physical_spaces: Set<ResourceSpace>)
+            initialize(physical_spaces: Map<SpaceId, ResourceSpace<K>>, initial_quotas: Map<ResourceType<K>, nat>, core_periods: Map<nat, nat>, space_costs: Map<ResourceType<K>, nat>)
             {
-                // All the initial ResourceSpaces are Physical
-                require forall |rs| #[trigger] physical_spaces.contains(rs) ==> rs.rtype() is Physical;
-                // The set of initial ResourceSpaces must be finite
-                require physical_spaces.finite();
+                // All the initial ResourceSpaces are Physical, of a kind which exists physically, or
+                // the time of a core
+                require forall |id| #[trigger] physical_spaces.contains_key(id) ==> {
+                    ||| physical_spaces[id].rtype() is Physical
+                    ||| physical_spaces[id].rtype() is Time
+                };
+                require forall |id| #[trigger] physical_spaces.contains_key(id) ==> physical_spaces[id].rtype().declared();
+                // The time of a core is made up of the slots of its scheduling period
+                require forall |id| #[trigger] physical_spaces.contains_key(id) && physical_spaces[id].rtype() is Time ==> {
+                    &&& core_periods.contains_key(physical_spaces[id].rtype()->Time_0)
+                    &&& physical_spaces[id].vals().subset_of(nat_range(core_periods[physical_spaces[id].rtype()->Time_0]))
+                };
+                // The set of initial ResourceSpaces must be finite, and so must their vals
+                require physical_spaces.dom().finite();
+                require forall |id| #[trigger] physical_spaces.contains_key(id) ==> physical_spaces[id].vals().finite();
+                // Initial spaces of the same type never share a val, so no slot of a core is
+                // admitted to two of its time spaces
+                require forall |s1, s2| #[trigger] physical_spaces.contains_key(s1) && #[trigger] physical_spaces.contains_key(s2) && s1 != s2
+                    && physical_spaces[s1].rtype() == physical_spaces[s2].rtype()
+                    ==> physical_spaces[s1].vals().disjoint(physical_spaces[s2].vals());
                 
                 let initial_domain = ProtectionDomain { id: 0 };
                 init domains = Set::empty().insert(initial_domain);
-                init resources = Set::empty();
+                init resources = Map::empty();
                 init spaces = physical_spaces;
-                init holds = physical_spaces.map(|space: ResourceSpace| -> (HoldEdge) { 
-                    HoldEdge { src: initial_domain, dst: ResourceLike::Space { space } }
+                init holds = physical_spaces.dom().map(|space: SpaceId| -> (HoldEdge) { 
+                    HoldEdge { src: initial_domain, dst: ResourceLike::Space { space }, rights: Set::full() }
                 });
                 init maps = Set::empty();
                 init subsets = Set::empty();
                 init requests = Set::empty();
+                init partitions = Set::empty();
+                // The initial ProtectionDomain starts out with all of the quota
+                init quotas = Map::new(
+                    |k: (ProtectionDomain, ResourceType<K>)| k.0 == initial_domain && initial_quotas.contains_key(k.1),
+                    |k: (ProtectionDomain, ResourceType<K>)| initial_quotas[k.1],
+                );
+                init parents = Map::empty();
+                init periods = core_periods;
+                init devices = Map::empty();
+                init retyped = Map::empty();
+                init initial_spaces = physical_spaces;
+                init metadata_costs = space_costs;
+                init metadata = Map::empty();
+                init issued = Map::empty();
+                init epoch = 0;
+                init leases = Map::empty();
+                init derived_from = Map::empty();
+                init depth = Map::empty();
+                init history = Seq::empty();
             }
         }
 
@@ -228,16 +1305,17 @@ state_machine!
         //}
 
 
-        /// Create a new resource node. This is done by subsetting it from a specific resource space
+        /// Create a new resource node [id] with attributes [res]. This is done by subsetting it
+        /// from a specific resource space, and [pd] holds it with [rights]
         transition! {
-            create_resource(pd: ProtectionDomain, res: Resource, space: ResourceSpace)
+            create_resource(pd: ProtectionDomain, id: ResourceId, res: Resource<K>, space: SpaceId, rights: Set<Right>)
             {
                 // The Protection Domain must exist
                 require pre.domains.contains(pd);
                 // The Resource Space must exist
-                require pre.spaces.contains(space);
+                require pre.spaces.contains_key(space);
                 // The new Resource must not already exist
-                require !pre.resources.contains(res);
+                require !pre.resources.contains_key(id);
                 // There must be a hold edge from the pd protection domain to the resource space
                 require exists |he: HoldEdge|
                     pre.holds.contains(he)
@@ -245,17 +1323,31 @@ state_machine!
                     && he.dst() is Space
                     && he.dst()->space == space;
                 // The Resource must be of the same type as the Resource Space
-                require res.rtype() == space.rtype();
-                // The value must be in the Resource Space
-                require space.vals().contains(res.val());
-                // The Resource must not already be held
-                require !pre.holds.contains(HoldEdge { src: pd, dst: ResourceLike::Resource { res } });
-                // There must be a hold edge from the holder to the space
-                require pre.holds.contains(HoldEdge { src: pd, dst: ResourceLike::Space { space } });
+                require res.rtype() == pre.spaces[space].rtype();
+                // The Resource must be well formed
+                require res.well_formed();
+                // The whole range must be in the Resource Space
+                require res.range().subset_of(pre.spaces[space].vals());
+                // The range must not overlap with any Resource already allocated from the Resource Space
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.dst() == space ==>
+                    pre.resources[se.src()].range().disjoint(res.range());
+                // The rights on the Resource may only attenuate those the holder has on the space
+                require pre.holds_with_rights(pd, ResourceLike::Space { space }, rights);
+                // The range must not have been carved out into a child of the Resource Space
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == space ==>
+                    pre.spaces[pe.src()].vals().disjoint(res.range());
+                // The holder must have enough quota left for the Resource
+                require pre.charged(pd, res.rtype()) + res.size() <= pre.quota(pd, res.rtype());
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
+                // Physical vals are only ever allocated once, whichever space they are allocated from
+                require res.rtype() is Physical ==> forall |r: ResourceId| #[trigger] pre.resources.contains_key(r)
+                    && pre.resources[r].rtype() == res.rtype() ==> pre.resources[r].range().disjoint(res.range());
 
-                update resources = pre.resources.insert(res);
-                update subsets = pre.subsets.insert(SubsetEdge { src: res, dst: space });
-                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Resource { res }});
+                update resources = pre.resources.insert(id, res);
+                update subsets = pre.subsets.insert(SubsetEdge { src: id, dst: space });
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Resource { res: id }, rights });
+                update history = pre.history.push(Step::create_resource(pd, id, res, space, rights));
             }
         }
 
@@ -268,19 +1360,28 @@ state_machine!
         /// with @Reto.
         ///
         transition! {
-            destroy_resource(pd: ProtectionDomain, res: Resource)
+            destroy_resource(pd: ProtectionDomain, res: ResourceId)
             {
                 // The Resource must exist
-                require pre.resources.contains(res);
+                require pre.resources.contains_key(res);
                 // The Protection Domain must exist
                 require pre.domains.contains(pd);
-                // The Protection Domain must hold the Resource 
-                require exists |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == pd && he.dst() is Resource && he.dst()->res == res;
-                // The Resource must not be mapped or being used to map
+                // The Protection Domain must hold the Resource with the right to destroy it
+                require exists |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == pd && he.dst() is Resource && he.dst()->res == res
+                    && he.rights().contains(Right::Destroy);
+                // The Resource must not be mapped or being used to map, nor be an interrupt line
+                // which is routed to a handler
                 require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> ({
                     ||| me is SpaceBacking && me->sb_dst != res
                     ||| me is SpaceMap 
-                    ||| me is ResourceMap && me->rm_src != res && me->rm_dst != res });
+                    ||| me is ResourceMap && me->rm_src != res && me->rm_dst != res
+                    ||| me is Delivery && me->dl_src != res });
+                // The Resource must not be charged as metadata, which is released with its space
+                require !pre.is_metadata(res);
+                // The Resource must not have been granted by a Request which is not closed yet
+                require forall |id: RequestId| !#[trigger] pre.granted_by(id, res);
+                // A lent Resource must be reclaimed before it is destroyed
+                require !pre.is_lent(ResourceLike::Resource { res });
 
                 let se = choose |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.src() == res;
                 let reslike = ResourceLike::Resource { res };
@@ -289,6 +1390,8 @@ state_machine!
                 update resources = pre.resources.remove(res);
                 update subsets = pre.subsets.remove(se);
                 update holds = pre.holds.filter(hold_edge_filter);
+                update derived_from = pre.prune_derivations(pre.holds.filter(hold_edge_filter));
+                update history = pre.history.push(Step::destroy_resource(pd, res));
             }
         }
 
@@ -296,10 +1399,13 @@ state_machine!
         transition! {
             create_pd(parent: ProtectionDomain, pd: ProtectionDomain)
             {
-                // The ProtectionDomain must not already exist
+                // The parent must exist, and the ProtectionDomain must not already exist
+                require pre.domains.contains(parent);
                 require !pre.domains.contains(pd);
                 
                 update domains = pre.domains.insert(pd);
+                update parents = pre.parents.insert(pd, parent);
+                update history = pre.history.push(Step::create_pd(parent, pd));
             }
         }
 
@@ -311,34 +1417,144 @@ state_machine!
                 require pre.domains.contains(pd);
                 // There should be no edges from/towards this protection domain
                 require forall |he: HoldEdge| pre.holds.contains(he) ==> #[trigger] he.src() != pd;
-                require forall |re: RequestEdge| #[trigger] pre.requests.contains(re) ==> re.src() != pd && re.dst() != pd;
+                require forall |re: RequestEdge<K>| #[trigger] pre.requests.contains(re) ==> re.src() != pd && re.dst() != pd;
+                // Devices are never destroyed
+                require !pre.devices.contains_key(pd);
 
                 update domains = pre.domains.remove(pd);
+                update parents = pre.parents.remove(pd);
+                update history = pre.history.push(Step::destroy_pd(pd));
             }
         }
 
         /// Insert a RequestEdge 
         transition! {
-            create_request_edge(req: RequestEdge)
+            create_request_edge(req: RequestEdge<K>)
             {
                 // The two ProtectionDomains must already exist
                 require pre.domains.contains(req.src());
                 require pre.domains.contains(req.dst()); 
                 // The dst ProtectionDomain must be holding a ResourceSpace of the requested type
-                require exists |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == req.dst() && he.dst() is Space && he.dst()->space.rtype() == req.rtype();
+                require exists |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == req.dst() && he.dst() is Space && pre.spaces[he.dst()->space].rtype() == req.rtype();
 
                 update requests = pre.requests.insert(req);
+                update history = pre.history.push(Step::create_request_edge(req));
             }
         }
 
         /// Remove a RequestEdge
         transition! {
-            destroy_request_edge(req: RequestEdge)
+            destroy_request_edge(req: RequestEdge<K>)
             {
                 // The request edge must be in the graph
                 require pre.requests.contains(req);
+                // No Request made over the edge may still be waiting for an answer
+                require forall |id: RequestId| #[trigger] pre.is_pending(id) ==> pre.issued[id].edge() != req;
 
                 update requests = pre.requests.remove(req);
+                update history = pre.history.push(Step::destroy_request_edge(req));
+            }
+        }
+
+        /// The client of [req] issues it as the Request [id], which is pending until its server
+        /// either fulfils it by handing the requested Resources over, or refuses it. Once answered,
+        /// the client closes it, and until then it may neither give away nor destroy the Resources
+        /// it was granted.
+        transition! {
+            issue_request(id: RequestId, req: Request<K>)
+            {
+                // The Request must not already exist
+                require !pre.issued.contains_key(id);
+                // The client must be permitted to make the Request
+                require pre.requests.contains(req.edge());
+                // The Request must ask for something, and wait for an answer with nothing handed
+                // over yet
+                require req.count() > 0;
+                require req.status() == (RequestStatus::Pending { handed: Set::empty() });
+
+                update issued = pre.issued.insert(id, req);
+                update history = pre.history.push(Step::issue_request(id, req));
+            }
+        }
+
+        /// The server of the pending Request [id] fulfils it one Resource at a time: it hands its
+        /// [he] hold on a Resource of the requested type over to the [client], which receives the
+        /// attenuated [rights], and the Request accounts for the Resource in the same step. Once
+        /// the server handed over as many Resources as were requested, the Request is fulfilled.
+        transition! {
+            fulfil_request(id: RequestId, client: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+            {
+                // The Request must be waiting for an answer
+                require pre.is_pending(id);
+                let req = pre.issued[id];
+                // Both ends of the Request must exist
+                require client == req.client();
+                require pre.domains.contains(client);
+                require pre.domains.contains(req.server());
+                // The handed over hold must belong to the server and carry the right to grant it
+                require pre.holds.contains(he);
+                require he.src() == req.server();
+                require he.rights().contains(Right::Grant);
+                // Rights can only ever be attenuated
+                require rights.subset_of(he.rights());
+                // The handed over hold must be on a Resource of the requested type
+                require he.dst() is Resource;
+                require pre.resources[he.dst()->res].rtype() == req.rtype();
+                // The Resource must be fresh to the client, and not accounted for by any Request
+                // yet, so that it fulfils this Request only
+                require !holds_resource(pre.holds, client, he.dst()->res);
+                require forall |i: RequestId| !#[trigger] pre.granted_by(i, he.dst()->res);
+                // The client must have enough quota left for the Resource
+                require ({
+                    let r = pre.resources[he.dst()->res];
+                    pre.charged(client, r.rtype()) + r.size() <= pre.quota(client, r.rtype())
+                });
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(client);
+                // A driver may not give away what its devices can reach, nor a handler the interrupt
+                // lines routed to it, and a lent Resource is not handed out any further
+                require !pre.pins(he.src(), he.dst());
+                require !pre.handles(he.src(), he.dst());
+                require !pre.is_lent(he.dst());
+
+                let granted = HoldEdge { src: client, dst: he.dst(), rights };
+                let handed = req.granted().insert(he.dst()->res);
+                let status = if (handed.len() == req.count()) {
+                    RequestStatus::Granted { granted: handed }
+                } else {
+                    RequestStatus::Pending { handed }
+                };
+
+                update holds = pre.holds.remove(he).insert(granted);
+                update derived_from = pre.hand_over_derivations(he, granted);
+                update depth = pre.depth.insert(granted, pre.rank(he));
+                update issued = pre.issued.insert(id, req.with_status(status));
+                update history = pre.history.push(Step::fulfil_request(id, client, he, rights));
+            }
+        }
+
+        /// The server refuses the pending Request [id]
+        transition! {
+            deny_request(id: RequestId)
+            {
+                // The Request must be waiting for an answer
+                require pre.is_pending(id);
+
+                update issued = pre.issued.insert(id, pre.issued[id].with_status(RequestStatus::Denied));
+                update history = pre.history.push(Step::deny_request(id));
+            }
+        }
+
+        /// The client closes the answered Request [id]
+        transition! {
+            close_request(id: RequestId)
+            {
+                // The Request must have been answered
+                require pre.issued.contains_key(id);
+                require !(pre.issued[id].status() is Pending);
+
+                update issued = pre.issued.remove(id);
+                update history = pre.history.push(Step::close_request(id));
             }
         }
 
@@ -366,71 +1582,977 @@ state_machine!
         ///
         /// Insert a MapEdge
         transition! {
-            create_map_edge(me: MapEdge)
+            create_map_edge(pd: ProtectionDomain, me: MapEdge)
             {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // Interrupt lines are only delivered by routing them
+                require !(me is Delivery);
+                // The MapEdge must be between nodes in the graph
+                require pre.node_in_graph(me.src_node());
+                require pre.node_in_graph(me.dst_node());
+                // The MapEdge must be well formed
+                require me.well_formed(pre.resources, pre.spaces);
+                // No other SpaceMap may translate the vals it translates, and together with those
+                // fanning out of the same space it must translate every val of it
+                require me is SpaceMap ==> pre.translates_disjointly(me);
+                require me is SpaceMap ==> pre.completes_fan_out(me);
+                // The Protection Domain must hold both ends with the right to map them, and may not
+                // permit more access through the mapping than it has on the dst
+                require pre.holds_with_rights(pd, me.src_node(), Set::empty().insert(Right::Map));
+                require pre.holds_with_rights(pd, me.dst_node(), me.perms().insert(Right::Map));
+                // IOMMU spaces are only programmed by the driver of their device
+                require !(me.src_node() is Space && pre.is_iommu(me.src_node()->space));
+                // Retyped Resources can no longer be mapped as plain memory, nor can metadata
+                require !pre.is_retyped(me.src_node()) && !pre.is_retyped(me.dst_node());
+                require !pre.is_metadata_node(me.src_node()) && !pre.is_metadata_node(me.dst_node());
+                // A lent Resource never backs a space, which could not outlive the lease
+                require me is SpaceBacking ==> !pre.is_lent(me.dst_node());
+                // Only the vals of a space are backed lazily, a Resource is mapped onto its backing
+                require me is ResourceMap ==> !me->rm_attrs.lazy;
+
+                update maps = pre.maps.insert(me);
+                update history = pre.history.push(Step::create_map_edge(pd, me));
             }
         }
 
-
-        /// Create a ResourceSpace
+        /// The [pd] ProtectionDomain hands the vals the SpaceMap [part] translates over to it from
+        /// the SpaceMap [me], which keeps translating the rest of its vals. This is how a space fans
+        /// out onto several spaces while its SpaceMaps keep translating every one of its vals.
         transition! {
-            create_resource_space(space: ResourceSpace, backing: ResourceLike)
+            split_map_edge(pd: ProtectionDomain, me: MapEdge, part: MapEdge)
             {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The part must take over some of the vals of a SpaceMap of the same space
+                require pre.maps.contains(me);
+                require me is SpaceMap && part is SpaceMap;
+                require part->sm_src == me->sm_src;
+                require part->sm_vals.subset_of(me->sm_vals);
+                // The part must be well formed, onto a space in the graph
+                require pre.node_in_graph(part.dst_node());
+                require part.well_formed(pre.resources, pre.spaces);
+                // The Protection Domain must hold the mapped space with the right to map it, and the
+                // dst of the part with the right to map it with the permissions of the part
+                require pre.holds_with_rights(pd, me.src_node(), Set::empty().insert(Right::Map));
+                require pre.holds_with_rights(pd, part.dst_node(), part.perms().insert(Right::Map));
+                // IOMMU spaces are only programmed by the driver of their device
+                require !pre.is_iommu(me->sm_src);
+
+                update maps = pre.maps.remove(me).insert(me.without_vals(part->sm_vals)).insert(part);
+                update history = pre.history.push(Step::split_map_edge(pd, me, part));
             }
         }
 
-        /// Create a Virtual ResourceSpace
-        /// TODO
-        //transition! {
-        //    create_virtual_space(space: ResourceSpace, 
-
-        /// Remove a ResourceSpace
-        ///
-        /// Note (2025-03-17)
-        ///
-        /// Should this be an operation on a ResourceSpace which has already revoked all its
-        /// allocations and Request edges, or should it rather operate as a recursive operation
-        /// which deletes Resources and Request edges?
-        ///
+        /// The [pd] ProtectionDomain writes through the copy-on-write mapping [me], which splits
+        /// what it writes off the backing it shares onto its own [copy], allocated beforehand with
+        /// create_resource. A ResourceMap is moved onto the copy as a whole, while a SpaceMap only
+        /// hands the written val [v] over to the copy and keeps sharing the rest of its vals.
         transition! {
-            destroy_resource_space(space: ResourceSpace)
+            cow_write(pd: ProtectionDomain, me: MapEdge, v: nat, copy: ResourceId)
             {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The mapping must be a copy-on-write mapping of a Resource, or of the val of a space
+                require pre.maps.contains(me);
+                require ({
+                    ||| me is ResourceMap && me->rm_attrs.cow
+                    ||| me is SpaceMap && me->sm_attrs.cow && me->sm_vals.contains(v)
+                });
+                // pd must hold the mapped node, which is virtual, with the right to write it
+                require me.src_node().rtype(pre.resources, pre.spaces) is Virtual;
+                require pre.holds_with_rights(pd, me.src_node(), Set::empty().insert(Right::Write));
+                // The written node must be private to pd: no one else holds it, and nothing is
+                // mapped onto it
+                require forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.dst() == me.src_node() ==> he.src() == pd;
+                require forall |m: MapEdge| #[trigger] pre.maps.contains(m) ==> !m.maps_onto(me.src_node());
+                // A written Resource must not be backed by anything else
+                require me is ResourceMap ==> forall |m: MapEdge| #[trigger] pre.maps.contains(m) && m.src_node() == me.src_node() ==> m == me;
+                // The copy must be a physical Resource as large as what is written
+                require pre.resources.contains_key(copy);
+                require pre.resources[copy].rtype() is Physical;
+                require me.src_node().rtype(pre.resources, pre.spaces).may_map_onto(pre.resources[copy].rtype());
+                require me is ResourceMap ==> pre.resources[copy].size() == pre.resources[me->rm_dst].size();
+                require me is SpaceMap ==> pre.resources[copy].size() == 1;
+                // The copy must be fresh: pd alone holds it, and nothing is mapped onto it or
+                // translated into it yet
+                require !pre.is_mapped(ResourceLike::Resource { res: copy });
+                require forall |s: SpaceId, w: nat| !#[trigger] pre.lands_in(s, w, copy);
+                require !pre.is_metadata(copy);
+                require forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.dst() == (ResourceLike::Resource { res: copy }) ==> he.src() == pd;
+                // pd must hold the copy with the right to map it with the permissions of the mapping,
+                // and so the space it is allocated from for a val handed over to it
+                require pre.holds_with_rights(pd, ResourceLike::Resource { res: copy }, me.perms().insert(Right::Map));
+                require me is SpaceMap ==>
+                    pre.holds_with_rights(pd, ResourceLike::Space { space: pre.allocated_from(copy) }, me.perms().insert(Right::Map));
 
+                update maps = pre.cow_split(me, v, copy);
+                update history = pre.history.push(Step::cow_write(pd, me, v, copy));
             }
         }
 
+        /// The [pd] ProtectionDomain first touches the val [v] through the lazy mapping [me],
+        /// which does not back it yet. This allocates the [backing] Resource covering the val [v]
+        /// lands on out of the physical space [me] is mapped onto, held by pd, and narrows [me]
+        /// down to the vals not touched yet, translating [v] eagerly from then on.
+        transition! {
+            demand_fault(pd: ProtectionDomain, me: MapEdge, v: nat, backing: ResourceId)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The val must be translated by a lazy mapping onto a physical space
+                require pre.maps.contains(me);
+                require me is SpaceMap;
+                require me->sm_attrs.lazy;
+                require me->sm_vals.contains(v);
+                require pre.spaces[me->sm_dst].rtype() is Physical;
+                // pd must hold the mapped space, and the space it is mapped onto with the right to
+                // map the backing with the permissions of the mapping
+                require pre.holds_with_rights(pd, me.src_node(), Set::empty());
+                require pre.holds_with_rights(pd, me.dst_node(), me.perms().insert(Right::Map));
+                // The backing must not already exist
+                require !pre.resources.contains_key(backing);
+                // The val landed on must neither be allocated from the space, nor carved out of it
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.dst() == me->sm_dst ==>
+                    pre.resources[se.src()].range().disjoint(pre.faulted(me, v).range());
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == me->sm_dst ==>
+                    pre.spaces[pe.src()].vals().disjoint(pre.faulted(me, v).range());
+                // Physical vals are only ever allocated once, whichever space they are allocated from
+                require forall |r: ResourceId| #[trigger] pre.resources.contains_key(r)
+                    && pre.resources[r].rtype() == pre.faulted(me, v).rtype() ==> pre.resources[r].range().disjoint(pre.faulted(me, v).range());
+                // pd must have enough quota left for the backing
+                require pre.charged(pd, pre.faulted(me, v).rtype()) + 1 <= pre.quota(pd, pre.faulted(me, v).rtype());
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
 
-        // Inductiveness Proofs:
-
-        #[inductive(initialize)]
-        fn initialize_inductive(post: Self, physical_spaces: Set<ResourceSpace>) {
+                update resources = pre.resources.insert(backing, pre.faulted(me, v));
+                update subsets = pre.subsets.insert(SubsetEdge { src: backing, dst: me->sm_dst });
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Resource { res: backing }, rights: me.perms().insert(Right::Map) });
+                update maps = pre.maps.remove(me).insert(me.without(v))
+                    .insert(me.single(v, me->sm_dst, me->sm_attrs.translation.apply(v) as nat));
+                update history = pre.history.push(Step::demand_fault(pd, me, v, backing));
+            }
+        }
 
-            let map_fn = |space: ResourceSpace| -> HoldEdge {
-                HoldEdge { src: ProtectionDomain { id: 0 }, dst: ResourceLike::Space { space } }
-            };
+        /// The [pd] ProtectionDomain moves the backing of the virtual node mapped by [me] onto the
+        /// [dst] Resource in one step, as memory compaction, NUMA migration and swapping do. The
+        /// new backing may be of a different physical kind than the old one, as long as the node
+        /// may be mapped onto it.
+        transition! {
+            remap(pd: ProtectionDomain, me: MapEdge, dst: ResourceId)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The mapping must back a virtual node with a physical Resource
+                require pre.maps.contains(me);
+                require me is ResourceMap || me is SpaceBacking;
+                require me.src_node().rtype(pre.resources, pre.spaces) is Virtual;
+                require pre.resources[me.dst_node()->res].rtype() is Physical;
+                // The old backing must not be mapped by anything else
+                require forall |m: MapEdge| #[trigger] pre.maps.contains(m)
+                    && m.touches(me.dst_node()) ==> m == me;
+                // IOMMU spaces are only programmed by the driver of their device
+                require !(me.src_node() is Space && pre.is_iommu(me.src_node()->space));
+                // Retyped Resources hold metadata in place, and are not migrated
+                require !pre.is_retyped(me.dst_node());
+                // The new backing must be a fresh physical Resource as large as the old one
+                require pre.resources.contains_key(dst);
+                require pre.resources[dst].rtype() is Physical;
+                require me.src_node().rtype(pre.resources, pre.spaces).may_map_onto(pre.resources[dst].rtype());
+                require pre.resources[dst].size() == pre.resources[me.dst_node()->res].size();
+                require !pre.is_mapped(ResourceLike::Resource { res: dst });
+                require !pre.is_metadata(dst);
+                // A lent Resource never backs a space, which could not outlive the lease
+                require me is SpaceBacking ==> !pre.is_lent(ResourceLike::Resource { res: dst });
+                // The Protection Domain must hold the mapped node and the new backing with the right
+                // to map them, and may not permit more access through the mapping than it has on
+                // the new backing
+                require pre.holds_with_rights(pd, me.src_node(), Set::empty().insert(Right::Map));
+                require pre.holds_with_rights(pd, ResourceLike::Resource { res: dst }, me.perms().insert(Right::Map));
 
-            // Invariant: holds_is_finite
-            assert(post.holds.finite()) by {
-                set_map_finite_preserving(physical_spaces, map_fn);
+                update maps = pre.maps.remove(me).insert(pre.retarget(me, dst));
+                update history = pre.history.push(Step::remap(pd, me, dst));
             }
-            
+        }
+
+        /// The [pd] ProtectionDomain retypes the Resource [res] into the backing of a new [space]
+        /// of type [rtype] managing [vals], such as the entries of a page table or the slots of a
+        /// capability table, and holds the new space with [rights]
+        transition! {
+            retype(pd: ProtectionDomain, res: ResourceId, space: SpaceId, rtype: ResourceType<K>, vals: Set<nat>, rights: Set<Right>)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The Resource must exist, and its type must be retypable into the new one
+                require pre.resources.contains_key(res);
+                require pre.resources[res].rtype().can_retype_into(rtype);
+                // The Resource must not be in use as plain memory, nor as metadata
+                require !pre.is_mapped(ResourceLike::Resource { res });
+                require !pre.is_metadata(res);
+                // A lent Resource never backs a space, which could not outlive the lease
+                require !pre.is_lent(ResourceLike::Resource { res });
+                // The new ResourceSpace must not already exist
+                require !pre.spaces.contains_key(space);
+                // The new ResourceSpace may not manage more vals than fit in its backing
+                require vals.finite();
+                require vals.len() <= pre.resources[res].size();
+                // The rights on the new space may only attenuate those pd has on the Resource, which
+                // must include the right to map it
+                require pre.holds_with_rights(pd, ResourceLike::Resource { res }, rights.insert(Right::Map));
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
+
+                update spaces = pre.spaces.insert(space, ResourceSpace { rtype, vals });
+                update maps = pre.maps.insert(MapEdge::SpaceBacking { sb_src: space, sb_dst: res });
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights });
+                update retyped = pre.retyped.insert(res, space);
+                update history = pre.history.push(Step::retype(pd, res, space, rtype, vals, rights));
+            }
+        }
+
+        /// The [pd] ProtectionDomain shares its [he] hold with [to], which receives the
+        /// attenuated [rights]
+        transition! {
+            share_hold(pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(pd);
+                require pre.domains.contains(to);
+                // The shared hold must belong to pd and carry the right to grant it
+                require pre.holds.contains(he);
+                require he.src() == pd;
+                require he.rights().contains(Right::Grant);
+                // Rights can only ever be attenuated
+                require rights.subset_of(he.rights());
+                // The receiver must have enough quota left for a Resource it does not hold yet
+                require he.dst() is Resource && !holds_resource(pre.holds, to, he.dst()->res) ==> ({
+                    let r = pre.resources[he.dst()->res];
+                    pre.charged(to, r.rtype()) + r.size() <= pre.quota(to, r.rtype())
+                });
+                // And for a reservation it does not hold yet
+                require he.dst() is Space && carved_out(pre.partitions, he.dst()->space)
+                    && !holds_space(pre.holds, to, he.dst()->space) ==> ({
+                    let sp = pre.spaces[he.dst()->space];
+                    pre.charged(to, sp.rtype()) + sp.vals().len() <= pre.quota(to, sp.rtype())
+                });
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(to);
+                // A lent Resource is not handed out any further until it is reclaimed
+                require !pre.is_lent(he.dst());
+
+                update holds = pre.holds.insert(HoldEdge { src: to, dst: he.dst(), rights });
+                update derived_from = pre.derive(HoldEdge { src: to, dst: he.dst(), rights }, he);
+                update depth = pre.deepen(HoldEdge { src: to, dst: he.dst(), rights }, he);
+                update history = pre.history.push(Step::share_hold(pd, to, he, rights));
+            }
+        }
+
+        /// The [pd] ProtectionDomain hands its [he] hold over to [to], which receives the
+        /// attenuated [rights]
+        transition! {
+            grant_hold(pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(pd);
+                require pre.domains.contains(to);
+                // The granted hold must belong to pd and carry the right to grant it
+                require pre.holds.contains(he);
+                require he.src() == pd;
+                require he.rights().contains(Right::Grant);
+                // Rights can only ever be attenuated
+                require rights.subset_of(he.rights());
+                // The receiver must have enough quota left for a Resource it does not hold yet
+                require he.dst() is Resource && !holds_resource(pre.holds, to, he.dst()->res) ==> ({
+                    let r = pre.resources[he.dst()->res];
+                    pre.charged(to, r.rtype()) + r.size() <= pre.quota(to, r.rtype())
+                });
+                // And for a reservation it does not hold yet
+                require he.dst() is Space && carved_out(pre.partitions, he.dst()->space)
+                    && !holds_space(pre.holds, to, he.dst()->space) ==> ({
+                    let sp = pre.spaces[he.dst()->space];
+                    pre.charged(to, sp.rtype()) + sp.vals().len() <= pre.quota(to, sp.rtype())
+                });
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(to);
+                // A driver may not give away what its devices can reach
+                require pre.pins(pd, he.dst()) ==> to == pd;
+                // A handler may not give away the interrupt lines routed to it
+                require pre.handles(pd, he.dst()) ==> to == pd;
+                // A client may not give away what it was granted before it closes the Request
+                require pre.granted_to(pd, he.dst()) ==> to == pd;
+                // A lent Resource is not handed out any further until it is reclaimed
+                require !pre.is_lent(he.dst());
+                // The granted hold edge must be new, as it takes the place of he among the
+                // derivations, which an existing hold edge already has a place of its own in
+                require !pre.holds.contains(HoldEdge { src: to, dst: he.dst(), rights });
+
+                update holds = pre.holds.remove(he).insert(HoldEdge { src: to, dst: he.dst(), rights });
+                update derived_from = pre.hand_over_derivations(he, HoldEdge { src: to, dst: he.dst(), rights });
+                update depth = pre.depth.insert(HoldEdge { src: to, dst: he.dst(), rights }, pre.rank(he));
+                update history = pre.history.push(Step::grant_hold(pd, to, he, rights));
+            }
+        }
+
+        /// The [pd] ProtectionDomain revokes every hold edge derived from its [h] hold, directly
+        /// or not, and keeps [h] itself. Hold edges handed out by sharing, lending, assigning a
+        /// device or routing an interrupt derive from the one they were handed out from, so pd
+        /// takes back whatever it shared without tracking who shared it further. The Resource
+        /// mappings which relied on a revoked hold edge go with it.
+        transition! {
+            revoke_hold(pd: ProtectionDomain, h: HoldEdge)
+            {
+                // The revoked hold must belong to pd
+                require pre.holds.contains(h);
+                require h.src() == pd;
+                // Leases are only ever taken back by reclaiming them
+                require !pre.is_lent(h.dst());
+                // None of the revoked hold edges may be pinned by a device, an interrupt line or a
+                // Request
+                require forall |k: HoldEdge| #[trigger] pre.descends_from(k, h) && pre.holds.contains(k) && k != h ==> {
+                    &&& !pre.pins(k.src(), k.dst())
+                    &&& !pre.handles(k.src(), k.dst())
+                    &&& !pre.granted_to(k.src(), k.dst())
+                };
+
+                let hold_edge_filter = |k: HoldEdge| -> (bool) { k == h || !pre.descends_from(k, h) };
+                let map_edge_filter = |me: MapEdge| -> (bool) { pre.map_survives(me, h.dst(), pre.holds.filter(hold_edge_filter)) };
+
+                // Spaces are never left unmapped, so the spaces mapped through the revoked hold
+                // edges must be unmapped first
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) && !map_edge_filter(me) ==> me is ResourceMap;
+
+                update holds = pre.holds.filter(hold_edge_filter);
+                update maps = pre.maps.filter(map_edge_filter);
+                update derived_from = pre.prune_derivations(pre.holds.filter(hold_edge_filter));
+                update history = pre.history.push(Step::revoke_hold(pd, h));
+            }
+        }
+
+        /// Advance the epoch, expiring the leases which were lent until it
+        transition! {
+            advance_epoch()
+            {
+                update epoch = pre.epoch + 1;
+                update history = pre.history.push(Step::advance_epoch());
+            }
+        }
+
+        /// The [lender] ProtectionDomain lends the Resource it holds through [he] to [borrower],
+        /// which holds it with the attenuated [rights] until the [expiry] epoch. The lender keeps
+        /// its own hold, and whatever the borrower maps of the Resource in the meantime goes with
+        /// the lease once it is reclaimed
+        transition! {
+            lend_hold(lender: ProtectionDomain, borrower: ProtectionDomain, he: HoldEdge, rights: Set<Right>, expiry: nat)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(lender);
+                require pre.domains.contains(borrower);
+                require lender != borrower;
+                // The lent hold must be on a Resource, belong to the lender and carry the right to
+                // grant it
+                require pre.holds.contains(he);
+                require he.src() == lender;
+                require he.dst() is Resource;
+                require he.rights().contains(Right::Grant);
+                // Rights can only ever be attenuated
+                require rights.subset_of(he.rights());
+                // The lease must not have expired already
+                require pre.epoch < expiry;
+                // The Resource must not be lent already, in use as memory, or granted by a Request
+                // which is not closed yet
+                require !pre.is_lent(he.dst());
+                require !pre.is_mapped(he.dst());
+                require forall |id: RequestId| !#[trigger] pre.granted_by(id, he.dst()->res);
+                // The borrower must not hold the Resource yet, and must have enough quota left for it
+                require !pre.holds_with_rights(borrower, he.dst(), Set::empty());
+                require ({
+                    let r = pre.resources[he.dst()->res];
+                    pre.charged(borrower, r.rtype()) + r.size() <= pre.quota(borrower, r.rtype())
+                });
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(borrower);
+
+                let lease = HoldEdge { src: borrower, dst: he.dst(), rights };
+
+                update holds = pre.holds.insert(lease);
+                update leases = pre.leases.insert(lease, Lease { lender, expiry });
+                update derived_from = pre.derive(lease, he);
+                update depth = pre.deepen(lease, he);
+                update history = pre.history.push(Step::lend_hold(lender, borrower, he, rights, expiry));
+            }
+        }
+
+        /// The [lender] ProtectionDomain reclaims the expired lease [he], revoking the borrower's
+        /// hold and every mapping of the lent Resource
+        transition! {
+            reclaim_lease(lender: ProtectionDomain, he: HoldEdge)
+            {
+                // The lease must have been lent by the lender, and have expired
+                require pre.leases.contains_key(he);
+                require pre.leases[he].lender() == lender;
+                require pre.leases[he].expiry() <= pre.epoch;
+
+                let map_edge_filter = |me: MapEdge| -> (bool) { !me.touches(he.dst()) };
+
+                update holds = pre.holds.remove(he);
+                update maps = pre.maps.filter(map_edge_filter);
+                update leases = pre.leases.remove(he);
+                update derived_from = pre.prune_derivations(pre.holds.remove(he));
+                update history = pre.history.push(Step::reclaim_lease(lender, he));
+            }
+        }
+
+        /// The [from] ProtectionDomain hands [amount] of its unused quota for [rtype] over to [to],
+        /// a ProtectionDomain it created
+        transition! {
+            delegate_quota(from: ProtectionDomain, to: ProtectionDomain, rtype: ResourceType<K>, amount: nat)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(from);
+                require pre.domains.contains(to);
+                require from != to;
+                // Quota is only ever handed down, to a child of [from]
+                require pre.parents.contains_key(to) && pre.parents[to] == from;
+                // Only quota which is not charged for can be delegated
+                require pre.charged(from, rtype) + amount <= pre.quota(from, rtype);
+
+                update quotas = pre.quotas
+                    .insert((from, rtype), (pre.quota(from, rtype) - amount) as nat)
+                    .insert((to, rtype), pre.quota(to, rtype) + amount);
+                update history = pre.history.push(Step::delegate_quota(from, to, rtype, amount));
+            }
+        }
+
+        /// Assign the [device] ProtectionDomain to the [driver] ProtectionDomain. Every access of
+        /// the device goes through the [iommu] ResourceSpace, which it holds with [rights]
+        transition! {
+            assign_device(driver: ProtectionDomain, device: ProtectionDomain, iommu: SpaceId, rights: Set<Right>)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(driver);
+                require pre.domains.contains(device);
+                require driver != device;
+                // The device must not already be assigned, nor hold anything
+                require !pre.devices.contains_key(device);
+                require forall |he: HoldEdge| #[trigger] pre.holds.contains(he) ==> he.src() != device;
+                // The IOMMU space must exist and not be in use by another device
+                require pre.spaces.contains_key(iommu);
+                require !pre.is_iommu(iommu);
+                // The IOMMU space must not be programmed yet
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> me.src_node() != (ResourceLike::Space { space: iommu });
+                // The driver must hold the IOMMU space with the right to program it, and the device
+                // may only receive attenuated rights on it
+                require pre.holds_with_rights(driver, ResourceLike::Space { space: iommu }, rights.insert(Right::Map));
+                // The device must have enough quota left for an IOMMU space which is a reservation
+                require carved_out(pre.partitions, iommu) ==>
+                    pre.charged(device, pre.spaces[iommu].rtype()) + pre.spaces[iommu].vals().len()
+                        <= pre.quota(device, pre.spaces[iommu].rtype());
+
+                update devices = pre.devices.insert(device, Device { driver, iommu });
+                update holds = pre.holds.insert(HoldEdge { src: device, dst: ResourceLike::Space { space: iommu }, rights });
+                update derived_from = pre.derive(
+                    HoldEdge { src: device, dst: ResourceLike::Space { space: iommu }, rights },
+                    pre.hold_edge_with_rights(driver, ResourceLike::Space { space: iommu }, rights.insert(Right::Map)),
+                );
+                update depth = pre.deepen(
+                    HoldEdge { src: device, dst: ResourceLike::Space { space: iommu }, rights },
+                    pre.hold_edge_with_rights(driver, ResourceLike::Space { space: iommu }, rights.insert(Right::Map)),
+                );
+                update history = pre.history.push(Step::assign_device(driver, device, iommu, rights));
+            }
+        }
+
+        /// The [driver] ProtectionDomain programs the IOMMU of its [device] with the [me] mapping
+        transition! {
+            program_iommu(driver: ProtectionDomain, device: ProtectionDomain, me: MapEdge)
+            {
+                // The device must be assigned to the driver
+                require pre.devices.contains_key(device);
+                require pre.devices[device].driver() == driver;
+                // The mapping must be from the IOMMU space of the device
+                require me.src_node() == (ResourceLike::Space { space: pre.devices[device].iommu() });
+                // The MapEdge must be between nodes in the graph
+                require pre.node_in_graph(me.src_node());
+                require pre.node_in_graph(me.dst_node());
+                // The MapEdge must be well formed
+                require me.well_formed(pre.resources, pre.spaces);
+                // No other SpaceMap may translate the vals it translates, and together with those
+                // fanning out of the IOMMU space it must translate every val of it
+                require me is SpaceMap ==> pre.translates_disjointly(me);
+                require me is SpaceMap ==> pre.completes_fan_out(me);
+                // The driver may not permit the device more access than it has on the dst itself
+                require pre.holds_with_rights(driver, me.dst_node(), me.perms().insert(Right::Map));
+                // Retyped Resources can no longer be mapped as plain memory, nor can metadata
+                require !pre.is_retyped(me.dst_node());
+                require !pre.is_metadata_node(me.dst_node());
+                // A lent Resource never backs a space, which could not outlive the lease
+                require me is SpaceBacking ==> !pre.is_lent(me.dst_node());
+
+                update maps = pre.maps.insert(me);
+                update history = pre.history.push(Step::program_iommu(driver, device, me));
+            }
+        }
+
+        /// The [pd] ProtectionDomain routes the interrupt line [irq] to the [handler]
+        /// ProtectionDomain, which comes to hold it with the attenuated [rights]
+        transition! {
+            route_interrupt(pd: ProtectionDomain, irq: ResourceId, handler: ProtectionDomain, rights: Set<Right>)
+            {
+                // Both Protection Domains must exist
+                require pre.domains.contains(pd);
+                require pre.domains.contains(handler);
+                // The interrupt line must exist
+                require pre.resources.contains_key(irq);
+                require pre.resources[irq].rtype().delivered();
+                // pd must hold the interrupt line with the right to grant it
+                require pre.holds_with_rights(pd, ResourceLike::Resource { res: irq }, rights.insert(Right::Grant));
+                // The interrupt line must not already be routed
+                require !pre.is_routed(irq);
+                // A lent interrupt line is not routed until it is reclaimed
+                require !pre.is_lent(ResourceLike::Resource { res: irq });
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(handler);
+                // The handler must have enough quota left for an interrupt line it does not hold yet
+                require !holds_resource(pre.holds, handler, irq) ==>
+                    pre.charged(handler, pre.resources[irq].rtype()) + pre.resources[irq].size()
+                        <= pre.quota(handler, pre.resources[irq].rtype());
+
+                update holds = pre.holds.insert(HoldEdge { src: handler, dst: ResourceLike::Resource { res: irq }, rights });
+                update derived_from = pre.derive(
+                    HoldEdge { src: handler, dst: ResourceLike::Resource { res: irq }, rights },
+                    pre.hold_edge_with_rights(pd, ResourceLike::Resource { res: irq }, rights.insert(Right::Grant)),
+                );
+                update depth = pre.deepen(
+                    HoldEdge { src: handler, dst: ResourceLike::Resource { res: irq }, rights },
+                    pre.hold_edge_with_rights(pd, ResourceLike::Resource { res: irq }, rights.insert(Right::Grant)),
+                );
+                update maps = pre.maps.insert(MapEdge::Delivery { dl_src: irq, dl_dst: handler });
+                update history = pre.history.push(Step::route_interrupt(pd, irq, handler, rights));
+            }
+        }
+
+        /// The [pd] ProtectionDomain stops the delivery of the interrupt line [irq]. The handler
+        /// keeps holding the line.
+        transition! {
+            unroute_interrupt(pd: ProtectionDomain, irq: ResourceId)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // pd must hold the interrupt line with the right to grant it
+                require pre.holds_with_rights(pd, ResourceLike::Resource { res: irq }, Set::empty().insert(Right::Grant));
+
+                let map_edge_filter = |me: MapEdge| -> (bool) { !(me is Delivery && me->dl_src == irq) };
+
+                update maps = pre.maps.filter(map_edge_filter);
+                update history = pre.history.push(Step::unroute_interrupt(pd, irq));
+            }
+        }
+
+        /// The [pd] ProtectionDomain creates the virtual ResourceSpace [space] with attributes
+        /// [res], which is mapped by [me] and held with [rights]. Managing the space is charged to
+        /// the [meta] Resources of pd, such as the memory of its page tables.
+        transition! {
+            create_resource_space(pd: ProtectionDomain, space: SpaceId, res: ResourceSpace<K>, me: MapEdge, meta: Set<ResourceId>, rights: Set<Right>)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The new ResourceSpace must not already exist, and only virtual spaces are created
+                require !pre.spaces.contains_key(space);
+                require res.rtype() is Virtual;
+                // The space must be mapped onto a node in the graph
+                require me.src_node() == (ResourceLike::Space { space });
+                require pre.node_in_graph(me.dst_node());
+                require me.well_formed(pre.resources, pre.spaces.insert(space, res));
+                // A SpaceMap is the only one fanning out of the new space, so it translates all of it
+                require me is SpaceMap ==> me->sm_vals == res.vals();
+                // The Protection Domain must hold the dst with the right to map it, may not permit
+                // more access through the mapping than it has on the dst, and the rights on the new
+                // space may only attenuate those it has on the dst
+                require pre.holds_with_rights(pd, me.dst_node(), me.perms().insert(Right::Map));
+                require pre.holds_with_rights(pd, me.dst_node(), rights);
+                // Retyped Resources can no longer be mapped as plain memory, nor can metadata
+                require !pre.is_retyped(me.dst_node());
+                require !pre.is_metadata_node(me.dst_node());
+                require !(me.dst_node() is Resource && meta.contains(me.dst_node()->res));
+                // A lent Resource never backs a space, which could not outlive the lease
+                require !pre.is_lent(me.dst_node());
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
+                // The metadata must be physical Resources held by pd, which are neither in use as
+                // memory nor charged to another space
+                require meta.finite();
+                require forall |r: ResourceId| #[trigger] meta.contains(r) ==> {
+                    &&& pre.resources.contains_key(r)
+                    &&& pre.resources[r].rtype() is Physical
+                    &&& pre.holds_with_rights(pd, ResourceLike::Resource { res: r }, Set::empty())
+                    &&& !pre.is_mapped(ResourceLike::Resource { res: r })
+                    &&& !pre.is_metadata(r)
+                    &&& !pre.is_lent(ResourceLike::Resource { res: r })
+                };
+                // The metadata must cover the cost of managing the space
+                require pre.metadata_cost(res.rtype(), res.vals()) <= set_sum(meta, resource_size(pre.resources));
+
+                update spaces = pre.spaces.insert(space, res);
+                update maps = pre.maps.insert(me);
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights });
+                update metadata = pre.metadata.insert(space, meta);
+                update history = pre.history.push(Step::create_resource_space(pd, space, res, me, meta, rights));
+            }
+        }
+
+        /// Carve a new [child] ResourceSpace managing [vals] out of an existing [parent]
+        /// ResourceSpace. The parent keeps its vals, but can no longer allocate the ones handed to
+        /// the child.
+        transition! {
+            split_space(pd: ProtectionDomain, parent: SpaceId, child: SpaceId, vals: Set<nat>, rights: Set<Right>)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The parent Resource Space must exist
+                require pre.spaces.contains_key(parent);
+                // The child Resource Space must not already exist
+                require !pre.spaces.contains_key(child);
+                // The rights on the child may only attenuate those pd has on the parent space
+                require pre.holds_with_rights(pd, ResourceLike::Space { space: parent }, rights);
+                // The child vals must be managed by the parent
+                require vals.subset_of(pre.spaces[parent].vals());
+                // None of the child vals may already be allocated from the parent
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.dst() == parent ==>
+                    vals.disjoint(pre.resources[se.src()].range());
+                // None of the child vals may already be carved out into a sibling
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == parent ==>
+                    pre.spaces[pe.src()].vals().disjoint(vals);
+                // The child is a reservation, pd must have enough quota left for its vals
+                require pre.charged(pd, pre.spaces[parent].rtype()) + vals.len() <= pre.quota(pd, pre.spaces[parent].rtype());
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
+
+                // The child is of the same type as the parent
+                let space = ResourceSpace { rtype: pre.spaces[parent].rtype(), vals };
+
+                update spaces = pre.spaces.insert(child, space);
+                update partitions = pre.partitions.insert(PartitionEdge { src: child, dst: parent });
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Space { space: child }, rights });
+                update history = pre.history.push(Step::split_space(pd, parent, child, vals, rights));
+            }
+        }
+
+        /// The [pd] ProtectionDomain admits the new time reservation [budget] on the core of the
+        /// time space [source] it holds, covering the [vals] slots of the core's period, and holds
+        /// it with [rights]. Admission control only lets the reservation in if none of its slots
+        /// is admitted to another reservation on the core, and spends its budget out of the quota
+        /// of pd.
+        transition! {
+            create_budget(pd: ProtectionDomain, source: SpaceId, budget: SpaceId, vals: Set<nat>, rights: Set<Right>)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The source must be a time space, and the rights on the reservation may only
+                // attenuate those pd has on it
+                require pre.spaces.contains_key(source);
+                require pre.spaces[source].rtype() is Time;
+                require pre.holds_with_rights(pd, ResourceLike::Space { space: source }, rights);
+                // The new ResourceSpace must not already exist
+                require !pre.spaces.contains_key(budget);
+                // Devices only ever hold their IOMMU space
+                require !pre.devices.contains_key(pd);
+
+                let rtype = pre.spaces[source].rtype();
+                let core = rtype->Time_0;
+
+                // The reservation may only cover slots of its core's period
+                require vals.subset_of(pre.period_slots(core));
+                // None of the slots may already be admitted to another reservation on the core
+                require forall |s: SpaceId| #[trigger] pre.admitted(core).contains(s) ==> pre.spaces[s].vals().disjoint(vals);
+                // pd must have enough quota left for the budget, which it spends on it
+                require pre.charged(pd, rtype) + vals.len() <= pre.quota(pd, rtype);
+
+                update spaces = pre.spaces.insert(budget, ResourceSpace { rtype, vals });
+                update holds = pre.holds.insert(HoldEdge { src: pd, dst: ResourceLike::Space { space: budget }, rights });
+                update quotas = pre.quotas.insert((pd, rtype), (pre.quota(pd, rtype) - vals.len()) as nat);
+                update history = pre.history.push(Step::create_budget(pd, source, budget, vals, rights));
+            }
+        }
+
+        /// Grow or shrink the vals managed by an existing ResourceSpace, keeping its identity.
+        ///
+        /// A physical space which was set up in initialize can only shrink, as no transition
+        /// introduces new physical vals.
+        transition! {
+            resize_space(pd: ProtectionDomain, space: SpaceId, vals: Set<nat>)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The Resource Space must exist
+                require pre.spaces.contains_key(space);
+                // pd must hold the space with the right to write it, which changes the vals it manages
+                require pre.holds_with_rights(pd, ResourceLike::Space { space }, Set::empty().insert(Right::Write));
+                // Live resources allocated from the space must stay in range
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.dst() == space ==>
+                    pre.resources[se.src()].range().subset_of(vals);
+                // Children carved out of the space must stay in range
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == space ==>
+                    pre.spaces[pe.src()].vals().subset_of(vals);
+                // If the space was carved out of a parent, the new vals must respect the partitioning
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space ==> {
+                    &&& vals.subset_of(pre.spaces[pe.dst()].vals())
+                    &&& forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.dst() == pe.dst() ==>
+                        vals.disjoint(pre.resources[se.src()].range())
+                    &&& forall |sib: PartitionEdge| #[trigger] pre.partitions.contains(sib) && sib.dst() == pe.dst() && sib.src() != space ==>
+                        pre.spaces[sib.src()].vals().disjoint(vals)
+                };
+                // Physical spaces which were not carved out of a parent may not grow
+                require pre.spaces[space].rtype() is Physical
+                    && (forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space)
+                    ==> vals.subset_of(pre.spaces[space].vals());
+                // A time reservation may not cover slots outside of its core's period
+                require pre.spaces[space].rtype() is Time ==> vals.subset_of(pre.period_slots(pre.spaces[space].rtype()->Time_0));
+                // A time reservation admitted on its core may not grow onto the slots admitted to
+                // another one
+                require pre.spaces[space].rtype() is Time && !carved_out(pre.partitions, space) ==>
+                    forall |s: SpaceId| #[trigger] pre.admitted(pre.spaces[space].rtype()->Time_0).contains(s) && s != space ==>
+                        pre.spaces[s].vals().disjoint(vals);
+                // A space fanning out through SpaceMaps may not grow past the vals they translate
+                require pre.fans_out(space) ==> vals.subset_of(pre.spaces[space].vals());
+                // The metadata charged for the space must cover the cost of managing the new vals
+                require pre.metadata.contains_key(space) ==>
+                    pre.metadata_cost(pre.spaces[space].rtype(), vals) <= set_sum(pre.metadata[space], resource_size(pre.resources));
+                // Each holder of a reservation must have enough quota left for the vals it grows by
+                require carved_out(pre.partitions, space) ==> forall |p: ProtectionDomain| #[trigger] holds_space(pre.holds, p, space) ==>
+                    pre.charged(p, pre.spaces[space].rtype()) + vals.len() <= pre.quota(p, pre.spaces[space].rtype()) + pre.spaces[space].vals().len();
+
+                let resized = pre.spaces.insert(space, ResourceSpace { rtype: pre.spaces[space].rtype(), vals });
+
+                // Mappings from and onto the space must still translate into their dst
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> me.well_formed(pre.resources, resized);
+
+                update spaces = resized;
+                update history = pre.history.push(Step::resize_space(pd, space, vals));
+            }
+        }
+
+        /// Merge the [other] ResourceSpace back into its sibling [keep]; both must have been carved
+        /// out of [parent]. The merged space keeps the identity of [keep].
+        ///
+        /// Merging [other] away drops every hold on it, so the [pd] ProtectionDomain must be its
+        /// only holder: rather than revoking the holds of other ProtectionDomains behind their back
+        /// or handing them a hold on [keep] they never had, they must release [other] first.
+        transition! {
+            merge_spaces(pd: ProtectionDomain, parent: SpaceId, keep: SpaceId, other: SpaceId)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The two spaces must be distinct siblings
+                require keep != other;
+                require pre.partitions.contains(PartitionEdge { src: keep, dst: parent });
+                require pre.partitions.contains(PartitionEdge { src: other, dst: parent });
+                // pd must hold the kept space with the right to write it, as it grows, and the merged
+                // away space with the right to destroy it
+                require pre.holds_with_rights(pd, ResourceLike::Space { space: keep }, Set::empty().insert(Right::Write));
+                require pre.holds_with_rights(pd, ResourceLike::Space { space: other }, Set::empty().insert(Right::Destroy));
+                // No other Protection Domain may hold the merged away space
+                require forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.dst() == (ResourceLike::Space { space: other }) ==> he.src() == pd;
+                // The merged away space must have no live resources
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) ==> se.dst() != other;
+                // The merged away space must not be the IOMMU of a device
+                require !pre.is_iommu(other);
+                // The merged away space must have no children
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.dst() != other;
+                // The kept space must not fan out through SpaceMaps, which would leave the vals it
+                // gains untranslated
+                require !pre.fans_out(keep);
+                // The merged away space must not be mapped or being used to map
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> ({
+                    ||| me is SpaceBacking && me->sb_src != other
+                    ||| me is SpaceMap && me->sm_src != other && me->sm_dst != other
+                    ||| me is ResourceMap
+                    ||| me is Delivery });
+
+                let merged = ResourceSpace {
+                    rtype: pre.spaces[keep].rtype(),
+                    vals: pre.spaces[keep].vals().union(pre.spaces[other].vals()),
+                };
+                let merged_spaces = pre.spaces.remove(other).insert(keep, merged);
+                let spacelike = ResourceLike::Space { space: other };
+                let hold_edge_filter = |he: HoldEdge| -> (bool) { he.dst() != spacelike };
+
+                // Mappings from and onto the merged space must still translate into their dst
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> me.well_formed(pre.resources, merged_spaces);
+                // Each holder of [keep] must have enough quota left for the vals it grows by, less
+                // those of [other] when it held that reservation too
+                require forall |p: ProtectionDomain| #[trigger] holds_space(pre.holds, p, keep) ==>
+                    pre.charged(p, merged.rtype()) + merged.vals().len() <= pre.quota(p, merged.rtype()) + pre.spaces[keep].vals().len()
+                        + if (holds_space(pre.holds, p, other)) { pre.spaces[other].vals().len() } else { 0 };
+
+                update spaces = merged_spaces;
+                update partitions = pre.partitions.remove(PartitionEdge { src: other, dst: parent });
+                update holds = pre.holds.filter(hold_edge_filter);
+                update derived_from = pre.prune_derivations(pre.holds.filter(hold_edge_filter));
+                update history = pre.history.push(Step::merge_spaces(pd, parent, keep, other));
+            }
+        }
+
+        /// Create a Virtual ResourceSpace
+        /// TODO
+        //transition! {
+        //    create_virtual_space(space: ResourceSpace, 
+
+        /// Remove a ResourceSpace
+        ///
+        /// Note (2025-03-17)
+        ///
+        /// Should this be an operation on a ResourceSpace which has already revoked all its
+        /// allocations and Request edges, or should it rather operate as a recursive operation
+        /// which deletes Resources and Request edges?
+        ///
+        /// The [pd] ProtectionDomain removes the virtual [space], which has no allocations,
+        /// children or mappings onto it left. Its own mappings go with it, and the metadata
+        /// charged for it and a Resource retyped into it are released
+        ///
+        transition! {
+            destroy_resource_space(pd: ProtectionDomain, space: SpaceId)
+            {
+                // The Protection Domain must exist
+                require pre.domains.contains(pd);
+                // The space must be a virtual space, held by pd with the right to destroy it
+                require pre.spaces.contains_key(space);
+                require pre.spaces[space].rtype() is Virtual;
+                require pre.holds_with_rights(pd, ResourceLike::Space { space }, Set::empty().insert(Right::Destroy));
+                // The space must have no live resources
+                require forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) ==> se.dst() != space;
+                // The space must not be carved out of another, nor have children
+                require forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space && pe.dst() != space;
+                // Nothing may be mapped onto the space
+                require forall |me: MapEdge| #[trigger] pre.maps.contains(me) ==> !me.maps_onto(ResourceLike::Space { space });
+                // The space must not be the IOMMU of a device
+                require !pre.is_iommu(space);
+
+                let spacelike = ResourceLike::Space { space };
+                let hold_edge_filter = |he: HoldEdge| -> (bool) { he.dst() != spacelike };
+                let map_edge_filter = |me: MapEdge| -> (bool) { me.src_node() != spacelike };
+                let kept_retyped = pre.retyped.dom().filter(|r: ResourceId| pre.retyped[r] != space);
+
+                update spaces = pre.spaces.remove(space);
+                update holds = pre.holds.filter(hold_edge_filter);
+                update derived_from = pre.prune_derivations(pre.holds.filter(hold_edge_filter));
+                update maps = pre.maps.filter(map_edge_filter);
+                update metadata = pre.metadata.remove(space);
+                update retyped = pre.retyped.restrict(kept_retyped);
+                update history = pre.history.push(Step::destroy_resource_space(pd, space));
+            }
+        }
+
+
+        // Inductiveness Proofs:
+
+        #[inductive(initialize)]
+        fn initialize_inductive(post: Self, physical_spaces: Map<SpaceId, ResourceSpace<K>>, initial_quotas: Map<ResourceType<K>, nat>, core_periods: Map<nat, nat>, space_costs: Map<ResourceType<K>, nat>) {
+
+            let map_fn = |space: SpaceId| -> HoldEdge {
+                HoldEdge { src: ProtectionDomain { id: 0 }, dst: ResourceLike::Space { space }, rights: Set::full() }
+            };
+
+            // Invariant: holds_is_finite
+            assert(post.holds.finite()) by {
+                set_map_finite_preserving(physical_spaces.dom(), map_fn);
+            }
+            
             // Invariant: hold_edge_to_each_space
-            assert forall |s: ResourceSpace| post.spaces.contains(s) implies
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
                 exists |he: HoldEdge| #[trigger] post.holds.contains(he) && he.dst() is Space && he.dst()->space == s by {
                     let he = map_fn(s);
                     assert(post.holds.contains(he) && he.dst() is Space && he.dst()->space == s);
                 }
+
+            // Invariant: usage_within_quota
+            assert forall |pd: ProtectionDomain, rtype: ResourceType<K>|
+                #[trigger] post.charged(pd, rtype) <= post.quota(pd, rtype) by {
+                    assert(held_resources(post.resources, post.holds, pd, rtype) =~= Set::empty());
+                    assert(held_reservations(post.spaces, post.partitions, post.holds, pd, rtype) =~= Set::empty());
+                }
+
+            // Invariant: physical_spaces_within_initial
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Physical implies
+                post.spaces[s].vals().subset_of(post.physical_vals(post.spaces[s].rtype())) by {
+                    assert forall |v: nat| post.spaces[s].vals().contains(v) implies
+                        #[trigger] post.physical_vals(post.spaces[s].rtype()).contains(v) by {
+                            assert(post.initial_spaces.contains_key(s));
+                        }
+                }
             
+
+            // Invariant: holds_traced
+            assert forall |he: HoldEdge| #[trigger] post.holds.contains(he) implies post.traced(he.dst(), he.src()) by {
+                assert(traces_back(post.history, he.dst(), he.src(), ProtectionDomain { id: 0 }, 0));
+            }
         }
 
         #[inductive(create_resource)]
-        fn create_resource_inductive(pre: Self, post: Self, pd: ProtectionDomain, res: Resource, space: ResourceSpace)
+        fn create_resource_inductive(pre: Self, post: Self, pd: ProtectionDomain, id: ResourceId, res: Resource<K>, space: SpaceId, rights: Set<Right>)
         {
+            let new_edge = SubsetEdge { src: id, dst: space };
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Resource { res: id }, rights };
+            // The new Resource is fresh, so nothing holds it yet
+            assert(!pre.holds.contains(new_hold));
+
+            // The new resource does not change any existing resource
+            assert forall |r: ResourceId| pre.resources.contains_key(r) implies post.resources[r] == pre.resources[r] by {
+                assert(r != id);
+            }
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == pd && r == id) by {
+                    let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                        && he.dst() == (ResourceLike::Resource { res: r });
+                    if (he != new_hold) {
+                        assert(pre.holds.contains(he));
+                    }
+                }
+            assert forall |p: ProtectionDomain| !#[trigger] holds_resource(pre.holds, p, id) by {
+                if (holds_resource(pre.holds, p, id)) {
+                    let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == p
+                        && he.dst() == (ResourceLike::Resource { res: id });
+                    assert(pre.resources.contains_key(id));
+                }
+            }
+            assert(!held_resources(pre.resources, pre.holds, pd, res.rtype()).contains(id));
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies holds_space(pre.holds, p, s) by {
+                let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                    && he.dst() == (ResourceLike::Space { space: s });
+                assert(pre.holds.contains(he));
+            }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                    if (p == pd && t == res.rtype()) {
+                        assert(holds_resource(post.holds, pd, id));
+                        assert(held_resources(post.resources, post.holds, p, t)
+                            =~= held_resources(pre.resources, pre.holds, p, t).insert(id));
+                        lemma_usage_insert(pre.resources, pre.holds, post.resources, post.holds, p, t, id);
+                    } else {
+                        assert(held_resources(post.resources, post.holds, p, t)
+                            =~= held_resources(pre.resources, pre.holds, p, t));
+                        lemma_usage_subset(pre.resources, pre.holds, post.resources, post.holds, p, t);
+                    }
+                }
+
             // Invariant: hold_edge_to_each_resource
-            assert forall |r: Resource| post.resources.contains(r) implies
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
                 exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
-                    let e = if (r == res) {
-                        HoldEdge { src: pd, dst: ResourceLike::Resource { res }}
+                    let e = if (r == id) {
+                        HoldEdge { src: pd, dst: ResourceLike::Resource { res: id }, rights }
                     } else {
                         choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r
                     };
@@ -438,36 +2560,156 @@ state_machine!
                 }
 
             // Invariant: hold_edge_to_each_space
-            assert forall |s: ResourceSpace| post.spaces.contains(s) implies
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
                 exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
                     let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
                     assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
                 }
 
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(me.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                if (se != new_edge) {
+                    assert(se.well_formed(pre.resources, pre.spaces));
+                }
+            }
+
             // Invariant: resources_are_subsets
-            assert forall |r: Resource| post.resources.contains(r) implies
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
                 exists |e: SubsetEdge| #[trigger] post.subsets.contains(e) && e.src() == r by {
-                    let e = if (r == res) {
-                        SubsetEdge { src: res, dst: space }
+                    let e = if (r == id) {
+                        new_edge
                     } else {
                         choose |e| pre.subsets.contains(e) && #[trigger] e.src() == r
                     };
                     assert(post.subsets.contains(e) && e.src() == r);
                 }
+
+            // Invariant: subset_src_are_unique
+            assert forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) implies se.src() != id by {
+                assert(pre.resources.contains_key(se.src()));
+            }
+
+            // Invariant: resources_well_formed
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies #[trigger] post.resources[r].well_formed() by {
+                if (r != id) {
+                    assert(pre.resources[r].well_formed());
+                }
+            }
+
+            // Invariant: subset_ranges_disjoint
+            assert forall |e1: SubsetEdge, e2: SubsetEdge|
+                #[trigger] post.subsets.contains(e1) && #[trigger] post.subsets.contains(e2) && e1.dst() == e2.dst()
+                && e1.src() != e2.src() implies post.resources[e1.src()].range().disjoint(post.resources[e2.src()].range()) by {
+                    if (e1 == new_edge) {
+                        assert(pre.subsets.contains(e2));
+                        assert(pre.resources[e2.src()].range().disjoint(res.range()));
+                    } else if (e2 == new_edge) {
+                        assert(pre.subsets.contains(e1));
+                        assert(pre.resources[e1.src()].range().disjoint(res.range()));
+                    } else {
+                        assert(pre.subsets.contains(e1) && pre.subsets.contains(e2));
+                    }
+                }
+
+            // Invariant: partition_excludes_parent_allocations
+            assert forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] post.partitions.contains(pe) && #[trigger] post.subsets.contains(se) && se.dst() == pe.dst()
+                implies post.spaces[pe.src()].vals().disjoint(post.resources[se.src()].range()) by {
+                    if (se != new_edge) {
+                        assert(pre.subsets.contains(se));
+                    }
+                }
+
+            // Invariant: physical_ranges_disjoint
+            assert forall |r1: ResourceId, r2: ResourceId|
+                #[trigger] post.resources.contains_key(r1) && #[trigger] post.resources.contains_key(r2) && r1 != r2
+                && post.resources[r1].rtype() is Physical && post.resources[r1].rtype() == post.resources[r2].rtype()
+                implies post.resources[r1].range().disjoint(post.resources[r2].range()) by {
+                    if (r1 == id) {
+                        assert(pre.resources.contains_key(r2) && pre.resources[r2].range().disjoint(res.range()));
+                    } else if (r2 == id) {
+                        assert(pre.resources.contains_key(r1) && pre.resources[r1].range().disjoint(res.range()));
+                    }
+                }
+
+            // Invariant: metadata_covers_cost
+            assert forall |s: SpaceId| #[trigger] post.metadata.contains_key(s) implies
+                post.metadata_cost(post.spaces[s].rtype(), post.spaces[s].vals()) <= set_sum(post.metadata[s], resource_size(post.resources)) by {
+                    // The new Resource is not charged as metadata yet
+                    lemma_set_sum_congruent(pre.metadata[s], resource_size(post.resources), resource_size(pre.resources));
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_resource(pd, id, res, space, rights));
         }
 
         #[inductive(destroy_resource)]
-        fn destroy_resource_inductive(pre: Self, post: Self, pd: ProtectionDomain, res: Resource)
+        fn destroy_resource_inductive(pre: Self, post: Self, pd: ProtectionDomain, res: ResourceId)
         {
+            // Invariant: devices_confined
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.pins(he.src(), he.dst()) implies post.holds.contains(he) by {
+                let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev)
+                    && pre.devices[dev].driver() == he.src() && pre.pinned_by(dev, he.dst());
+                let iommu = ResourceLike::Space { space: pre.devices[dev].iommu() };
+                if (he.dst() != iommu) {
+                    let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me) && me.src_node() == iommu && me.dst_node() == he.dst();
+                    assert(pre.maps.contains(me));
+                }
+            }
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.handles(he.src(), he.dst()) implies post.holds.contains(he) by {
+                let d = choose |d: MapEdge| #[trigger] pre.maps.contains(d) && d is Delivery && d->dl_dst == he.src()
+                    && he.dst() == d.src_node();
+                assert(pre.maps.contains(d));
+            }
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // The remaining resources are unchanged
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies post.resources[r] == pre.resources[r] by { }
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    assert forall |r: ResourceId| #[trigger] held_resources(post.resources, post.holds, p, t).contains(r) implies
+                        held_resources(pre.resources, pre.holds, p, t).contains(r) by {
+                            let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                                && he.dst() == (ResourceLike::Resource { res: r });
+                            assert(pre.holds.contains(he));
+                        }
+                    lemma_usage_subset(pre.resources, pre.holds, post.resources, post.holds, p, t);
+                    assert forall |s: SpaceId| #[trigger] held_reservations(post.spaces, post.partitions, post.holds, p, t).contains(s) implies
+                        held_reservations(pre.spaces, pre.partitions, pre.holds, p, t).contains(s) by {
+                            let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                                && he.dst() == (ResourceLike::Space { space: s });
+                            assert(pre.holds.contains(he));
+                        }
+                    lemma_reserved_subset(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds, p, t);
+                }
+
             // Invariant: hold_edge_to_each_resource
-            assert forall |r: Resource| post.resources.contains(r) implies
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
                 exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
                     let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
                     assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
                 }
 
             // Invariant: hold_edge_to_each_space
-            assert forall |s: ResourceSpace| post.spaces.contains(s) implies
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
                 exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
                     let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
                     assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
@@ -477,34 +2719,5867 @@ state_machine!
             assert(post.subset_nodes_in_graph()) by {
                 assert(forall |e| #[trigger] post.subsets.contains(e) ==> e.src() != res);
             }
-        }
 
-        #[inductive(create_pd)]
-        fn create_pd_inductive(pre: Self, post: Self, parent: ProtectionDomain, pd: ProtectionDomain) { }
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(me.well_formed(pre.resources, pre.spaces));
+            }
 
-        #[inductive(destroy_pd)]
-        fn destry_pd_inductve(pre: Self, post: Self, pd: ProtectionDomain) { }
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
 
-        #[inductive(create_request_edge)]
-        fn create_request_edge_inductive(pre: Self, post: Self, req: RequestEdge) { }
+            // Invariant: retyped_nodes_in_graph
+            assert forall |r: ResourceId| #[trigger] post.retyped.contains_key(r) implies post.resources.contains_key(r) by {
+                // A retyped Resource is in use as a backing, so it cannot have been destroyed
+                assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+            }
 
-        #[inductive(destroy_request_edge)]
-        fn destroy_request_edge_inductive(pre: Self, post: Self, req: RequestEdge) { }
+            // Invariant: metadata_nodes_in_graph
+            assert forall |s: SpaceId, r: ResourceId| #[trigger] post.metadata.contains_key(s) && #[trigger] post.metadata[s].contains(r)
+                implies post.resources.contains_key(r) by {
+                    // The destroyed Resource was not charged as metadata
+                    assert(pre.is_metadata(r));
+                }
 
-        #[inductive(create_map_edge)]
-        fn create_map_edge_inductive(pre: Self, post: Self, me: MapEdge) { }
+            // Invariant: metadata_covers_cost
+            assert forall |s: SpaceId| #[trigger] post.metadata.contains_key(s) implies
+                post.metadata_cost(post.spaces[s].rtype(), post.spaces[s].vals()) <= set_sum(post.metadata[s], resource_size(post.resources)) by {
+                    assert forall |r: ResourceId| #[trigger] pre.metadata[s].contains(r) implies r != res by {
+                        assert(pre.is_metadata(r));
+                    }
+                    lemma_set_sum_congruent(pre.metadata[s], resource_size(post.resources), resource_size(pre.resources));
+                }
 
-        #[inductive(create_resource_space)]
-        fn create_resource_space_inductive(pre: Self, post: Self, space: ResourceSpace, backing: ResourceLike) { }
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
 
-        #[inductive(destroy_resource_space)]
-        fn destroy_resource_space_inductive(pre: Self, post: Self, space: ResourceSpace) { }
+            // Invariant: derivation_forest
+            lemma_pruned_derivations_form_forest(pre, post);
 
-        // Helper functions:
-        
-    } // osmosis_dag
-} // state_machine!
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::destroy_resource(pd, res));
+        }
 
-} // verus!
+        #[inductive(create_pd)]
+        fn create_pd_inductive(pre: Self, post: Self, parent: ProtectionDomain, pd: ProtectionDomain)
+        {
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_pd(parent, pd));
+        }
+
+        #[inductive(destroy_pd)]
+        fn destry_pd_inductve(pre: Self, post: Self, pd: ProtectionDomain)
+        {
+            // Invariant: devices_confined
+            assert forall |d: ProtectionDomain| #[trigger] pre.devices.contains_key(d) implies post.domains.contains(d) by {
+                assert(pre.device_confined(d));
+            }
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: delivery_nodes_in_graph
+            assert forall |d: MapEdge| #[trigger] pre.maps.contains(d) && d is Delivery implies d->dl_dst != pd by {
+                let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == d->dl_dst
+                    && he.dst() == (ResourceLike::Resource { res: d->dl_src });
+                assert(pre.holds.contains(he));
+            }
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
 
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
 
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::destroy_pd(pd));
+        }
+
+        #[inductive(create_request_edge)]
+        fn create_request_edge_inductive(pre: Self, post: Self, req: RequestEdge<K>)
+        {
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_request_edge(req));
+        }
+
+        #[inductive(destroy_request_edge)]
+        fn destroy_request_edge_inductive(pre: Self, post: Self, req: RequestEdge<K>)
+        {
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::destroy_request_edge(req));
+        }
+
+        #[inductive(issue_request)]
+        fn issue_request_inductive(pre: Self, post: Self, id: RequestId, req: Request<K>)
+        {
+            // Invariant: pending_requests_over_edges
+            assert forall |i: RequestId| #[trigger] post.is_pending(i) implies post.requests.contains(post.issued[i].edge()) by {
+                if (i != id) {
+                    assert(pre.is_pending(i));
+                }
+            }
+
+            // Invariant: granted_requests_held
+            // Nothing was handed over for the new Request yet
+            assert(post.issued[id].granted() == Set::<ResourceId>::empty());
+            assert forall |i: RequestId, r: ResourceId| #[trigger] post.granted_by(i, r) implies pre.granted_by(i, r) by { }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::issue_request(id, req));
+        }
+
+        #[inductive(fulfil_request)]
+        fn fulfil_request_inductive(pre: Self, post: Self, id: RequestId, client: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+        {
+            let req = pre.issued[id];
+            let r = he.dst()->res;
+            let granted = HoldEdge { src: client, dst: he.dst(), rights };
+            assert(he.dst() == (ResourceLike::Resource { res: r }));
+            // The Resource is fresh to the Request, so it accounts for one more Resource
+            assert(!pre.granted_by(id, r));
+            assert(post.issued[id].granted() == req.granted().insert(r));
+            assert(post.issued[id].granted().len() == req.granted().len() + 1);
+
+            // Invariant: pending_requests_over_edges
+            assert forall |i: RequestId| #[trigger] post.is_pending(i) implies post.requests.contains(post.issued[i].edge()) by {
+                assert(pre.is_pending(i) && post.issued[i].edge() == pre.issued[i].edge());
+            }
+
+            // Invariant: devices_confined
+            assert forall |p: ProtectionDomain, n: ResourceLike| #[trigger] pre.pins(p, n) implies
+                post.holds_with_rights(p, n, Set::empty()) by {
+                    pre.lemma_pinned_nodes_are_held(p, n);
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == p && w.dst() == n
+                        && Set::<Right>::empty().subset_of(w.rights());
+                    // The server does not hand over what its devices can reach
+                    assert(w != he);
+                    assert(post.holds.contains(w));
+                }
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            assert forall |d: MapEdge| #[trigger] post.maps.contains(d) && d is Delivery implies holds_resource(post.holds, d->dl_dst, d->dl_src) by {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == d->dl_dst
+                    && w.dst() == (ResourceLike::Resource { res: d->dl_src });
+                // The server does not hand over the interrupt lines routed to it
+                assert(w != he);
+                assert(post.holds.contains(w));
+            }
+
+            // Invariant: granted_requests_held
+            assert forall |i: RequestId, q: ResourceId| #[trigger] post.granted_by(i, q) implies
+                (i == id && q == r) || pre.granted_by(i, q) by { }
+            assert forall |i: RequestId, q: ResourceId| #[trigger] post.granted_by(i, q) implies
+                holds_resource(post.holds, post.issued[i].client(), q) by {
+                    if (i == id && q == r) {
+                        assert(post.holds.contains(granted));
+                    } else {
+                        assert(pre.granted_by(i, q));
+                        let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pre.issued[i].client()
+                            && w.dst() == (ResourceLike::Resource { res: q });
+                        // The handed over Resource was not accounted for by any Request
+                        assert(w != he);
+                        assert(post.holds.contains(w));
+                    }
+                }
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, q: ResourceId| #[trigger] holds_resource(post.holds, p, q) implies
+                holds_resource(pre.holds, p, q) || (p == client && he.dst() == (ResourceLike::Resource { res: q })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: q });
+                    if (e != granted) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    assert(e != granted);
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, client, he.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        client, he.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |q: ResourceId| post.resources.contains_key(q) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == q by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == q;
+                    let e = if (e == he) { granted } else { e };
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == q);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(e != he);
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            // The handed over hold edge is new to the client, so it is nobody's parent yet, and
+            // takes over the depth of he along with its place
+            assert(!pre.holds.contains(granted));
+            assert forall |k: HoldEdge| #[trigger] post.derived_from.contains_key(k) implies {
+                &&& post.holds.contains(k)
+                &&& post.holds.contains(post.derived_from[k])
+                &&& post.derived_from[k].dst() == k.dst()
+                &&& post.rank(post.derived_from[k]) < post.rank(k)
+            } by {
+                if (k == granted) {
+                    assert(pre.derived_from.contains_key(he));
+                    assert(pre.holds.contains(pre.derived_from[he]));
+                    assert(post.rank(pre.derived_from[he]) == pre.rank(pre.derived_from[he]));
+                } else {
+                    assert(pre.derived_from.contains_key(k));
+                    assert(pre.holds.contains(k) && k != granted);
+                    if (pre.derived_from[k] != he) {
+                        assert(pre.holds.contains(pre.derived_from[k]));
+                        assert(post.rank(pre.derived_from[k]) == pre.rank(pre.derived_from[k]));
+                    }
+                }
+            }
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::fulfil_request(id, client, he, rights));
+        }
+
+        #[inductive(deny_request)]
+        fn deny_request_inductive(pre: Self, post: Self, id: RequestId)
+        {
+            // Invariant: pending_requests_over_edges
+            assert forall |i: RequestId| #[trigger] post.is_pending(i) implies pre.is_pending(i) by { }
+
+            // Invariant: granted_requests_held
+            assert forall |i: RequestId, r: ResourceId| #[trigger] post.granted_by(i, r) implies pre.granted_by(i, r) by { }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::deny_request(id));
+        }
+
+        #[inductive(close_request)]
+        fn close_request_inductive(pre: Self, post: Self, id: RequestId)
+        {
+            // Invariant: pending_requests_over_edges
+            assert forall |i: RequestId| #[trigger] post.is_pending(i) implies pre.is_pending(i) by { }
+
+            // Invariant: granted_requests_held
+            assert forall |i: RequestId, r: ResourceId| #[trigger] post.granted_by(i, r) implies pre.granted_by(i, r) by { }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::close_request(id));
+        }
+
+        #[inductive(create_map_edge)]
+        fn create_map_edge_inductive(pre: Self, post: Self, pd: ProtectionDomain, me: MapEdge)
+        {
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_map_edge(pd, me));
+
+            // Invariant: translations_disjoint
+            lemma_translations_stay_disjoint(pre, post, me);
+
+            // Invariant: translations_cover
+            if (me is SpaceMap) {
+                lemma_fan_out_completed(pre, post, me);
+            } else {
+                lemma_translations_stay_covering(pre, post);
+            }
+        }
+
+        #[inductive(split_map_edge)]
+        fn split_map_edge_inductive(pre: Self, post: Self, pd: ProtectionDomain, me: MapEdge, part: MapEdge)
+        {
+            let narrowed = me.without_vals(part->sm_vals);
+
+            // Invariant: map_edges_well_formed
+            // Invariant: map_nodes_in_graph
+            assert(pre.maps.contains(me) && me.well_formed(pre.resources, pre.spaces));
+            assert(narrowed.well_formed(post.resources, post.spaces));
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    if (e == me) {
+                        // A split SpaceMap keeps translating the rest of its vals
+                        assert(post.maps.contains(narrowed) && narrowed is SpaceMap);
+                    } else {
+                        assert(post.maps.contains(e));
+                    }
+                }
+            }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                assert(pre.is_metadata(r) && !pre.is_mapped(ResourceLike::Resource { res: r }));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    // The SpaceMaps of a split only map spaces
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    assert(pre.maps.contains(m));
+                    assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::split_map_edge(pd, me, part));
+
+            // Invariant: translations_disjoint
+            lemma_split_translations_stay_disjoint(pre, post, me, narrowed, part);
+
+            // Invariant: translations_cover
+            lemma_split_translations_stay_covering(pre, post, me, narrowed, part);
+        }
+
+        #[inductive(cow_write)]
+        fn cow_write_inductive(pre: Self, post: Self, pd: ProtectionDomain, me: MapEdge, v: nat, copy: ResourceId)
+        {
+            let copied = pre.resource_mapping(me->rm_src, copy, me->rm_attrs.perms, me->rm_attrs.cache, false);
+            let cs = pre.allocated_from(copy);
+            let split = me.single(v, cs, pre.resources[copy].val());
+            pre.lemma_allocated_from(copy);
+
+            // Invariant: map_edges_well_formed
+            // Invariant: map_nodes_in_graph
+            assert(pre.maps.contains(me) && me.well_formed(pre.resources, pre.spaces));
+            if (me is ResourceMap) {
+                assert(copied.well_formed(post.resources, post.spaces));
+            } else {
+                // The written val is handed over to the first val of the copy, which the space it is
+                // allocated from manages
+                assert(pre.resources[copy].well_formed());
+                assert(pre.resources[copy].range().contains(pre.resources[copy].val()));
+                assert(me.without(v).well_formed(post.resources, post.spaces));
+                assert(split.well_formed(post.resources, post.spaces));
+            }
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    if (e == me) {
+                        // A split SpaceMap keeps translating the rest of its vals
+                        assert(post.maps.contains(me.without(v)) && me.without(v) is SpaceMap);
+                    } else {
+                        assert(post.maps.contains(e));
+                    }
+                }
+            }
+
+            // Invariant: retyped_resources_exclusive
+            assert forall |r: ResourceId| #[trigger] pre.retyped.contains_key(r) implies
+                !copied.touches(ResourceLike::Resource { res: r }) by {
+                    // Only its backing maps a retyped Resource, while the written one is mapped by a
+                    // ResourceMap and the copy was not mapped at all
+                    assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+                    assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                assert(pre.is_metadata(r) && !pre.is_mapped(ResourceLike::Resource { res: r }));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    if (pre.maps.contains(m)) {
+                        assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                    } else if (m == copied) {
+                        // The copy is not metadata, and the written Resource was mapped already,
+                        // while the SpaceMaps of a split only map spaces
+                        assert(r != copy);
+                        assert(pre.maps.contains(me) && me.src_node() == copied.src_node());
+                        assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                    }
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::cow_write(pd, me, v, copy));
+
+            // Invariant: translations_disjoint
+            // Invariant: translations_cover
+            if (me is ResourceMap) {
+                lemma_translations_stay_disjoint(pre, post, copied);
+                lemma_translations_stay_covering(pre, post);
+            } else {
+                lemma_split_translations_stay_disjoint(pre, post, me, me.without(v), split);
+                lemma_split_translations_stay_covering(pre, post, me, me.without(v), split);
+            }
+        }
+
+        #[inductive(demand_fault)]
+        fn demand_fault_inductive(pre: Self, post: Self, pd: ProtectionDomain, me: MapEdge, v: nat, backing: ResourceId)
+        {
+            let res = pre.faulted(me, v);
+            let new_edge = SubsetEdge { src: backing, dst: me->sm_dst };
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Resource { res: backing }, rights: me.perms().insert(Right::Map) };
+            let faulted = me.single(v, me->sm_dst, me->sm_attrs.translation.apply(v) as nat);
+            pre.lemma_translation_lands_in_dst(me, v);
+            assert(res.range() =~= Set::empty().insert(res.val()));
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // The backing does not change any existing resource
+            assert forall |r: ResourceId| pre.resources.contains_key(r) implies post.resources[r] == pre.resources[r] by {
+                assert(r != backing);
+            }
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == pd && r == backing) by {
+                    let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                        && he.dst() == (ResourceLike::Resource { res: r });
+                    if (he != new_hold) {
+                        assert(pre.holds.contains(he));
+                    }
+                }
+            assert forall |p: ProtectionDomain| !#[trigger] holds_resource(pre.holds, p, backing) by {
+                if (holds_resource(pre.holds, p, backing)) {
+                    let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == p
+                        && he.dst() == (ResourceLike::Resource { res: backing });
+                    assert(pre.resources.contains_key(backing));
+                }
+            }
+            assert(!held_resources(pre.resources, pre.holds, pd, res.rtype()).contains(backing));
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies holds_space(pre.holds, p, s) by {
+                let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p
+                    && he.dst() == (ResourceLike::Space { space: s });
+                assert(pre.holds.contains(he));
+            }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                    if (p == pd && t == res.rtype()) {
+                        assert(holds_resource(post.holds, pd, backing));
+                        assert(held_resources(post.resources, post.holds, p, t)
+                            =~= held_resources(pre.resources, pre.holds, p, t).insert(backing));
+                        lemma_usage_insert(pre.resources, pre.holds, post.resources, post.holds, p, t, backing);
+                    } else {
+                        assert(held_resources(post.resources, post.holds, p, t)
+                            =~= held_resources(pre.resources, pre.holds, p, t));
+                        lemma_usage_subset(pre.resources, pre.holds, post.resources, post.holds, p, t);
+                    }
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = if (r == backing) {
+                        new_hold
+                    } else {
+                        choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r
+                    };
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert(pre.maps.contains(me) && me.well_formed(pre.resources, pre.spaces));
+            assert forall |m: MapEdge| post.maps.contains(m) implies #[trigger] m.well_formed(post.resources, post.spaces) by {
+                if (m == me.without(v)) {
+                    assert(me.well_formed(post.resources, post.spaces));
+                } else if (m == faulted) {
+                    assert(me->sm_vals.contains(v));
+                } else {
+                    assert(m.well_formed(pre.resources, pre.spaces));
+                }
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    if (e == me) {
+                        // The touched val moves out of the lazy mapping, which keeps the rest
+                        assert(post.maps.contains(me.without(v)) && me.without(v) is SpaceMap);
+                    } else {
+                        assert(post.maps.contains(e));
+                    }
+                }
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                if (se != new_edge) {
+                    assert(se.well_formed(pre.resources, pre.spaces));
+                }
+            }
+
+            // Invariant: resources_are_subsets
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: SubsetEdge| #[trigger] post.subsets.contains(e) && e.src() == r by {
+                    let e = if (r == backing) {
+                        new_edge
+                    } else {
+                        choose |e| pre.subsets.contains(e) && #[trigger] e.src() == r
+                    };
+                    assert(post.subsets.contains(e) && e.src() == r);
+                }
+
+            // Invariant: subset_src_are_unique
+            assert forall |se: SubsetEdge| #[trigger] pre.subsets.contains(se) implies se.src() != backing by {
+                assert(pre.resources.contains_key(se.src()));
+            }
+
+            // Invariant: resources_well_formed
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies #[trigger] post.resources[r].well_formed() by {
+                if (r != backing) {
+                    assert(pre.resources[r].well_formed());
+                }
+            }
+
+            // Invariant: subset_ranges_disjoint
+            assert forall |e1: SubsetEdge, e2: SubsetEdge|
+                #[trigger] post.subsets.contains(e1) && #[trigger] post.subsets.contains(e2) && e1.dst() == e2.dst()
+                && e1.src() != e2.src() implies post.resources[e1.src()].range().disjoint(post.resources[e2.src()].range()) by {
+                    if (e1 == new_edge) {
+                        assert(pre.subsets.contains(e2));
+                        assert(pre.resources[e2.src()].range().disjoint(res.range()));
+                    } else if (e2 == new_edge) {
+                        assert(pre.subsets.contains(e1));
+                        assert(pre.resources[e1.src()].range().disjoint(res.range()));
+                    } else {
+                        assert(pre.subsets.contains(e1) && pre.subsets.contains(e2));
+                    }
+                }
+
+            // Invariant: partition_excludes_parent_allocations
+            assert forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] post.partitions.contains(pe) && #[trigger] post.subsets.contains(se) && se.dst() == pe.dst()
+                implies post.spaces[pe.src()].vals().disjoint(post.resources[se.src()].range()) by {
+                    if (se != new_edge) {
+                        assert(pre.subsets.contains(se));
+                    }
+                }
+
+            // Invariant: physical_ranges_disjoint
+            assert forall |r1: ResourceId, r2: ResourceId|
+                #[trigger] post.resources.contains_key(r1) && #[trigger] post.resources.contains_key(r2) && r1 != r2
+                && post.resources[r1].rtype() is Physical && post.resources[r1].rtype() == post.resources[r2].rtype()
+                implies post.resources[r1].range().disjoint(post.resources[r2].range()) by {
+                    if (r1 == backing) {
+                        assert(pre.resources.contains_key(r2) && pre.resources[r2].range().disjoint(res.range()));
+                    } else if (r2 == backing) {
+                        assert(pre.resources.contains_key(r1) && pre.resources[r1].range().disjoint(res.range()));
+                    }
+                }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                assert(pre.is_metadata(r) && !pre.is_mapped(ResourceLike::Resource { res: r }));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    // The mappings of the fault only map spaces
+                    assert(m != me.without(v) && m != faulted);
+                    assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                }
+            }
+
+            // Invariant: metadata_covers_cost
+            assert forall |s: SpaceId| #[trigger] post.metadata.contains_key(s) implies
+                post.metadata_cost(post.spaces[s].rtype(), post.spaces[s].vals()) <= set_sum(post.metadata[s], resource_size(post.resources)) by {
+                    // The backing is not charged as metadata
+                    lemma_set_sum_congruent(pre.metadata[s], resource_size(post.resources), resource_size(pre.resources));
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            let dh = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(dh.rights()));
+            lemma_history_records_step(pre, post, Step::demand_fault(pd, me, v, backing));
+
+            // Invariant: translations_disjoint
+            lemma_split_translations_stay_disjoint(pre, post, me, me.without(v), faulted);
+
+            // Invariant: translations_cover
+            lemma_split_translations_stay_covering(pre, post, me, me.without(v), faulted);
+        }
+
+        #[inductive(remap)]
+        fn remap_inductive(pre: Self, post: Self, pd: ProtectionDomain, me: MapEdge, dst: ResourceId)
+        {
+            let moved = pre.retarget(me, dst);
+
+            // Invariant: map_edges_well_formed
+            assert(pre.maps.contains(me) && me.well_formed(pre.resources, pre.spaces));
+            assert(moved.well_formed(post.resources, post.spaces));
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    if (e == me) {
+                        // The space keeps its backing, only onto a different Resource
+                        assert(post.maps.contains(moved) && moved is SpaceBacking && moved->sb_src == s);
+                    } else {
+                        assert(post.maps.contains(e));
+                    }
+                }
+            }
+
+            // Invariant: retyped_nodes_in_graph
+            assert forall |r: ResourceId| #[trigger] post.retyped.contains_key(r) implies
+                post.maps.contains(MapEdge::SpaceBacking { sb_src: post.retyped[r], sb_dst: r }) by {
+                    // The moved backing is not the one of a retyped Resource
+                    assert(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r } != me);
+                }
+
+            // Invariant: retyped_resources_exclusive
+            assert forall |r: ResourceId| #[trigger] pre.retyped.contains_key(r) implies
+                !moved.touches(ResourceLike::Resource { res: r }) by {
+                    // Only its backing, which is not moved, maps a retyped Resource, and the new
+                    // backing was not mapped at all
+                    assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::remap(pd, me, dst));
+
+            // Invariant: translations_disjoint
+            lemma_translations_stay_disjoint(pre, post, moved);
+
+            // Invariant: translations_cover
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(retype)]
+        fn retype_inductive(pre: Self, post: Self, pd: ProtectionDomain, res: ResourceId, space: SpaceId, rtype: ResourceType<K>, vals: Set<nat>, rights: Set<Right>)
+        {
+            let backing = MapEdge::SpaceBacking { sb_src: space, sb_dst: res };
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+
+            // The new space is not the IOMMU of a device, as those are in the graph
+            if (pre.is_iommu(space)) {
+                let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev) && pre.devices[dev].iommu() == space;
+                assert(pre.device_confined(dev));
+            }
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(e != new_hold && pre.holds.contains(e));
+                }
+
+            // The new space does not change any existing space
+            assert forall |s: SpaceId| pre.spaces.contains_key(s) implies post.spaces[s] == pre.spaces[s] by {
+                assert(s != space);
+            }
+
+            // The new space is not a reservation, as partition edges are between spaces in the graph
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == pd && s == space) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != new_hold) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert(!carved_out(post.partitions, space));
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, new_hold.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = if (s == space) {
+                        new_hold
+                    } else {
+                        choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s
+                    };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                if (me != backing) {
+                    assert(me.well_formed(pre.resources, pre.spaces));
+                }
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (s == space) {
+                    assert(post.maps.contains(backing));
+                } else if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: retyped_resources_exclusive
+            assert forall |r: ResourceId, me: MapEdge| #[trigger] post.retyped.contains_key(r) && #[trigger] post.maps.contains(me)
+                && me.touches(ResourceLike::Resource { res: r })
+                implies me == (MapEdge::SpaceBacking { sb_src: post.retyped[r], sb_dst: r }) by {
+                    if (r == res) {
+                        // The retyped Resource was not mapped before
+                        if (me != backing) {
+                            assert(pre.maps.contains(me) && pre.is_mapped(ResourceLike::Resource { res }));
+                        }
+                    } else if (me == backing) {
+                        // A Resource which was retyped before is mapped, unlike the new one
+                        assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+                        assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                    }
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: spaces_disjoint
+            // The new space is virtual, the physical ones keep their vals
+            assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2)
+                && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+                implies s1 != space && s2 != space by {}
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::retype(pd, res, space, rtype, vals, rights));
+
+            // Invariant: translations_disjoint
+            lemma_translations_stay_disjoint(pre, post, MapEdge::SpaceBacking { sb_src: space, sb_dst: res });
+
+            // Invariant: translations_cover
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap implies
+                post.spaces[m->sm_src] == pre.spaces[m->sm_src] by {
+                // The new space is fresh, so no SpaceMap fans out of it
+                assert(pre.maps.contains(m) && pre.spaces.contains_key(m->sm_src));
+            }
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(share_hold)]
+        fn share_hold_inductive(pre: Self, post: Self, pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+        {
+            let granted = HoldEdge { src: to, dst: he.dst(), rights };
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == to && he.dst() == (ResourceLike::Resource { res: r })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    if (e != granted) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == to && he.dst() == (ResourceLike::Space { space: s })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != granted) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, to, he.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        to, he.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            lemma_derived_hold_joins_forest(pre, post, granted, he);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::share_hold(pd, to, he, rights));
+        }
+
+        #[inductive(grant_hold)]
+        fn grant_hold_inductive(pre: Self, post: Self, pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>)
+        {
+            let granted = HoldEdge { src: to, dst: he.dst(), rights };
+
+            // Invariant: devices_confined
+            assert forall |p: ProtectionDomain, n: ResourceLike| #[trigger] pre.pins(p, n) implies
+                post.holds_with_rights(p, n, Set::empty()) by {
+                    pre.lemma_pinned_nodes_are_held(p, n);
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == p && w.dst() == n
+                        && Set::<Right>::empty().subset_of(w.rights());
+                    if (w == he) {
+                        assert(post.holds.contains(granted));
+                    } else {
+                        assert(post.holds.contains(w));
+                    }
+                }
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            assert forall |d: MapEdge| #[trigger] post.maps.contains(d) && d is Delivery implies holds_resource(post.holds, d->dl_dst, d->dl_src) by {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == d->dl_dst
+                    && w.dst() == (ResourceLike::Resource { res: d->dl_src });
+                if (w == he) {
+                    assert(pre.handles(pd, he.dst()));
+                    assert(post.holds.contains(granted));
+                } else {
+                    assert(post.holds.contains(w));
+                }
+            }
+
+            // Invariant: granted_requests_held
+            assert forall |i: RequestId, r: ResourceId| #[trigger] post.granted_by(i, r) implies
+                holds_resource(post.holds, post.issued[i].client(), r) by {
+                    assert(pre.granted_by(i, r));
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pre.issued[i].client()
+                        && w.dst() == (ResourceLike::Resource { res: r });
+                    if (w == he) {
+                        assert(pre.granted_to(pd, he.dst()));
+                        assert(post.holds.contains(granted));
+                    } else {
+                        assert(post.holds.contains(w));
+                    }
+                }
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == to && he.dst() == (ResourceLike::Resource { res: r })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    if (e != granted) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == to && he.dst() == (ResourceLike::Space { space: s })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != granted) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, to, he.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        to, he.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    let e = if (e == he) { granted } else { e };
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    let e = if (e == he) { granted } else { e };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            // The granted hold edge is new, so it is nobody's parent yet, and takes over the depth
+            // of he along with its place
+            assert forall |k: HoldEdge| #[trigger] post.derived_from.contains_key(k) implies {
+                &&& post.holds.contains(k)
+                &&& post.holds.contains(post.derived_from[k])
+                &&& post.derived_from[k].dst() == k.dst()
+                &&& post.rank(post.derived_from[k]) < post.rank(k)
+            } by {
+                if (k == granted) {
+                    assert(pre.derived_from.contains_key(he));
+                    assert(pre.holds.contains(pre.derived_from[he]));
+                    assert(post.rank(pre.derived_from[he]) == pre.rank(pre.derived_from[he]));
+                } else {
+                    assert(pre.derived_from.contains_key(k));
+                    assert(pre.holds.contains(k) && k != granted);
+                    if (pre.derived_from[k] != he) {
+                        assert(pre.holds.contains(pre.derived_from[k]));
+                        assert(post.rank(pre.derived_from[k]) == pre.rank(pre.derived_from[k]));
+                    }
+                }
+            }
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::grant_hold(pd, to, he, rights));
+        }
+
+        #[inductive(revoke_hold)]
+        fn revoke_hold_inductive(pre: Self, post: Self, pd: ProtectionDomain, h: HoldEdge)
+        {
+            // Every revoked hold edge is on the node of h, which pd keeps holding
+            assert forall |k: HoldEdge| #[trigger] pre.holds.contains(k) && !post.holds.contains(k) implies
+                pre.descends_from(k, h) && k != h && k.dst() == h.dst() by {
+                    let fuel = choose |fuel: nat| #[trigger] pre.derived_through(k, h, fuel);
+                    lemma_descendants_hold_same_node(pre, k, h, fuel);
+                }
+            assert(post.holds.contains(h));
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, h.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, h.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    let e = if (post.holds.contains(e)) { e } else { h };
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    let e = if (post.holds.contains(e)) { e } else { h };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            lemma_pruned_derivations_form_forest(pre, post);
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                assert(pre.is_metadata(r));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    assert(pre.maps.contains(m));
+                }
+            }
+
+            // Invariant: spaces_are_mapped
+            // Invariant: retyped_nodes_in_graph
+            // Only Resource mappings are dropped
+            assert(post.maps.subset_of(pre.maps));
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) && !(me is ResourceMap) implies post.maps.contains(me) by { }
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::revoke_hold(pd, h));
+
+            // Invariant: translations_disjoint
+            assert(post.maps.subset_of(pre.maps));
+
+            // Invariant: translations_cover
+            // Only ResourceMaps go with the revoked hold edges
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(advance_epoch)]
+        fn advance_epoch_inductive(pre: Self, post: Self)
+        {
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::advance_epoch());
+        }
+
+        #[inductive(lend_hold)]
+        fn lend_hold_inductive(pre: Self, post: Self, lender: ProtectionDomain, borrower: ProtectionDomain, he: HoldEdge, rights: Set<Right>, expiry: nat)
+        {
+            let lease = HoldEdge { src: borrower, dst: he.dst(), rights };
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == borrower && he.dst() == (ResourceLike::Resource { res: r })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    if (e != lease) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == borrower && he.dst() == (ResourceLike::Space { space: s })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != lease) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, borrower, he.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        borrower, he.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            assert forall |l: HoldEdge| #[trigger] post.leases.contains_key(l) implies post.lease_well_formed(l) by {
+                if (l == lease) {
+                    assert(post.holds.contains(he) && he.dst() == (ResourceLike::Resource { res: he.dst()->res }));
+                    // The borrower held nothing of the Resource before
+                    assert forall |h: HoldEdge| #[trigger] post.holds.contains(h) && h.src() == borrower && h.dst() == he.dst()
+                        implies h == lease by {
+                            if (h != lease) {
+                                assert(pre.holds.contains(h) && Set::<Right>::empty().subset_of(h.rights()));
+                            }
+                        }
+                    assert forall |k: HoldEdge| #[trigger] post.leases.contains_key(k) && k.dst() == he.dst() implies k == lease by {
+                        if (k != lease) {
+                            assert(pre.leases.contains_key(k));
+                        }
+                    }
+                    // Nothing is mapped onto the Resource, let alone backed by it
+                    assert forall |me: MapEdge| #[trigger] post.maps.contains(me) implies
+                        !(me is SpaceBacking && me->sb_dst == he.dst()->res) by {
+                            if (me is SpaceBacking && me->sb_dst == he.dst()->res) {
+                                assert(me.dst_node() == he.dst());
+                            }
+                        }
+                    // A handler holds its interrupt lines, which the borrower does not
+                    if (pre.handles(borrower, he.dst())) {
+                        let d = choose |d: MapEdge| #[trigger] pre.maps.contains(d) && d is Delivery && d->dl_dst == borrower
+                            && he.dst() == d.src_node();
+                        let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == borrower
+                            && w.dst() == (ResourceLike::Resource { res: d->dl_src });
+                        assert(Set::<Right>::empty().subset_of(w.rights()));
+                    }
+                } else {
+                    assert(pre.leases.contains_key(l) && pre.lease_well_formed(l));
+                    assert(l.dst() != he.dst());
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pre.leases[l].lender()
+                        && w.dst() == (ResourceLike::Resource { res: l.dst()->res });
+                    assert(post.holds.contains(w));
+                }
+            }
+
+            // Invariant: derivation_forest
+            lemma_derived_hold_joins_forest(pre, post, lease, he);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::lend_hold(lender, borrower, he, rights, expiry));
+        }
+
+        #[inductive(reclaim_lease)]
+        fn reclaim_lease_inductive(pre: Self, post: Self, lender: ProtectionDomain, he: HoldEdge)
+        {
+            let lent = he.dst();
+            assert(pre.lease_well_formed(he));
+            assert(lent == (ResourceLike::Resource { res: lent->res }));
+            let lender_hold = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == lender
+                && h.dst() == (ResourceLike::Resource { res: lent->res });
+
+            // Only the mappings of the lent Resource are revoked, and it backs no space
+            assert forall |m: MapEdge| #[trigger] pre.maps.contains(m) && !(m is ResourceMap) implies post.maps.contains(m) by {
+                assert(!m.touches(lent));
+            }
+
+            // Invariant: devices_confined
+            assert forall |h: HoldEdge| #[trigger] pre.holds.contains(h) && pre.pins(h.src(), h.dst()) implies post.holds.contains(h) by {
+                if (h == he) {
+                    let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev)
+                        && pre.devices[dev].driver() == h.src() && pre.pinned_by(dev, h.dst());
+                    let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me)
+                        && me.src_node() == (ResourceLike::Space { space: pre.devices[dev].iommu() }) && me.dst_node() == h.dst();
+                    assert(me is SpaceBacking && me->sb_dst == lent->res);
+                }
+            }
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, lender, lent, p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        lender, lent, p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    if (e == he) {
+                        assert(post.holds.contains(lender_hold) && lender_hold.dst() is Resource && lender_hold.dst()->res == r);
+                    } else {
+                        assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                    }
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |m: MapEdge| post.maps.contains(m) implies #[trigger] m.well_formed(post.resources, post.spaces) by {
+                assert(m.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: retyped_nodes_in_graph
+            assert forall |r: ResourceId| #[trigger] post.retyped.contains_key(r) implies
+                post.maps.contains(MapEdge::SpaceBacking { sb_src: post.retyped[r], sb_dst: r }) by {
+                    assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+                }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                assert(pre.is_metadata(r));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    assert(pre.maps.contains(m));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            assert forall |l: HoldEdge| #[trigger] post.leases.contains_key(l) implies post.lease_well_formed(l) by {
+                assert(pre.leases.contains_key(l) && pre.lease_well_formed(l));
+                assert(l.dst() != lent);
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pre.leases[l].lender()
+                    && w.dst() == (ResourceLike::Resource { res: l.dst()->res });
+                assert(post.holds.contains(w));
+            }
+
+            // Invariant: derivation_forest
+            lemma_pruned_derivations_form_forest(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::reclaim_lease(lender, he));
+
+            // Invariant: translations_disjoint
+            assert(post.maps.subset_of(pre.maps));
+
+            // Invariant: translations_cover
+            // Only mappings of the lent Resource go with the lease, while SpaceMaps only map spaces
+            assert(pre.leases.contains_key(he) && he.dst() is Resource);
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(split_space)]
+        fn split_space_inductive(pre: Self, post: Self, pd: ProtectionDomain, parent: SpaceId, child: SpaceId, vals: Set<nat>, rights: Set<Right>)
+        {
+            let new_edge = PartitionEdge { src: child, dst: parent };
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space: child }, rights };
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(e != new_hold && pre.holds.contains(e));
+                }
+
+            // The new space does not change any existing space
+            assert forall |s: SpaceId| pre.spaces.contains_key(s) implies post.spaces[s] == pre.spaces[s] by {
+                assert(s != child);
+            }
+
+            // The child is the only new reservation, of the type of its parent, and nobody held it
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == pd && s == child) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != new_hold) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain| !#[trigger] holds_space(pre.holds, p, child) by {
+                if (holds_space(pre.holds, p, child)) {
+                    let e = choose |e: HoldEdge| #[trigger] pre.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: child });
+                    assert(pre.spaces.contains_key(child));
+                }
+            }
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && carved_out(post.partitions, s) && s != child implies
+                pre.spaces.contains_key(s) && carved_out(pre.partitions, s) && post.spaces[s] == pre.spaces[s] by {
+                    let pe = choose |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) && pe.src() == s;
+                    assert(pre.partitions.contains(pe));
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, new_hold.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                    assert(post.spaces[child].rtype() == pre.spaces[parent].rtype());
+                }
+
+            // Invariant: time_spaces_within_period
+            assert(post.spaces[child].vals().subset_of(pre.spaces[parent].vals()));
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = if (s == child) {
+                        HoldEdge { src: pd, dst: ResourceLike::Space { space: child }, rights }
+                    } else {
+                        choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s
+                    };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(me.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (s == child) {
+                    assert(post.partitions.contains(new_edge) && new_edge.src() == s);
+                } else if (exists |p: PartitionEdge| #[trigger] pre.partitions.contains(p) && p.src() == s) {
+                    let p = choose |p: PartitionEdge| #[trigger] pre.partitions.contains(p) && p.src() == s;
+                    assert(post.partitions.contains(p) && p.src() == s);
+                }
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                if (pe != new_edge) {
+                    assert(pe.well_formed(pre.spaces));
+                }
+            }
+
+            // Invariant: partition_src_are_unique
+            assert forall |e: PartitionEdge| #[trigger] pre.partitions.contains(e) implies e.src() != child by {
+                assert(pre.spaces.contains_key(e.src()));
+            }
+
+            // Invariant: partition_siblings_disjoint
+            assert forall |e1: PartitionEdge, e2: PartitionEdge|
+                #[trigger] post.partitions.contains(e1) && #[trigger] post.partitions.contains(e2)
+                && e1.dst() == e2.dst() && e1.src() != e2.src()
+                implies post.spaces[e1.src()].vals().disjoint(post.spaces[e2.src()].vals()) by {
+                    if (e1 == new_edge) {
+                        assert(pre.partitions.contains(e2));
+                        assert(pre.spaces[e2.src()].vals().disjoint(vals));
+                    } else if (e2 == new_edge) {
+                        assert(pre.partitions.contains(e1));
+                    }
+                }
+
+            // Invariant: partition_excludes_parent_allocations
+            assert forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] post.partitions.contains(pe) && #[trigger] post.subsets.contains(se) && se.dst() == pe.dst()
+                implies post.spaces[pe.src()].vals().disjoint(post.resources[se.src()].range()) by {
+                    if (pe != new_edge) {
+                        assert(pre.partitions.contains(pe));
+                    }
+                }
+
+            // Invariant: physical_spaces_within_initial
+            assert(post.spaces[child].vals().subset_of(pre.spaces[parent].vals()));
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: spaces_disjoint
+            lemma_split_keeps_spaces_disjoint(pre, post, parent, child, vals);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::split_space(pd, parent, child, vals, rights));
+
+            // Invariant: translations_cover
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap implies
+                post.spaces[m->sm_src] == pre.spaces[m->sm_src] by {
+                // The child is fresh, so no SpaceMap fans out of it
+                assert(pre.spaces.contains_key(m->sm_src));
+            }
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(create_budget)]
+        fn create_budget_inductive(pre: Self, post: Self, pd: ProtectionDomain, source: SpaceId, budget: SpaceId, vals: Set<nat>, rights: Set<Right>)
+        {
+            let rtype = pre.spaces[source].rtype();
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space: budget }, rights };
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(e != new_hold && pre.holds.contains(e));
+                }
+
+            // The new space does not change any existing space
+            assert forall |s: SpaceId| pre.spaces.contains_key(s) implies post.spaces[s] == pre.spaces[s] by {
+                assert(s != budget);
+            }
+
+            // The budget is no reservation, as partition edges are between spaces in the graph, so
+            // pd is only charged for it through the quota it spends
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == pd && s == budget) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != new_hold) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert(!carved_out(post.partitions, budget));
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, new_hold.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                    assert(pre.charged(p, t) <= pre.quota(p, t));
+                }
+
+            // Invariant: time_spaces_within_period
+            assert(pre.periods.contains_key(rtype->Time_0));
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = if (s == budget) {
+                        new_hold
+                    } else {
+                        choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s
+                    };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(me.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            // The budget is a time space, the virtual ones keep their mappings and partition edges
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies
+                pre.spaces.contains_key(s) && pre.spaces[s].rtype() is Virtual by {}
+
+            // Invariant: spaces_disjoint
+            // The budget is a time space, the physical ones keep their vals
+            assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2)
+                && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+                implies s1 != budget && s2 != budget by {}
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_budget(pd, source, budget, vals, rights));
+
+            // Invariant: translations_cover
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap implies
+                post.spaces[m->sm_src] == pre.spaces[m->sm_src] by {
+                // The budget is fresh, so no SpaceMap fans out of it
+                assert(pre.spaces.contains_key(m->sm_src));
+            }
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(resize_space)]
+        fn resize_space_inductive(pre: Self, post: Self, pd: ProtectionDomain, space: SpaceId, vals: Set<nat>)
+        {
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(pre.maps.contains(me));
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+            }
+
+            // Invariant: partition_siblings_disjoint
+            assert forall |e1: PartitionEdge, e2: PartitionEdge|
+                #[trigger] post.partitions.contains(e1) && #[trigger] post.partitions.contains(e2)
+                && e1.dst() == e2.dst() && e1.src() != e2.src()
+                implies post.spaces[e1.src()].vals().disjoint(post.spaces[e2.src()].vals()) by {
+                    if (e1.src() == space) {
+                        assert(pre.spaces[e2.src()].vals().disjoint(vals));
+                    } else if (e2.src() == space) {
+                        assert(pre.spaces[e1.src()].vals().disjoint(vals));
+                    }
+                }
+
+            // Invariant: partition_excludes_parent_allocations
+            assert forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] post.partitions.contains(pe) && #[trigger] post.subsets.contains(se) && se.dst() == pe.dst()
+                implies post.spaces[pe.src()].vals().disjoint(post.resources[se.src()].range()) by {
+                    if (pe.src() == space) {
+                        assert(vals.disjoint(post.resources[se.src()].range()));
+                    }
+                }
+
+            // Invariant: physical_spaces_within_initial
+            if (pre.spaces[space].rtype() is Physical) {
+                if (exists |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space) {
+                    // The vals stay within the parent, which is of the same type
+                    let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == space;
+                    assert(pe.well_formed(pre.spaces));
+                    assert(vals.subset_of(pre.spaces[pe.dst()].vals()));
+                } else {
+                    assert(vals.subset_of(pre.spaces[space].vals()));
+                }
+            }
+
+            // Invariant: usage_within_quota
+            // Only the budget of the resized space changes, for each holder when it is a reservation
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_reserved_change(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        p, Some(space), p, t);
+                    if (held_reservations(pre.spaces, pre.partitions, pre.holds, p, t).contains(space)) {
+                        assert(holds_space(pre.holds, p, space));
+                        assert(held_reservations(post.spaces, post.partitions, post.holds, p, t).contains(space));
+                    } else {
+                        assert(!held_reservations(post.spaces, post.partitions, post.holds, p, t).contains(space));
+                    }
+                }
+
+            // Invariant: spaces_disjoint
+            lemma_resize_keeps_spaces_disjoint(pre, post, space, vals);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::resize_space(pd, space, vals));
+
+            // Invariant: translations_cover
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap implies
+                post.spaces[m->sm_src].vals().subset_of(pre.spaces[m->sm_src].vals()) by {
+                // A space fanning out through SpaceMaps only ever shrinks
+                if (m->sm_src == space) {
+                    assert(pre.fans_out(space));
+                }
+            }
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(merge_spaces)]
+        fn merge_spaces_inductive(pre: Self, post: Self, pd: ProtectionDomain, parent: SpaceId, keep: SpaceId, other: SpaceId)
+        {
+            let keep_edge = PartitionEdge { src: keep, dst: parent };
+            let other_edge = PartitionEdge { src: other, dst: parent };
+
+            // Invariant: devices_confined
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.pins(he.src(), he.dst()) implies post.holds.contains(he) by {
+                let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev)
+                    && pre.devices[dev].driver() == he.src() && pre.pinned_by(dev, he.dst());
+                let iommu = ResourceLike::Space { space: pre.devices[dev].iommu() };
+                if (he.dst() != iommu) {
+                    let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me) && me.src_node() == iommu && me.dst_node() == he.dst();
+                    assert(pre.maps.contains(me));
+                }
+            }
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.handles(he.src(), he.dst()) implies post.holds.contains(he) by {
+                let d = choose |d: MapEdge| #[trigger] pre.maps.contains(d) && d is Delivery && d->dl_dst == he.src()
+                    && he.dst() == d.src_node();
+                assert(pre.maps.contains(d));
+            }
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) <==>
+                holds_space(pre.holds, p, s) && s != other by {
+                    if (holds_space(post.holds, p, s)) {
+                        let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                            && e.dst() == (ResourceLike::Space { space: s });
+                        assert(pre.holds.contains(e));
+                    }
+                    if (holds_space(pre.holds, p, s) && s != other) {
+                        let e = choose |e: HoldEdge| #[trigger] pre.holds.contains(e) && e.src() == p
+                            && e.dst() == (ResourceLike::Space { space: s });
+                        assert(post.holds.contains(e));
+                    }
+                }
+            assert(pre.spaces[other].rtype() == pre.spaces[keep].rtype()) by {
+                assert(keep_edge.well_formed(pre.spaces) && other_edge.well_formed(pre.spaces));
+            }
+            // What is reserved drops by the budget of [other] as it goes away, then grows by the
+            // vals [keep] gains
+            let mid_spaces = pre.spaces.remove(other);
+            assert(mid_spaces.dom() =~= pre.spaces.dom().remove(other));
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && carved_out(post.partitions, s) implies
+                carved_out(pre.partitions, s) by {
+                    let pe = choose |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) && pe.src() == s;
+                    assert(pre.partitions.contains(pe));
+                }
+            assert(carved_out(post.partitions, keep) && carved_out(pre.partitions, other));
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, ResourceLike::Space { space: keep }, p, t);
+                    lemma_reserved_change(pre.spaces, pre.partitions, pre.holds, mid_spaces, post.partitions, post.holds,
+                        p, Some(other), p, t);
+                    lemma_reserved_change(mid_spaces, post.partitions, post.holds, post.spaces, post.partitions, post.holds,
+                        p, Some(keep), p, t);
+                    if (holds_space(pre.holds, p, keep) && t == pre.spaces[keep].rtype()) {
+                        assert(held_reservations(mid_spaces, post.partitions, post.holds, p, t).contains(keep));
+                        assert(held_reservations(post.spaces, post.partitions, post.holds, p, t).contains(keep));
+                        if (holds_space(pre.holds, p, other)) {
+                            assert(held_reservations(pre.spaces, pre.partitions, pre.holds, p, t).contains(other));
+                        }
+                    }
+                }
+
+            // The only partition edge leaving the merged away space is the one that was removed
+            assert forall |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) implies
+                pe.src() != other && pe.dst() != other by {
+                    assert(pre.partitions.contains(pe));
+                    if (pe.src() == other) {
+                        assert(pe.dst() == parent);
+                    }
+                }
+
+            // The merged space only ever grows
+            assert(pre.spaces[keep].vals().subset_of(post.spaces[keep].vals()));
+
+            // Invariant: time_spaces_within_period
+            assert(pre.spaces[other].rtype() == pre.spaces[keep].rtype()) by {
+                assert(keep_edge.well_formed(pre.spaces) && other_edge.well_formed(pre.spaces));
+            }
+
+            // Invariant: hold_nodes_in_graph
+            assert forall |e: HoldEdge| #[trigger] post.holds.contains(e) implies {
+                &&& post.domains.contains(e.src())
+                &&& e.dst() is Resource ==> post.resources.contains_key(e.dst()->res)
+                &&& e.dst() is Space ==> post.spaces.contains_key(e.dst()->space)
+            } by {
+                assert(pre.holds.contains(e));
+            }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |me: MapEdge| post.maps.contains(me) implies #[trigger] me.well_formed(post.resources, post.spaces) by {
+                assert(pre.maps.contains(me));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |p: PartitionEdge| #[trigger] pre.partitions.contains(p) && p.src() == s) {
+                    let p = choose |p: PartitionEdge| #[trigger] pre.partitions.contains(p) && p.src() == s;
+                    assert(post.partitions.contains(p) && p.src() == s);
+                }
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+                if (pe.src() == keep) {
+                    assert(pe.dst() == parent);
+                    assert(other_edge.well_formed(pre.spaces));
+                }
+            }
+
+            // Invariant: partition_siblings_disjoint
+            assert forall |e1: PartitionEdge, e2: PartitionEdge|
+                #[trigger] post.partitions.contains(e1) && #[trigger] post.partitions.contains(e2)
+                && e1.dst() == e2.dst() && e1.src() != e2.src()
+                implies post.spaces[e1.src()].vals().disjoint(post.spaces[e2.src()].vals()) by {
+                    if (e1.src() == keep) {
+                        assert(pre.partitions.contains(keep_edge));
+                        assert(pre.partitions.contains(e2) && pre.partitions.contains(other_edge));
+                        assert(pre.spaces[other].vals().disjoint(pre.spaces[e2.src()].vals()));
+                    } else if (e2.src() == keep) {
+                        assert(pre.partitions.contains(e1) && pre.partitions.contains(other_edge));
+                        assert(pre.spaces[e1.src()].vals().disjoint(pre.spaces[other].vals()));
+                    }
+                }
+
+            // Invariant: partition_excludes_parent_allocations
+            assert forall |pe: PartitionEdge, se: SubsetEdge|
+                #[trigger] post.partitions.contains(pe) && #[trigger] post.subsets.contains(se) && se.dst() == pe.dst()
+                implies post.spaces[pe.src()].vals().disjoint(post.resources[se.src()].range()) by {
+                    if (pe.src() == keep) {
+                        assert(pre.partitions.contains(other_edge));
+                    }
+                }
+
+            // Invariant: retyped_nodes_in_graph
+            assert forall |r: ResourceId| #[trigger] post.retyped.contains_key(r) implies post.spaces.contains_key(post.retyped[r]) by {
+                // The merged away space is not backed, so it is no retyped Resource's space
+                assert(pre.maps.contains(MapEdge::SpaceBacking { sb_src: pre.retyped[r], sb_dst: r }));
+            }
+
+            // Invariant: physical_spaces_within_initial
+            assert(pre.spaces[keep].vals().subset_of(pre.physical_vals(pre.spaces[keep].rtype()))
+                || !(pre.spaces[keep].rtype() is Physical));
+            assert(pre.spaces[other].vals().subset_of(pre.physical_vals(pre.spaces[other].rtype()))
+                || !(pre.spaces[other].rtype() is Physical));
+
+            // Invariant: metadata_nodes_in_graph
+            assert(!pre.metadata.contains_key(keep) && !pre.metadata.contains_key(other)) by {
+                assert(pre.partitions.contains(keep_edge) && pre.partitions.contains(other_edge));
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            lemma_pruned_derivations_form_forest(pre, post);
+
+            // Invariant: spaces_disjoint
+            lemma_merge_keeps_spaces_disjoint(pre, post, parent, keep, other);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::merge_spaces(pd, parent, keep, other));
+
+            // Invariant: translations_cover
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap implies
+                post.spaces[m->sm_src] == pre.spaces[m->sm_src] by {
+                // Neither the kept nor the merged away space fans out through SpaceMaps
+                assert(m->sm_src != keep);
+                assert(m->sm_src != other);
+            }
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        #[inductive(delegate_quota)]
+        fn delegate_quota_inductive(pre: Self, post: Self, from: ProtectionDomain, to: ProtectionDomain, rtype: ResourceType<K>, amount: nat)
+        {
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    assert(pre.charged(p, t) <= pre.quota(p, t));
+                }
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::delegate_quota(from, to, rtype, amount));
+        }
+
+        #[inductive(assign_device)]
+        fn assign_device_inductive(pre: Self, post: Self, driver: ProtectionDomain, device: ProtectionDomain, iommu: SpaceId, rights: Set<Right>)
+        {
+            let new_hold = HoldEdge { src: device, dst: ResourceLike::Space { space: iommu }, rights };
+
+            // Invariant: devices_confined
+            assert forall |dev: ProtectionDomain| #[trigger] post.devices.contains_key(dev) implies post.device_confined(dev) by {
+                if (dev == device) {
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == driver
+                        && w.dst() == (ResourceLike::Space { space: iommu }) && rights.insert(Right::Map).subset_of(w.rights());
+                    assert(post.holds.contains(w) && Set::<Right>::empty().subset_of(w.rights()));
+                } else {
+                    let d = pre.devices[dev];
+                    assert(pre.device_confined(dev));
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == d.driver()
+                        && w.dst() == (ResourceLike::Space { space: d.iommu() }) && Set::<Right>::empty().subset_of(w.rights());
+                    assert(post.holds.contains(w));
+                    assert forall |me: MapEdge| #[trigger] post.maps.contains(me) && me.src_node() == (ResourceLike::Space { space: d.iommu() })
+                        implies post.holds_with_rights(d.driver(), me.dst_node(), Set::empty()) by {
+                            let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == d.driver()
+                                && w.dst() == me.dst_node() && Set::<Right>::empty().subset_of(w.rights());
+                            assert(post.holds.contains(w));
+                        }
+                }
+            }
+
+            // Invariant: device_iommus_unique
+            assert forall |d: ProtectionDomain| #[trigger] pre.devices.contains_key(d) implies pre.devices[d].iommu() != iommu by { }
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(e != new_hold && pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == device && s == iommu) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != new_hold) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, device, new_hold.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        device, new_hold.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            let parent = pre.hold_edge_with_rights(driver, ResourceLike::Space { space: iommu }, rights.insert(Right::Map));
+            assert(pre.holds.contains(parent) && parent.dst() == new_hold.dst());
+            lemma_derived_hold_joins_forest(pre, post, new_hold, parent);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::assign_device(driver, device, iommu, rights));
+        }
+
+        #[inductive(route_interrupt)]
+        fn route_interrupt_inductive(pre: Self, post: Self, pd: ProtectionDomain, irq: ResourceId, handler: ProtectionDomain, rights: Set<Right>)
+        {
+            let routed = HoldEdge { src: handler, dst: ResourceLike::Resource { res: irq }, rights };
+            let delivery = MapEdge::Delivery { dl_src: irq, dl_dst: handler };
+
+            // Invariant: map_edges_well_formed
+            // Invariant: map_nodes_in_graph
+            // Invariant: delivery_nodes_in_graph
+            assert(post.maps.contains(delivery));
+
+            // Invariant: interrupts_routed_once
+            assert forall |m1: MapEdge, m2: MapEdge|
+                #[trigger] post.maps.contains(m1) && #[trigger] post.maps.contains(m2) && m1 is Delivery && m2 is Delivery
+                && m1->dl_src == m2->dl_src implies m1 == m2 by {
+                    // The line was not routed before, so no other delivery edge starts at it
+                    if (m1 != delivery && m1->dl_src == irq) {
+                        assert(pre.maps.contains(m1));
+                    }
+                    if (m2 != delivery && m2->dl_src == irq) {
+                        assert(pre.maps.contains(m2));
+                    }
+                }
+
+            // Invariant: retyped_resources_exclusive
+            // Invariant: metadata_exclusive
+            // The delivery touches no node, so no node becomes mapped
+            assert forall |n: ResourceLike| #[trigger] post.is_mapped(n) implies pre.is_mapped(n) by {
+                let me = choose |me: MapEdge| #[trigger] post.maps.contains(me) && me.touches(n);
+                assert(pre.maps.contains(me));
+            }
+
+            // Invariant: handlers_hold_interrupts
+            assert forall |d: MapEdge| #[trigger] post.maps.contains(d) && d is Delivery implies holds_resource(post.holds, d->dl_dst, d->dl_src) by {
+                if (d == delivery) {
+                    assert(post.holds.contains(routed));
+                } else {
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == d->dl_dst
+                        && w.dst() == (ResourceLike::Resource { res: d->dl_src });
+                    assert(post.holds.contains(w));
+                }
+            }
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) || (p == handler && routed.dst() == (ResourceLike::Resource { res: r })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    if (e != routed) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == handler && routed.dst() == (ResourceLike::Space { space: s })) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != routed) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, handler, routed.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        handler, routed.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            let parent = pre.hold_edge_with_rights(pd, ResourceLike::Resource { res: irq }, rights.insert(Right::Grant));
+            assert(pre.holds.contains(parent) && parent.dst() == routed.dst());
+            lemma_derived_hold_joins_forest(pre, post, routed, parent);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::route_interrupt(pd, irq, handler, rights));
+        }
+
+        #[inductive(unroute_interrupt)]
+        fn unroute_interrupt_inductive(pre: Self, post: Self, pd: ProtectionDomain, irq: ResourceId)
+        {
+            // Invariant: spaces_are_mapped
+            // Invariant: translations_cover
+            // Only delivery edges are dropped, which neither back, map nor translate a space
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) && !(me is Delivery) implies post.maps.contains(me) by { }
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::unroute_interrupt(pd, irq));
+        }
+
+        #[inductive(program_iommu)]
+        fn program_iommu_inductive(pre: Self, post: Self, driver: ProtectionDomain, device: ProtectionDomain, me: MapEdge)
+        {
+            // Invariant: devices_confined
+            assert forall |dev: ProtectionDomain| #[trigger] post.devices.contains_key(dev) implies post.device_confined(dev) by {
+                assert(pre.device_confined(dev));
+                let iommu = ResourceLike::Space { space: pre.devices[dev].iommu() };
+                assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m.src_node() == iommu
+                    implies post.holds_with_rights(pre.devices[dev].driver(), m.dst_node(), Set::empty()) by {
+                        if (m == me) {
+                            assert(dev == device);
+                            let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == driver
+                                && w.dst() == me.dst_node() && me.perms().insert(Right::Map).subset_of(w.rights());
+                            assert(Set::<Right>::empty().subset_of(w.rights()));
+                        }
+                    }
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::program_iommu(driver, device, me));
+
+            // Invariant: translations_disjoint
+            lemma_translations_stay_disjoint(pre, post, me);
+
+            // Invariant: translations_cover
+            if (me is SpaceMap) {
+                lemma_fan_out_completed(pre, post, me);
+            } else {
+                lemma_translations_stay_covering(pre, post);
+            }
+        }
+
+        #[inductive(create_resource_space)]
+        fn create_resource_space_inductive(pre: Self, post: Self, pd: ProtectionDomain, space: SpaceId, res: ResourceSpace<K>, me: MapEdge, meta: Set<ResourceId>, rights: Set<Right>)
+        {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+
+            // The new space is not the IOMMU of a device, as those are in the graph
+            if (pre.is_iommu(space)) {
+                let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev) && pre.devices[dev].iommu() == space;
+                assert(pre.device_confined(dev));
+            }
+
+            // Invariant: devices_confined
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(e != new_hold && pre.holds.contains(e));
+                }
+
+            // The new space does not change any existing space
+            assert forall |s: SpaceId| pre.spaces.contains_key(s) implies post.spaces[s] == pre.spaces[s] by {
+                assert(s != space);
+            }
+
+            // The new space is not a reservation, as partition edges are between spaces in the graph
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) || (p == pd && s == space) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    if (e != new_hold) {
+                        assert(pre.holds.contains(e));
+                    }
+                }
+            assert(!carved_out(post.partitions, space));
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, new_hold.dst(), p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, new_hold.dst(), p, t);
+                }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = if (s == space) {
+                        new_hold
+                    } else {
+                        choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s
+                    };
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |m: MapEdge| post.maps.contains(m) implies #[trigger] m.well_formed(post.resources, post.spaces) by {
+                if (m != me) {
+                    assert(m.well_formed(pre.resources, pre.spaces));
+                }
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (s == space) {
+                    assert(post.maps.contains(me));
+                } else if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: retyped_resources_exclusive
+            assert forall |r: ResourceId| #[trigger] pre.retyped.contains_key(r) implies
+                !me.touches(ResourceLike::Resource { res: r }) by { }
+
+            // Invariant: metadata_nodes_in_graph
+            assert forall |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) implies !post.metadata.contains_key(pe.src()) by {
+                // The new space is not carved out of another
+                assert(pre.spaces.contains_key(pe.src()));
+            }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                let s = choose |s: SpaceId| #[trigger] post.metadata.contains_key(s) && post.metadata[s].contains(r);
+                if (s != space) {
+                    assert(pre.is_metadata(r));
+                }
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    assert(m != me);
+                    assert(pre.is_mapped(ResourceLike::Resource { res: r }));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: spaces_disjoint
+            // The new space is virtual, the physical ones keep their vals
+            assert forall |s1: SpaceId, s2: SpaceId| #[trigger] post.spaces.contains_key(s1) && #[trigger] post.spaces.contains_key(s2)
+                && post.spaces[s1].rtype() is Physical && post.spaces[s1].rtype() == post.spaces[s2].rtype()
+                implies s1 != space && s2 != space by {}
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::create_resource_space(pd, space, res, me, meta, rights));
+
+            // Invariant: translations_disjoint
+            assert forall |m: MapEdge| #[trigger] pre.maps.contains(m) && m is SpaceMap implies m->sm_src != space by {
+                assert(pre.spaces.contains_key(m->sm_src));
+            }
+            lemma_translations_stay_disjoint(pre, post, me);
+
+            // Invariant: translations_cover
+            if (me is SpaceMap) {
+                // The SpaceMap of the new space translates all of it
+                lemma_fan_out_completed(pre, post, me);
+            } else {
+                lemma_translations_stay_covering(pre, post);
+            }
+        }
+
+        #[inductive(destroy_resource_space)]
+        fn destroy_resource_space_inductive(pre: Self, post: Self, pd: ProtectionDomain, space: SpaceId)
+        {
+            let spacelike = ResourceLike::Space { space };
+
+            // Nothing the space is mapped through, and no allocation, refers to it
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) implies !m.touches(spacelike) by {
+                assert(pre.maps.contains(m));
+            }
+
+            // Invariant: devices_confined
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.pins(he.src(), he.dst()) implies post.holds.contains(he) by {
+                let dev = choose |dev: ProtectionDomain| #[trigger] pre.devices.contains_key(dev)
+                    && pre.devices[dev].driver() == he.src() && pre.pinned_by(dev, he.dst());
+                let iommu = ResourceLike::Space { space: pre.devices[dev].iommu() };
+                if (he.dst() != iommu) {
+                    let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me) && me.src_node() == iommu && me.dst_node() == he.dst();
+                    assert(pre.maps.contains(me));
+                } else {
+                    assert(pre.is_iommu(pre.devices[dev].iommu()));
+                }
+            }
+            lemma_pinned_holds_survive(pre, post);
+            lemma_devices_stay_confined(pre, post);
+
+            // Invariant: handlers_hold_interrupts
+            lemma_handlers_keep_interrupts(pre, post);
+
+            // Invariant: granted_requests_held
+            lemma_clients_keep_grants(pre, post);
+
+            // Invariant: usage_within_quota
+            assert forall |p: ProtectionDomain, r: ResourceId| #[trigger] holds_resource(post.holds, p, r) implies
+                holds_resource(pre.holds, p, r) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Resource { res: r });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, s: SpaceId| #[trigger] holds_space(post.holds, p, s) implies
+                holds_space(pre.holds, p, s) by {
+                    let e = choose |e: HoldEdge| #[trigger] post.holds.contains(e) && e.src() == p
+                        && e.dst() == (ResourceLike::Space { space: s });
+                    assert(pre.holds.contains(e));
+                }
+            assert forall |p: ProtectionDomain, t: ResourceType<K>|
+                #[trigger] post.charged(p, t) <= post.quota(p, t) by {
+                    lemma_usage_receive_hold(pre.resources, pre.holds, post.holds, pd, spacelike, p, t);
+                    lemma_reserved_receive_hold(pre.spaces, pre.partitions, pre.holds, post.spaces, post.partitions, post.holds,
+                        pd, spacelike, p, t);
+                }
+
+            // Invariant: hold_nodes_in_graph
+            assert forall |e: HoldEdge| #[trigger] post.holds.contains(e) implies {
+                &&& post.domains.contains(e.src())
+                &&& e.dst() is Resource ==> post.resources.contains_key(e.dst()->res)
+                &&& e.dst() is Space ==> post.spaces.contains_key(e.dst()->space)
+            } by {
+                assert(pre.holds.contains(e));
+            }
+
+            // Invariant: hold_edge_to_each_resource
+            assert forall |r: ResourceId| post.resources.contains_key(r) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Resource && e.dst()->res == r;
+                    assert(post.holds.contains(e) && e.dst() is Resource && e.dst()->res == r);
+                }
+
+            // Invariant: hold_edge_to_each_space
+            assert forall |s: SpaceId| post.spaces.contains_key(s) implies
+                exists |e: HoldEdge| #[trigger] post.holds.contains(e) && e.dst() is Space && e.dst()->space == s by {
+                    let e = choose |e| #[trigger] pre.holds.contains(e) && e.dst() is Space && e.dst()->space == s;
+                    assert(post.holds.contains(e) && e.dst() is Space && e.dst()->space == s);
+                }
+
+            // Invariant: map_edges_well_formed
+            assert forall |m: MapEdge| post.maps.contains(m) implies #[trigger] m.well_formed(post.resources, post.spaces) by {
+                assert(m.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: subset_edges_well_formed
+            assert forall |se: SubsetEdge| post.subsets.contains(se) implies #[trigger] se.well_formed(post.resources, post.spaces) by {
+                assert(se.well_formed(pre.resources, pre.spaces));
+            }
+
+            // Invariant: partition_edges_well_formed
+            assert forall |pe: PartitionEdge| post.partitions.contains(pe) implies #[trigger] pe.well_formed(post.spaces) by {
+                assert(pe.well_formed(pre.spaces));
+            }
+
+            // Invariant: spaces_are_mapped
+            assert forall |s: SpaceId| #[trigger] post.spaces.contains_key(s) && post.spaces[s].rtype() is Virtual implies {
+                ||| exists |e: MapEdge| #[trigger] post.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })
+                ||| exists |p: PartitionEdge| #[trigger] post.partitions.contains(p) && p.src() == s
+            } by {
+                if (exists |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                    ||| e is SpaceBacking && e->sb_src == s
+                    ||| e is SpaceMap && e->sm_src == s
+                })) {
+                    let e = choose |e: MapEdge| #[trigger] pre.maps.contains(e) && ({
+                        ||| e is SpaceBacking && e->sb_src == s
+                        ||| e is SpaceMap && e->sm_src == s
+                    });
+                    assert(post.maps.contains(e));
+                }
+            }
+
+            // Invariant: retyped_nodes_in_graph
+            assert forall |r: ResourceId| #[trigger] post.retyped.contains_key(r) implies
+                post.maps.contains(MapEdge::SpaceBacking { sb_src: post.retyped[r], sb_dst: r }) by {
+                    assert(pre.retyped.contains_key(r) && pre.retyped[r] != space);
+                }
+
+            // Invariant: metadata_covers_cost
+            assert forall |s: SpaceId| #[trigger] post.metadata.contains_key(s) implies
+                post.metadata_cost(post.spaces[s].rtype(), post.spaces[s].vals()) <= set_sum(post.metadata[s], resource_size(post.resources)) by {
+                    assert(pre.metadata.contains_key(s) && s != space);
+                }
+
+            // Invariant: metadata_exclusive
+            assert forall |r: ResourceId| #[trigger] post.is_metadata(r) implies !post.is_mapped(ResourceLike::Resource { res: r }) by {
+                let s = choose |s: SpaceId| #[trigger] post.metadata.contains_key(s) && post.metadata[s].contains(r);
+                assert(pre.metadata.contains_key(s) && pre.is_metadata(r));
+                if (post.is_mapped(ResourceLike::Resource { res: r })) {
+                    let m = choose |m: MapEdge| #[trigger] post.maps.contains(m)
+                        && m.touches(ResourceLike::Resource { res: r });
+                    assert(pre.maps.contains(m));
+                }
+            }
+
+            // Invariant: leases_well_formed
+            lemma_leases_stay_well_formed(pre, post);
+
+            // Invariant: derivation_forest
+            lemma_pruned_derivations_form_forest(pre, post);
+
+            // Invariant: created_nodes_recorded
+            // Invariant: holds_traced
+            lemma_history_records_step(pre, post, Step::destroy_resource_space(pd, space));
+
+            // Invariant: translations_disjoint
+            assert(post.maps.subset_of(pre.maps));
+
+            // Invariant: translations_cover
+            // The SpaceMaps fanning out of the space go with it, and no other space changes
+            lemma_translations_stay_covering(pre, post);
+        }
+
+        // Helper functions:
+
+        /// The quota [pd] has for [rtype], a ProtectionDomain without one may not hold any of it
+        pub open spec fn quota(&self, pd: ProtectionDomain, rtype: ResourceType<K>) -> nat {
+            if (self.quotas.contains_key((pd, rtype))) { self.quotas[(pd, rtype)] } else { 0 }
+        }
+
+        /// What [pd] is charged for [rtype]: the Resources and the reservations of that type it holds
+        pub open spec fn charged(&self, pd: ProtectionDomain, rtype: ResourceType<K>) -> nat {
+            usage(self.resources, self.holds, pd, rtype) + reserved(self.spaces, self.partitions, self.holds, pd, rtype)
+        }
+
+        /// The slots of the scheduling period of [core]
+        pub open spec fn period_slots(&self, core: nat) -> Set<nat> {
+            nat_range(self.periods[core])
+        }
+
+        /// The spaces which were carved out of [parent]
+        pub open spec fn children(&self, parent: SpaceId) -> Set<SpaceId> {
+            self.spaces.dom().filter(|c: SpaceId| self.partitions.contains(PartitionEdge { src: c, dst: parent }))
+        }
+
+        /// The budget of the time reservation [space]: the number of slots it covers in every period
+        pub open spec fn budget(&self, space: SpaceId) -> nat {
+            space_budget(self.spaces)(space)
+        }
+
+        /// The sum of the budgets of the reservations carved out of [parent]
+        pub open spec fn child_budgets(&self, parent: SpaceId) -> nat {
+            set_sum(self.children(parent), space_budget(self.spaces))
+        }
+
+        /// The time reservations admitted on [core]: those which were not carved out of another one
+        pub open spec fn admitted(&self, core: nat) -> Set<SpaceId> {
+            self.spaces.dom().filter(|s: SpaceId| self.spaces[s].rtype() == ResourceType::<K>::Time(core) && !carved_out(self.partitions, s))
+        }
+
+        /// The sum of the budgets of the reservations admitted on [core]
+        pub open spec fn admitted_budget(&self, core: nat) -> nat {
+            set_sum(self.admitted(core), space_budget(self.spaces))
+        }
+
+        /// Whether no two reservations admitted on [core] share a slot of its period
+        pub open spec fn admitted_disjointly(&self, core: nat) -> bool {
+            forall |a: SpaceId, b: SpaceId| #[trigger] self.admitted(core).contains(a) && #[trigger] self.admitted(core).contains(b) && a != b
+                ==> self.spaces[a].vals().disjoint(self.spaces[b].vals())
+        }
+
+        /// Whether [pd] handles the interrupt line [n]
+        pub open spec fn handles(&self, pd: ProtectionDomain, n: ResourceLike) -> bool {
+            exists |me: MapEdge| #[trigger] self.maps.contains(me) && me is Delivery && me->dl_dst == pd && n == me.src_node()
+        }
+
+        /// Whether the interrupt line [irq] is routed to a handler
+        pub open spec fn is_routed(&self, irq: ResourceId) -> bool {
+            exists |me: MapEdge| #[trigger] self.maps.contains(me) && me is Delivery && me->dl_src == irq
+        }
+
+        /// Whether the Request [id] is waiting for an answer
+        pub open spec fn is_pending(&self, id: RequestId) -> bool {
+            self.issued.contains_key(id) && self.issued[id].status() is Pending
+        }
+
+        /// Whether the server handed the Resource [r] over for the Request [id], which is not
+        /// denied
+        pub open spec fn granted_by(&self, id: RequestId, r: ResourceId) -> bool {
+            &&& self.issued.contains_key(id)
+            &&& self.issued[id].granted().contains(r)
+        }
+
+        /// Whether [pd] was granted [n] by a Request it has not closed yet
+        pub open spec fn granted_to(&self, pd: ProtectionDomain, n: ResourceLike) -> bool {
+            n is Resource && exists |id: RequestId| #[trigger] self.granted_by(id, n->res) && self.issued[id].client() == pd
+        }
+
+        /// The lease [he] is the borrower's only hold on a Resource which the lender holds as well.
+        /// While it is lent, the Resource is not lent again, backs no space, and is neither granted
+        /// by a Request nor routed to the borrower
+        pub open spec fn lease_well_formed(&self, he: HoldEdge) -> bool {
+            &&& self.holds.contains(he)
+            &&& he.dst() is Resource
+            &&& he.src() != self.leases[he].lender()
+            &&& holds_resource(self.holds, self.leases[he].lender(), he.dst()->res)
+            &&& forall |h: HoldEdge| #[trigger] self.holds.contains(h) && h.src() == he.src() && h.dst() == he.dst() ==> h == he
+            &&& forall |l: HoldEdge| #[trigger] self.leases.contains_key(l) && l.dst() == he.dst() ==> l == he
+            &&& forall |me: MapEdge| #[trigger] self.maps.contains(me) ==> !(me is SpaceBacking && me->sb_dst == he.dst()->res)
+            &&& forall |id: RequestId| !#[trigger] self.granted_by(id, he.dst()->res)
+            &&& !self.handles(he.src(), he.dst())
+        }
+
+        /// Whether [n] is lent through one of the leases
+        pub open spec fn is_lent(&self, n: ResourceLike) -> bool {
+            exists |he: HoldEdge| #[trigger] self.leases.contains_key(he) && he.dst() == n
+        }
+
+        /// A hold edge of [pd] on [n] with at least [rights]
+        pub open spec fn hold_edge_with_rights(&self, pd: ProtectionDomain, n: ResourceLike, rights: Set<Right>) -> HoldEdge {
+            choose |he: HoldEdge| #[trigger] self.holds.contains(he) && he.src() == pd && he.dst() == n && rights.subset_of(he.rights())
+        }
+
+        /// The derivations with [child] derived from [parent], unless [child] was already held
+        pub open spec fn derive(&self, child: HoldEdge, parent: HoldEdge) -> Map<HoldEdge, HoldEdge> {
+            if (self.holds.contains(child)) { self.derived_from } else { self.derived_from.insert(child, parent) }
+        }
+
+        /// The depth of the hold edge [he] in the forest of derivations, a root being at depth 0
+        pub open spec fn rank(&self, he: HoldEdge) -> nat {
+            if (self.depth.contains_key(he)) { self.depth[he] } else { 0 }
+        }
+
+        /// The depths once [child] is derived from [parent], unless [child] was already held
+        pub open spec fn deepen(&self, child: HoldEdge, parent: HoldEdge) -> Map<HoldEdge, nat> {
+            if (self.holds.contains(child)) { self.depth } else { self.depth.insert(child, self.rank(parent) + 1) }
+        }
+
+        /// The derivations between hold edges which are both among [holds]
+        pub open spec fn prune_derivations(&self, holds: Set<HoldEdge>) -> Map<HoldEdge, HoldEdge> {
+            Map::new(
+                |k: HoldEdge| self.derived_from.contains_key(k) && holds.contains(k) && holds.contains(self.derived_from[k]),
+                |k: HoldEdge| self.derived_from[k],
+            )
+        }
+
+        /// The derivations once the hold edge [from] is replaced by [to]: [to] takes over both the
+        /// parent and the children of [from]
+        pub open spec fn hand_over_derivations(&self, from: HoldEdge, to: HoldEdge) -> Map<HoldEdge, HoldEdge> {
+            Map::new(
+                |k: HoldEdge| {
+                    ||| k == to && self.derived_from.contains_key(from) && self.derived_from[from] != to
+                    ||| k != from && k != to && self.derived_from.contains_key(k)
+                },
+                |k: HoldEdge| if (k == to) {
+                    self.derived_from[from]
+                } else if (self.derived_from[k] == from) {
+                    to
+                } else {
+                    self.derived_from[k]
+                },
+            )
+        }
+
+        /// Whether [h] can be reached from [k] by following at most [fuel] derivations
+        pub open spec fn derived_through(&self, k: HoldEdge, h: HoldEdge, fuel: nat) -> bool
+            decreases fuel
+        {
+            ||| k == h
+            ||| fuel > 0 && self.derived_from.contains_key(k) && self.derived_through(self.derived_from[k], h, (fuel - 1) as nat)
+        }
+
+        /// Whether the hold edge [k] is [h], or was derived from it, directly or not
+        pub open spec fn descends_from(&self, k: HoldEdge, h: HoldEdge) -> bool {
+            exists |fuel: nat| #[trigger] self.derived_through(k, h, fuel)
+        }
+
+        /// Whether [pd] came to hold [n] through the history, starting from the initial
+        /// ProtectionDomain
+        pub open spec fn traced(&self, n: ResourceLike, pd: ProtectionDomain) -> bool {
+            exists |fuel: nat| #[trigger] traces_back(self.history, n, pd, ProtectionDomain { id: 0 }, fuel)
+        }
+
+        /// The ProtectionDomain which created [n] last, or the initial ProtectionDomain if [n] was
+        /// set up in initialize
+        pub open spec fn created_by(&self, n: ResourceLike) -> ProtectionDomain {
+            let created = last_creation(self.history, n);
+            if (created == -1) { ProtectionDomain { id: 0 } } else { self.history[created].creator() }
+        }
+
+        /// The node [n] was last created out of: the space it was allocated from or carved out of,
+        /// the Resource it was retyped from, or the node it is mapped onto when created, if [n]
+        /// was not set up in initialize
+        pub open spec fn created_from(&self, n: ResourceLike) -> Option<ResourceLike> {
+            let created = last_creation(self.history, n);
+            if (created == -1) { None } else { Some(self.history[created].source()) }
+        }
+
+        /// The steps which handed a hold on [n] over to another ProtectionDomain since it was
+        /// last created, in the order they were taken
+        pub open spec fn hand_overs(&self, n: ResourceLike) -> Set<int> {
+            Set::new(|i: int| last_creation(self.history, n) < i < self.history.len() && self.history[i].hands_over(n))
+        }
+
+        /// Whether the map edge [me] survives revoking the hold edges on [n] which are not among
+        /// [holds]: it does not touch [n], or whoever holds its other end through [holds] still
+        /// holds [n] through them
+        pub open spec fn map_survives(&self, me: MapEdge, n: ResourceLike, holds: Set<HoldEdge>) -> bool {
+            let end = if (me.src_node() == n) { me.dst_node() } else { me.src_node() };
+            ||| !me.touches(n)
+            ||| forall |he: HoldEdge| #[trigger] holds.contains(he) && he.dst() == end ==>
+                exists |k: HoldEdge| #[trigger] holds.contains(k) && k.src() == he.src() && k.dst() == n
+        }
+
+        /// Whether [n] is a Resource or ResourceSpace node of the graph
+        pub open spec fn node_in_graph(&self, n: ResourceLike) -> bool {
+            match n {
+                ResourceLike::Resource { res } => self.resources.contains_key(res),
+                ResourceLike::Space { space } => self.spaces.contains_key(space),
+            }
+        }
+
+        /// Whether [pd] holds [n] with at least [rights]
+        pub open spec fn holds_with_rights(&self, pd: ProtectionDomain, n: ResourceLike, rights: Set<Right>) -> bool {
+            exists |he: HoldEdge| #[trigger] self.holds.contains(he) && he.src() == pd && he.dst() == n && rights.subset_of(he.rights())
+        }
+
+        /// The mapping of the Resource [src] onto the Resource [dst], translating the val of one
+        /// into the val of the other
+        pub open spec fn resource_mapping(&self, src: ResourceId, dst: ResourceId, perms: Set<Right>, cache: CacheAttribute, cow: bool) -> MapEdge {
+            MapEdge::ResourceMap {
+                rm_src: src,
+                rm_dst: dst,
+                rm_attrs: MapAttributes {
+                    perms,
+                    cache,
+                    translation: Translation::Offset { offset: self.resources[dst].val() - self.resources[src].val() },
+                    cow,
+                    lazy: false,
+                },
+            }
+        }
+
+        /// The maps once the copy-on-write mapping [me] is split onto [copy]: a ResourceMap is
+        /// moved onto the copy, while a SpaceMap hands its val [v] over to the copy
+        pub open spec fn cow_split(&self, me: MapEdge, v: nat, copy: ResourceId) -> Set<MapEdge> {
+            if me is ResourceMap {
+                self.maps.remove(me).insert(self.resource_mapping(me->rm_src, copy, me->rm_attrs.perms, me->rm_attrs.cache, false))
+            } else {
+                self.maps.remove(me).insert(me.without(v))
+                    .insert(me.single(v, self.allocated_from(copy), self.resources[copy].val()))
+            }
+        }
+
+        /// The ResourceSpace the Resource [r] is allocated from
+        pub open spec fn allocated_from(&self, r: ResourceId) -> SpaceId {
+            (choose |se: SubsetEdge| #[trigger] self.subsets.contains(se) && se.src() == r).dst()
+        }
+
+        /// Whether the val [v] of the space [s] is translated into the Resource [r], allocated
+        /// from the space it lands in
+        pub open spec fn lands_in(&self, s: SpaceId, v: nat, r: ResourceId) -> bool {
+            exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.covers(s, v)
+                && self.subsets.contains(SubsetEdge { src: r, dst: me->sm_dst })
+                && self.resources[r].range().contains(me->sm_attrs.translation.apply(v) as nat)
+        }
+
+        /// The Resource faulted in when the val [v] translated by the lazy mapping [me] is first
+        /// touched: the val it lands on in the space [me] is mapped onto
+        pub open spec fn faulted(&self, me: MapEdge, v: nat) -> Resource<K> {
+            Resource { rtype: self.spaces[me->sm_dst].rtype(), val: me->sm_attrs.translation.apply(v) as nat, size: 1, align: 1 }
+        }
+
+        /// A live Resource is allocated from the space allocated_from finds
+        pub proof fn lemma_allocated_from(&self, r: ResourceId)
+            requires
+                self.invariant(),
+                self.resources.contains_key(r),
+            ensures
+                self.subsets.contains(SubsetEdge { src: r, dst: self.allocated_from(r) }),
+                self.spaces.contains_key(self.allocated_from(r)),
+                self.resources[r].range().subset_of(self.spaces[self.allocated_from(r)].vals()),
+                self.resources[r].rtype() == self.spaces[self.allocated_from(r)].rtype(),
+        {
+            let e = choose |e: SubsetEdge| self.subsets.contains(e) && #[trigger] e.src() == r;
+            let se = choose |se: SubsetEdge| #[trigger] self.subsets.contains(se) && se.src() == r;
+            assert(se == SubsetEdge { src: r, dst: self.allocated_from(r) });
+            assert(se.well_formed(self.resources, self.spaces));
+        }
+
+        /// The physical vals of type [rtype] set up in initialize
+        pub open spec fn physical_vals(&self, rtype: ResourceType<K>) -> Set<nat> {
+            Set::new(|v: nat| exists |id: SpaceId| #[trigger] self.initial_spaces.contains_key(id)
+                && self.initial_spaces[id].rtype() == rtype && self.initial_spaces[id].vals().contains(v))
+        }
+
+        /// The live Resources of type [rtype]
+        pub open spec fn resources_of(&self, rtype: ResourceType<K>) -> Set<ResourceId> {
+            self.resources.dom().filter(|r: ResourceId| self.resources[r].rtype() == rtype)
+        }
+
+        /// The size of the metadata needed to manage [vals] in a ResourceSpace of type [rtype]
+        pub open spec fn metadata_cost(&self, rtype: ResourceType<K>, vals: Set<nat>) -> nat {
+            if (self.metadata_costs.contains_key(rtype)) { self.metadata_costs[rtype] * vals.len() } else { 0 }
+        }
+
+        /// The Resources of type [rtype] charged as metadata of the ResourceSpace [space]
+        pub open spec fn metadata_of(&self, space: SpaceId, rtype: ResourceType<K>) -> Set<ResourceId> {
+            self.metadata[space].filter(|r: ResourceId| self.resources[r].rtype() == rtype)
+        }
+
+        /// The total size of the Resources of type [rtype] charged as metadata, space by space
+        pub open spec fn metadata_size(&self, rtype: ResourceType<K>) -> nat {
+            set_sum(self.metadata.dom(), |space: SpaceId| set_sum(self.metadata_of(space, rtype), resource_size(self.resources)))
+        }
+
+        /// The live Resources of type [rtype] in use as data, rather than charged as metadata
+        pub open spec fn data_of(&self, rtype: ResourceType<K>) -> Set<ResourceId> {
+            self.resources_of(rtype).filter(|r: ResourceId| !self.is_metadata(r))
+        }
+
+        /// Whether the Resource [r] is charged as metadata of a ResourceSpace
+        pub open spec fn is_metadata(&self, r: ResourceId) -> bool {
+            exists |s: SpaceId| #[trigger] self.metadata.contains_key(s) && self.metadata[s].contains(r)
+        }
+
+        /// Whether [n] is a Resource charged as metadata of a ResourceSpace
+        pub open spec fn is_metadata_node(&self, n: ResourceLike) -> bool {
+            n is Resource && self.is_metadata(n->res)
+        }
+
+        /// Whether [n] is a Resource which was retyped into the backing of a ResourceSpace
+        pub open spec fn is_retyped(&self, n: ResourceLike) -> bool {
+            n is Resource && self.retyped.contains_key(n->res)
+        }
+
+        /// The mapping [me] with its dst replaced by the Resource [dst]
+        pub open spec fn retarget(&self, me: MapEdge, dst: ResourceId) -> MapEdge {
+            match me {
+                MapEdge::SpaceBacking { sb_src, .. } => MapEdge::SpaceBacking { sb_src, sb_dst: dst },
+                MapEdge::SpaceMap { .. } | MapEdge::Delivery { .. } => me,
+                MapEdge::ResourceMap { rm_src, rm_attrs, .. } =>
+                    self.resource_mapping(rm_src, dst, rm_attrs.perms, rm_attrs.cache, rm_attrs.cow),
+            }
+        }
+
+        /// A val translated by a mapping always translates into a val of the space it is mapped onto
+        pub proof fn lemma_translation_lands_in_dst(&self, me: MapEdge, v: nat)
+            requires
+                self.invariant(),
+                self.maps.contains(me),
+                me is SpaceMap,
+                me->sm_vals.contains(v),
+            ensures
+                0 <= me->sm_attrs.translation.apply(v),
+                self.spaces[me->sm_dst].vals().contains(me->sm_attrs.translation.apply(v) as nat),
+        {
+            assert(me.well_formed(self.resources, self.spaces));
+        }
+
+        /// Whether no SpaceMap but [me] translates any of the vals [me] translates
+        pub open spec fn translates_disjointly(&self, me: MapEdge) -> bool {
+            forall |m: MapEdge| #[trigger] self.maps.contains(m) && m is SpaceMap && m->sm_src == me->sm_src && m != me
+                ==> m->sm_vals.disjoint(me->sm_vals)
+        }
+
+        /// Whether SpaceMaps fan out of the space [s]
+        pub open spec fn fans_out(&self, s: SpaceId) -> bool {
+            exists |me: MapEdge| #[trigger] self.maps.contains(me) && me is SpaceMap && me->sm_src == s
+        }
+
+        /// Whether the SpaceMap [me] translates every val of its src which no SpaceMap fanning out
+        /// of it translates yet
+        pub open spec fn completes_fan_out(&self, me: MapEdge) -> bool {
+            forall |v: nat| #[trigger] self.spaces[me->sm_src].vals().contains(v) ==>
+                me->sm_vals.contains(v) || exists |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(me->sm_src, v)
+        }
+
+        /// Resolves the val [v] of the space [s] through the SpaceMap translating it, into the space
+        /// it is mapped onto and the val it lands on
+        pub open spec fn resolve(&self, s: SpaceId, v: nat) -> Option<(SpaceId, nat)> {
+            if exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.covers(s, v) {
+                let me = choose |me: MapEdge| #[trigger] self.maps.contains(me) && me.covers(s, v);
+                Some((me->sm_dst, me->sm_attrs.translation.apply(v) as nat))
+            } else {
+                None
+            }
+        }
+
+        /// Translation is deterministic: every SpaceMap translating a val is the only one which does,
+        /// and resolves it to where it lands
+        pub proof fn lemma_resolution_deterministic(&self, me: MapEdge, s: SpaceId, v: nat)
+            requires
+                self.invariant(),
+                self.maps.contains(me),
+                me.covers(s, v),
+            ensures
+                forall |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(s, v) ==> m == me,
+                self.resolve(s, v) == Some((me->sm_dst, me->sm_attrs.translation.apply(v) as nat)),
+                self.spaces[me->sm_dst].vals().contains(me->sm_attrs.translation.apply(v) as nat),
+        {
+            assert forall |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(s, v) implies m == me by {
+                if (m != me) {
+                    assert(m->sm_vals.disjoint(me->sm_vals));
+                    assert(m->sm_vals.contains(v) && me->sm_vals.contains(v));
+                }
+            }
+            let chosen = choose |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(s, v);
+            assert(chosen == me);
+            self.lemma_translation_lands_in_dst(me, v);
+        }
+
+        /// Translation is total on a space which fans out: each of its vals resolves, through the
+        /// only SpaceMap translating it, to a val of the space that SpaceMap is mapped onto
+        pub proof fn lemma_resolution_total(&self, s: SpaceId, v: nat)
+            requires
+                self.invariant(),
+                self.fans_out(s),
+                self.spaces[s].vals().contains(v),
+            ensures
+                exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.covers(s, v)
+                    && self.resolve(s, v) == Some((me->sm_dst, me->sm_attrs.translation.apply(v) as nat))
+                    && self.spaces[me->sm_dst].vals().contains(me->sm_attrs.translation.apply(v) as nat),
+        {
+            let fan = choose |me: MapEdge| #[trigger] self.maps.contains(me) && me is SpaceMap && me->sm_src == s;
+            assert(self.maps.contains(fan) && self.spaces[fan->sm_src].vals().contains(v));
+            let me = choose |m: MapEdge| #[trigger] self.maps.contains(m) && m.covers(s, v);
+            self.lemma_resolution_deterministic(me, s, v);
+        }
+
+        /// The vals of [space] which are not covered by any Resource allocated from it
+        pub open spec fn free_vals(&self, space: SpaceId) -> Set<nat> {
+            self.spaces[space].vals().filter(|v: nat| !exists |se: SubsetEdge| #[trigger] self.subsets.contains(se)
+                && se.dst() == space && self.resources[se.src()].range().contains(v))
+        }
+
+        /// The reservations carved out of a time reservation never add up to more than its budget
+        pub proof fn lemma_child_budgets_within_reservation(&self, parent: SpaceId)
+            requires
+                self.invariant(),
+                self.spaces.contains_key(parent),
+                self.spaces[parent].rtype() is Time,
+            ensures
+                self.child_budgets(parent) <= self.budget(parent),
+        {
+            let children = self.children(parent);
+            let vals = self.spaces[parent].vals();
+            lemma_nat_range_len(self.periods[self.spaces[parent].rtype()->Time_0]);
+            lemma_len_subset(vals, self.period_slots(self.spaces[parent].rtype()->Time_0));
+
+            assert forall |c: SpaceId| #[trigger] children.contains(c) implies
+                self.spaces[c].vals().subset_of(vals) && space_budget(self.spaces)(c) == self.spaces[c].vals().len() by {
+                    assert(PartitionEdge { src: c, dst: parent }.well_formed(self.spaces));
+                }
+            assert forall |a: SpaceId, b: SpaceId| #[trigger] children.contains(a) && #[trigger] children.contains(b) && a != b
+                implies self.spaces[a].vals().disjoint(self.spaces[b].vals()) by {
+                    assert(self.partitions.contains(PartitionEdge { src: a, dst: parent }));
+                    assert(self.partitions.contains(PartitionEdge { src: b, dst: parent }));
+                }
+            lemma_set_sum_disjoint_subsets(children, |c: SpaceId| self.spaces[c].vals(), space_budget(self.spaces), vals);
+        }
+
+        /// Whether [space] is the IOMMU space of a device
+        pub open spec fn is_iommu(&self, space: SpaceId) -> bool {
+            exists |dev: ProtectionDomain| #[trigger] self.devices.contains_key(dev) && self.devices[dev].iommu() == space
+        }
+
+        /// Whether [n] is the IOMMU space of [dev], or a node it is mapped onto
+        pub open spec fn pinned_by(&self, dev: ProtectionDomain, n: ResourceLike) -> bool {
+            let iommu = ResourceLike::Space { space: self.devices[dev].iommu() };
+            ||| n == iommu
+            ||| exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.src_node() == iommu && me.dst_node() == n
+        }
+
+        /// Whether [pd] drives a device which can reach [n]
+        pub open spec fn pins(&self, pd: ProtectionDomain, n: ResourceLike) -> bool {
+            exists |dev: ProtectionDomain| #[trigger] self.devices.contains_key(dev) && self.devices[dev].driver() == pd && self.pinned_by(dev, n)
+        }
+
+        /// [dev] only holds its IOMMU space, and its driver holds both that space and every node
+        /// it is mapped onto
+        pub open spec fn device_confined(&self, dev: ProtectionDomain) -> bool {
+            let iommu = ResourceLike::Space { space: self.devices[dev].iommu() };
+            let driver = self.devices[dev].driver();
+            &&& self.domains.contains(dev)
+            &&& forall |he: HoldEdge| #[trigger] self.holds.contains(he) && he.src() == dev ==> he.dst() == iommu
+            &&& self.holds_with_rights(driver, iommu, Set::empty())
+            &&& forall |me: MapEdge| #[trigger] self.maps.contains(me) && me.src_node() == iommu ==>
+                self.holds_with_rights(driver, me.dst_node(), Set::empty())
+        }
+
+        /// The driver of a device holds everything the device can reach
+        pub proof fn lemma_pinned_nodes_are_held(&self, pd: ProtectionDomain, n: ResourceLike)
+            requires
+                self.devices_confined(),
+                self.pins(pd, n),
+            ensures
+                self.holds_with_rights(pd, n, Set::empty()),
+        {
+            let dev = choose |dev: ProtectionDomain| #[trigger] self.devices.contains_key(dev)
+                && self.devices[dev].driver() == pd && self.pinned_by(dev, n);
+            let iommu = ResourceLike::Space { space: self.devices[dev].iommu() };
+            assert(self.device_confined(dev));
+            if (n != iommu) {
+                let me = choose |me: MapEdge| #[trigger] self.maps.contains(me) && me.src_node() == iommu && me.dst_node() == n;
+                assert(self.maps.contains(me));
+            }
+        }
+
+        /// Whether [pd] can reach [n], either by holding it or by holding a node mapped onto it
+        pub open spec fn reaches(&self, pd: ProtectionDomain, n: ResourceLike) -> bool {
+            ||| self.holds_with_rights(pd, n, Set::empty())
+            ||| exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.maps_onto(n)
+                && self.holds_with_rights(pd, me.src_node(), Set::empty())
+        }
+
+        /// Whether [m] can be reached from [n] by following at most [fuel] map edges
+        pub open spec fn resolves_to(&self, n: ResourceLike, m: ResourceLike, fuel: nat) -> bool
+            decreases fuel
+        {
+            ||| n == m
+            ||| fuel > 0 && exists |me: MapEdge| #[trigger] self.maps.contains(me) && !(me is Delivery) && me.src_node() == n
+                && self.resolves_to(me.dst_node(), m, (fuel - 1) as nat)
+        }
+
+        /// Whether [n] depends on [m] through at most [fuel] subset, partition and map edges: [n]
+        /// is [m], or it is allocated from, carved out of or mapped onto a node which depends on [m]
+        pub open spec fn depends_on(&self, n: ResourceLike, m: ResourceLike, fuel: nat) -> bool
+            decreases fuel
+        {
+            ||| n == m
+            ||| fuel > 0 && exists |se: SubsetEdge| #[trigger] self.subsets.contains(se) && n == (ResourceLike::Resource { res: se.src() })
+                && self.depends_on(ResourceLike::Space { space: se.dst() }, m, (fuel - 1) as nat)
+            ||| fuel > 0 && exists |pe: PartitionEdge| #[trigger] self.partitions.contains(pe) && n == (ResourceLike::Space { space: pe.src() })
+                && self.depends_on(ResourceLike::Space { space: pe.dst() }, m, (fuel - 1) as nat)
+            ||| fuel > 0 && exists |me: MapEdge| #[trigger] self.maps.contains(me) && !(me is Delivery) && me.src_node() == n
+                && self.depends_on(me.dst_node(), m, (fuel - 1) as nat)
+        }
+
+        /// Whether [server] can be reached from [client] by following at most [fuel] request edges
+        pub open spec fn requests_from(&self, client: ProtectionDomain, server: ProtectionDomain, fuel: nat) -> bool
+            decreases fuel
+        {
+            ||| client == server
+            ||| fuel > 0 && exists |re: RequestEdge<K>| #[trigger] self.requests.contains(re) && re.src() == client
+                && self.requests_from(re.dst(), server, (fuel - 1) as nat)
+        }
+
+        /// The trusted computing base of [n]: the ProtectionDomains which hold [n] or a node it
+        /// depends on, and the servers they may request Resources from, directly or not
+        pub open spec fn tcb(&self, n: ResourceLike) -> Set<ProtectionDomain> {
+            Set::new(|pd: ProtectionDomain| exists |holder: ProtectionDomain, m: ResourceLike, dfuel: nat, rfuel: nat|
+                #[trigger] self.depends_on(n, m, dfuel) && self.holds_with_rights(holder, m, Set::empty())
+                && #[trigger] self.requests_from(holder, pd, rfuel))
+        }
+
+        /// The ProtectionDomain which took [step] from this state, if any did: the server offers
+        /// request edges and answers Requests, and the clock advances the epoch
+        pub open spec fn actor(&self, step: Step<K>) -> Option<ProtectionDomain> {
+            match step {
+                Step::create_resource(pd, ..) => Some(pd),
+                Step::destroy_resource(pd, _) => Some(pd),
+                Step::create_pd(parent, _) => Some(parent),
+                Step::destroy_pd(pd) => Some(pd),
+                Step::create_request_edge(req) => Some(req.dst()),
+                Step::destroy_request_edge(req) => Some(req.dst()),
+                Step::issue_request(_, req) => Some(req.client()),
+                Step::fulfil_request(_, _, he, _) => Some(he.src()),
+                Step::deny_request(id) => Some(self.issued[id].server()),
+                Step::close_request(id) => Some(self.issued[id].client()),
+                Step::create_map_edge(pd, _) => Some(pd),
+                Step::split_map_edge(pd, ..) => Some(pd),
+                Step::cow_write(pd, ..) => Some(pd),
+                Step::demand_fault(pd, ..) => Some(pd),
+                Step::remap(pd, ..) => Some(pd),
+                Step::retype(pd, ..) => Some(pd),
+                Step::share_hold(pd, ..) => Some(pd),
+                Step::grant_hold(pd, ..) => Some(pd),
+                Step::revoke_hold(pd, _) => Some(pd),
+                Step::advance_epoch() => None,
+                Step::lend_hold(lender, ..) => Some(lender),
+                Step::reclaim_lease(lender, _) => Some(lender),
+                Step::delegate_quota(from, ..) => Some(from),
+                Step::assign_device(driver, ..) => Some(driver),
+                Step::program_iommu(driver, ..) => Some(driver),
+                Step::route_interrupt(pd, ..) => Some(pd),
+                Step::unroute_interrupt(pd, _) => Some(pd),
+                Step::create_resource_space(pd, ..) => Some(pd),
+                Step::split_space(pd, ..) => Some(pd),
+                Step::create_budget(pd, ..) => Some(pd),
+                Step::resize_space(pd, ..) => Some(pd),
+                Step::merge_spaces(pd, ..) => Some(pd),
+                Step::destroy_resource_space(pd, _) => Some(pd),
+                Step::dummy_to_use_type_params(_) => None,
+            }
+        }
+
+        /// Whether [pd] can reach the physical Resource [r] through its holds and map edges
+        pub open spec fn reaches_physical(&self, pd: ProtectionDomain, r: ResourceId) -> bool {
+            &&& self.resources.contains_key(r)
+            &&& self.resources[r].rtype() is Physical
+            &&& exists |n: ResourceLike, fuel: nat| #[trigger] self.resolves_to(n, ResourceLike::Resource { res: r }, fuel)
+                && self.holds_with_rights(pd, n, Set::empty())
+        }
+
+        /// Whether two distinct ProtectionDomains can both reach a physical val of the Resource
+        /// [r], which [pd_a] reaches
+        pub open spec fn shares_resource(&self, pd_a: ProtectionDomain, pd_b: ProtectionDomain, r: ResourceId) -> bool {
+            &&& pd_a != pd_b
+            &&& self.reaches_physical(pd_a, r)
+            &&& exists |r2: ResourceId| self.reaches_physical(pd_b, r2)
+                && self.resources[r].rtype() == self.resources[r2].rtype()
+                && !self.resources[r].range().disjoint(self.resources[r2].range())
+        }
+
+        /// Whether two distinct ProtectionDomains can both reach a common physical val of a type
+        pub open spec fn shares(&self, pd_a: ProtectionDomain, pd_b: ProtectionDomain) -> bool {
+            exists |r: ResourceId| #[trigger] self.shares_resource(pd_a, pd_b, r)
+        }
+
+        /// Whether [n] is either end of a map edge other than a delivery
+        pub open spec fn is_mapped(&self, n: ResourceLike) -> bool {
+            exists |me: MapEdge| #[trigger] self.maps.contains(me) && me.touches(n)
+        }
+
+        /// Whether both ends of [me] are held by a common ProtectionDomain
+        pub open spec fn map_owned(&self, me: MapEdge) -> bool {
+            exists |pd: ProtectionDomain| self.holds_with_rights(pd, me.src_node(), Set::empty())
+                && #[trigger] self.holds_with_rights(pd, me.dst_node(), Set::empty())
+        }
+
+        /// Every node is held by at most one ProtectionDomain, which also holds whatever it is
+        /// mapped onto
+        pub open spec fn exclusively_owned(&self) -> bool {
+            &&& forall |h1: HoldEdge, h2: HoldEdge| #[trigger] self.holds.contains(h1) && #[trigger] self.holds.contains(h2)
+                && h1.dst() == h2.dst() ==> h1.src() == h2.src()
+            &&& forall |me: MapEdge| #[trigger] self.maps.contains(me) && !(me is Delivery) ==> self.map_owned(me)
+        }
+
+        /// The union of the rights with which any ProtectionDomain holds [n]
+        pub open spec fn max_rights(&self, n: ResourceLike) -> Set<Right> {
+            Set::new(|r: Right| exists |he: HoldEdge| #[trigger] self.holds.contains(he) && he.dst() == n && he.rights().contains(r))
+        }
+
+        /// A value is never allocated from both a space and a child carved out of it
+        pub proof fn lemma_split_spaces_never_share_allocations(&self, pe: PartitionEdge, parent_se: SubsetEdge, child_se: SubsetEdge)
+            requires
+                self.invariant(),
+                self.partitions.contains(pe),
+                self.subsets.contains(parent_se),
+                self.subsets.contains(child_se),
+                parent_se.dst() == pe.dst(),
+                child_se.dst() == pe.src(),
+            ensures
+                self.resources[parent_se.src()].range().disjoint(self.resources[child_se.src()].range()),
+                parent_se.src() != child_se.src(),
+        {
+            let child_res = self.resources[child_se.src()];
+            assert(child_se.well_formed(self.resources, self.spaces));
+            assert(self.spaces[pe.src()].vals().disjoint(self.resources[parent_se.src()].range()));
+            assert(child_res.well_formed() && child_res.range().contains(child_res.val()));
+        }
+
+    } // osmosis_dag
+} // state_machine!
+
+// Rights Attenuation:
+
+/// Whether the hold edge [h] after a step is bounded by the hold edge [prev] before it: [prev]
+/// is on the same node, or, for the node the step created, it is the hold of the creator on the
+/// node it was created out of
+pub open spec fn hold_bounded_by<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, h: HoldEdge, prev: HoldEdge) -> bool {
+    &&& h.rights().subset_of(prev.rights())
+    &&& prev.dst() == h.dst() || ({
+        let step = post.history.last();
+        &&& !pre.node_in_graph(h.dst())
+        &&& step.creates(h.dst())
+        &&& prev.src() == h.src()
+        &&& prev.dst() == step.source()
+    })
+}
+
+/// Every hold after a step is bounded by a hold which existed before it
+pub open spec fn rights_attenuated<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>) -> bool {
+    forall |h: HoldEdge| #[trigger] post.holds.contains(h) ==>
+        exists |prev: HoldEdge| #[trigger] pre.holds.contains(prev) && hold_bounded_by(pre, post, h, prev)
+}
+
+/// A step which only removes hold edges trivially attenuates rights
+proof fn lemma_fewer_holds_attenuate_rights<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires post.holds.subset_of(pre.holds)
+    ensures rights_attenuated(pre, post)
+{
+    assert forall |h: HoldEdge| #[trigger] post.holds.contains(h) implies
+        exists |prev: HoldEdge| #[trigger] pre.holds.contains(prev) && hold_bounded_by(pre, post, h, prev) by {
+            assert(pre.holds.contains(h) && hold_bounded_by(pre, post, h, h));
+        }
+}
+
+/// A step which adds no hold edge but [new], bounded by the hold edge [prev] before it,
+/// attenuates rights
+proof fn lemma_new_hold_attenuates_rights<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, new: HoldEdge, prev: HoldEdge)
+    requires
+        forall |h: HoldEdge| #[trigger] post.holds.contains(h) ==> pre.holds.contains(h) || h == new,
+        pre.holds.contains(prev),
+        hold_bounded_by(pre, post, new, prev),
+    ensures
+        rights_attenuated(pre, post),
+{
+    assert forall |h: HoldEdge| #[trigger] post.holds.contains(h) implies
+        exists |p: HoldEdge| #[trigger] pre.holds.contains(p) && hold_bounded_by(pre, post, h, p) by {
+            if (pre.holds.contains(h)) {
+                assert(hold_bounded_by(pre, post, h, h));
+            } else {
+                assert(pre.holds.contains(prev) && hold_bounded_by(pre, post, h, prev));
+            }
+        }
+}
+
+/// No step of the Osmosis DAG amplifies rights
+pub proof fn lemma_step_attenuates_rights<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::next(pre, post),
+    ensures
+        rights_attenuated(pre, post),
+{
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Resource { res: id }, rights };
+            let space_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space }) && rights.subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, space_hold);
+        }
+        destroy_resource(pd, res) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        create_pd(parent, pd) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        destroy_pd(pd) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        create_request_edge(req) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        destroy_request_edge(req) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        issue_request(id, req) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        fulfil_request(id, client, he, rights) => {
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: client, dst: he.dst(), rights }, he);
+        }
+        deny_request(id) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        close_request(id) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        create_map_edge(pd, me) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        split_map_edge(pd, me, part) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        cow_write(pd, me, v, copy) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        demand_fault(pd, me, v, backing) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Resource { res: backing }, rights: me.perms().insert(Right::Map) };
+            let space_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == me.dst_node() && me.perms().insert(Right::Map).subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, space_hold);
+        }
+        remap(pd, me, dst) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        retype(pd, res, space, rtype, vals, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+            let res_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Resource { res }) && rights.insert(Right::Map).subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, res_hold);
+        }
+        share_hold(pd, to, he, rights) => {
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: to, dst: he.dst(), rights }, he);
+        }
+        grant_hold(pd, to, he, rights) => {
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: to, dst: he.dst(), rights }, he);
+        }
+        revoke_hold(pd, h) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        advance_epoch() => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        lend_hold(lender, borrower, he, rights, expiry) => {
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: borrower, dst: he.dst(), rights }, he);
+        }
+        reclaim_lease(lender, he) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        delegate_quota(from, to, rtype, amount) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        assign_device(driver, device, iommu, rights) => {
+            let driver_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == driver
+                && he.dst() == (ResourceLike::Space { space: iommu }) && rights.insert(Right::Map).subset_of(he.rights());
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: device, dst: ResourceLike::Space { space: iommu }, rights }, driver_hold);
+        }
+        program_iommu(driver, device, me) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        route_interrupt(pd, irq, handler, rights) => {
+            let irq_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Resource { res: irq }) && rights.insert(Right::Grant).subset_of(he.rights());
+            lemma_new_hold_attenuates_rights(pre, post, HoldEdge { src: handler, dst: ResourceLike::Resource { res: irq }, rights }, irq_hold);
+        }
+        unroute_interrupt(pd, irq) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        create_resource_space(pd, space, res, me, meta, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+            let dst_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == me.dst_node() && rights.subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, dst_hold);
+        }
+        split_space(pd, parent, child, vals, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space: child }, rights };
+            let parent_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space: parent }) && rights.subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, parent_hold);
+        }
+        create_budget(pd, source, budget, vals, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space: budget }, rights };
+            let source_hold = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space: source }) && rights.subset_of(he.rights());
+            assert(!pre.node_in_graph(new_hold.dst()));
+            lemma_new_hold_attenuates_rights(pre, post, new_hold, source_hold);
+        }
+        resize_space(pd, space, vals) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        merge_spaces(pd, parent, keep, other) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+        destroy_resource_space(pd, space) => {
+            lemma_fewer_holds_attenuate_rights(pre, post);
+        }
+    }}
+}
+
+/// Along any sequence of steps, the rights with which a node that stays in the graph is held
+/// never grow
+pub proof fn theorem_rights_never_amplify<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, n: ResourceLike)
+    requires
+        trace.len() > 0,
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].invariant(),
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1]),
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].node_in_graph(n),
+    ensures
+        trace.last().max_rights(n).subset_of(trace[0].max_rights(n)),
+    decreases trace.len()
+{
+    if (trace.len() > 1) {
+        let prefix = trace.drop_last();
+        assert forall |i: int| 0 <= i < prefix.len() implies #[trigger] prefix[i] == trace[i] by { }
+        assert forall |i: int| 0 <= i < prefix.len() - 1 implies OsmosisDAG::State::next(#[trigger] prefix[i], prefix[i + 1]) by {
+            assert(OsmosisDAG::State::next(trace[i], trace[i + 1]));
+        }
+        theorem_rights_never_amplify(prefix, n);
+
+        let pre = trace[trace.len() - 2];
+        let post = trace.last();
+        lemma_step_attenuates_rights(pre, post);
+        assert forall |r: Right| post.max_rights(n).contains(r) implies pre.max_rights(n).contains(r) by {
+            let h = choose |h: HoldEdge| #[trigger] post.holds.contains(h) && h.dst() == n && h.rights().contains(r);
+            let prev = choose |prev: HoldEdge| #[trigger] pre.holds.contains(prev) && hold_bounded_by(pre, post, h, prev);
+            assert(pre.holds.contains(prev) && prev.dst() == n && prev.rights().contains(r));
+        }
+    }
+}
+
+// Admission Control:
+
+/// A step which leaves the reservations admitted on [core] as they were, but [changed], whose
+/// slots no other reservation admitted on it covers, keeps them from sharing a slot
+proof fn lemma_admission_kept<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, core: nat, changed: Option<SpaceId>)
+    requires
+        pre.admitted_disjointly(core),
+        forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) && changed != Some(s) ==>
+            pre.admitted(core).contains(s) && post.spaces[s].vals() == pre.spaces[s].vals(),
+        changed is Some && post.admitted(core).contains(changed->Some_0) ==>
+            forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) && s != changed->Some_0 ==>
+                post.spaces[s].vals().disjoint(post.spaces[changed->Some_0].vals()),
+    ensures
+        post.admitted_disjointly(core),
+{
+    assert forall |a: SpaceId, b: SpaceId| #[trigger] post.admitted(core).contains(a) && #[trigger] post.admitted(core).contains(b) && a != b
+        implies post.spaces[a].vals().disjoint(post.spaces[b].vals()) by {
+            if (changed == Some(a)) {
+                assert(post.spaces[b].vals().disjoint(post.spaces[a].vals()));
+            } else if (changed != Some(b)) {
+                assert(pre.admitted(core).contains(a) && pre.admitted(core).contains(b));
+            }
+        }
+}
+
+/// No step admits a slot of [core] to two of its reservations: only create_budget admits a new
+/// reservation, and resize_space grows an admitted one, both onto slots no other one covers,
+/// while the spaces split_space and merge_spaces leave behind stay carved out
+proof fn lemma_step_keeps_admission<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, core: nat)
+    requires
+        pre.invariant(),
+        pre.admitted_disjointly(core),
+        OsmosisDAG::State::next(pre, post),
+    ensures
+        post.admitted_disjointly(core),
+{
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => { }
+        destroy_resource(pd, res) => { }
+        create_pd(parent, pd) => { }
+        destroy_pd(pd) => { }
+        create_request_edge(req) => { }
+        destroy_request_edge(req) => { }
+        issue_request(id, req) => { }
+        fulfil_request(id, client, he, rights) => { }
+        deny_request(id) => { }
+        close_request(id) => { }
+        create_map_edge(pd, me) => { }
+        split_map_edge(pd, me, part) => { }
+        cow_write(pd, me, v, copy) => { }
+        demand_fault(pd, me, v, backing) => { }
+        remap(pd, me, dst) => { }
+        retype(pd, res, space, rtype, vals, rights) => {
+            // Only virtual spaces are retyped into
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) implies s != space by { }
+            lemma_admission_kept(pre, post, core, None);
+        }
+        share_hold(pd, to, he, rights) => { }
+        grant_hold(pd, to, he, rights) => { }
+        revoke_hold(pd, h) => { }
+        advance_epoch() => { }
+        lend_hold(lender, borrower, he, rights, expiry) => { }
+        reclaim_lease(lender, he) => { }
+        delegate_quota(from, to, rtype, amount) => { }
+        assign_device(driver, device, iommu, rights) => { }
+        program_iommu(driver, device, me) => { }
+        route_interrupt(pd, irq, handler, rights) => { }
+        unroute_interrupt(pd, irq) => { }
+        create_resource_space(pd, space, res, me, meta, rights) => {
+            // Only virtual spaces are created
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) implies s != space by { }
+            lemma_admission_kept(pre, post, core, None);
+        }
+        split_space(pd, parent, child, vals, rights) => {
+            // The child is carved out of its parent, and no admitted space comes to be carved out
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) implies
+                pre.admitted(core).contains(s) && post.spaces[s] == pre.spaces[s] by {
+                    assert(post.partitions.contains(PartitionEdge { src: child, dst: parent }));
+                    if (carved_out(pre.partitions, s)) {
+                        let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == s;
+                        assert(post.partitions.contains(pe));
+                    }
+                }
+            lemma_admission_kept(pre, post, core, None);
+        }
+        create_budget(pd, source, budget, vals, rights) => {
+            // The budget is fresh, and admitted onto slots no other reservation on its core covers
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) && s != budget implies
+                pre.admitted(core).contains(s) && post.spaces[s] == pre.spaces[s] by { }
+            lemma_admission_kept(pre, post, core, Some(budget));
+        }
+        resize_space(pd, space, vals) => {
+            // An admitted space only grows onto slots no other reservation on its core covers
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) && s != space implies
+                pre.admitted(core).contains(s) && post.spaces[s] == pre.spaces[s] by { }
+            lemma_admission_kept(pre, post, core, Some(space));
+        }
+        merge_spaces(pd, parent, keep, other) => {
+            // Both spaces are carved out of the parent, and keep stays so
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) implies
+                pre.admitted(core).contains(s) && post.spaces[s] == pre.spaces[s] by {
+                    assert(post.partitions.contains(PartitionEdge { src: keep, dst: parent }));
+                    if (carved_out(pre.partitions, s)) {
+                        let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.src() == s;
+                        assert(pe.src() != other && post.partitions.contains(pe));
+                    }
+                }
+            lemma_admission_kept(pre, post, core, None);
+        }
+        destroy_resource_space(pd, space) => {
+            assert forall |s: SpaceId| #[trigger] post.admitted(core).contains(s) implies
+                pre.admitted(core).contains(s) && post.spaces[s] == pre.spaces[s] by { }
+            lemma_admission_kept(pre, post, core, None);
+        }
+    }}
+}
+
+/// The time spaces set up in initialize on [core] are all admitted, and never share a slot
+proof fn lemma_initially_admitted_disjointly<K: ResourceKind>(s: OsmosisDAG::State<K>, core: nat)
+    requires
+        OsmosisDAG::State::init(s),
+    ensures
+        s.admitted_disjointly(core),
+{
+    case_on_init!{s, OsmosisDAG => {
+        initialize(physical_spaces, initial_quotas, core_periods, space_costs) => {
+            assert forall |a: SpaceId, b: SpaceId| #[trigger] s.admitted(core).contains(a) && #[trigger] s.admitted(core).contains(b) && a != b
+                implies s.spaces[a].vals().disjoint(s.spaces[b].vals()) by {
+                    assert(physical_spaces.contains_key(a) && physical_spaces.contains_key(b));
+                }
+        }
+    }}
+}
+
+/// No two reservations admitted on [core] share a slot along any sequence of steps from a state
+/// where they did not
+proof fn lemma_admitted_disjointly_along<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, core: nat)
+    requires
+        trace.len() > 0,
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].invariant(),
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1]),
+        trace[0].admitted_disjointly(core),
+    ensures
+        trace.last().admitted_disjointly(core),
+    decreases trace.len()
+{
+    if (trace.len() > 1) {
+        let prefix = trace.drop_last();
+        assert forall |i: int| 0 <= i < prefix.len() implies #[trigger] prefix[i] == trace[i] by { }
+        assert forall |i: int| 0 <= i < prefix.len() - 1 implies OsmosisDAG::State::next(#[trigger] prefix[i], prefix[i + 1]) by {
+            assert(OsmosisDAG::State::next(trace[i], trace[i + 1]));
+        }
+        lemma_admitted_disjointly_along(prefix, core);
+        lemma_step_keeps_admission(trace[trace.len() - 2], trace.last(), core);
+    }
+}
+
+/// Admission control on a single core is sound: along any sequence of steps from initialize,
+/// however budgets are created, resized, split and delegated, the budgets admitted on [core]
+/// never add up to more than its period
+pub proof fn theorem_time_admission_sound<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, core: nat)
+    requires
+        trace.len() > 0,
+        OsmosisDAG::State::init(trace[0]),
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].invariant(),
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1]),
+        trace.last().periods.contains_key(core),
+    ensures
+        trace.last().admitted_budget(core) <= trace.last().periods[core],
+{
+    lemma_initially_admitted_disjointly(trace[0], core);
+    lemma_admitted_disjointly_along(trace, core);
+
+    let s = trace.last();
+    let admitted = s.admitted(core);
+    lemma_nat_range_len(s.periods[core]);
+    lemma_len_subset(admitted, s.spaces.dom());
+    // Invariant: time_spaces_within_period
+    assert forall |a: SpaceId| #[trigger] admitted.contains(a) implies
+        s.spaces[a].vals().subset_of(s.period_slots(core)) && space_budget(s.spaces)(a) == s.spaces[a].vals().len() by { }
+    lemma_set_sum_disjoint_subsets(admitted, |a: SpaceId| s.spaces[a].vals(), space_budget(s.spaces), s.period_slots(core));
+}
+
+// Interrupt Delivery:
+
+/// Handlers keep holding their interrupt lines if the hold edges they rely on survive a step
+proof fn lemma_handlers_keep_interrupts<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.handlers_hold_interrupts(),
+        forall |me: MapEdge| #[trigger] post.maps.contains(me) && me is Delivery ==> pre.maps.contains(me),
+        forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.handles(he.src(), he.dst()) ==> post.holds.contains(he),
+    ensures
+        post.handlers_hold_interrupts(),
+{
+    assert forall |d: MapEdge| #[trigger] post.maps.contains(d) && d is Delivery implies holds_resource(post.holds, d->dl_dst, d->dl_src) by {
+        assert(pre.maps.contains(d));
+        let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == d->dl_dst
+            && he.dst() == (ResourceLike::Resource { res: d->dl_src });
+        assert(pre.handles(he.src(), he.dst()));
+        assert(post.holds.contains(he));
+    }
+}
+
+// Device Confinement:
+
+/// Nodes pinned by a device stay held by its driver if the hold edges pinning them survive a step
+proof fn lemma_pinned_holds_survive<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.devices_confined(),
+        forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.pins(he.src(), he.dst()) ==> post.holds.contains(he),
+    ensures
+        forall |pd: ProtectionDomain, n: ResourceLike| #[trigger] pre.pins(pd, n) ==> post.holds_with_rights(pd, n, Set::empty()),
+{
+    assert forall |pd: ProtectionDomain, n: ResourceLike| #[trigger] pre.pins(pd, n) implies post.holds_with_rights(pd, n, Set::empty()) by {
+        pre.lemma_pinned_nodes_are_held(pd, n);
+        let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd && he.dst() == n
+            && Set::<Right>::empty().subset_of(he.rights());
+        assert(post.holds.contains(he));
+    }
+}
+
+/// A step which leaves devices alone, gives them nothing new to hold, programs no IOMMU and keeps
+/// every node pinned by a device held by its driver, keeps devices confined
+proof fn lemma_devices_stay_confined<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.devices_confined(),
+        post.devices == pre.devices,
+        forall |d: ProtectionDomain| #[trigger] pre.devices.contains_key(d) ==> post.domains.contains(d),
+        forall |he: HoldEdge| #[trigger] post.holds.contains(he) && pre.devices.contains_key(he.src()) ==> pre.holds.contains(he),
+        forall |pd: ProtectionDomain, n: ResourceLike| #[trigger] pre.pins(pd, n) ==> post.holds_with_rights(pd, n, Set::empty()),
+        forall |me: MapEdge| #[trigger] post.maps.contains(me) && me.src_node() is Space && pre.is_iommu(me.src_node()->space)
+            ==> pre.maps.contains(me),
+    ensures
+        post.devices_confined(),
+{
+    assert forall |dev: ProtectionDomain| #[trigger] post.devices.contains_key(dev) implies post.device_confined(dev) by {
+        let driver = pre.devices[dev].driver();
+        let iommu = ResourceLike::Space { space: pre.devices[dev].iommu() };
+        assert(pre.device_confined(dev));
+        assert(pre.pins(driver, iommu));
+        assert forall |me: MapEdge| #[trigger] post.maps.contains(me) && me.src_node() == iommu implies
+            post.holds_with_rights(driver, me.dst_node(), Set::empty()) by {
+                assert(pre.is_iommu(pre.devices[dev].iommu()));
+                assert(pre.maps.contains(me));
+                assert(pre.pins(driver, me.dst_node()));
+            }
+    }
+}
+
+/// Appending the map edge [me] to a chain of at most [fuel] map edges from [n] onto its src
+/// resolves [n] to its dst with one more edge
+proof fn lemma_resolution_extends<K: ResourceKind>(s: OsmosisDAG::State<K>, n: ResourceLike, me: MapEdge, fuel: nat)
+    requires
+        s.maps.contains(me),
+        !(me is Delivery),
+        s.resolves_to(n, me.src_node(), fuel),
+    ensures
+        s.resolves_to(n, me.dst_node(), (fuel + 1) as nat),
+    decreases fuel
+{
+    if (n == me.src_node()) {
+        assert(s.resolves_to(me.dst_node(), me.dst_node(), fuel));
+    } else {
+        let e = choose |e: MapEdge| #[trigger] s.maps.contains(e) && !(e is Delivery) && e.src_node() == n
+            && s.resolves_to(e.dst_node(), me.src_node(), (fuel - 1) as nat);
+        lemma_resolution_extends(s, e.dst_node(), me, (fuel - 1) as nat);
+        assert(s.resolves_to(e.dst_node(), me.dst_node(), fuel));
+    }
+}
+
+/// Following the chain of at most [fuel] map edges from [n] to [m] one edge at a time, [m] is
+/// resolved to from [h] whenever [n] is
+proof fn lemma_resolution_follows_chain<K: ResourceKind>(s: OsmosisDAG::State<K>, h: ResourceLike, n: ResourceLike, m: ResourceLike, f: nat, fuel: nat)
+    requires
+        s.resolves_to(h, n, f),
+        s.resolves_to(n, m, fuel),
+    ensures
+        exists |k: nat| #[trigger] s.resolves_to(h, m, k),
+    decreases fuel
+{
+    if (n != m) {
+        let me = choose |me: MapEdge| #[trigger] s.maps.contains(me) && !(me is Delivery) && me.src_node() == n
+            && s.resolves_to(me.dst_node(), m, (fuel - 1) as nat);
+        lemma_resolution_extends(s, h, me, f);
+        lemma_resolution_follows_chain(s, h, me.dst_node(), m, (f + 1) as nat, (fuel - 1) as nat);
+    }
+}
+
+/// A device can only reach what its driver can reach itself. A device only holds its IOMMU
+/// space, which its driver holds as well, so the driver resolves every chain of map edges the
+/// device resolves through, one edge at a time, down to the same physical Resource.
+pub proof fn theorem_device_reach_bounded_by_driver<K: ResourceKind>(s: OsmosisDAG::State<K>, dev: ProtectionDomain, r: ResourceId)
+    requires
+        s.invariant(),
+        s.devices.contains_key(dev),
+        s.reaches_physical(dev, r),
+    ensures
+        s.reaches_physical(s.devices[dev].driver(), r),
+{
+    let driver = s.devices[dev].driver();
+    let iommu = ResourceLike::Space { space: s.devices[dev].iommu() };
+    let target = ResourceLike::Resource { res: r };
+    assert(s.device_confined(dev));
+    let (n, fuel) = choose |n: ResourceLike, fuel: nat| #[trigger] s.resolves_to(n, target, fuel)
+        && s.holds_with_rights(dev, n, Set::empty());
+    // The chain of the device starts at its IOMMU space, the only node it holds
+    let he = choose |he: HoldEdge| #[trigger] s.holds.contains(he) && he.src() == dev && he.dst() == n
+        && Set::<Right>::empty().subset_of(he.rights());
+    assert(n == iommu);
+    assert(s.resolves_to(iommu, iommu, 0));
+    lemma_resolution_follows_chain(s, iommu, n, target, 0, fuel);
+    let k = choose |k: nat| #[trigger] s.resolves_to(iommu, target, k);
+    assert(s.resolves_to(iommu, target, k) && s.holds_with_rights(driver, iommu, Set::empty()));
+}
+
+// Denial of Service Freedom:
+
+/// The free vals of [share] never shrink across a step which keeps its vals and the Resources
+/// allocated from it
+proof fn lemma_free_vals_grow<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, share: SpaceId)
+    requires
+        post.spaces.contains_key(share),
+        pre.spaces[share].vals().subset_of(post.spaces[share].vals()),
+        forall |se: SubsetEdge| #[trigger] post.subsets.contains(se) && se.dst() == share ==>
+            pre.subsets.contains(se) && post.resources[se.src()].range() == pre.resources[se.src()].range(),
+    ensures
+        pre.free_vals(share).subset_of(post.free_vals(share)),
+{
+    assert forall |v: nat| pre.free_vals(share).contains(v) implies post.free_vals(share).contains(v) by {
+        if (!post.free_vals(share).contains(v)) {
+            let se = choose |se: SubsetEdge| #[trigger] post.subsets.contains(se)
+                && se.dst() == share && post.resources[se.src()].range().contains(v);
+            assert(pre.subsets.contains(se));
+        }
+    }
+}
+
+/// A step taken by a ProtectionDomain which does not hold the [share] reserved to another one
+/// never takes vals from it: the share survives the step, and its free vals only ever grow.
+/// Allocating from the space it was carved out of does not eat into it either, as
+/// partition_excludes_parent_allocations keeps parent allocations clear of its vals.
+pub proof fn lemma_steps_preserve_reserved_share<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, share: SpaceId)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::next(pre, post),
+        pre.spaces.contains_key(share),
+        pre.actor(post.history.last()) is Some ==>
+            !pre.holds_with_rights(pre.actor(post.history.last())->Some_0, ResourceLike::Space { space: share }, Set::empty()),
+    ensures
+        post.spaces.contains_key(share),
+        pre.free_vals(share).subset_of(post.free_vals(share)),
+{
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => {
+            // pd allocates from a space it holds, which is not the share
+            let he = choose |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == pd
+                && he.dst() is Space && he.dst()->space == space;
+            assert(Set::<Right>::empty().subset_of(he.rights()));
+            assert(space != share);
+            assert forall |se: SubsetEdge| #[trigger] post.subsets.contains(se) && se.dst() == share implies
+                pre.subsets.contains(se) && post.resources[se.src()].range() == pre.resources[se.src()].range() by {
+                    assert(pre.resources.contains_key(se.src()));
+                }
+            lemma_free_vals_grow(pre, post, share);
+        }
+        destroy_resource(pd, res) => {
+            // The only subset edge going away is the one of the destroyed Resource
+            let gone = choose |se: SubsetEdge| #[trigger] pre.subsets.contains(se) && se.src() == res;
+            assert forall |se: SubsetEdge| #[trigger] post.subsets.contains(se) && se.dst() == share implies
+                pre.subsets.contains(se) && post.resources[se.src()].range() == pre.resources[se.src()].range() by {
+                    if (se.src() == res) {
+                        assert(se.dst() == gone.dst());
+                    }
+                }
+            lemma_free_vals_grow(pre, post, share);
+        }
+        create_pd(parent, pd) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        destroy_pd(pd) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        create_request_edge(req) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        destroy_request_edge(req) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        issue_request(id, req) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        fulfil_request(id, client, he, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        deny_request(id) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        close_request(id) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        create_map_edge(pd, me) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        split_map_edge(pd, me, part) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        cow_write(pd, me, v, copy) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        demand_fault(pd, me, v, backing) => {
+            // pd faults the backing in from a space it holds, which is not the share
+            let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == me.dst_node() && me.perms().insert(Right::Map).subset_of(he.rights());
+            assert(Set::<Right>::empty().subset_of(he.rights()));
+            assert(me->sm_dst != share);
+            assert forall |se: SubsetEdge| #[trigger] post.subsets.contains(se) && se.dst() == share implies
+                pre.subsets.contains(se) && post.resources[se.src()].range() == pre.resources[se.src()].range() by {
+                    assert(pre.resources.contains_key(se.src()));
+                }
+            lemma_free_vals_grow(pre, post, share);
+        }
+        remap(pd, me, dst) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        retype(pd, res, space, rtype, vals, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        share_hold(pd, to, he, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        grant_hold(pd, to, he, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        revoke_hold(pd, h) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        advance_epoch() => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        lend_hold(lender, borrower, he, rights, expiry) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        reclaim_lease(lender, he) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        delegate_quota(from, to, rtype, amount) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        assign_device(driver, device, iommu, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        program_iommu(driver, device, me) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        route_interrupt(pd, irq, handler, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        unroute_interrupt(pd, irq) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        create_resource_space(pd, space, res, me, meta, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        split_space(pd, parent, child, vals, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        create_budget(pd, source, budget, vals, rights) => {
+            lemma_free_vals_grow(pre, post, share);
+        }
+        resize_space(pd, space, vals) => {
+            // Only a holder of the space resizes it
+            let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space }) && Set::empty().insert(Right::Write).subset_of(he.rights());
+            assert(Set::<Right>::empty().subset_of(he.rights()));
+            assert(space != share);
+            lemma_free_vals_grow(pre, post, share);
+        }
+        merge_spaces(pd, parent, keep, other) => {
+            // Only a holder of both spaces merges them
+            let hk = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space: keep }) && Set::empty().insert(Right::Write).subset_of(he.rights());
+            let ho = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space: other }) && Set::empty().insert(Right::Destroy).subset_of(he.rights());
+            assert(Set::<Right>::empty().subset_of(hk.rights()) && Set::<Right>::empty().subset_of(ho.rights()));
+            assert(keep != share && other != share);
+            lemma_free_vals_grow(pre, post, share);
+        }
+        destroy_resource_space(pd, space) => {
+            // Only a holder of the space destroys it
+            let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pd
+                && he.dst() == (ResourceLike::Space { space }) && Set::empty().insert(Right::Destroy).subset_of(he.rights());
+            assert(Set::<Right>::empty().subset_of(he.rights()));
+            assert(space != share);
+            lemma_free_vals_grow(pre, post, share);
+        }
+    }}
+}
+
+// Exclusive Ownership:
+
+/// Whether [pre] steps to [post] by handing a hold on an existing node to another ProtectionDomain
+pub open spec fn hands_out_holds<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>) -> bool {
+    ||| exists |pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>|
+        #[trigger] OsmosisDAG::State::share_hold(pre, post, pd, to, he, rights)
+    ||| exists |pd: ProtectionDomain, to: ProtectionDomain, he: HoldEdge, rights: Set<Right>|
+        #[trigger] OsmosisDAG::State::grant_hold(pre, post, pd, to, he, rights)
+    ||| exists |id: RequestId, client: ProtectionDomain, he: HoldEdge, rights: Set<Right>|
+        #[trigger] OsmosisDAG::State::fulfil_request(pre, post, id, client, he, rights)
+    ||| exists |driver: ProtectionDomain, device: ProtectionDomain, iommu: SpaceId, rights: Set<Right>|
+        #[trigger] OsmosisDAG::State::assign_device(pre, post, driver, device, iommu, rights)
+    ||| exists |pd: ProtectionDomain, irq: ResourceId, handler: ProtectionDomain, rights: Set<Right>|
+        #[trigger] OsmosisDAG::State::route_interrupt(pre, post, pd, irq, handler, rights)
+    ||| exists |lender: ProtectionDomain, borrower: ProtectionDomain, he: HoldEdge, rights: Set<Right>, expiry: nat|
+        #[trigger] OsmosisDAG::State::lend_hold(pre, post, lender, borrower, he, rights, expiry)
+}
+
+/// A step which keeps the hold edges on mapped nodes, maps nothing new and only adds holds on a
+/// fresh node for a single ProtectionDomain keeps nodes exclusively owned
+proof fn lemma_exclusive_ownership_kept<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.invariant(),
+        pre.exclusively_owned(),
+        post.maps.subset_of(pre.maps),
+        forall |he: HoldEdge| #[trigger] post.holds.contains(he) ==> pre.holds.contains(he) || !pre.node_in_graph(he.dst()),
+        forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+            && h1.dst() == h2.dst() && !pre.node_in_graph(h1.dst()) ==> h1.src() == h2.src(),
+        forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.is_mapped(he.dst()) ==> post.holds.contains(he),
+    ensures
+        post.exclusively_owned(),
+{
+    assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+        && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+            if (pre.node_in_graph(h1.dst())) {
+                assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+            }
+        }
+    assert forall |me: MapEdge| #[trigger] post.maps.contains(me) && !(me is Delivery) implies post.map_owned(me) by {
+        assert(pre.maps.contains(me) && pre.map_owned(me));
+        let pd = choose |pd: ProtectionDomain| pre.holds_with_rights(pd, me.src_node(), Set::empty())
+            && #[trigger] pre.holds_with_rights(pd, me.dst_node(), Set::empty());
+        let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.src_node()
+            && Set::<Right>::empty().subset_of(h.rights());
+        let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.dst_node()
+            && Set::<Right>::empty().subset_of(h.rights());
+        assert(pre.is_mapped(hs.dst()) && pre.is_mapped(hd.dst()));
+        assert(post.holds.contains(hs) && post.holds.contains(hd));
+        assert(post.holds_with_rights(pd, me.src_node(), Set::empty()));
+    }
+}
+
+/// A step which does not hand out holds keeps nodes exclusively owned
+pub proof fn lemma_step_keeps_exclusive_ownership<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.invariant(),
+        pre.exclusively_owned(),
+        OsmosisDAG::State::next(pre, post),
+        !hands_out_holds(pre, post),
+    ensures
+        post.exclusively_owned(),
+{
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        destroy_resource(pd, res) => {
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.is_mapped(he.dst()) implies post.holds.contains(he) by {
+                let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me) && me.touches(he.dst());
+                assert(pre.maps.contains(me));
+            }
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        create_pd(parent, pd) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        destroy_pd(pd) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        create_request_edge(req) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        destroy_request_edge(req) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        issue_request(id, req) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        fulfil_request(id, client, he, rights) => {
+            assert(OsmosisDAG::State::fulfil_request(pre, post, id, client, he, rights));
+        }
+        deny_request(id) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        close_request(id) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        create_map_edge(pd, me) => {
+            let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.src_node()
+                && Set::<Right>::empty().insert(Right::Map).subset_of(h.rights());
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hs.rights()) && Set::<Right>::empty().subset_of(hd.rights()));
+            assert(post.holds_with_rights(pd, me.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, me.dst_node(), Set::empty()));
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != me) {
+                    assert(pre.map_owned(m));
+                }
+            }
+        }
+        split_map_edge(pd, me, part) => {
+            let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.src_node()
+                && Set::<Right>::empty().insert(Right::Map).subset_of(h.rights());
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == part.dst_node()
+                && part.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hs.rights()) && Set::<Right>::empty().subset_of(hd.rights()));
+            assert(post.holds_with_rights(pd, part.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, part.dst_node(), Set::empty()));
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m == me.without_vals(part->sm_vals)) {
+                    // The narrowed mapping keeps both ends of the split one
+                    assert(pre.map_owned(me));
+                    let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, me.src_node(), Set::empty())
+                        && #[trigger] pre.holds_with_rights(o, me.dst_node(), Set::empty());
+                    assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+                } else if (m != part) {
+                    assert(pre.map_owned(m));
+                }
+            }
+        }
+        cow_write(pd, me, v, copy) => {
+            let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.src_node()
+                && Set::empty().insert(Right::Write).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hs.rights()));
+            assert(post.holds_with_rights(pd, me.src_node(), Set::empty()));
+            if (me is ResourceMap) {
+                let copied = pre.resource_mapping(me->rm_src, copy, me->rm_attrs.perms, me->rm_attrs.cache, false);
+                let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == copied.dst_node()
+                    && me.perms().insert(Right::Map).subset_of(h.rights());
+                assert(Set::<Right>::empty().subset_of(hd.rights()));
+                assert(post.holds_with_rights(pd, copied.dst_node(), Set::empty()));
+                assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                    if (m != copied) {
+                        assert(pre.map_owned(m));
+                    }
+                }
+            } else {
+                let split = me.single(v, pre.allocated_from(copy), pre.resources[copy].val());
+                let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == split.dst_node()
+                    && me.perms().insert(Right::Map).subset_of(h.rights());
+                assert(Set::<Right>::empty().subset_of(hd.rights()));
+                assert(post.holds_with_rights(pd, split.dst_node(), Set::empty()));
+                assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                    if (m == me.without(v)) {
+                        // The narrowed mapping keeps both ends of the shared one
+                        assert(pre.map_owned(me));
+                        let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, me.src_node(), Set::empty())
+                            && #[trigger] pre.holds_with_rights(o, me.dst_node(), Set::empty());
+                        assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+                    } else if (m != split) {
+                        assert(pre.map_owned(m));
+                    }
+                }
+            }
+        }
+        demand_fault(pd, me, v, backing) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Resource { res: backing }, rights: me.perms().insert(Right::Map) };
+            let faulted = me.single(v, me->sm_dst, me->sm_attrs.translation.apply(v) as nat);
+            let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.src_node()
+                && Set::<Right>::empty().subset_of(h.rights());
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hd.rights()));
+            assert(post.holds.contains(hs) && post.holds.contains(hd));
+            assert(post.holds_with_rights(pd, me.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, me.dst_node(), Set::empty()));
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                    if (pre.node_in_graph(h1.dst())) {
+                        // The backing is fresh, so neither is the new hold
+                        assert(!pre.resources.contains_key(backing));
+                        assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+                    } else {
+                        assert(!pre.holds.contains(h1) && !pre.holds.contains(h2));
+                    }
+                }
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != me.without(v) && m != faulted) {
+                    assert(pre.map_owned(m));
+                    let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, m.src_node(), Set::empty())
+                        && #[trigger] pre.holds_with_rights(o, m.dst_node(), Set::empty());
+                    let ms = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.src_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    let md = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.dst_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    assert(post.holds.contains(ms) && post.holds.contains(md));
+                    assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+                }
+            }
+        }
+        remap(pd, me, dst) => {
+            let moved = pre.retarget(me, dst);
+            let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == moved.src_node()
+                && Set::empty().insert(Right::Map).subset_of(h.rights());
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == moved.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hs.rights()) && Set::<Right>::empty().subset_of(hd.rights()));
+            assert(post.holds_with_rights(pd, moved.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, moved.dst_node(), Set::empty()));
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != moved) {
+                    assert(pre.map_owned(m));
+                }
+            }
+        }
+        retype(pd, res, space, rtype, vals, rights) => {
+            let backing = MapEdge::SpaceBacking { sb_src: space, sb_dst: res };
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+            let hr = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == backing.dst_node()
+                && rights.insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hr.rights()) && Set::<Right>::empty().subset_of(new_hold.rights()));
+            assert(post.holds.contains(hr) && post.holds.contains(new_hold));
+            assert(post.holds_with_rights(pd, backing.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, backing.dst_node(), Set::empty()));
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                    if (pre.node_in_graph(h1.dst())) {
+                        assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+                    }
+                }
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != backing) {
+                    assert(pre.map_owned(m));
+                    let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, m.src_node(), Set::empty())
+                        && #[trigger] pre.holds_with_rights(o, m.dst_node(), Set::empty());
+                    let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.src_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.dst_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    assert(post.holds.contains(hs) && post.holds.contains(hd));
+                    assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+                }
+            }
+        }
+        share_hold(pd, to, he, rights) => {
+            assert(OsmosisDAG::State::share_hold(pre, post, pd, to, he, rights));
+        }
+        grant_hold(pd, to, he, rights) => {
+            assert(OsmosisDAG::State::grant_hold(pre, post, pd, to, he, rights));
+        }
+        revoke_hold(pd, h) => {
+            // Only pd holds the node of h, so whatever it held through a revoked hold edge it
+            // still holds through h
+            assert forall |p: ProtectionDomain, n: ResourceLike| #[trigger] pre.holds_with_rights(p, n, Set::empty()) implies
+                post.holds_with_rights(p, n, Set::empty()) by {
+                    let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == p && w.dst() == n
+                        && Set::<Right>::empty().subset_of(w.rights());
+                    if (!post.holds.contains(w)) {
+                        let fuel = choose |fuel: nat| #[trigger] pre.derived_through(w, h, fuel);
+                        lemma_descendants_hold_same_node(pre, w, h, fuel);
+                        assert(pre.holds.contains(h) && w.src() == h.src());
+                        assert(post.holds.contains(h) && Set::<Right>::empty().subset_of(h.rights()));
+                    } else {
+                        assert(post.holds.contains(w));
+                    }
+                }
+            assert forall |me: MapEdge| #[trigger] post.maps.contains(me) && !(me is Delivery) implies post.map_owned(me) by {
+                assert(pre.maps.contains(me) && pre.map_owned(me));
+                let p = choose |p: ProtectionDomain| pre.holds_with_rights(p, me.src_node(), Set::empty())
+                    && #[trigger] pre.holds_with_rights(p, me.dst_node(), Set::empty());
+                assert(post.holds_with_rights(p, me.src_node(), Set::empty()));
+                assert(post.holds_with_rights(p, me.dst_node(), Set::empty()));
+            }
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                    assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+                }
+        }
+        advance_epoch() => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        lend_hold(lender, borrower, he, rights, expiry) => {
+            assert(OsmosisDAG::State::lend_hold(pre, post, lender, borrower, he, rights, expiry));
+        }
+        reclaim_lease(lender, he) => {
+            // The lender and the borrower both hold the lent Resource, so nothing is lent in an
+            // exclusively owned graph
+            assert(pre.lease_well_formed(he));
+            let lender_hold = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == lender
+                && h.dst() == (ResourceLike::Resource { res: he.dst()->res });
+            assert(pre.holds.contains(he) && pre.holds.contains(lender_hold) && he.dst() == lender_hold.dst());
+        }
+        delegate_quota(from, to, rtype, amount) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        assign_device(driver, device, iommu, rights) => {
+            assert(OsmosisDAG::State::assign_device(pre, post, driver, device, iommu, rights));
+        }
+        program_iommu(driver, device, me) => {
+            assert(pre.device_confined(device));
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == driver && h.dst() == me.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hd.rights()));
+            assert(post.holds_with_rights(driver, me.dst_node(), Set::empty()));
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != me) {
+                    assert(pre.map_owned(m));
+                }
+            }
+        }
+        route_interrupt(pd, irq, handler, rights) => {
+            assert(OsmosisDAG::State::route_interrupt(pre, post, pd, irq, handler, rights));
+        }
+        unroute_interrupt(pd, irq) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        create_resource_space(pd, space, res, me, meta, rights) => {
+            let new_hold = HoldEdge { src: pd, dst: ResourceLike::Space { space }, rights };
+            let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == pd && h.dst() == me.dst_node()
+                && me.perms().insert(Right::Map).subset_of(h.rights());
+            assert(Set::<Right>::empty().subset_of(hd.rights()) && Set::<Right>::empty().subset_of(new_hold.rights()));
+            assert(post.holds.contains(hd) && post.holds.contains(new_hold));
+            assert(post.holds_with_rights(pd, me.src_node(), Set::empty()));
+            assert(post.holds_with_rights(pd, me.dst_node(), Set::empty()));
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                    if (pre.node_in_graph(h1.dst())) {
+                        assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+                    }
+                }
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                if (m != me) {
+                    assert(pre.map_owned(m));
+                    let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, m.src_node(), Set::empty())
+                        && #[trigger] pre.holds_with_rights(o, m.dst_node(), Set::empty());
+                    let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.src_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.dst_node()
+                        && Set::<Right>::empty().subset_of(h.rights());
+                    assert(post.holds.contains(hs) && post.holds.contains(hd));
+                    assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+                }
+            }
+        }
+        split_space(pd, parent, child, vals, rights) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        create_budget(pd, source, budget, vals, rights) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        resize_space(pd, space, vals) => {
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        merge_spaces(pd, parent, keep, other) => {
+            assert forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.is_mapped(he.dst()) implies post.holds.contains(he) by {
+                let me = choose |me: MapEdge| #[trigger] pre.maps.contains(me) && me.touches(he.dst());
+                assert(pre.maps.contains(me));
+            }
+            lemma_exclusive_ownership_kept(pre, post);
+        }
+        destroy_resource_space(pd, space) => {
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] post.holds.contains(h1) && #[trigger] post.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                    assert(pre.holds.contains(h1) && pre.holds.contains(h2));
+                }
+            assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && !(m is Delivery) implies post.map_owned(m) by {
+                assert(pre.maps.contains(m) && pre.map_owned(m));
+                let o = choose |o: ProtectionDomain| pre.holds_with_rights(o, m.src_node(), Set::empty())
+                    && #[trigger] pre.holds_with_rights(o, m.dst_node(), Set::empty());
+                let hs = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.src_node()
+                    && Set::<Right>::empty().subset_of(h.rights());
+                let hd = choose |h: HoldEdge| #[trigger] pre.holds.contains(h) && h.src() == o && h.dst() == m.dst_node()
+                    && Set::<Right>::empty().subset_of(h.rights());
+                // The mappings which are left neither map the space nor map onto it, so the holds
+                // owning them survive
+                assert(!m.touches(ResourceLike::Space { space }));
+                assert(post.holds.contains(hs) && post.holds.contains(hd));
+                assert(post.holds_with_rights(o, m.src_node(), Set::empty()));
+            }
+        }
+    }}
+}
+
+/// In an exclusively owned graph, whatever a ProtectionDomain resolves through map edges is held
+/// by it
+proof fn lemma_resolution_stays_with_holder<K: ResourceKind>(s: OsmosisDAG::State<K>, pd: ProtectionDomain, n: ResourceLike, m: ResourceLike, fuel: nat)
+    requires
+        s.exclusively_owned(),
+        s.holds_with_rights(pd, n, Set::empty()),
+        s.resolves_to(n, m, fuel),
+    ensures
+        s.holds_with_rights(pd, m, Set::empty()),
+    decreases fuel
+{
+    if (n != m) {
+        let me = choose |me: MapEdge| #[trigger] s.maps.contains(me) && !(me is Delivery) && me.src_node() == n
+            && s.resolves_to(me.dst_node(), m, (fuel - 1) as nat);
+        assert(s.map_owned(me));
+        let owner = choose |o: ProtectionDomain| s.holds_with_rights(o, me.src_node(), Set::empty())
+            && #[trigger] s.holds_with_rights(o, me.dst_node(), Set::empty());
+        let h1 = choose |h: HoldEdge| #[trigger] s.holds.contains(h) && h.src() == pd && h.dst() == n
+            && Set::<Right>::empty().subset_of(h.rights());
+        let h2 = choose |h: HoldEdge| #[trigger] s.holds.contains(h) && h.src() == owner && h.dst() == n
+            && Set::<Right>::empty().subset_of(h.rights());
+        assert(h1.src() == h2.src());
+        lemma_resolution_stays_with_holder(s, pd, me.dst_node(), m, (fuel - 1) as nat);
+    }
+}
+
+/// In an exclusively owned graph, a ProtectionDomain holds every physical Resource it reaches
+proof fn lemma_reached_resources_are_held<K: ResourceKind>(s: OsmosisDAG::State<K>, pd: ProtectionDomain, r: ResourceId)
+    requires
+        s.exclusively_owned(),
+        s.reaches_physical(pd, r),
+    ensures
+        s.holds_with_rights(pd, ResourceLike::Resource { res: r }, Set::empty()),
+{
+    let (n, fuel) = choose |n: ResourceLike, fuel: nat| #[trigger] s.resolves_to(n, ResourceLike::Resource { res: r }, fuel)
+        && s.holds_with_rights(pd, n, Set::empty());
+    lemma_resolution_stays_with_holder(s, pd, n, ResourceLike::Resource { res: r }, fuel);
+}
+
+/// No two ProtectionDomains share memory in an exclusively owned graph
+pub proof fn lemma_exclusively_owned_never_shares<K: ResourceKind>(s: OsmosisDAG::State<K>, pd_a: ProtectionDomain, pd_b: ProtectionDomain)
+    requires
+        s.invariant(),
+        s.exclusively_owned(),
+    ensures
+        !s.shares(pd_a, pd_b),
+        forall |r: ResourceId| s.reaches_physical(pd_a, r) && s.reaches_physical(pd_b, r) ==> pd_a == pd_b,
+{
+    assert forall |r: ResourceId| s.reaches_physical(pd_a, r) && s.reaches_physical(pd_b, r) implies pd_a == pd_b by {
+        lemma_reached_resources_are_held(s, pd_a, r);
+        lemma_reached_resources_are_held(s, pd_b, r);
+        let ha = choose |h: HoldEdge| #[trigger] s.holds.contains(h) && h.src() == pd_a
+            && h.dst() == (ResourceLike::Resource { res: r }) && Set::<Right>::empty().subset_of(h.rights());
+        let hb = choose |h: HoldEdge| #[trigger] s.holds.contains(h) && h.src() == pd_b
+            && h.dst() == (ResourceLike::Resource { res: r }) && Set::<Right>::empty().subset_of(h.rights());
+        assert(s.holds.contains(ha) && s.holds.contains(hb));
+    }
+
+    if (s.shares(pd_a, pd_b)) {
+        let r1 = choose |r: ResourceId| #[trigger] s.shares_resource(pd_a, pd_b, r);
+        let r2 = choose |r2: ResourceId| s.reaches_physical(pd_b, r2)
+            && s.resources[r1].rtype() == s.resources[r2].rtype()
+            && !s.resources[r1].range().disjoint(s.resources[r2].range());
+        if (r1 == r2) {
+            assert(s.reaches_physical(pd_a, r1) && s.reaches_physical(pd_b, r1));
+        } else {
+            // Live physical Resources of the same type never overlap
+            assert(s.resources[r1].range().disjoint(s.resources[r2].range()));
+        }
+    }
+}
+
+/// The graph set up by initialize is exclusively owned: the initial ProtectionDomain holds every
+/// space, and nothing is mapped yet
+pub proof fn lemma_initially_exclusively_owned<K: ResourceKind>(s: OsmosisDAG::State<K>)
+    requires
+        OsmosisDAG::State::init(s),
+    ensures
+        s.exclusively_owned(),
+{
+    case_on_init!{s, OsmosisDAG => {
+        initialize(physical_spaces, initial_quotas, core_periods, space_costs) => {
+            assert forall |h1: HoldEdge, h2: HoldEdge| #[trigger] s.holds.contains(h1) && #[trigger] s.holds.contains(h2)
+                && h1.dst() == h2.dst() implies h1.src() == h2.src() by {
+                assert(h1.src() == (ProtectionDomain { id: 0 }) && h2.src() == (ProtectionDomain { id: 0 }));
+            }
+        }
+    }}
+}
+
+/// Along any sequence of steps from initialize which never hand out holds, every physical
+/// Resource is reachable by at most one ProtectionDomain, and no two ProtectionDomains share
+/// memory
+pub proof fn theorem_exclusive_ownership<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, pd_a: ProtectionDomain, pd_b: ProtectionDomain)
+    requires
+        trace.len() > 0,
+        OsmosisDAG::State::init(trace[0]),
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].invariant(),
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1])
+            && !hands_out_holds(trace[i], trace[i + 1]),
+    ensures
+        !trace.last().shares(pd_a, pd_b),
+        forall |r: ResourceId| trace.last().reaches_physical(pd_a, r) && trace.last().reaches_physical(pd_b, r) ==> pd_a == pd_b,
+{
+    lemma_initially_exclusively_owned(trace[0]);
+    lemma_exclusively_owned_along(trace);
+    lemma_exclusively_owned_never_shares(trace.last(), pd_a, pd_b);
+}
+
+/// Exclusive ownership holds along any sequence of steps which never hand out holds
+proof fn lemma_exclusively_owned_along<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>)
+    requires
+        trace.len() > 0,
+        forall |i: int| 0 <= i < trace.len() ==> #[trigger] trace[i].invariant(),
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1])
+            && !hands_out_holds(trace[i], trace[i + 1]),
+        trace[0].exclusively_owned(),
+    ensures
+        trace.last().exclusively_owned(),
+    decreases trace.len()
+{
+    if (trace.len() > 1) {
+        let prefix = trace.drop_last();
+        assert forall |i: int| 0 <= i < prefix.len() implies #[trigger] prefix[i] == trace[i] by { }
+        assert forall |i: int| 0 <= i < prefix.len() - 1 implies OsmosisDAG::State::next(#[trigger] prefix[i], prefix[i + 1])
+            && !hands_out_holds(prefix[i], prefix[i + 1]) by {
+                assert(OsmosisDAG::State::next(trace[i], trace[i + 1]));
+            }
+        lemma_exclusively_owned_along(prefix);
+        lemma_step_keeps_exclusive_ownership(trace[trace.len() - 2], trace.last());
+    }
+}
+
+// Copy-on-Write:
+
+/// A node which is only mapped onto from [x] can only be resolved to through [x], by following
+/// a map edge onto it
+proof fn lemma_resolution_passes_through<K: ResourceKind>(s: OsmosisDAG::State<K>, n: ResourceLike, m: ResourceLike, x: ResourceLike, fuel: nat)
+    requires
+        s.resolves_to(n, m, fuel),
+        n != m,
+        forall |me: MapEdge| #[trigger] s.maps.contains(me) && me.maps_onto(m) ==> me.src_node() == x,
+    ensures
+        s.resolves_to(n, x, fuel),
+        exists |me: MapEdge| #[trigger] s.maps.contains(me) && me.maps_onto(m),
+    decreases fuel
+{
+    let me = choose |me: MapEdge| #[trigger] s.maps.contains(me) && !(me is Delivery) && me.src_node() == n
+        && s.resolves_to(me.dst_node(), m, (fuel - 1) as nat);
+    if (me.dst_node() == m) {
+        assert(n == x);
+    } else {
+        lemma_resolution_passes_through(s, me.dst_node(), m, x, (fuel - 1) as nat);
+    }
+}
+
+/// Once [pd] splits the copy-on-write mapping [me] onto its [copy], it no longer shares what it
+/// writes with any other ProtectionDomain: none reaches a physical val of the copy. Through a
+/// SpaceMap, the written val [v] is moreover the only val of any space translated into the copy,
+/// and pd alone holds the space it belongs to.
+pub proof fn theorem_cow_write_unshares<K: ResourceKind>(
+    pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>,
+    pd: ProtectionDomain, me: MapEdge, v: nat, copy: ResourceId, other: ProtectionDomain,
+)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::cow_write(pre, post, pd, me, v, copy),
+    ensures
+        !post.shares_resource(pd, other, copy),
+        me is SpaceMap ==> post.lands_in(me->sm_src, v, copy),
+        me is SpaceMap ==> forall |s: SpaceId, w: nat| #[trigger] post.lands_in(s, w, copy) ==> s == me->sm_src && w == v,
+        me is SpaceMap && other != pd ==> !post.holds_with_rights(other, me.src_node(), Set::empty()),
+{
+    let written = me.src_node();
+    let copy_node = ResourceLike::Resource { res: copy };
+    let copied = pre.resource_mapping(me->rm_src, copy, me->rm_attrs.perms, me->rm_attrs.cache, false);
+    let split = me.single(v, pre.allocated_from(copy), pre.resources[copy].val());
+    pre.lemma_allocated_from(copy);
+
+    // The written node is virtual, unlike the copy and the space it is allocated from
+    assert(pre.maps.contains(me) && me.well_formed(pre.resources, pre.spaces));
+    assert(written != copy_node && written != split.dst_node());
+
+    // Nothing is mapped onto the written node, and only the written node onto the copy
+    assert forall |m: MapEdge| #[trigger] post.maps.contains(m) implies !m.maps_onto(written) by {
+        if (!pre.maps.contains(m) && m != copied && m != split) {
+            // The rest of a split SpaceMap is mapped onto what it shared
+            assert(m.dst_node() == me.dst_node());
+        }
+    }
+    assert forall |m: MapEdge| #[trigger] post.maps.contains(m) && m.maps_onto(copy_node) implies m.src_node() == written by {
+        if (pre.maps.contains(m)) {
+            assert(pre.is_mapped(copy_node));
+        }
+    }
+
+    // pd alone holds the written node and the copy
+    assert forall |p: ProtectionDomain, n: ResourceLike| #[trigger] post.holds_with_rights(p, n, Set::empty())
+        && (n == written || n == copy_node) implies p == pd by {
+            let he = choose |he: HoldEdge| #[trigger] post.holds.contains(he) && he.src() == p && he.dst() == n
+                && Set::<Right>::empty().subset_of(he.rights());
+            assert(pre.holds.contains(he));
+        }
+
+    if (post.shares_resource(pd, other, copy)) {
+        let r2 = choose |r2: ResourceId| post.reaches_physical(other, r2)
+            && post.resources[copy].rtype() == post.resources[r2].rtype()
+            && !post.resources[copy].range().disjoint(post.resources[r2].range());
+        if (r2 != copy) {
+            // Live physical Resources of the same type never overlap
+            assert(pre.resources.contains_key(r2) && pre.resources.contains_key(copy));
+        } else {
+            let (n, fuel) = choose |n: ResourceLike, fuel: nat| #[trigger] post.resolves_to(n, copy_node, fuel)
+                && post.holds_with_rights(other, n, Set::empty());
+            if (n != copy_node && n != written) {
+                // Whatever other holds would resolve to the copy through the written node, which
+                // nothing is mapped onto
+                lemma_resolution_passes_through(post, n, copy_node, written, fuel);
+                lemma_resolution_passes_through(post, n, written, written, fuel);
+            }
+        }
+    }
+
+    if (me is SpaceMap) {
+        // The written val is translated into the first val of the copy
+        assert(pre.resources[copy].well_formed());
+        assert(post.maps.contains(split) && split.covers(me->sm_src, v));
+        assert(pre.resources[copy].range().contains(split->sm_attrs.translation.apply(v) as nat));
+        assert(post.lands_in(me->sm_src, v, copy));
+
+        assert forall |s: SpaceId, w: nat| #[trigger] post.lands_in(s, w, copy) implies s == me->sm_src && w == v by {
+            let m = choose |m: MapEdge| #[trigger] post.maps.contains(m) && m.covers(s, w)
+                && post.subsets.contains(SubsetEdge { src: copy, dst: m->sm_dst })
+                && post.resources[copy].range().contains(m->sm_attrs.translation.apply(w) as nat);
+            if (pre.maps.contains(m)) {
+                assert(pre.lands_in(s, w, copy));
+            } else if (m != split) {
+                // The rest of the split SpaceMap translates as it did
+                assert(m == me.without(v));
+                assert(me.covers(s, w));
+                assert(pre.lands_in(s, w, copy));
+            }
+        }
+    }
+}
+
+// Migration:
+
+/// Remapping [me] onto [dst] moves the data without changing who can name it: exactly the
+/// ProtectionDomains which reached the virtual node before still reach it, the node stays backed
+/// by [dst], and the old backing is left unmapped, free to be destroyed
+pub proof fn theorem_remap_preserves_reach<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, pd: ProtectionDomain, me: MapEdge, dst: ResourceId)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::remap(pre, post, pd, me, dst),
+    ensures
+        forall |p: ProtectionDomain| #[trigger] post.reaches(p, me.src_node()) == pre.reaches(p, me.src_node()),
+        post.maps.contains(pre.retarget(me, dst)),
+        pre.retarget(me, dst).src_node() == me.src_node(),
+        pre.retarget(me, dst).dst_node() == (ResourceLike::Resource { res: dst }),
+        !post.is_mapped(me.dst_node()),
+{
+    let moved = pre.retarget(me, dst);
+    let virt = me.src_node();
+    let old = me.dst_node();
+
+    // The mapped node is virtual, while both backings are physical
+    assert(pre.is_mapped(virt));
+    assert(virt != old);
+    assert(virt != (ResourceLike::Resource { res: dst }));
+    assert(old != (ResourceLike::Resource { res: dst }));
+
+    assert forall |p: ProtectionDomain| #[trigger] post.reaches(p, virt) == pre.reaches(p, virt) by {
+        if (!pre.holds_with_rights(p, virt, Set::empty())) {
+            if (post.reaches(p, virt)) {
+                let m = choose |m: MapEdge| #[trigger] post.maps.contains(m) && m.maps_onto(virt)
+                    && post.holds_with_rights(p, m.src_node(), Set::empty());
+                assert(m != moved);
+                assert(pre.maps.contains(m));
+            }
+            if (pre.reaches(p, virt)) {
+                let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.maps_onto(virt)
+                    && pre.holds_with_rights(p, m.src_node(), Set::empty());
+                assert(m != me);
+                assert(post.maps.contains(m));
+            }
+        }
+    }
+
+    if (post.is_mapped(old)) {
+        let m = choose |m: MapEdge| #[trigger] post.maps.contains(m) && m.touches(old);
+        assert(m != moved);
+        assert(pre.maps.contains(m) && m != me);
+    }
+}
+
+// Retyping:
+
+/// A retyped Resource is never mapped as plain memory: no ResourceMap maps it or maps onto it,
+/// and it backs no ResourceSpace other than the one it was retyped into
+pub proof fn lemma_retyped_never_mapped_as_memory<K: ResourceKind>(s: OsmosisDAG::State<K>, r: ResourceId, me: MapEdge)
+    requires
+        s.invariant(),
+        s.retyped.contains_key(r),
+        s.maps.contains(me),
+        me.touches(ResourceLike::Resource { res: r }),
+    ensures
+        !(me is ResourceMap),
+        me is SpaceBacking && me->sb_src == s.retyped[r] && s.spaces[me->sb_src].rtype() is Virtual,
+{
+    assert(s.retyped_resources_exclusive());
+}
+
+// Metadata Accounting:
+
+/// Metadata is charged as physical Resources allocated like any other, so the physical Resources
+/// of type [rtype] in use as data, together with the ones charged as metadata of every
+/// ResourceSpace, never exceed the physical vals of that type set up in initialize
+pub proof fn theorem_physical_consumption_bounded<K: ResourceKind>(s: OsmosisDAG::State<K>, rtype: ResourceType<K>)
+    requires
+        s.invariant(),
+        rtype is Physical,
+    ensures
+        set_sum(s.data_of(rtype), resource_size(s.resources)) + s.metadata_size(rtype) <= s.physical_vals(rtype).len(),
+{
+    let ids = s.initial_spaces.dom().filter(|id: SpaceId| s.initial_spaces[id].rtype() == rtype);
+    let parts = |id: SpaceId| s.initial_spaces[id].vals();
+    let ranges = |r: ResourceId| s.resources[r].range();
+    let physical = s.resources_of(rtype);
+
+    // The physical vals are a union of finitely many finite sets
+    lemma_union_finite(ids, parts);
+    assert(s.physical_vals(rtype) =~= Set::new(|v: nat| exists |id: SpaceId| #[trigger] ids.contains(id) && parts(id).contains(v)));
+
+    // Every physical Resource covers as many physical vals as its size
+    assert forall |r: ResourceId| #[trigger] physical.contains(r) implies
+        ranges(r).subset_of(s.physical_vals(rtype)) && resource_size(s.resources)(r) == ranges(r).len() by {
+            let se = choose |e: SubsetEdge| s.subsets.contains(e) && #[trigger] e.src() == r;
+            assert(se.well_formed(s.resources, s.spaces));
+            assert(s.spaces.contains_key(se.dst()) && s.spaces[se.dst()].rtype() == rtype);
+            lemma_nat_interval_len(s.resources[r].val(), s.resources[r].size());
+            assert(ranges(r) =~= nat_interval(s.resources[r].val(), s.resources[r].size()));
+        }
+
+    // No two of them overlap
+    assert forall |a: ResourceId, b: ResourceId| #[trigger] physical.contains(a) && #[trigger] physical.contains(b) && a != b
+        implies ranges(a).disjoint(ranges(b)) by {
+            assert(s.resources.contains_key(a) && s.resources.contains_key(b));
+        }
+
+    lemma_set_sum_disjoint_subsets(physical, ranges, resource_size(s.resources), s.physical_vals(rtype));
+
+    // The physical Resources are the ones in use as data, and the ones charged as metadata
+    let size = resource_size(s.resources);
+    let spaces = s.metadata.dom();
+    let parts = |space: SpaceId| s.metadata_of(space, rtype);
+    let charged = Set::new(|r: ResourceId| exists |space: SpaceId| #[trigger] spaces.contains(space) && parts(space).contains(r));
+    assert(spaces =~= s.spaces.dom().intersect(spaces));
+    lemma_union_finite(spaces, parts);
+    assert forall |r: ResourceId| #[trigger] charged.contains(r) implies physical.contains(r) && !s.data_of(rtype).contains(r) by {
+        let space = choose |space: SpaceId| #[trigger] spaces.contains(space) && parts(space).contains(r);
+        assert(s.metadata.contains_key(space) && s.metadata[space].contains(r));
+    }
+    assert forall |r: ResourceId| #[trigger] physical.contains(r) && s.is_metadata(r) implies charged.contains(r) by {
+        let space = choose |space: SpaceId| #[trigger] s.metadata.contains_key(space) && s.metadata[space].contains(r);
+        assert(parts(space).contains(r));
+    }
+    assert(physical =~= s.data_of(rtype).union(charged));
+    lemma_set_sum_union(s.data_of(rtype), charged, size);
+
+    // Metadata is charged to a single space, so the spaces' shares add up to all of it
+    assert forall |a: SpaceId, b: SpaceId| #[trigger] spaces.contains(a) && #[trigger] spaces.contains(b) && a != b
+        implies parts(a).disjoint(parts(b)) by {
+            assert forall |r: ResourceId| #[trigger] parts(a).contains(r) implies !parts(b).contains(r) by {
+                assert(s.metadata[a].contains(r));
+            }
+        }
+    let shares = |space: SpaceId| set_sum(s.metadata_of(space, rtype), resource_size(s.resources));
+    lemma_set_sum_parts(spaces, parts, size, shares);
+    assert(s.metadata_size(rtype) == set_sum(spaces, shares));
+}
+
+// Request Lifecycle:
+
+/// Clients keep holding what their Requests granted them if the granted Resources, and the hold
+/// edges they rely on, survive a step which leaves the Requests alone
+proof fn lemma_clients_keep_grants<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.granted_requests_held(),
+        post.issued == pre.issued,
+        forall |id: RequestId, r: ResourceId| #[trigger] pre.granted_by(id, r) ==>
+            post.resources.contains_key(r) && post.resources[r] == pre.resources[r],
+        forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.granted_to(he.src(), he.dst()) ==> post.holds.contains(he),
+    ensures
+        post.granted_requests_held(),
+{
+    assert forall |id: RequestId, r: ResourceId| #[trigger] post.granted_by(id, r) implies
+        holds_resource(post.holds, post.issued[id].client(), r) by {
+            assert(pre.granted_by(id, r));
+            let he = choose |he: HoldEdge| #[trigger] pre.holds.contains(he) && he.src() == pre.issued[id].client()
+                && he.dst() == (ResourceLike::Resource { res: r });
+            assert(pre.granted_to(he.src(), he.dst()));
+            assert(post.holds.contains(he));
+        }
+}
+
+/// Whether [ex] is an infinite execution of the Osmosis DAG, one state per step
+pub open spec fn is_execution<K: ResourceKind>(ex: spec_fn(nat) -> OsmosisDAG::State<K>) -> bool {
+    forall |i: nat| OsmosisDAG::State::next(#[trigger] ex(i), ex(i + 1))
+}
+
+/// Whether the step from [pre] to [post] is the server serving the Request [id]: handing one of
+/// the requested Resources over, or refusing it
+pub open spec fn serves<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, id: RequestId) -> bool {
+    ||| exists |client: ProtectionDomain, he: HoldEdge, rights: Set<Right>| #[trigger] OsmosisDAG::State::fulfil_request(pre, post, id, client, he, rights)
+    ||| OsmosisDAG::State::deny_request(pre, post, id)
+}
+
+/// The number of Resources the server still has to hand over for the Request [id]
+pub open spec fn outstanding<K: ResourceKind>(s: OsmosisDAG::State<K>, id: RequestId) -> nat {
+    (s.issued[id].count() - s.issued[id].granted().len()) as nat
+}
+
+/// Whether the servers of [ex] are fair: the server of a pending Request serves it again at some
+/// later step, unless the Request is answered before. This is an obligation on every single
+/// step of the server, and not on the Request as a whole.
+pub open spec fn servers_fair<K: ResourceKind>(ex: spec_fn(nat) -> OsmosisDAG::State<K>) -> bool {
+    forall |id: RequestId, i: nat| #[trigger] ex(i).is_pending(id)
+        ==> exists |j: nat| j >= i && (!ex(j).is_pending(id) || #[trigger] serves(ex(j), ex(j + 1), id))
+}
+
+/// Only its server moves a pending Request along
+proof fn lemma_only_servers_advance_requests<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, id: RequestId)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::next(pre, post),
+        pre.is_pending(id),
+        !serves(pre, post, id),
+    ensures
+        post.issued.contains_key(id),
+        post.issued[id] == pre.issued[id],
+{
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, res_id, res, space, rights) => { }
+        destroy_resource(pd, res) => { }
+        create_pd(parent, pd) => { }
+        destroy_pd(pd) => { }
+        create_request_edge(req) => { }
+        destroy_request_edge(req) => { }
+        issue_request(i, req) => {
+            assert(i != id);
+        }
+        fulfil_request(i, client, he, rights) => {
+            if (i == id) {
+                assert(OsmosisDAG::State::fulfil_request(pre, post, id, client, he, rights));
+            }
+        }
+        deny_request(i) => {
+            assert(i != id);
+        }
+        close_request(i) => {
+            assert(i != id);
+        }
+        create_map_edge(pd, me) => { }
+        split_map_edge(pd, me, part) => { }
+        cow_write(pd, me, v, copy) => { }
+        demand_fault(pd, me, v, backing) => { }
+        remap(pd, me, dst) => { }
+        retype(pd, res, space, rtype, vals, rights) => { }
+        share_hold(pd, to, he, rights) => { }
+        grant_hold(pd, to, he, rights) => { }
+        revoke_hold(pd, h) => { }
+        advance_epoch() => { }
+        lend_hold(lender, borrower, he, rights, expiry) => { }
+        reclaim_lease(lender, he) => { }
+        delegate_quota(from, to, rtype, amount) => { }
+        assign_device(driver, device, iommu, rights) => { }
+        program_iommu(driver, device, me) => { }
+        route_interrupt(pd, irq, handler, rights) => { }
+        unroute_interrupt(pd, irq) => { }
+        create_resource_space(pd, space, res, me, meta, rights) => { }
+        split_space(pd, parent, child, vals, rights) => { }
+        create_budget(pd, source, budget, vals, rights) => { }
+        resize_space(pd, space, vals) => { }
+        merge_spaces(pd, parent, keep, other) => { }
+        destroy_resource_space(pd, space) => { }
+    }}
+}
+
+/// Serving a pending Request either answers it, or hands one more of the requested Resources
+/// over
+proof fn lemma_serving_advances_request<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, id: RequestId)
+    requires
+        pre.invariant(),
+        pre.is_pending(id),
+        serves(pre, post, id),
+    ensures
+        !post.is_pending(id) || {
+            &&& post.issued[id].count() == pre.issued[id].count()
+            &&& outstanding(post, id) < outstanding(pre, id)
+        },
+{
+    if (!OsmosisDAG::State::deny_request(pre, post, id)) {
+        let (client, he, rights) = choose |client: ProtectionDomain, he: HoldEdge, rights: Set<Right>|
+            #[trigger] OsmosisDAG::State::fulfil_request(pre, post, id, client, he, rights);
+        let r = he.dst()->res;
+        assert(!pre.granted_by(id, r));
+        assert(!pre.issued[id].granted().contains(r));
+        assert(pre.issued[id].granted().insert(r).len() == pre.issued[id].granted().len() + 1);
+    }
+}
+
+/// A Request which stays pending keeps what it asks for, and never has more Resources outstanding
+/// than before
+proof fn lemma_outstanding_never_grows<K: ResourceKind>(ex: spec_fn(nat) -> OsmosisDAG::State<K>, id: RequestId, i: nat, k: nat)
+    requires
+        is_execution(ex),
+        forall |l: nat| #[trigger] ex(l).invariant(),
+        i <= k,
+        forall |l: nat| i <= l <= k ==> #[trigger] ex(l).is_pending(id),
+    ensures
+        ex(k).issued[id].count() == ex(i).issued[id].count(),
+        outstanding(ex(k), id) <= outstanding(ex(i), id),
+    decreases k - i,
+{
+    if (k > i) {
+        let l = (k - 1) as nat;
+        lemma_outstanding_never_grows(ex, id, i, l);
+        assert(ex(l).is_pending(id) && ex(k).is_pending(id));
+        assert(OsmosisDAG::State::next(ex(l), ex(l + 1)));
+        if (serves(ex(l), ex(k), id)) {
+            lemma_serving_advances_request(ex(l), ex(k), id);
+        } else {
+            lemma_only_servers_advance_requests(ex(l), ex(k), id);
+        }
+    }
+}
+
+/// In an execution with fair servers, every Request which is issued is eventually answered
+pub proof fn theorem_requests_eventually_answered<K: ResourceKind>(ex: spec_fn(nat) -> OsmosisDAG::State<K>, id: RequestId, i: nat)
+    requires
+        is_execution(ex),
+        forall |k: nat| #[trigger] ex(k).invariant(),
+        servers_fair(ex),
+        ex(i).is_pending(id),
+    ensures
+        exists |j: nat| j >= i && !#[trigger] ex(j).is_pending(id),
+    decreases outstanding(ex(i), id),
+{
+    let j = choose |j: nat| j >= i && (!ex(j).is_pending(id) || #[trigger] serves(ex(j), ex(j + 1), id));
+    if (forall |k: nat| i <= k <= j ==> #[trigger] ex(k).is_pending(id)) {
+        // The server serves the Request at j, which either answers it or leaves less outstanding
+        lemma_outstanding_never_grows(ex, id, i, j);
+        lemma_serving_advances_request(ex(j), ex(j + 1), id);
+        let next = (j + 1) as nat;
+        if (ex(next).is_pending(id)) {
+            theorem_requests_eventually_answered(ex, id, next);
+            let k = choose |k: nat| k >= next && !#[trigger] ex(k).is_pending(id);
+            assert(k >= i);
+        }
+    } else {
+        let k = choose |k: nat| i <= k <= j && !#[trigger] ex(k).is_pending(id);
+        assert(k >= i);
+    }
+}
+
+// Fan-out Mappings:
+
+/// SpaceMaps stay disjoint through a step which adds no SpaceMap but [me], if no other SpaceMap
+/// translates the vals [me] translates
+proof fn lemma_translations_stay_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, me: MapEdge)
+    requires
+        pre.translations_disjoint(),
+        forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap ==> pre.maps.contains(m) || m == me,
+        post.maps.contains(me) && me is SpaceMap ==> pre.translates_disjointly(me),
+    ensures
+        post.translations_disjoint(),
+{
+    assert forall |m1: MapEdge, m2: MapEdge|
+        #[trigger] post.maps.contains(m1) && #[trigger] post.maps.contains(m2) && m1 is SpaceMap && m2 is SpaceMap
+        && m1->sm_src == m2->sm_src && m1 != m2 implies m1->sm_vals.disjoint(m2->sm_vals) by {
+        if (m1 == me) {
+            assert(pre.maps.contains(m2));
+        } else if (m2 == me) {
+            assert(pre.maps.contains(m1));
+        } else {
+            assert(pre.maps.contains(m1) && pre.maps.contains(m2));
+        }
+    }
+}
+
+/// Splitting the SpaceMap [me] into [narrowed] and [split], which translate disjoint parts of its
+/// vals, keeps the translations disjoint
+proof fn lemma_split_translations_stay_disjoint<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, me: MapEdge, narrowed: MapEdge, split: MapEdge)
+    requires
+        pre.translations_disjoint(),
+        pre.maps.contains(me),
+        me is SpaceMap && narrowed is SpaceMap && split is SpaceMap,
+        narrowed->sm_src == me->sm_src && split->sm_src == me->sm_src,
+        narrowed->sm_vals.subset_of(me->sm_vals),
+        split->sm_vals.subset_of(me->sm_vals),
+        narrowed->sm_vals.disjoint(split->sm_vals),
+        forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap ==>
+            (pre.maps.contains(m) && m != me) || m == narrowed || m == split,
+    ensures
+        post.translations_disjoint(),
+{
+    assert forall |m1: MapEdge, m2: MapEdge|
+        #[trigger] post.maps.contains(m1) && #[trigger] post.maps.contains(m2) && m1 is SpaceMap && m2 is SpaceMap
+        && m1->sm_src == m2->sm_src && m1 != m2 implies m1->sm_vals.disjoint(m2->sm_vals) by {
+        if (m1 != narrowed && m1 != split) {
+            assert(pre.maps.contains(m1) && m1 != me);
+            if (m2 != narrowed && m2 != split) {
+                assert(pre.maps.contains(m2));
+            } else {
+                // Both parts of the split translate vals of the SpaceMap they split
+                assert(m1->sm_vals.disjoint(me->sm_vals));
+            }
+        } else if (m2 != narrowed && m2 != split) {
+            assert(pre.maps.contains(m2) && m2 != me);
+            assert(m2->sm_vals.disjoint(me->sm_vals));
+        } else if (m1 == split) {
+            assert(m2 == narrowed);
+        }
+    }
+}
+
+/// The SpaceMaps fanning out of each space keep covering it through a step which adds no SpaceMap,
+/// removes none but whole fan-outs, and only shrinks the spaces SpaceMaps fan out of
+proof fn lemma_translations_stay_covering<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.translations_cover(),
+        forall |m: MapEdge| #[trigger] post.maps.contains(m) && m is SpaceMap ==> pre.maps.contains(m)
+            && post.spaces[m->sm_src].vals().subset_of(pre.spaces[m->sm_src].vals()),
+        forall |m1: MapEdge, m2: MapEdge| #[trigger] pre.maps.contains(m1) && #[trigger] post.maps.contains(m2)
+            && m1 is SpaceMap && m2 is SpaceMap && m1->sm_src == m2->sm_src ==> post.maps.contains(m1),
+    ensures
+        post.translations_cover(),
+{
+    assert forall |me: MapEdge, v: nat| #[trigger] post.maps.contains(me) && me is SpaceMap && #[trigger] post.spaces[me->sm_src].vals().contains(v)
+        implies exists |m: MapEdge| #[trigger] post.maps.contains(m) && m.covers(me->sm_src, v) by {
+        assert(pre.maps.contains(me) && pre.spaces[me->sm_src].vals().contains(v));
+        let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(me->sm_src, v);
+        assert(post.maps.contains(m));
+    }
+}
+
+/// The SpaceMaps fanning out of each space keep covering it through a step which adds the SpaceMap
+/// [me], if it translates every val of its src which no other SpaceMap translates, and leaves the
+/// other spaces SpaceMaps fan out of alone
+proof fn lemma_fan_out_completed<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, me: MapEdge)
+    requires
+        pre.translations_cover(),
+        me is SpaceMap,
+        post.maps == pre.maps.insert(me),
+        forall |m: MapEdge| #[trigger] pre.maps.contains(m) && m is SpaceMap ==> post.spaces[m->sm_src] == pre.spaces[m->sm_src],
+        forall |v: nat| #[trigger] post.spaces[me->sm_src].vals().contains(v) ==>
+            me->sm_vals.contains(v) || exists |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(me->sm_src, v),
+    ensures
+        post.translations_cover(),
+{
+    assert forall |m1: MapEdge, v: nat| #[trigger] post.maps.contains(m1) && m1 is SpaceMap && #[trigger] post.spaces[m1->sm_src].vals().contains(v)
+        implies exists |m: MapEdge| #[trigger] post.maps.contains(m) && m.covers(m1->sm_src, v) by {
+        if (m1 == me) {
+            if (me->sm_vals.contains(v)) {
+                assert(post.maps.contains(me) && me.covers(me->sm_src, v));
+            } else {
+                let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(me->sm_src, v);
+                assert(post.maps.contains(m));
+            }
+        } else {
+            assert(pre.maps.contains(m1) && pre.spaces[m1->sm_src].vals().contains(v));
+            let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(m1->sm_src, v);
+            assert(post.maps.contains(m));
+        }
+    }
+}
+
+/// Splitting the SpaceMap [me] into [narrowed] and [split], which translate every val of it between
+/// them, keeps the SpaceMaps fanning out of each space covering it
+proof fn lemma_split_translations_stay_covering<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, me: MapEdge, narrowed: MapEdge, split: MapEdge)
+    requires
+        pre.translations_cover(),
+        pre.maps.contains(me),
+        me is SpaceMap && narrowed is SpaceMap && split is SpaceMap,
+        narrowed->sm_src == me->sm_src && split->sm_src == me->sm_src,
+        me->sm_vals.subset_of(narrowed->sm_vals.union(split->sm_vals)),
+        post.spaces == pre.spaces,
+        post.maps == pre.maps.remove(me).insert(narrowed).insert(split),
+    ensures
+        post.translations_cover(),
+{
+    assert forall |m1: MapEdge, v: nat| #[trigger] post.maps.contains(m1) && m1 is SpaceMap && #[trigger] post.spaces[m1->sm_src].vals().contains(v)
+        implies exists |m: MapEdge| #[trigger] post.maps.contains(m) && m.covers(m1->sm_src, v) by {
+        // Every SpaceMap left fans out of a space some SpaceMap fanned out of before
+        let fan = if (pre.maps.contains(m1)) { m1 } else { me };
+        assert(pre.maps.contains(fan) && pre.spaces[fan->sm_src].vals().contains(v));
+        let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(m1->sm_src, v);
+        if (m == me) {
+            // The val is handed over to one of the two parts
+            if (narrowed->sm_vals.contains(v)) {
+                assert(post.maps.contains(narrowed) && narrowed.covers(m1->sm_src, v));
+            } else {
+                assert(post.maps.contains(split) && split.covers(m1->sm_src, v));
+            }
+        } else {
+            assert(post.maps.contains(m));
+        }
+    }
+}
+
+// Leases:
+
+/// Leases stay well formed through a step which lends and reclaims nothing, if the hold edges on
+/// lent Resources survive it, and nothing new holds, backs, grants or handles a lent Resource
+proof fn lemma_leases_stay_well_formed<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.leases_well_formed(),
+        post.leases == pre.leases,
+        forall |he: HoldEdge| #[trigger] pre.holds.contains(he) && pre.is_lent(he.dst()) ==> post.holds.contains(he),
+        forall |he: HoldEdge| #[trigger] post.holds.contains(he) && pre.is_lent(he.dst()) ==> pre.holds.contains(he),
+        forall |me: MapEdge| #[trigger] post.maps.contains(me) && me is SpaceBacking ==>
+            pre.maps.contains(me) || !pre.is_lent(me.dst_node()),
+        forall |id: RequestId, r: ResourceId| #[trigger] post.granted_by(id, r) ==>
+            pre.granted_by(id, r) || !pre.is_lent(ResourceLike::Resource { res: r }),
+        forall |me: MapEdge| #[trigger] post.maps.contains(me) && me is Delivery ==>
+            pre.maps.contains(me) || !pre.is_lent(me.src_node()),
+    ensures
+        post.leases_well_formed(),
+{
+    assert forall |he: HoldEdge| #[trigger] post.leases.contains_key(he) implies post.lease_well_formed(he) by {
+        let lent = he.dst();
+        assert(pre.lease_well_formed(he));
+        assert(pre.leases.contains_key(he) && he.dst() == lent);
+        assert(lent == (ResourceLike::Resource { res: lent->res }));
+        let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pre.leases[he].lender()
+            && w.dst() == (ResourceLike::Resource { res: lent->res });
+        assert(post.holds.contains(w));
+        assert forall |me: MapEdge| #[trigger] post.maps.contains(me) implies !(me is SpaceBacking && me->sb_dst == lent->res) by {
+            if (me is SpaceBacking && me->sb_dst == lent->res) {
+                assert(me.dst_node() == lent);
+            }
+        }
+        if (post.handles(he.src(), lent)) {
+            let d = choose |d: MapEdge| #[trigger] post.maps.contains(d) && d is Delivery && d->dl_dst == he.src()
+                && lent == d.src_node();
+            assert(pre.maps.contains(d));
+        }
+    }
+}
+
+/// Once [lender] reclaims its expired lease [he], the borrower holds nothing of the lent Resource
+/// and nothing is mapped onto or from it, so the borrower can reach nothing through the lease
+pub proof fn theorem_reclaimed_lease_unreachable<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, lender: ProtectionDomain, he: HoldEdge)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::reclaim_lease(pre, post, lender, he),
+    ensures
+        !post.holds_with_rights(he.src(), he.dst(), Set::empty()),
+        forall |me: MapEdge| #[trigger] post.maps.contains(me) ==> !me.touches(he.dst()),
+        !post.reaches(he.src(), he.dst()),
+        forall |n: ResourceLike, fuel: nat| #[trigger] post.resolves_to(he.dst(), n, fuel) ==> n == he.dst(),
+{
+    assert(pre.lease_well_formed(he));
+    if (post.holds_with_rights(he.src(), he.dst(), Set::empty())) {
+        let h = choose |h: HoldEdge| #[trigger] post.holds.contains(h) && h.src() == he.src() && h.dst() == he.dst()
+            && Set::<Right>::empty().subset_of(h.rights());
+        assert(pre.holds.contains(h));
+    }
+    assert forall |n: ResourceLike, fuel: nat| #[trigger] post.resolves_to(he.dst(), n, fuel) implies n == he.dst() by {
+        if (n != he.dst()) {
+            let me = choose |me: MapEdge| #[trigger] post.maps.contains(me) && !(me is Delivery) && me.src_node() == he.dst()
+                && post.resolves_to(me.dst_node(), n, (fuel - 1) as nat);
+            assert(pre.maps.contains(me) && me.src_node() == he.dst());
+        }
+    }
+}
+
+// Derivation:
+
+/// Pruning the derivations down to the hold edges which survive a step keeps them a forest
+proof fn lemma_pruned_derivations_form_forest<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>)
+    requires
+        pre.derivation_forest(),
+        post.derived_from == pre.prune_derivations(post.holds),
+        post.depth == pre.depth,
+    ensures
+        post.derivation_forest(),
+{
+    assert forall |k: HoldEdge| #[trigger] post.derived_from.contains_key(k) implies {
+        &&& post.holds.contains(k)
+        &&& post.holds.contains(post.derived_from[k])
+        &&& post.derived_from[k].dst() == k.dst()
+        &&& post.rank(post.derived_from[k]) < post.rank(k)
+    } by {
+        assert(pre.derived_from.contains_key(k));
+    }
+}
+
+/// Deriving the hold edge [child] from the hold edge [parent] on the same node keeps the
+/// derivations a forest
+proof fn lemma_derived_hold_joins_forest<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, child: HoldEdge, parent: HoldEdge)
+    requires
+        pre.derivation_forest(),
+        pre.holds.contains(parent),
+        parent.dst() == child.dst(),
+        post.holds == pre.holds.insert(child),
+        post.derived_from == pre.derive(child, parent),
+        post.depth == pre.deepen(child, parent),
+    ensures
+        post.derivation_forest(),
+{
+    assert forall |k: HoldEdge| #[trigger] post.derived_from.contains_key(k) implies {
+        &&& post.holds.contains(k)
+        &&& post.holds.contains(post.derived_from[k])
+        &&& post.derived_from[k].dst() == k.dst()
+        &&& post.rank(post.derived_from[k]) < post.rank(k)
+    } by {
+        if (k != child || pre.holds.contains(child)) {
+            // Only the depth of a hold edge which was not held before changes, and every hold
+            // edge a derivation was recorded for is held
+            assert(pre.derived_from.contains_key(k));
+            assert(pre.holds.contains(k) && pre.holds.contains(pre.derived_from[k]));
+            assert(post.rank(k) == pre.rank(k));
+            assert(post.rank(pre.derived_from[k]) == pre.rank(pre.derived_from[k]));
+        } else {
+            assert(post.rank(parent) == pre.rank(parent));
+        }
+    }
+}
+
+/// Every hold edge derived from a hold edge [h] in the graph is in the graph, and on the same node
+proof fn lemma_descendants_hold_same_node<K: ResourceKind>(s: OsmosisDAG::State<K>, k: HoldEdge, h: HoldEdge, fuel: nat)
+    requires
+        s.derivation_forest(),
+        s.holds.contains(h),
+        s.derived_through(k, h, fuel),
+    ensures
+        s.holds.contains(k),
+        k.dst() == h.dst(),
+    decreases fuel
+{
+    if (k != h) {
+        assert(s.derived_from.contains_key(k));
+        lemma_descendants_hold_same_node(s, s.derived_from[k], h, (fuel - 1) as nat);
+    }
+}
+
+/// A derivation which only loses entries only loses descendants
+proof fn lemma_fewer_derivations_fewer_descendants<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, k: HoldEdge, h: HoldEdge, fuel: nat)
+    requires
+        forall |d: HoldEdge| #[trigger] post.derived_from.contains_key(d) ==>
+            pre.derived_from.contains_key(d) && post.derived_from[d] == pre.derived_from[d],
+        post.derived_through(k, h, fuel),
+    ensures
+        pre.derived_through(k, h, fuel),
+    decreases fuel
+{
+    if (k != h) {
+        assert(post.derived_from.contains_key(k));
+        lemma_fewer_derivations_fewer_descendants(pre, post, post.derived_from[k], h, (fuel - 1) as nat);
+    }
+}
+
+/// Once [pd] revokes its [h] hold, no other hold edge left in the graph descends from it, and a
+/// ProtectionDomain which only held the node of [h] through hold edges derived from it neither
+/// holds it anymore, nor reaches it through a mapping
+pub proof fn theorem_revoke_removes_derived_authority<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, pd: ProtectionDomain, h: HoldEdge)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::revoke_hold(pre, post, pd, h),
+    ensures
+        post.holds.contains(h),
+        forall |k: HoldEdge| #[trigger] post.holds.contains(k) && k != h ==> !pre.descends_from(k, h),
+        forall |k: HoldEdge| #[trigger] post.holds.contains(k) && k != h ==> !post.descends_from(k, h),
+        forall |other: ProtectionDomain|
+            (forall |k: HoldEdge| #[trigger] pre.holds.contains(k) && k.src() == other && k.dst() == h.dst()
+                ==> k != h && pre.descends_from(k, h))
+            ==> !#[trigger] post.holds_with_rights(other, h.dst(), Set::empty()) && !post.reaches(other, h.dst()),
+{
+    assert forall |k: HoldEdge| #[trigger] post.holds.contains(k) && k != h implies !post.descends_from(k, h) by {
+        if (post.descends_from(k, h)) {
+            let fuel = choose |fuel: nat| #[trigger] post.derived_through(k, h, fuel);
+            lemma_fewer_derivations_fewer_descendants(pre, post, k, h, fuel);
+            assert(pre.descends_from(k, h));
+        }
+    }
+    assert forall |other: ProtectionDomain|
+        (forall |k: HoldEdge| #[trigger] pre.holds.contains(k) && k.src() == other && k.dst() == h.dst()
+            ==> k != h && pre.descends_from(k, h))
+        implies !#[trigger] post.holds_with_rights(other, h.dst(), Set::empty()) && !post.reaches(other, h.dst()) by {
+            if (post.holds_with_rights(other, h.dst(), Set::empty())) {
+                let k = choose |k: HoldEdge| #[trigger] post.holds.contains(k) && k.src() == other && k.dst() == h.dst()
+                    && Set::<Right>::empty().subset_of(k.rights());
+                assert(pre.holds.contains(k));
+            } else if (post.reaches(other, h.dst())) {
+                let m = choose |m: MapEdge| #[trigger] post.maps.contains(m) && m.maps_onto(h.dst())
+                    && post.holds_with_rights(other, m.src_node(), Set::empty());
+                let w = choose |w: HoldEdge| #[trigger] post.holds.contains(w) && w.src() == other && w.dst() == m.src_node()
+                    && Set::<Right>::empty().subset_of(w.rights());
+                // The mapping survived, so whoever holds its other end still holds the node of h
+                assert(pre.map_survives(m, h.dst(), post.holds));
+                let k = choose |k: HoldEdge| #[trigger] post.holds.contains(k) && k.src() == w.src() && k.dst() == h.dst();
+                assert(Set::<Right>::empty().subset_of(k.rights()));
+                assert(post.holds_with_rights(other, h.dst(), Set::empty()));
+            }
+        }
+}
+
+// Batches:
+
+/// Common operations, such as creating a space, mapping it and granting it to a client, take
+/// several steps, and the states in between can break policies stated over the graph, like
+/// exclusive ownership. AtomicOsmosisDAG takes whole batches of OsmosisDAG steps as its steps,
+/// so that only the states before and after a batch are ever observed.
+
+/// Whether [step] can be taken from [pre]
+pub open spec fn step_enabled<K: ResourceKind>(pre: OsmosisDAG::State<K>, step: OsmosisDAG::Step<K>) -> bool {
+    exists |post: OsmosisDAG::State<K>| #[trigger] OsmosisDAG::State::next_by(pre, post, step)
+}
+
+/// The state [step] leads to from [pre], if it can be taken there
+pub open spec fn after_step<K: ResourceKind>(pre: OsmosisDAG::State<K>, step: OsmosisDAG::Step<K>) -> OsmosisDAG::State<K> {
+    choose |post: OsmosisDAG::State<K>| OsmosisDAG::State::next_by(pre, post, step)
+}
+
+/// Whether each of [steps] can be taken in the state left by the ones before it, starting
+/// from [pre]
+pub open spec fn batch_enabled<K: ResourceKind>(pre: OsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>) -> bool
+    decreases steps.len()
+{
+    steps.len() == 0 || (step_enabled(pre, steps[0]) && batch_enabled(after_step(pre, steps[0]), steps.drop_first()))
+}
+
+/// The state left by taking each of [steps] in turn, starting from [pre]
+pub open spec fn batch_result<K: ResourceKind>(pre: OsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>) -> OsmosisDAG::State<K>
+    decreases steps.len()
+{
+    if (steps.len() == 0) { pre } else { batch_result(after_step(pre, steps[0]), steps.drop_first()) }
+}
+
+/// Whether [steps] atomically take [pre] to [post]
+pub open spec fn batch<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>) -> bool {
+    batch_enabled(pre, steps) && post == batch_result(pre, steps)
+}
+
+/// Whether [trace] takes each of [steps] in turn
+pub open spec fn runs_in_order<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, steps: Seq<OsmosisDAG::Step<K>>) -> bool {
+    &&& trace.len() == steps.len() + 1
+    &&& forall |i: int| 0 <= i < steps.len() ==> OsmosisDAG::State::next_by(#[trigger] trace[i], trace[i + 1], steps[i])
+}
+
+/// A step leads to a single state
+proof fn lemma_step_deterministic<K: ResourceKind>(pre: OsmosisDAG::State<K>, a: OsmosisDAG::State<K>, b: OsmosisDAG::State<K>, step: OsmosisDAG::Step<K>)
+    requires
+        OsmosisDAG::State::next_by(pre, a, step),
+        OsmosisDAG::State::next_by(pre, b, step),
+    ensures
+        a == b,
+{
+    reveal(OsmosisDAG::State::next_by);
+}
+
+/// A batch takes [pre] to [post] exactly when running its steps in order, one at a time, does
+pub proof fn lemma_batch_runs_steps_in_order<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>)
+    ensures
+        batch(pre, post, steps) <==> exists |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps)
+            && trace[0] == pre && trace.last() == post,
+    decreases steps.len()
+{
+    if (steps.len() == 0) {
+        if (batch(pre, post, steps)) {
+            let trace = seq![pre];
+            assert(runs_in_order(trace, steps) && trace[0] == pre && trace.last() == post);
+        }
+        if (exists |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps) && trace[0] == pre && trace.last() == post) {
+            let trace = choose |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps)
+                && trace[0] == pre && trace.last() == post;
+            assert(trace.len() == 1);
+        }
+    } else {
+        let mid = after_step(pre, steps[0]);
+        let rest = steps.drop_first();
+        lemma_batch_runs_steps_in_order(mid, post, rest);
+
+        if (batch(pre, post, steps)) {
+            // The first step is enabled, and the rest of the batch runs in order from where it leads
+            assert(OsmosisDAG::State::next_by(pre, mid, steps[0]));
+            let tail = choose |tail: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(tail, rest)
+                && tail[0] == mid && tail.last() == post;
+            let trace = seq![pre] + tail;
+            assert forall |i: int| 0 <= i < steps.len() implies
+                OsmosisDAG::State::next_by(#[trigger] trace[i], trace[i + 1], steps[i]) by {
+                    if (i > 0) {
+                        assert(trace[i] == tail[i - 1] && trace[i + 1] == tail[i] && steps[i] == rest[i - 1]);
+                    }
+                }
+            assert(runs_in_order(trace, steps) && trace[0] == pre && trace.last() == post);
+        }
+
+        if (exists |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps) && trace[0] == pre && trace.last() == post) {
+            let trace = choose |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps)
+                && trace[0] == pre && trace.last() == post;
+            // The first step is enabled, and leads to the only state it can lead to
+            assert(OsmosisDAG::State::next_by(trace[0], trace[1], steps[0]));
+            assert(step_enabled(pre, steps[0]));
+            lemma_step_deterministic(pre, trace[1], mid, steps[0]);
+            let tail = trace.drop_first();
+            assert forall |i: int| 0 <= i < rest.len() implies
+                OsmosisDAG::State::next_by(#[trigger] tail[i], tail[i + 1], rest[i]) by {
+                    assert(OsmosisDAG::State::next_by(trace[i + 1], trace[i + 2], steps[i + 1]));
+                }
+            assert(runs_in_order(tail, rest) && tail[0] == mid && tail.last() == post);
+        }
+    }
+}
+
+/// Each step of a batch is a step of the Osmosis DAG, so the theorems over sequences of steps
+/// apply to the states a batch passes through
+pub proof fn lemma_batch_steps_are_next<K: ResourceKind>(trace: Seq<OsmosisDAG::State<K>>, steps: Seq<OsmosisDAG::Step<K>>)
+    requires
+        runs_in_order(trace, steps),
+    ensures
+        forall |i: int| 0 <= i < trace.len() - 1 ==> OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1]),
+{
+    assert forall |i: int| 0 <= i < trace.len() - 1 implies OsmosisDAG::State::next(#[trigger] trace[i], trace[i + 1]) by {
+        assert(OsmosisDAG::State::next_by(trace[i], trace[i + 1], steps[i]));
+    }
+}
+
+/// Whichever [step] is taken from a state satisfying the invariants of the Osmosis DAG, the state
+/// it leads to satisfies them as well
+pub proof fn lemma_step_keeps_invariant<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, step: OsmosisDAG::Step<K>)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::next_by(pre, post, step),
+    ensures
+        post.invariant(),
+{
+    assert(OsmosisDAG::State::next(pre, post));
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => {
+            OsmosisDAG::State::create_resource_inductive(pre, post, pd, id, res, space, rights);
+        }
+        destroy_resource(pd, res) => {
+            OsmosisDAG::State::destroy_resource_inductive(pre, post, pd, res);
+        }
+        create_pd(parent, pd) => {
+            OsmosisDAG::State::create_pd_inductive(pre, post, parent, pd);
+        }
+        destroy_pd(pd) => {
+            OsmosisDAG::State::destry_pd_inductve(pre, post, pd);
+        }
+        create_request_edge(req) => {
+            OsmosisDAG::State::create_request_edge_inductive(pre, post, req);
+        }
+        destroy_request_edge(req) => {
+            OsmosisDAG::State::destroy_request_edge_inductive(pre, post, req);
+        }
+        issue_request(id, req) => {
+            OsmosisDAG::State::issue_request_inductive(pre, post, id, req);
+        }
+        fulfil_request(id, client, he, rights) => {
+            OsmosisDAG::State::fulfil_request_inductive(pre, post, id, client, he, rights);
+        }
+        deny_request(id) => {
+            OsmosisDAG::State::deny_request_inductive(pre, post, id);
+        }
+        close_request(id) => {
+            OsmosisDAG::State::close_request_inductive(pre, post, id);
+        }
+        create_map_edge(pd, me) => {
+            OsmosisDAG::State::create_map_edge_inductive(pre, post, pd, me);
+        }
+        split_map_edge(pd, me, part) => {
+            OsmosisDAG::State::split_map_edge_inductive(pre, post, pd, me, part);
+        }
+        cow_write(pd, me, v, copy) => {
+            OsmosisDAG::State::cow_write_inductive(pre, post, pd, me, v, copy);
+        }
+        demand_fault(pd, me, v, backing) => {
+            OsmosisDAG::State::demand_fault_inductive(pre, post, pd, me, v, backing);
+        }
+        remap(pd, me, dst) => {
+            OsmosisDAG::State::remap_inductive(pre, post, pd, me, dst);
+        }
+        retype(pd, res, space, rtype, vals, rights) => {
+            OsmosisDAG::State::retype_inductive(pre, post, pd, res, space, rtype, vals, rights);
+        }
+        share_hold(pd, to, he, rights) => {
+            OsmosisDAG::State::share_hold_inductive(pre, post, pd, to, he, rights);
+        }
+        grant_hold(pd, to, he, rights) => {
+            OsmosisDAG::State::grant_hold_inductive(pre, post, pd, to, he, rights);
+        }
+        revoke_hold(pd, h) => {
+            OsmosisDAG::State::revoke_hold_inductive(pre, post, pd, h);
+        }
+        advance_epoch() => {
+            OsmosisDAG::State::advance_epoch_inductive(pre, post);
+        }
+        lend_hold(lender, borrower, he, rights, expiry) => {
+            OsmosisDAG::State::lend_hold_inductive(pre, post, lender, borrower, he, rights, expiry);
+        }
+        reclaim_lease(lender, he) => {
+            OsmosisDAG::State::reclaim_lease_inductive(pre, post, lender, he);
+        }
+        split_space(pd, parent, child, vals, rights) => {
+            OsmosisDAG::State::split_space_inductive(pre, post, pd, parent, child, vals, rights);
+        }
+        create_budget(pd, source, budget, vals, rights) => {
+            OsmosisDAG::State::create_budget_inductive(pre, post, pd, source, budget, vals, rights);
+        }
+        resize_space(pd, space, vals) => {
+            OsmosisDAG::State::resize_space_inductive(pre, post, pd, space, vals);
+        }
+        merge_spaces(pd, parent, keep, other) => {
+            OsmosisDAG::State::merge_spaces_inductive(pre, post, pd, parent, keep, other);
+        }
+        delegate_quota(from, to, rtype, amount) => {
+            OsmosisDAG::State::delegate_quota_inductive(pre, post, from, to, rtype, amount);
+        }
+        assign_device(driver, device, iommu, rights) => {
+            OsmosisDAG::State::assign_device_inductive(pre, post, driver, device, iommu, rights);
+        }
+        route_interrupt(pd, irq, handler, rights) => {
+            OsmosisDAG::State::route_interrupt_inductive(pre, post, pd, irq, handler, rights);
+        }
+        unroute_interrupt(pd, irq) => {
+            OsmosisDAG::State::unroute_interrupt_inductive(pre, post, pd, irq);
+        }
+        program_iommu(driver, device, me) => {
+            OsmosisDAG::State::program_iommu_inductive(pre, post, driver, device, me);
+        }
+        create_resource_space(pd, space, res, me, meta, rights) => {
+            OsmosisDAG::State::create_resource_space_inductive(pre, post, pd, space, res, me, meta, rights);
+        }
+        destroy_resource_space(pd, space) => {
+            OsmosisDAG::State::destroy_resource_space_inductive(pre, post, pd, space);
+        }
+    }}
+}
+
+/// A batch taken from a state satisfying the invariants of the Osmosis DAG leads to a state
+/// satisfying them as well
+pub proof fn lemma_batch_keeps_invariant<K: ResourceKind>(pre: OsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>)
+    requires
+        pre.invariant(),
+        batch_enabled(pre, steps),
+    ensures
+        batch_result(pre, steps).invariant(),
+    decreases steps.len()
+{
+    if (steps.len() > 0) {
+        let mid = after_step(pre, steps[0]);
+        assert(OsmosisDAG::State::next_by(pre, mid, steps[0]));
+        lemma_step_keeps_invariant(pre, mid, steps[0]);
+        lemma_batch_keeps_invariant(mid, steps.drop_first());
+    }
+}
+
+state_machine!
+{
+    AtomicOsmosisDAG<K: ResourceKind> {
+        fields {
+            /// The Osmosis DAG as left by the last batch
+            pub dag: OsmosisDAG::State<K>,
+        }
+
+        /// The Osmosis DAG satisfies its invariants between batches
+        #[invariant]
+        pub open spec fn dag_invariant(&self) -> bool {
+            self.dag.invariant()
+        }
+
+        init! {
+            initialize(dag: OsmosisDAG::State<K>)
+            {
+                // The Osmosis DAG starts out initialized
+                require OsmosisDAG::State::init(dag);
+                init dag = dag;
+            }
+        }
+
+        /// Takes each of [steps] in turn as one atomic step, none of the states in between being
+        /// observable. A single step of the Osmosis DAG is a batch of one.
+        transition! {
+            batch(steps: Seq<OsmosisDAG::Step<K>>)
+            {
+                // Each step must be enabled in the state left by the ones before it
+                require batch_enabled(pre.dag, steps);
+                update dag = batch_result(pre.dag, steps);
+            }
+        }
+
+        #[inductive(initialize)]
+        fn initialize_inductive(post: Self, dag: OsmosisDAG::State<K>) {
+            case_on_init!{dag, OsmosisDAG => {
+                initialize(physical_spaces, initial_quotas, core_periods, space_costs) => {
+                    OsmosisDAG::State::initialize_inductive(dag, physical_spaces, initial_quotas, core_periods, space_costs);
+                }
+            }}
+        }
+
+        #[inductive(batch)]
+        fn batch_inductive(pre: Self, post: Self, steps: Seq<OsmosisDAG::Step<K>>) {
+            lemma_batch_keeps_invariant(pre.dag, steps);
+        }
+    }
+}
+
+/// A batch of AtomicOsmosisDAG runs its steps one at a time through states of the Osmosis DAG
+/// satisfying its invariants, so the theorems over sequences of steps apply to it, while only its
+/// first and last state are observed
+pub proof fn theorem_batch_atomic<K: ResourceKind>(pre: AtomicOsmosisDAG::State<K>, post: AtomicOsmosisDAG::State<K>, steps: Seq<OsmosisDAG::Step<K>>)
+    requires
+        pre.invariant(),
+        AtomicOsmosisDAG::State::next_by(pre, post, AtomicOsmosisDAG::Step::batch(steps)),
+    ensures
+        post.invariant(),
+        exists |trace: Seq<OsmosisDAG::State<K>>| #[trigger] runs_in_order(trace, steps)
+            && trace[0] == pre.dag && trace.last() == post.dag,
+{
+    reveal(AtomicOsmosisDAG::State::next_by);
+    lemma_batch_keeps_invariant(pre.dag, steps);
+    lemma_batch_runs_steps_in_order(pre.dag, post.dag, steps);
+}
+
+// History:
+
+/// A step which records itself as [l], only creates fresh nodes, each held by its creator which
+/// holds the node it was created out of, and only hands over holds its giver has, keeps every
+/// created node recorded and every hold edge traced back to the initial ProtectionDomain
+proof fn lemma_history_records_step<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, l: OsmosisDAG::Step<K>)
+    requires
+        pre.hold_nodes_in_graph(),
+        pre.created_nodes_recorded(),
+        pre.holds_traced(),
+        post.history == pre.history.push(l),
+        post.initial_spaces == pre.initial_spaces,
+        forall |n: ResourceLike| #[trigger] l.creates(n) ==> !pre.node_in_graph(n),
+        forall |n: ResourceLike| #[trigger] post.node_in_graph(n) ==> pre.node_in_graph(n) || l.creates(n),
+        forall |he: HoldEdge| #[trigger] post.holds.contains(he) ==> {
+            ||| pre.holds.contains(he)
+            ||| l.creates(he.dst()) && l.creator() == he.src() && pre.holds_with_rights(he.src(), l.source(), Set::empty())
+            ||| l.hands_over(he.dst()) && l.receiver() == he.src() && pre.holds_with_rights(l.giver(), he.dst(), Set::empty())
+        },
+    ensures
+        post.created_nodes_recorded(),
+        post.holds_traced(),
+{
+    let root = ProtectionDomain { id: 0 };
+    let i = pre.history.len() as int;
+    assert(post.history[i] == l);
+    assert(post.history.take(i) == pre.history);
+
+    assert forall |n: ResourceLike| #[trigger] post.node_in_graph(n) && last_creation(post.history, n) == -1 implies
+        n is Space && post.initial_spaces.contains_key(n->space) by {
+            lemma_last_creation_push(pre.history, l, n);
+            assert(pre.node_in_graph(n));
+        }
+
+    assert forall |he: HoldEdge| #[trigger] post.holds.contains(he) implies post.traced(he.dst(), he.src()) by {
+        let n = he.dst();
+        lemma_last_creation_push(pre.history, l, n);
+        if (pre.holds.contains(he)) {
+            // The node was in the graph already, so the step did not create it
+            assert(pre.node_in_graph(n));
+            assert(pre.traced(n, he.src()));
+            let fuel = choose |fuel: nat| #[trigger] traces_back(pre.history, n, he.src(), root, fuel);
+            lemma_traces_back_push(pre.history, l, n, he.src(), root, fuel);
+            assert(traces_back(post.history, n, he.src(), root, fuel));
+        } else if (l.creates(n)) {
+            // The creator came to hold the node it created the new one out of before
+            let g = choose |g: HoldEdge| #[trigger] pre.holds.contains(g) && g.src() == he.src() && g.dst() == l.source()
+                && Set::<Right>::empty().subset_of(g.rights());
+            assert(pre.traced(g.dst(), g.src()));
+            let fuel = choose |fuel: nat| #[trigger] traces_back(pre.history, l.source(), he.src(), root, fuel);
+            assert(traces_back(post.history, n, he.src(), root, fuel + 1));
+        } else {
+            // The giver came to hold the node before it handed it over
+            let g = choose |g: HoldEdge| #[trigger] pre.holds.contains(g) && g.src() == l.giver() && g.dst() == n
+                && Set::<Right>::empty().subset_of(g.rights());
+            assert(pre.traced(g.dst(), g.src()));
+            let fuel = choose |fuel: nat| #[trigger] traces_back(pre.history, n, l.giver(), root, fuel);
+            assert(post.history[i].hands_over(n));
+            assert(traces_back(post.history, n, he.src(), root, fuel + 1));
+        }
+    }
+}
+
+/// Every hold on a physical space traces back to the initial ProtectionDomain, through the steps
+/// which carved the space out of another and the steps which handed it over. A physical space
+/// which was never created was set up in initialize, so the initial ProtectionDomain created it.
+pub proof fn theorem_physical_holds_trace_to_initial_domain<K: ResourceKind>(s: OsmosisDAG::State<K>, he: HoldEdge)
+    requires
+        s.invariant(),
+        s.holds.contains(he),
+        he.dst() is Space,
+        s.spaces[he.dst()->space].rtype() is Physical,
+    ensures
+        exists |fuel: nat| #[trigger] traces_back(s.history, he.dst(), he.src(), ProtectionDomain { id: 0 }, fuel),
+        last_creation(s.history, he.dst()) == -1 ==>
+            s.initial_spaces.contains_key(he.dst()->space) && s.created_by(he.dst()) == (ProtectionDomain { id: 0 }),
+{
+    assert(s.traced(he.dst(), he.src()));
+    assert(s.node_in_graph(he.dst()));
+}
+
+// Trusted Computing Base:
+
+/// Whoever holds [n], or a node [n] is mapped onto, is in the trusted computing base of [n]
+proof fn lemma_tcb_contains_holders<K: ResourceKind>(s: OsmosisDAG::State<K>, n: ResourceLike, w: HoldEdge)
+    requires
+        s.holds.contains(w),
+        w.dst() == n || exists |me: MapEdge| #[trigger] s.maps.contains(me) && me.src_node() == n && me.maps_onto(w.dst()),
+    ensures
+        s.tcb(n).contains(w.src()),
+{
+    assert(s.holds_with_rights(w.src(), w.dst(), Set::empty()));
+    assert(s.requests_from(w.src(), w.src(), 0));
+    if (w.dst() == n) {
+        assert(s.depends_on(n, w.dst(), 0));
+    } else {
+        let me = choose |me: MapEdge| #[trigger] s.maps.contains(me) && me.src_node() == n && me.maps_onto(w.dst());
+        assert(s.depends_on(me.dst_node(), w.dst(), 0));
+        assert(s.depends_on(n, w.dst(), 1));
+    }
+}
+
+/// A step taken by a ProtectionDomain outside the trusted computing base of [n], or by nobody,
+/// neither changes the hold edges on [n] nor the subset, partition and map edges out of it,
+/// through which [n] depends on other nodes. Edges onto [n] are not covered: they belong to the
+/// nodes allocated from, carved out of or mapped onto [n], whose holders may change them
+pub proof fn theorem_steps_outside_tcb_keep_holders_and_dependencies<K: ResourceKind>(pre: OsmosisDAG::State<K>, post: OsmosisDAG::State<K>, l: OsmosisDAG::Step<K>, n: ResourceLike)
+    requires
+        pre.invariant(),
+        OsmosisDAG::State::next(pre, post),
+        post.history == pre.history.push(l),
+        pre.node_in_graph(n),
+        !(pre.actor(l) is Some && pre.tcb(n).contains(pre.actor(l)->0)),
+    ensures
+        forall |he: HoldEdge| he.dst() == n ==> (#[trigger] post.holds.contains(he) <==> pre.holds.contains(he)),
+        forall |me: MapEdge| me.src_node() == n ==> (#[trigger] post.maps.contains(me) <==> pre.maps.contains(me)),
+        forall |se: SubsetEdge| n == (ResourceLike::Resource { res: se.src() }) ==>
+            (#[trigger] post.subsets.contains(se) <==> pre.subsets.contains(se)),
+        forall |pe: PartitionEdge| n == (ResourceLike::Space { space: pe.src() }) ==>
+            (#[trigger] post.partitions.contains(pe) <==> pre.partitions.contains(pe)),
+{
+    assert(post.history.last() == l);
+    case_on_next!{pre, post, OsmosisDAG => {
+        create_resource(pd, id, res, space, rights) => {
+            // The only new subset edge is out of the fresh Resource
+            assert(n != (ResourceLike::Resource { res: id }));
+        }
+        destroy_resource(pd, res) => {
+            if (n == (ResourceLike::Resource { res })) {
+                let w = choose |w: HoldEdge| pre.holds.contains(w) && #[trigger] w.src() == pd && w.dst() is Resource
+                    && w.dst()->res == res && w.rights().contains(Right::Destroy);
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        create_pd(parent, pd) => { }
+        destroy_pd(pd) => { }
+        create_request_edge(req) => { }
+        destroy_request_edge(req) => { }
+        issue_request(id, req) => { }
+        fulfil_request(id, client, he, rights) => {
+            if (he.dst() == n) {
+                lemma_tcb_contains_holders(pre, n, he);
+            }
+        }
+        deny_request(id) => { }
+        close_request(id) => { }
+        create_map_edge(pd, me) => {
+            if (me.src_node() == n) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == me.src_node()
+                    && Set::empty().insert(Right::Map).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        split_map_edge(pd, me, part) => {
+            if (me.src_node() == n) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == me.src_node()
+                    && Set::empty().insert(Right::Map).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        cow_write(pd, me, v, copy) => {
+            if (me.src_node() == n) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == me.src_node()
+                    && Set::empty().insert(Right::Write).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        demand_fault(pd, me, v, backing) => {
+            if (me.src_node() == n) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == me.src_node()
+                    && Set::<Right>::empty().subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+            // The only new hold and subset edge are on and out of the fresh backing
+            assert(n != (ResourceLike::Resource { res: backing }));
+        }
+        remap(pd, me, dst) => {
+            if (me.src_node() == n) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == me.src_node()
+                    && Set::empty().insert(Right::Map).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+            assert(pre.retarget(me, dst).src_node() == me.src_node());
+        }
+        retype(pd, res, space, rtype, vals, rights) => { }
+        share_hold(pd, to, he, rights) => {
+            if (he.dst() == n) {
+                lemma_tcb_contains_holders(pre, n, he);
+            }
+        }
+        grant_hold(pd, to, he, rights) => {
+            if (he.dst() == n) {
+                lemma_tcb_contains_holders(pre, n, he);
+            }
+        }
+        revoke_hold(pd, h) => {
+            if (h.dst() == n) {
+                lemma_tcb_contains_holders(pre, n, h);
+            }
+            // Only hold edges on the node of h are revoked
+            assert forall |k: HoldEdge| #[trigger] pre.holds.contains(k) && k.dst() == n implies post.holds.contains(k) by {
+                if (pre.descends_from(k, h) && k != h) {
+                    let fuel = choose |fuel: nat| #[trigger] pre.derived_through(k, h, fuel);
+                    lemma_descendants_hold_same_node(pre, k, h, fuel);
+                }
+            }
+            // Only mappings of the node of h or onto it are dropped, and pd holds that node
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) && me.src_node() == n implies post.maps.contains(me) by {
+                if (me.maps_onto(h.dst())) {
+                    lemma_tcb_contains_holders(pre, n, h);
+                }
+            }
+        }
+        advance_epoch() => { }
+        lend_hold(lender, borrower, he, rights, expiry) => {
+            if (he.dst() == n) {
+                lemma_tcb_contains_holders(pre, n, he);
+            }
+        }
+        reclaim_lease(lender, he) => {
+            // The lender holds the lent Resource, and with it everything its lease affects
+            let lent = he.dst();
+            assert(pre.lease_well_formed(he));
+            assert(lent == (ResourceLike::Resource { res: lent->res }));
+            let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == lender
+                && w.dst() == (ResourceLike::Resource { res: lent->res });
+            if (lent == n) {
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) && me.src_node() == n implies post.maps.contains(me) by {
+                if (me.maps_onto(lent)) {
+                    lemma_tcb_contains_holders(pre, n, w);
+                }
+            }
+        }
+        delegate_quota(from, to, rtype, amount) => { }
+        assign_device(driver, device, iommu, rights) => {
+            if (n == (ResourceLike::Space { space: iommu })) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == driver && w.dst() == n
+                    && rights.insert(Right::Map).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        program_iommu(driver, device, me) => {
+            if (me.src_node() == n) {
+                // The driver holds the IOMMU space of its device
+                assert(pre.device_confined(device));
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == driver && w.dst() == n
+                    && Set::<Right>::empty().subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        route_interrupt(pd, irq, handler, rights) => {
+            if (n == (ResourceLike::Resource { res: irq })) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == n
+                    && rights.insert(Right::Grant).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        unroute_interrupt(pd, irq) => {
+            if (n == (ResourceLike::Resource { res: irq })) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == n
+                    && Set::empty().insert(Right::Grant).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        create_resource_space(pd, space, res, me, meta, rights) => { }
+        split_space(pd, parent, child, vals, rights) => {
+            // The only new partition edge is out of the fresh child
+            assert(n != (ResourceLike::Space { space: child }));
+        }
+        create_budget(pd, source, budget, vals, rights) => { }
+        resize_space(pd, space, vals) => { }
+        merge_spaces(pd, parent, keep, other) => {
+            if (n == (ResourceLike::Space { space: other })) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == n
+                    && Set::empty().insert(Right::Destroy).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+        destroy_resource_space(pd, space) => {
+            if (n == (ResourceLike::Space { space })) {
+                let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd && w.dst() == n
+                    && Set::empty().insert(Right::Destroy).subset_of(w.rights());
+                lemma_tcb_contains_holders(pre, n, w);
+            }
+        }
+    }}
+}
+
+} // verus!
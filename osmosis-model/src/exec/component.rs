@@ -0,0 +1,1098 @@
+/// MIT License
+///
+/// Copyright (c) 2025 Ilias Karimalis
+
+use vstd::prelude::*;
+
+use crate::component::*;
+use crate::kind::*;
+use crate::exec::mirror::KeyedEntry;
+use crate::exec::vals::ValSet;
+
+verus!
+{
+
+/// The ProtectionDomain identified by [id]
+pub open spec fn pd_of(id: u64) -> ProtectionDomain {
+    ProtectionDomain { id: id as nat }
+}
+
+/// Executable StandardKind
+#[derive(Clone, Copy)]
+pub enum ExecKind {
+    Memory,
+    Storage,
+    Device,
+    PdId,
+    Irq,
+}
+
+impl View for ExecKind {
+    type V = StandardKind;
+
+    open spec fn view(&self) -> StandardKind {
+        match self {
+            ExecKind::Memory => StandardKind::Memory,
+            ExecKind::Storage => StandardKind::Storage,
+            ExecKind::Device => StandardKind::Device,
+            ExecKind::PdId => StandardKind::PdId,
+            ExecKind::Irq => StandardKind::Irq,
+        }
+    }
+}
+
+impl ExecKind {
+    pub fn eq(self, other: ExecKind) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        match (self, other) {
+            (ExecKind::Memory, ExecKind::Memory) => true,
+            (ExecKind::Storage, ExecKind::Storage) => true,
+            (ExecKind::Device, ExecKind::Device) => true,
+            (ExecKind::PdId, ExecKind::PdId) => true,
+            (ExecKind::Irq, ExecKind::Irq) => true,
+            _ => false,
+        }
+    }
+
+    pub fn physical(self) -> (b: bool)
+        ensures
+            b == self@.physical(),
+    {
+        match self {
+            ExecKind::PdId => false,
+            _ => true,
+        }
+    }
+
+    pub fn maps_onto(self, other: ExecKind) -> (b: bool)
+        ensures
+            b == self@.maps_onto(other@),
+    {
+        match (self, other) {
+            (ExecKind::Memory, ExecKind::Memory) => true,
+            (ExecKind::Memory, ExecKind::Storage) => true,
+            (ExecKind::Memory, ExecKind::Device) => true,
+            (ExecKind::Device, ExecKind::Memory) => true,
+            (ExecKind::Irq, _) | (_, ExecKind::Irq) => false,
+            _ => self.eq(other),
+        }
+    }
+
+    pub fn delivered(self) -> (b: bool)
+        ensures
+            b == self@.delivered(),
+    {
+        match self {
+            ExecKind::Irq => true,
+            _ => false,
+        }
+    }
+
+    /// The position of this kind in the StandardKind declaration
+    pub fn rank(self) -> (r: usize)
+        ensures
+            r as nat == kind_rank(self@),
+            r < KINDS,
+    {
+        match self {
+            ExecKind::Memory => 0,
+            ExecKind::Storage => 1,
+            ExecKind::Device => 2,
+            ExecKind::PdId => 3,
+            ExecKind::Irq => 4,
+        }
+    }
+
+    /// The kind at position [r] in the StandardKind declaration
+    pub fn of_rank(r: usize) -> (k: ExecKind)
+        requires
+            r < KINDS,
+        ensures
+            kind_rank(k@) == r as nat,
+    {
+        match r {
+            0 => ExecKind::Memory,
+            1 => ExecKind::Storage,
+            2 => ExecKind::Device,
+            3 => ExecKind::PdId,
+            _ => ExecKind::Irq,
+        }
+    }
+}
+
+/// The number of kinds in the StandardKind declaration
+pub const KINDS: usize = 5;
+
+/// The position of [k] in the StandardKind declaration
+pub open spec fn kind_rank(k: StandardKind) -> nat {
+    match k {
+        StandardKind::Memory => 0,
+        StandardKind::Storage => 1,
+        StandardKind::Device => 2,
+        StandardKind::PdId => 3,
+        StandardKind::Irq => 4,
+    }
+}
+
+/// No two kinds share a position
+pub proof fn lemma_kind_rank_injective(k: StandardKind, l: StandardKind)
+    requires
+        kind_rank(k) == kind_rank(l),
+    ensures
+        k == l,
+{
+}
+
+/// Executable ResourceType
+#[derive(Clone, Copy)]
+pub enum ExecResourceType {
+    Virtual(ExecKind),
+    Physical(ExecKind),
+    Time(u64),
+}
+
+impl View for ExecResourceType {
+    type V = ResourceType<StandardKind>;
+
+    open spec fn view(&self) -> ResourceType<StandardKind> {
+        match self {
+            ExecResourceType::Virtual(k) => ResourceType::Virtual(k@),
+            ExecResourceType::Physical(k) => ResourceType::Physical(k@),
+            ExecResourceType::Time(core) => ResourceType::Time(*core as nat),
+        }
+    }
+}
+
+impl ExecResourceType {
+    pub fn eq(self, other: ExecResourceType) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        match (self, other) {
+            (ExecResourceType::Virtual(k), ExecResourceType::Virtual(l)) => k.eq(l),
+            (ExecResourceType::Physical(k), ExecResourceType::Physical(l)) => k.eq(l),
+            (ExecResourceType::Time(c), ExecResourceType::Time(d)) => c == d,
+            _ => false,
+        }
+    }
+
+    pub fn is_virtual(self) -> (b: bool)
+        ensures
+            b == self@ is Virtual,
+    {
+        match self {
+            ExecResourceType::Virtual(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_physical(self) -> (b: bool)
+        ensures
+            b == self@ is Physical,
+    {
+        match self {
+            ExecResourceType::Physical(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_time(self) -> (b: bool)
+        ensures
+            b == self@ is Time,
+    {
+        match self {
+            ExecResourceType::Time(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn declared(self) -> (b: bool)
+        ensures
+            b == self@.declared(),
+    {
+        match self {
+            ExecResourceType::Physical(k) => k.physical(),
+            _ => true,
+        }
+    }
+
+    pub fn may_map_onto(self, other: ExecResourceType) -> (b: bool)
+        ensures
+            b == self@.may_map_onto(other@),
+    {
+        match (self, other) {
+            (ExecResourceType::Virtual(k), ExecResourceType::Virtual(l)) => k.maps_onto(l),
+            (ExecResourceType::Virtual(k), ExecResourceType::Physical(l)) => k.maps_onto(l),
+            (ExecResourceType::Physical(k), ExecResourceType::Physical(l)) => k.maps_onto(l),
+            _ => false,
+        }
+    }
+
+    pub fn delivered(self) -> (b: bool)
+        ensures
+            b == self@.delivered(),
+    {
+        match self {
+            ExecResourceType::Physical(k) => k.delivered(),
+            _ => false,
+        }
+    }
+
+    pub fn can_retype_into(self, other: ExecResourceType) -> (b: bool)
+        ensures
+            b == self@.can_retype_into(other@),
+    {
+        self.is_physical() && other.is_virtual() && other.may_map_onto(self)
+    }
+}
+
+/// Executable Resource
+#[derive(Clone, Copy)]
+pub struct ExecResource {
+    pub rtype: ExecResourceType,
+    pub val: u64,
+    pub size: u64,
+    pub align: u64,
+}
+
+impl View for ExecResource {
+    type V = Resource<StandardKind>;
+
+    open spec fn view(&self) -> Resource<StandardKind> {
+        Resource { rtype: self.rtype@, val: self.val as nat, size: self.size as nat, align: self.align as nat }
+    }
+}
+
+impl ExecResource {
+    pub fn well_formed(&self) -> (b: bool)
+        ensures
+            b == self@.well_formed(),
+    {
+        self.size > 0 && self.align > 0 && self.val % self.align == 0
+    }
+
+    pub fn eq(&self, other: &ExecResource) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        self.rtype.eq(other.rtype) && self.val == other.val && self.size == other.size && self.align == other.align
+    }
+}
+
+/// Executable ResourceSpace
+pub struct ExecSpace {
+    pub rtype: ExecResourceType,
+    pub vals: ValSet,
+}
+
+impl View for ExecSpace {
+    type V = ResourceSpace<StandardKind>;
+
+    open spec fn view(&self) -> ResourceSpace<StandardKind> {
+        ResourceSpace { rtype: self.rtype@, vals: self.vals@ }
+    }
+}
+
+impl ExecSpace {
+    pub open spec fn wf(&self) -> bool {
+        self.vals.wf()
+    }
+
+    /// A copy of the space
+    pub fn copy(&self) -> (s: ExecSpace)
+        requires
+            self.wf(),
+        ensures
+            s.wf(),
+            s@ == self@,
+    {
+        ExecSpace { rtype: self.rtype, vals: self.vals.copy() }
+    }
+}
+
+/// Executable ResourceLike
+#[derive(Clone, Copy)]
+pub enum ExecResourceLike {
+    Resource { res: u64 },
+    Space { space: u64 },
+}
+
+impl View for ExecResourceLike {
+    type V = ResourceLike;
+
+    open spec fn view(&self) -> ResourceLike {
+        match self {
+            ExecResourceLike::Resource { res } => ResourceLike::Resource { res: *res as nat },
+            ExecResourceLike::Space { space } => ResourceLike::Space { space: *space as nat },
+        }
+    }
+}
+
+impl ExecResourceLike {
+    pub fn eq(self, other: ExecResourceLike) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        match (self, other) {
+            (ExecResourceLike::Resource { res: r }, ExecResourceLike::Resource { res: s }) => r == s,
+            (ExecResourceLike::Space { space: r }, ExecResourceLike::Space { space: s }) => r == s,
+            _ => false,
+        }
+    }
+}
+
+/// Executable Right
+#[derive(Clone, Copy)]
+pub enum ExecRight {
+    Read,
+    Write,
+    Execute,
+    Grant,
+    Map,
+    Destroy,
+}
+
+impl View for ExecRight {
+    type V = Right;
+
+    open spec fn view(&self) -> Right {
+        match self {
+            ExecRight::Read => Right::Read,
+            ExecRight::Write => Right::Write,
+            ExecRight::Execute => Right::Execute,
+            ExecRight::Grant => Right::Grant,
+            ExecRight::Map => Right::Map,
+            ExecRight::Destroy => Right::Destroy,
+        }
+    }
+}
+
+/// A set of Rights, one flag per Right
+#[derive(Clone, Copy)]
+pub struct Rights {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+    pub grant: bool,
+    pub map: bool,
+    pub destroy: bool,
+}
+
+impl View for Rights {
+    type V = Set<Right>;
+
+    open spec fn view(&self) -> Set<Right> {
+        Set::new(|r: Right| self.has(r))
+    }
+}
+
+impl Rights {
+    /// Whether the flag of [r] is set
+    pub open spec fn has(&self, r: Right) -> bool {
+        match r {
+            Right::Read => self.read,
+            Right::Write => self.write,
+            Right::Execute => self.execute,
+            Right::Grant => self.grant,
+            Right::Map => self.map,
+            Right::Destroy => self.destroy,
+        }
+    }
+
+    pub fn empty() -> (rights: Rights)
+        ensures
+            rights@ == Set::<Right>::empty(),
+    {
+        let rights = Rights { read: false, write: false, execute: false, grant: false, map: false, destroy: false };
+        assert(rights@ =~= Set::<Right>::empty());
+        rights
+    }
+
+    pub fn full() -> (rights: Rights)
+        ensures
+            rights@ == Set::<Right>::full(),
+    {
+        let rights = Rights { read: true, write: true, execute: true, grant: true, map: true, destroy: true };
+        assert(rights@ =~= Set::<Right>::full());
+        rights
+    }
+
+    pub fn contains(&self, r: ExecRight) -> (b: bool)
+        ensures
+            b == self@.contains(r@),
+    {
+        match r {
+            ExecRight::Read => self.read,
+            ExecRight::Write => self.write,
+            ExecRight::Execute => self.execute,
+            ExecRight::Grant => self.grant,
+            ExecRight::Map => self.map,
+            ExecRight::Destroy => self.destroy,
+        }
+    }
+
+    /// The Rights with [r] added
+    pub fn with(&self, r: ExecRight) -> (rights: Rights)
+        ensures
+            rights@ == self@.insert(r@),
+    {
+        let mut rights = *self;
+        match r {
+            ExecRight::Read => rights.read = true,
+            ExecRight::Write => rights.write = true,
+            ExecRight::Execute => rights.execute = true,
+            ExecRight::Grant => rights.grant = true,
+            ExecRight::Map => rights.map = true,
+            ExecRight::Destroy => rights.destroy = true,
+        }
+        assert(rights@ =~= self@.insert(r@));
+        rights
+    }
+
+    pub fn subset_of(&self, other: &Rights) -> (b: bool)
+        ensures
+            b == self@.subset_of(other@),
+    {
+        let b = (!self.read || other.read) && (!self.write || other.write) && (!self.execute || other.execute)
+            && (!self.grant || other.grant) && (!self.map || other.map) && (!self.destroy || other.destroy);
+        assert(self@.contains(Right::Read) == self.read && other@.contains(Right::Read) == other.read);
+        assert(self@.contains(Right::Write) == self.write && other@.contains(Right::Write) == other.write);
+        assert(self@.contains(Right::Execute) == self.execute && other@.contains(Right::Execute) == other.execute);
+        assert(self@.contains(Right::Grant) == self.grant && other@.contains(Right::Grant) == other.grant);
+        assert(self@.contains(Right::Map) == self.map && other@.contains(Right::Map) == other.map);
+        assert(self@.contains(Right::Destroy) == self.destroy && other@.contains(Right::Destroy) == other.destroy);
+        b
+    }
+
+    pub fn eq(&self, other: &Rights) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        let b = self.subset_of(other) && other.subset_of(self);
+        if b {
+            assert(self@ =~= other@);
+        }
+        b
+    }
+}
+
+/// Executable HoldEdge
+#[derive(Clone, Copy)]
+pub struct ExecHoldEdge {
+    pub src: u64,
+    pub dst: ExecResourceLike,
+    pub rights: Rights,
+}
+
+impl View for ExecHoldEdge {
+    type V = HoldEdge;
+
+    open spec fn view(&self) -> HoldEdge {
+        HoldEdge { src: pd_of(self.src), dst: self.dst@, rights: self.rights@ }
+    }
+}
+
+impl ExecHoldEdge {
+    pub fn eq(&self, other: &ExecHoldEdge) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        self.src == other.src && self.dst.eq(other.dst) && self.rights.eq(&other.rights)
+    }
+}
+
+/// Executable Lease
+#[derive(Clone, Copy)]
+pub struct ExecLease {
+    pub lender: u64,
+    pub expiry: u64,
+}
+
+impl View for ExecLease {
+    type V = Lease;
+
+    open spec fn view(&self) -> Lease {
+        Lease { lender: pd_of(self.lender), expiry: self.expiry as nat }
+    }
+}
+
+/// Executable CacheAttribute
+#[derive(Clone, Copy)]
+pub enum ExecCache {
+    WriteBack,
+    WriteThrough,
+    WriteCombining,
+    Uncached,
+}
+
+impl View for ExecCache {
+    type V = CacheAttribute;
+
+    open spec fn view(&self) -> CacheAttribute {
+        match self {
+            ExecCache::WriteBack => CacheAttribute::WriteBack,
+            ExecCache::WriteThrough => CacheAttribute::WriteThrough,
+            ExecCache::WriteCombining => CacheAttribute::WriteCombining,
+            ExecCache::Uncached => CacheAttribute::Uncached,
+        }
+    }
+}
+
+impl ExecCache {
+    pub fn eq(self, other: ExecCache) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        match (self, other) {
+            (ExecCache::WriteBack, ExecCache::WriteBack) => true,
+            (ExecCache::WriteThrough, ExecCache::WriteThrough) => true,
+            (ExecCache::WriteCombining, ExecCache::WriteCombining) => true,
+            (ExecCache::Uncached, ExecCache::Uncached) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Executable MapAttributes, translating by an [offset], since a Translation::Function has no
+/// executable counterpart
+#[derive(Clone, Copy)]
+pub struct ExecMapAttributes {
+    pub perms: Rights,
+    pub cache: ExecCache,
+    pub offset: i64,
+    pub cow: bool,
+    pub lazy: bool,
+}
+
+impl View for ExecMapAttributes {
+    type V = MapAttributes;
+
+    open spec fn view(&self) -> MapAttributes {
+        MapAttributes {
+            perms: self.perms@,
+            cache: self.cache@,
+            translation: Translation::Offset { offset: self.offset as int },
+            cow: self.cow,
+            lazy: self.lazy,
+        }
+    }
+}
+
+impl ExecMapAttributes {
+    pub fn eq(&self, other: &ExecMapAttributes) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        self.perms.eq(&other.perms) && self.cache.eq(other.cache) && self.offset == other.offset && self.cow == other.cow
+            && self.lazy == other.lazy
+    }
+}
+
+/// Executable MapEdge
+pub enum ExecMapEdge {
+    SpaceBacking { sb_src: u64, sb_dst: u64 },
+    SpaceMap { sm_src: u64, sm_dst: u64, sm_vals: ValSet, sm_attrs: ExecMapAttributes },
+    ResourceMap { rm_src: u64, rm_dst: u64, rm_attrs: ExecMapAttributes },
+    Delivery { dl_src: u64, dl_dst: u64 },
+}
+
+impl View for ExecMapEdge {
+    type V = MapEdge;
+
+    open spec fn view(&self) -> MapEdge {
+        match self {
+            ExecMapEdge::SpaceBacking { sb_src, sb_dst } => MapEdge::SpaceBacking { sb_src: *sb_src as nat, sb_dst: *sb_dst as nat },
+            ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } =>
+                MapEdge::SpaceMap { sm_src: *sm_src as nat, sm_dst: *sm_dst as nat, sm_vals: sm_vals@, sm_attrs: sm_attrs@ },
+            ExecMapEdge::ResourceMap { rm_src, rm_dst, rm_attrs } =>
+                MapEdge::ResourceMap { rm_src: *rm_src as nat, rm_dst: *rm_dst as nat, rm_attrs: rm_attrs@ },
+            ExecMapEdge::Delivery { dl_src, dl_dst } => MapEdge::Delivery { dl_src: *dl_src as nat, dl_dst: pd_of(*dl_dst) },
+        }
+    }
+}
+
+impl ExecMapEdge {
+    pub open spec fn wf(&self) -> bool {
+        self is SpaceMap ==> self->sm_vals.wf()
+    }
+
+    pub fn src_node(&self) -> (n: ExecResourceLike)
+        ensures
+            n@ == self@.src_node(),
+    {
+        match self {
+            ExecMapEdge::SpaceBacking { sb_src, .. } => ExecResourceLike::Space { space: *sb_src },
+            ExecMapEdge::SpaceMap { sm_src, .. } => ExecResourceLike::Space { space: *sm_src },
+            ExecMapEdge::ResourceMap { rm_src, .. } => ExecResourceLike::Resource { res: *rm_src },
+            ExecMapEdge::Delivery { dl_src, .. } => ExecResourceLike::Resource { res: *dl_src },
+        }
+    }
+
+    pub fn dst_node(&self) -> (n: ExecResourceLike)
+        requires
+            !(self@ is Delivery),
+        ensures
+            n@ == self@.dst_node(),
+    {
+        match self {
+            ExecMapEdge::SpaceBacking { sb_dst, .. } => ExecResourceLike::Resource { res: *sb_dst },
+            ExecMapEdge::SpaceMap { sm_dst, .. } => ExecResourceLike::Space { space: *sm_dst },
+            ExecMapEdge::ResourceMap { rm_dst, .. } => ExecResourceLike::Resource { res: *rm_dst },
+            ExecMapEdge::Delivery { .. } => vstd::pervasive::unreached(),
+        }
+    }
+
+    pub fn is_delivery(&self) -> (b: bool)
+        ensures
+            b == self@ is Delivery,
+    {
+        match self {
+            ExecMapEdge::Delivery { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn touches(&self, n: ExecResourceLike) -> (b: bool)
+        ensures
+            b == self@.touches(n@),
+    {
+        !self.is_delivery() && (self.src_node().eq(n) || self.dst_node().eq(n))
+    }
+
+    pub fn maps_onto(&self, n: ExecResourceLike) -> (b: bool)
+        ensures
+            b == self@.maps_onto(n@),
+    {
+        !self.is_delivery() && self.dst_node().eq(n)
+    }
+
+    pub fn perms(&self) -> (rights: Rights)
+        ensures
+            rights@ == self@.perms(),
+    {
+        match self {
+            ExecMapEdge::SpaceBacking { .. } => Rights::empty(),
+            ExecMapEdge::SpaceMap { sm_attrs, .. } => sm_attrs.perms,
+            ExecMapEdge::ResourceMap { rm_attrs, .. } => rm_attrs.perms,
+            ExecMapEdge::Delivery { .. } => Rights::empty(),
+        }
+    }
+
+    pub fn eq(&self, other: &ExecMapEdge) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == (self@ == other@),
+    {
+        match (self, other) {
+            (ExecMapEdge::SpaceBacking { sb_src: a, sb_dst: b }, ExecMapEdge::SpaceBacking { sb_src: c, sb_dst: d }) =>
+                *a == *c && *b == *d,
+            (ExecMapEdge::SpaceMap { sm_src: a, sm_dst: b, sm_vals: v, sm_attrs: x },
+             ExecMapEdge::SpaceMap { sm_src: c, sm_dst: d, sm_vals: w, sm_attrs: y }) =>
+                *a == *c && *b == *d && v.eq(w) && x.eq(y),
+            (ExecMapEdge::ResourceMap { rm_src: a, rm_dst: b, rm_attrs: x },
+             ExecMapEdge::ResourceMap { rm_src: c, rm_dst: d, rm_attrs: y }) =>
+                *a == *c && *b == *d && x.eq(y),
+            (ExecMapEdge::Delivery { dl_src: a, dl_dst: b }, ExecMapEdge::Delivery { dl_src: c, dl_dst: d }) =>
+                *a == *c && *b == *d,
+            _ => false,
+        }
+    }
+
+    /// A copy of the mapping
+    pub fn copy(&self) -> (me: ExecMapEdge)
+        requires
+            self.wf(),
+        ensures
+            me.wf(),
+            me@ == self@,
+    {
+        match self {
+            ExecMapEdge::SpaceBacking { sb_src, sb_dst } => ExecMapEdge::SpaceBacking { sb_src: *sb_src, sb_dst: *sb_dst },
+            ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } =>
+                ExecMapEdge::SpaceMap { sm_src: *sm_src, sm_dst: *sm_dst, sm_vals: sm_vals.copy(), sm_attrs: *sm_attrs },
+            ExecMapEdge::ResourceMap { rm_src, rm_dst, rm_attrs } =>
+                ExecMapEdge::ResourceMap { rm_src: *rm_src, rm_dst: *rm_dst, rm_attrs: *rm_attrs },
+            ExecMapEdge::Delivery { dl_src, dl_dst } => ExecMapEdge::Delivery { dl_src: *dl_src, dl_dst: *dl_dst },
+        }
+    }
+}
+
+/// Executable Device
+#[derive(Clone, Copy)]
+pub struct ExecDevice {
+    pub driver: u64,
+    pub iommu: u64,
+}
+
+impl View for ExecDevice {
+    type V = Device;
+
+    open spec fn view(&self) -> Device {
+        Device { driver: pd_of(self.driver), iommu: self.iommu as nat }
+    }
+}
+
+/// Executable SubsetEdge
+#[derive(Clone, Copy)]
+pub struct ExecSubsetEdge {
+    pub src: u64,
+    pub dst: u64,
+}
+
+impl View for ExecSubsetEdge {
+    type V = SubsetEdge;
+
+    open spec fn view(&self) -> SubsetEdge {
+        SubsetEdge { src: self.src as nat, dst: self.dst as nat }
+    }
+}
+
+/// Executable PartitionEdge
+#[derive(Clone, Copy)]
+pub struct ExecPartitionEdge {
+    pub src: u64,
+    pub dst: u64,
+}
+
+impl View for ExecPartitionEdge {
+    type V = PartitionEdge;
+
+    open spec fn view(&self) -> PartitionEdge {
+        PartitionEdge { src: self.src as nat, dst: self.dst as nat }
+    }
+}
+
+/// Executable RequestEdge
+#[derive(Clone, Copy)]
+pub struct ExecRequestEdge {
+    pub src: u64,
+    pub dst: u64,
+    pub rtype: ExecResourceType,
+}
+
+impl View for ExecRequestEdge {
+    type V = RequestEdge<StandardKind>;
+
+    open spec fn view(&self) -> RequestEdge<StandardKind> {
+        RequestEdge { src: pd_of(self.src), dst: pd_of(self.dst), rtype: self.rtype@ }
+    }
+}
+
+impl ExecRequestEdge {
+    pub fn eq(&self, other: &ExecRequestEdge) -> (b: bool)
+        ensures
+            b == (self@ == other@),
+    {
+        self.src == other.src && self.dst == other.dst && self.rtype.eq(other.rtype)
+    }
+}
+
+/// Executable RequestStatus
+pub enum ExecRequestStatus {
+    Pending { handed: ValSet },
+    Granted { granted: ValSet },
+    Denied,
+}
+
+impl View for ExecRequestStatus {
+    type V = RequestStatus;
+
+    open spec fn view(&self) -> RequestStatus {
+        match self {
+            ExecRequestStatus::Pending { handed } => RequestStatus::Pending { handed: handed@ },
+            ExecRequestStatus::Granted { granted } => RequestStatus::Granted { granted: granted@ },
+            ExecRequestStatus::Denied => RequestStatus::Denied,
+        }
+    }
+}
+
+/// Executable Request
+pub struct ExecRequest {
+    pub client: u64,
+    pub server: u64,
+    pub rtype: ExecResourceType,
+    pub count: u64,
+    pub status: ExecRequestStatus,
+}
+
+impl View for ExecRequest {
+    type V = Request<StandardKind>;
+
+    open spec fn view(&self) -> Request<StandardKind> {
+        Request { client: pd_of(self.client), server: pd_of(self.server), rtype: self.rtype@, count: self.count as nat, status: self.status@ }
+    }
+}
+
+impl ExecRequest {
+    pub open spec fn wf(&self) -> bool {
+        &&& self.status is Pending ==> self.status->handed.wf()
+        &&& self.status is Granted ==> self.status->granted.wf()
+    }
+
+    pub fn edge(&self) -> (re: ExecRequestEdge)
+        ensures
+            re@ == self@.edge(),
+    {
+        ExecRequestEdge { src: self.client, dst: self.server, rtype: self.rtype }
+    }
+
+    pub fn is_pending(&self) -> (b: bool)
+        ensures
+            b == self@.status() is Pending,
+    {
+        match self.status {
+            ExecRequestStatus::Pending { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the Request is pending, with nothing handed over yet
+    pub fn fresh(&self) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == (self@.status() == (RequestStatus::Pending { handed: Set::empty() })),
+    {
+        match &self.status {
+            ExecRequestStatus::Pending { handed } => {
+                let n = handed.len();
+                proof {
+                    vstd::set_lib::lemma_set_empty_equivalency_len(handed@);
+                }
+                n == 0
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the server handed the Resource [r] over for the Request
+    pub fn handed(&self, r: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.granted().contains(r as nat),
+    {
+        match &self.status {
+            ExecRequestStatus::Pending { handed } => handed.contains(r),
+            ExecRequestStatus::Granted { granted } => granted.contains(r),
+            ExecRequestStatus::Denied => false,
+        }
+    }
+}
+
+// Entries:
+
+/// A Resource keyed by its id
+#[derive(Clone, Copy)]
+pub struct ResourceEntry {
+    pub id: u64,
+    pub res: ExecResource,
+}
+
+impl View for ResourceEntry {
+    type V = (ResourceId, Resource<StandardKind>);
+
+    open spec fn view(&self) -> (ResourceId, Resource<StandardKind>) {
+        (self.id as nat, self.res@)
+    }
+}
+
+impl KeyedEntry<Resource<StandardKind>> for ResourceEntry {
+    fn key(&self) -> (k: u64) {
+        self.id
+    }
+}
+
+/// A ResourceSpace keyed by its id
+pub struct SpaceEntry {
+    pub id: u64,
+    pub space: ExecSpace,
+}
+
+impl View for SpaceEntry {
+    type V = (SpaceId, ResourceSpace<StandardKind>);
+
+    open spec fn view(&self) -> (SpaceId, ResourceSpace<StandardKind>) {
+        (self.id as nat, self.space@)
+    }
+}
+
+impl KeyedEntry<ResourceSpace<StandardKind>> for SpaceEntry {
+    fn key(&self) -> (k: u64) {
+        self.id
+    }
+}
+
+/// A set of vals keyed by the ResourceSpace they are indexed for
+pub struct ValsEntry {
+    pub space: u64,
+    pub vals: ValSet,
+}
+
+impl View for ValsEntry {
+    type V = (SpaceId, Set<nat>);
+
+    open spec fn view(&self) -> (SpaceId, Set<nat>) {
+        (self.space as nat, self.vals@)
+    }
+}
+
+impl KeyedEntry<Set<nat>> for ValsEntry {
+    fn key(&self) -> (k: u64) {
+        self.space
+    }
+}
+
+/// The vals taken by the Resources of a ResourceType
+pub struct UsageEntry {
+    pub rtype: ExecResourceType,
+    pub vals: ValSet,
+}
+
+impl View for UsageEntry {
+    type V = (ResourceType<StandardKind>, Set<nat>);
+
+    open spec fn view(&self) -> (ResourceType<StandardKind>, Set<nat>) {
+        (self.rtype@, self.vals@)
+    }
+}
+
+/// The quota of a ProtectionDomain for a ResourceType
+#[derive(Clone, Copy)]
+pub struct QuotaEntry {
+    pub pd: u64,
+    pub rtype: ExecResourceType,
+    pub amount: u64,
+}
+
+impl View for QuotaEntry {
+    type V = ((ProtectionDomain, ResourceType<StandardKind>), nat);
+
+    open spec fn view(&self) -> ((ProtectionDomain, ResourceType<StandardKind>), nat) {
+        ((pd_of(self.pd), self.rtype@), self.amount as nat)
+    }
+}
+
+/// The ProtectionDomain a ProtectionDomain was created by
+#[derive(Clone, Copy)]
+pub struct ParentEntry {
+    pub pd: u64,
+    pub parent: u64,
+}
+
+impl View for ParentEntry {
+    type V = (ProtectionDomain, ProtectionDomain);
+
+    open spec fn view(&self) -> (ProtectionDomain, ProtectionDomain) {
+        (pd_of(self.pd), pd_of(self.parent))
+    }
+}
+
+/// A nat keyed by a nat, such as the period of a core or the space a Resource was retyped into
+#[derive(Clone, Copy)]
+pub struct NatEntry {
+    pub key: u64,
+    pub val: u64,
+}
+
+impl View for NatEntry {
+    type V = (nat, nat);
+
+    open spec fn view(&self) -> (nat, nat) {
+        (self.key as nat, self.val as nat)
+    }
+}
+
+/// An amount keyed by a ResourceType, such as a quota or a metadata cost
+#[derive(Clone, Copy)]
+pub struct CostEntry {
+    pub rtype: ExecResourceType,
+    pub cost: u64,
+}
+
+impl View for CostEntry {
+    type V = (ResourceType<StandardKind>, nat);
+
+    open spec fn view(&self) -> (ResourceType<StandardKind>, nat) {
+        (self.rtype@, self.cost as nat)
+    }
+}
+
+/// A Device keyed by its ProtectionDomain
+#[derive(Clone, Copy)]
+pub struct DeviceEntry {
+    pub pd: u64,
+    pub device: ExecDevice,
+}
+
+impl View for DeviceEntry {
+    type V = (ProtectionDomain, Device);
+
+    open spec fn view(&self) -> (ProtectionDomain, Device) {
+        (pd_of(self.pd), self.device@)
+    }
+}
+
+/// The metadata charged for a ResourceSpace
+pub struct MetadataEntry {
+    pub space: u64,
+    pub meta: ValSet,
+}
+
+impl View for MetadataEntry {
+    type V = (SpaceId, Set<ResourceId>);
+
+    open spec fn view(&self) -> (SpaceId, Set<ResourceId>) {
+        (self.space as nat, self.meta@)
+    }
+}
+
+/// A Request keyed by its id
+pub struct RequestEntry {
+    pub id: u64,
+    pub req: ExecRequest,
+}
+
+impl View for RequestEntry {
+    type V = (RequestId, Request<StandardKind>);
+
+    open spec fn view(&self) -> (RequestId, Request<StandardKind>) {
+        (self.id as nat, self.req@)
+    }
+}
+
+/// The terms a hold edge was lent under
+#[derive(Clone, Copy)]
+pub struct LeaseEntry {
+    pub he: ExecHoldEdge,
+    pub lease: ExecLease,
+}
+
+impl View for LeaseEntry {
+    type V = (HoldEdge, Lease);
+
+    open spec fn view(&self) -> (HoldEdge, Lease) {
+        (self.he@, self.lease@)
+    }
+}
+
+/// The hold edge a derived hold edge was derived from
+#[derive(Clone, Copy)]
+pub struct DerivationEntry {
+    pub child: ExecHoldEdge,
+    pub parent: ExecHoldEdge,
+}
+
+impl View for DerivationEntry {
+    type V = (HoldEdge, HoldEdge);
+
+    open spec fn view(&self) -> (HoldEdge, HoldEdge) {
+        (self.child@, self.parent@)
+    }
+}
+
+} // verus!
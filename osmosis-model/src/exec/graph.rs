@@ -0,0 +1,6761 @@
+/// MIT License
+///
+/// Copyright (c) 2025 Ilias Karimalis
+
+use vstd::prelude::*;
+use vstd::set_lib::lemma_len_subset;
+
+use crate::component::*;
+use crate::dag::*;
+use crate::exec::component::*;
+use crate::exec::mirror::*;
+use crate::exec::vals::*;
+use crate::utils::{set_sum, lemma_set_sum_insert, nat_range, nat_interval};
+
+verus!
+{
+
+/// Why a step of an OsmosisGraph was not taken. Checks are sound but not always complete, so a
+/// step whose requirements could not be decided is Unchecked rather than Disabled
+pub enum StepError {
+    /// The requirements of the transition do not hold
+    Disabled,
+    /// The requirements of the transition could not be checked
+    Unchecked,
+}
+
+/// Executable Osmosis DAG, keeping every field of the state machine as a vector of executable
+/// components alongside the ghost state they are seen as
+pub struct OsmosisGraph {
+    domains: Vec<u64>,
+    resources: Vec<ResourceEntry>,
+    spaces: Vec<SpaceEntry>,
+    holds: Vec<ExecHoldEdge>,
+    maps: Vec<ExecMapEdge>,
+    subsets: Vec<ExecSubsetEdge>,
+    requests: Vec<ExecRequestEdge>,
+    partitions: Vec<ExecPartitionEdge>,
+    quotas: Vec<QuotaEntry>,
+    parents: Vec<ParentEntry>,
+    periods: Vec<NatEntry>,
+    devices: Vec<DeviceEntry>,
+    retyped: Vec<NatEntry>,
+    initial_spaces: Vec<SpaceEntry>,
+    metadata_costs: Vec<CostEntry>,
+    metadata: Vec<MetadataEntry>,
+    issued: Vec<RequestEntry>,
+    epoch: u64,
+    leases: Vec<LeaseEntry>,
+    derived_from: Vec<DerivationEntry>,
+    /// The vals allocated from each space, so that allocations are checked without walking the
+    /// subset edges. Like the resources and the spaces, it is sorted by id so that the entry of a
+    /// space is found by binary search
+    allocated: Vec<ValsEntry>,
+    /// The vals carved out of each space, sorted by id
+    carved: Vec<ValsEntry>,
+    /// The vals taken by the physical Resources of each kind, at the rank of the kind
+    used: Vec<UsageEntry>,
+    /// The state of the Osmosis DAG the graph is seen as
+    state: Ghost<OsmosisDAG::State<StandardKind>>,
+}
+
+impl View for OsmosisGraph {
+    type V = OsmosisDAG::State<StandardKind>;
+
+    closed spec fn view(&self) -> OsmosisDAG::State<StandardKind> {
+        self.state@
+    }
+}
+
+/// Whether the ids in [v] are exactly those of the ProtectionDomains in [s]
+pub open spec fn mirrors_domains(v: Seq<u64>, s: Set<ProtectionDomain>) -> bool {
+    forall |p: ProtectionDomain| #[trigger] s.contains(p) <==> exists |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p
+}
+
+/// The vals taken by the Resources allocated from [space]
+pub open spec fn allocated_vals(s: OsmosisDAG::State<StandardKind>, space: SpaceId) -> Set<nat> {
+    Set::new(|v: nat| exists |se: SubsetEdge| #[trigger] s.subsets.contains(se) && se.dst() == space
+        && s.resources[se.src()].range().contains(v))
+}
+
+/// The vals of the spaces carved out of [space]
+pub open spec fn carved_vals(s: OsmosisDAG::State<StandardKind>, space: SpaceId) -> Set<nat> {
+    Set::new(|v: nat| exists |pe: PartitionEdge| #[trigger] s.partitions.contains(pe) && pe.dst() == space
+        && s.spaces[pe.src()].vals().contains(v))
+}
+
+/// The vals taken by the Resources of [rtype], whichever space they were allocated from
+pub open spec fn used_vals(s: OsmosisDAG::State<StandardKind>, rtype: ResourceType<StandardKind>) -> Set<nat> {
+    Set::new(|v: nat| exists |r: ResourceId| #[trigger] s.resources.contains_key(r) && s.resources[r].rtype() == rtype
+        && s.resources[r].range().contains(v))
+}
+
+/// The vals allocated from each ResourceSpace of [s]
+pub open spec fn allocation_index(s: OsmosisDAG::State<StandardKind>) -> Map<SpaceId, Set<nat>> {
+    Map::new(|space: SpaceId| s.spaces.contains_key(space), |space: SpaceId| allocated_vals(s, space))
+}
+
+/// The vals carved out of each ResourceSpace of [s]
+pub open spec fn carving_index(s: OsmosisDAG::State<StandardKind>) -> Map<SpaceId, Set<nat>> {
+    Map::new(|space: SpaceId| s.spaces.contains_key(space), |space: SpaceId| carved_vals(s, space))
+}
+
+/// Whether the entries of [v] are the vals taken by the physical Resources of [s], one for each
+/// kind at its rank
+pub open spec fn indexes_usage(v: Seq<UsageEntry>, s: OsmosisDAG::State<StandardKind>) -> bool {
+    &&& v.len() == KINDS
+    &&& forall |i: int| 0 <= i < v.len() ==> {
+        &&& #[trigger] v[i].vals.wf()
+        &&& v[i]@.0 is Physical
+        &&& kind_rank(v[i]@.0->Physical_0) == i
+        &&& v[i]@.1 == used_vals(s, v[i]@.0)
+    }
+}
+
+impl OsmosisGraph {
+    /// Each vector of the graph is seen as the field of the ghost state it stands for, and the
+    /// state is one the Osmosis DAG keeps its invariants in
+    pub closed spec fn wf(&self) -> bool {
+        let s = self.state@;
+        &&& mirrors_domains(self.domains@, s.domains)
+        &&& mirrors_map(self.resources@, s.resources)
+        &&& keys_sorted(self.resources@)
+        &&& mirrors_map(self.spaces@, s.spaces)
+        &&& keys_sorted(self.spaces@)
+        &&& forall |i: int| 0 <= i < self.spaces.len() ==> #[trigger] self.spaces[i].space.wf()
+        &&& mirrors(self.holds@, s.holds)
+        &&& mirrors(self.maps@, s.maps)
+        &&& forall |i: int| 0 <= i < self.maps.len() ==> #[trigger] self.maps[i].wf()
+        &&& mirrors(self.subsets@, s.subsets)
+        &&& mirrors(self.requests@, s.requests)
+        &&& mirrors(self.partitions@, s.partitions)
+        &&& mirrors_map(self.quotas@, s.quotas)
+        &&& mirrors_map(self.parents@, s.parents)
+        &&& mirrors_map(self.periods@, s.periods)
+        &&& mirrors_map(self.devices@, s.devices)
+        &&& mirrors_map(self.retyped@, s.retyped)
+        &&& mirrors_map(self.initial_spaces@, s.initial_spaces)
+        &&& forall |i: int| 0 <= i < self.initial_spaces.len() ==> #[trigger] self.initial_spaces[i].space.wf()
+        &&& mirrors_map(self.metadata_costs@, s.metadata_costs)
+        &&& mirrors_map(self.metadata@, s.metadata)
+        &&& forall |i: int| 0 <= i < self.metadata.len() ==> #[trigger] self.metadata[i].meta.wf()
+        &&& mirrors_map(self.issued@, s.issued)
+        &&& forall |i: int| 0 <= i < self.issued.len() ==> #[trigger] self.issued[i].req.wf()
+        &&& self.epoch as nat == s.epoch
+        &&& mirrors_map(self.leases@, s.leases)
+        &&& mirrors_map(self.derived_from@, s.derived_from)
+        &&& s.invariant()
+        &&& mirrors_map(self.allocated@, allocation_index(s))
+        &&& keys_sorted(self.allocated@)
+        &&& forall |i: int| 0 <= i < self.allocated.len() ==> #[trigger] self.allocated[i].vals.wf()
+        &&& mirrors_map(self.carved@, carving_index(s))
+        &&& keys_sorted(self.carved@)
+        &&& forall |i: int| 0 <= i < self.carved.len() ==> #[trigger] self.carved[i].vals.wf()
+        &&& indexes_usage(self.used@, s)
+    }
+}
+
+/// Whether [r] reports taking [step] from [pre] to [post], or leaving [pre] as it was
+pub open spec fn stepped(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, step: OsmosisDAG::Step<StandardKind>, r: Result<(), StepError>) -> bool {
+    &&& r is Ok ==> OsmosisDAG::State::next_by(pre, post, step)
+    &&& r is Err ==> post == pre
+}
+
+// Vectors:
+
+/// Whether one of [holds] is seen as [he]
+fn hold_in(holds: &Vec<ExecHoldEdge>, he: &ExecHoldEdge) -> (b: bool)
+    ensures
+        b == exists |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == he@,
+{
+    let mut i: usize = 0;
+    while i < holds.len()
+        invariant
+            i <= holds.len(),
+            forall |j: int| 0 <= j < i ==> #[trigger] holds[j]@ != he@,
+        decreases holds.len() - i
+    {
+        if holds[i].eq(he) {
+            return true;
+        }
+        i = i + 1;
+    }
+    false
+}
+
+/// Flags the entries of [holds] which are not on the node [n]
+fn holds_not_on(holds: &Vec<ExecHoldEdge>, n: ExecResourceLike) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == holds.len(),
+        forall |i: int| 0 <= i < holds.len() ==> #[trigger] keep[i] == (holds[i]@.dst() != n@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < holds.len()
+        invariant
+            i <= holds.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (holds[j]@.dst() != n@),
+        decreases holds.len() - i
+    {
+        keep.push(!holds[i].dst.eq(n));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [maps] which neither map [n] nor are mapped onto it, or only the ones
+/// which do not map [n] when [src_only] is set
+fn maps_not_touching(maps: &Vec<ExecMapEdge>, n: ExecResourceLike, src_only: bool) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == maps.len(),
+        forall |i: int| 0 <= i < maps.len() ==> #[trigger] keep[i] ==
+            (if src_only { maps[i]@.src_node() != n@ } else { !maps[i]@.touches(n@) }),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < maps.len()
+        invariant
+            i <= maps.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] ==
+                (if src_only { maps[j]@.src_node() != n@ } else { !maps[j]@.touches(n@) }),
+        decreases maps.len() - i
+    {
+        let touches = if src_only { maps[i].src_node().eq(n) } else { maps[i].touches(n) };
+        keep.push(!touches);
+        i = i + 1;
+    }
+    keep
+}
+
+/// Whether one of the flagged entries of [holds] is a hold edge of the [p] ProtectionDomain on [n]
+fn held_in(holds: &Vec<ExecHoldEdge>, keep: &Vec<bool>, p: u64, n: ExecResourceLike) -> (b: bool)
+    requires
+        keep.len() == holds.len(),
+    ensures
+        b == exists |i: int| 0 <= i < holds.len() && keep[i] && #[trigger] holds[i]@.src() == pd_of(p) && holds[i]@.dst() == n@,
+{
+    let mut i: usize = 0;
+    while i < holds.len()
+        invariant
+            i <= holds.len(),
+            keep.len() == holds.len(),
+            forall |j: int| 0 <= j < i ==> !(keep[j] && #[trigger] holds[j]@.src() == pd_of(p) && holds[j]@.dst() == n@),
+        decreases holds.len() - i
+    {
+        if keep[i] && holds[i].src == p && holds[i].dst.eq(n) {
+            return true;
+        }
+        i = i + 1;
+    }
+    false
+}
+
+/// Whether every holder of [end] among the flagged entries of [holds] holds [n] through them
+fn holders_keep(holds: &Vec<ExecHoldEdge>, keep: &Vec<bool>, end: ExecResourceLike, n: ExecResourceLike) -> (b: bool)
+    requires
+        keep.len() == holds.len(),
+    ensures
+        b == forall |i: int| 0 <= i < holds.len() && keep[i] && #[trigger] holds[i]@.dst() == end@ ==>
+            exists |j: int| 0 <= j < holds.len() && keep[j] && #[trigger] holds[j]@.src() == holds[i]@.src() && holds[j]@.dst() == n@,
+{
+    let mut i: usize = 0;
+    while i < holds.len()
+        invariant
+            i <= holds.len(),
+            keep.len() == holds.len(),
+            forall |k: int| 0 <= k < i && keep[k] && #[trigger] holds[k]@.dst() == end@ ==>
+                exists |j: int| 0 <= j < holds.len() && keep[j] && #[trigger] holds[j]@.src() == holds[k]@.src() && holds[j]@.dst() == n@,
+        decreases holds.len() - i
+    {
+        if keep[i] && holds[i].dst.eq(end) && !held_in(holds, keep, holds[i].src, n) {
+            assert(holds[i as int]@.src() == pd_of(holds[i as int].src));
+            return false;
+        }
+        i = i + 1;
+    }
+    true
+}
+
+/// Flags the entries of [maps] which survive taking [n] away from whoever does not hold it
+/// through the hold edges of [pre] which satisfy [stays], which [keep] flags among [holds]
+fn maps_surviving(maps: &Vec<ExecMapEdge>, n: ExecResourceLike, holds: &Vec<ExecHoldEdge>, keep: &Vec<bool>,
+    Ghost(pre): Ghost<OsmosisDAG::State<StandardKind>>, Ghost(stays): Ghost<spec_fn(HoldEdge) -> bool>) -> (keep_maps: Vec<bool>)
+    requires
+        mirrors(holds@, pre.holds),
+        keep.len() == holds.len(),
+        forall |i: int| 0 <= i < holds.len() ==> #[trigger] keep[i] == stays(holds[i]@),
+    ensures
+        keep_maps.len() == maps.len(),
+        forall |i: int| 0 <= i < maps.len() ==> #[trigger] keep_maps[i] == pre.map_survives(maps[i]@, n@, pre.holds.filter(stays)),
+{
+    let mut keep_maps: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < maps.len()
+        invariant
+            mirrors(holds@, pre.holds),
+            keep.len() == holds.len(),
+            forall |j: int| 0 <= j < holds.len() ==> #[trigger] keep[j] == stays(holds[j]@),
+            i <= maps.len(),
+            keep_maps.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep_maps[j] == pre.map_survives(maps[j]@, n@, pre.holds.filter(stays)),
+        decreases maps.len() - i
+    {
+        let survives = if maps[i].touches(n) {
+            let src = maps[i].src_node();
+            let end = if src.eq(n) { maps[i].dst_node() } else { src };
+            proof {
+                lemma_holders_keep_mirrored(holds@, keep@, pre.holds, stays, end@, n@);
+            }
+            holders_keep(holds, keep, end, n)
+        } else {
+            true
+        };
+        keep_maps.push(survives);
+        i = i + 1;
+    }
+    keep_maps
+}
+
+/// Flags the entries of [holds] which are not seen as [he]
+fn holds_other_than(holds: &Vec<ExecHoldEdge>, he: &ExecHoldEdge) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == holds.len(),
+        forall |j: int| 0 <= j < holds.len() ==> #[trigger] keep[j] == (holds[j]@ != he@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < holds.len()
+        invariant
+            i <= holds.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (holds[j]@ != he@),
+        decreases holds.len() - i
+    {
+        keep.push(!holds[i].eq(he));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [maps] which are not seen as [me]
+fn maps_other_than(maps: &Vec<ExecMapEdge>, me: &ExecMapEdge) -> (keep: Vec<bool>)
+    requires
+        forall |i: int| 0 <= i < maps.len() ==> #[trigger] maps[i].wf(),
+        me.wf(),
+    ensures
+        keep.len() == maps.len(),
+        forall |j: int| 0 <= j < maps.len() ==> #[trigger] keep[j] == (maps[j]@ != me@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < maps.len()
+        invariant
+            forall |i: int| 0 <= i < maps.len() ==> #[trigger] maps[i].wf(),
+            me.wf(),
+            i <= maps.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (maps[j]@ != me@),
+        decreases maps.len() - i
+    {
+        keep.push(!maps[i].eq(me));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [subsets] which are not seen as [se]
+fn subsets_other_than(subsets: &Vec<ExecSubsetEdge>, se: ExecSubsetEdge) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == subsets.len(),
+        forall |j: int| 0 <= j < subsets.len() ==> #[trigger] keep[j] == (subsets[j]@ != se@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < subsets.len()
+        invariant
+            i <= subsets.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (subsets[j]@ != se@),
+        decreases subsets.len() - i
+    {
+        keep.push(!(subsets[i].src == se.src && subsets[i].dst == se.dst));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [requests] which are not seen as [re]
+fn requests_other_than(requests: &Vec<ExecRequestEdge>, re: &ExecRequestEdge) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == requests.len(),
+        forall |j: int| 0 <= j < requests.len() ==> #[trigger] keep[j] == (requests[j]@ != re@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < requests.len()
+        invariant
+            i <= requests.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (requests[j]@ != re@),
+        decreases requests.len() - i
+    {
+        keep.push(!requests[i].eq(re));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [partitions] which are not seen as [pe]
+fn partitions_other_than(partitions: &Vec<ExecPartitionEdge>, pe: ExecPartitionEdge) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == partitions.len(),
+        forall |j: int| 0 <= j < partitions.len() ==> #[trigger] keep[j] == (partitions[j]@ != pe@),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < partitions.len()
+        invariant
+            i <= partitions.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (partitions[j]@ != pe@),
+        decreases partitions.len() - i
+    {
+        keep.push(!(partitions[i].src == pe.src && partitions[i].dst == pe.dst));
+        i = i + 1;
+    }
+    keep
+}
+
+/// Flags the entries of [maps] which do not deliver the interrupt line [irq]
+fn maps_not_delivering(maps: &Vec<ExecMapEdge>, irq: u64) -> (keep: Vec<bool>)
+    ensures
+        keep.len() == maps.len(),
+        forall |j: int| 0 <= j < maps.len() ==> #[trigger] keep[j] == !(maps[j]@ is Delivery && maps[j]@->dl_src == irq as nat),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < maps.len()
+        invariant
+            i <= maps.len(),
+            keep.len() == i,
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == !(maps[j]@ is Delivery && maps[j]@->dl_src == irq as nat),
+        decreases maps.len() - i
+    {
+        let delivers = match &maps[i] {
+            ExecMapEdge::Delivery { dl_src, .. } => *dl_src == irq,
+            _ => false,
+        };
+        keep.push(!delivers);
+        i = i + 1;
+    }
+    keep
+}
+
+/// Keeps the derivations of [pre] between hold edges which are both among [holds], which
+/// [derived_from] and [kept] mirror
+fn prune_derivations(derived_from: &mut Vec<DerivationEntry>, kept: &Vec<ExecHoldEdge>,
+    Ghost(pre): Ghost<OsmosisDAG::State<StandardKind>>, Ghost(holds): Ghost<Set<HoldEdge>>)
+    requires
+        mirrors_map(old(derived_from)@, pre.derived_from),
+        mirrors(kept@, holds),
+    ensures
+        mirrors_map(derived_from@, pre.prune_derivations(holds)),
+{
+    let mut keep: Vec<bool> = Vec::new();
+    let mut i: usize = 0;
+    while i < derived_from.len()
+        invariant
+            i <= derived_from.len(),
+            keep.len() == i,
+            mirrors_map(derived_from@, pre.derived_from),
+            mirrors(kept@, holds),
+            forall |j: int| 0 <= j < i ==> #[trigger] keep[j] ==
+                (holds.contains(derived_from[j]@.0) && holds.contains(derived_from[j]@.1)),
+        decreases derived_from.len() - i
+    {
+        let e = derived_from[i];
+        let both = hold_in(kept, &e.child) && hold_in(kept, &e.parent);
+        keep.push(both);
+        i = i + 1;
+    }
+    let ghost v = derived_from@;
+    let ghost p = |k: HoldEdge| holds.contains(k) && holds.contains(pre.derived_from[k]);
+    proof {
+        assert forall |j: int| 0 <= j < v.len() implies #[trigger] keep[j] == p(v[j]@.0) by {
+            lemma_mirrored_key(v, pre.derived_from, j);
+        }
+    }
+    retain_flagged(derived_from, &keep);
+    proof {
+        lemma_kept_mirrors_map(v, pre.derived_from, keep@, p);
+        assert(pre.derived_from.restrict(pre.derived_from.dom().filter(p)) =~= pre.prune_derivations(holds));
+    }
+}
+
+/// Records the derivation of [child] from [parent] in [pre], unless [child] was [held] already
+fn derive(derived_from: &mut Vec<DerivationEntry>, child: ExecHoldEdge, parent: ExecHoldEdge, held: bool,
+    Ghost(pre): Ghost<OsmosisDAG::State<StandardKind>>)
+    requires
+        mirrors_map(old(derived_from)@, pre.derived_from),
+        held == pre.holds.contains(child@),
+    ensures
+        mirrors_map(derived_from@, pre.derive(child@, parent@)),
+{
+    if held {
+        return;
+    }
+    let mut i: usize = 0;
+    while i < derived_from.len()
+        invariant
+            i <= derived_from.len(),
+            mirrors_map(derived_from@, pre.derived_from),
+            forall |j: int| 0 <= j < i ==> #[trigger] derived_from[j]@.0 != child@,
+        decreases derived_from.len() - i
+    {
+        if derived_from[i].child.eq(&child) {
+            let ghost v = derived_from@;
+            let e = DerivationEntry { child, parent };
+            derived_from.set(i, e);
+            proof {
+                lemma_mirrors_map_update(v, pre.derived_from, i as int, e);
+            }
+            return;
+        }
+        i = i + 1;
+    }
+    let ghost v = derived_from@;
+    let e = DerivationEntry { child, parent };
+    proof {
+        if (pre.derived_from.contains_key(child@)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@.0 == child@;
+        }
+    }
+    derived_from.push(e);
+    proof {
+        lemma_mirrors_map_push(v, pre.derived_from, e);
+    }
+}
+
+/// The derivations of [pre] once [from] is replaced by [to]
+fn hand_over_derivations(derived_from: &Vec<DerivationEntry>, from: ExecHoldEdge, to: ExecHoldEdge,
+    Ghost(pre): Ghost<OsmosisDAG::State<StandardKind>>) -> (handed: Vec<DerivationEntry>)
+    requires
+        mirrors_map(derived_from@, pre.derived_from),
+    ensures
+        mirrors_map(handed@, pre.hand_over_derivations(from@, to@)),
+{
+    let ghost m = pre.derived_from;
+    let ghost target = pre.hand_over_derivations(from@, to@);
+    // The entries whose child is neither from nor to, and then the one for to, if any
+    let mut handed: Vec<DerivationEntry> = Vec::new();
+    let mut parent_of_from: Option<ExecHoldEdge> = None;
+    let mut i: usize = 0;
+    while i < derived_from.len()
+        invariant
+            i <= derived_from.len(),
+            mirrors_map(derived_from@, m),
+            match parent_of_from {
+                Some(p) => m.contains_key(from@) && m[from@] == p@ && exists |j: int| 0 <= j < i && #[trigger] derived_from[j]@.0 == from@,
+                None => forall |j: int| 0 <= j < i ==> #[trigger] derived_from[j]@.0 != from@,
+            },
+            forall |k: HoldEdge| #[trigger] prefix_keys(handed@, handed.len() as int).contains(k) <==>
+                k != from@ && k != to@ && prefix_keys(derived_from@, i as int).contains(k),
+            forall |j: int| 0 <= j < handed.len() ==> #[trigger] target[handed[j]@.0] == handed[j]@.1,
+            keys_unique(handed@),
+        decreases derived_from.len() - i
+    {
+        let e = derived_from[i];
+        proof {
+            lemma_mirrored_key(derived_from@, m, i as int);
+            lemma_prefix_keys_finite(derived_from@, i + 1);
+            lemma_prefix_keys_finite(handed@, handed.len() + 1);
+        }
+        if e.child.eq(&from) {
+            parent_of_from = Some(e.parent);
+        } else if !e.child.eq(&to) {
+            let moved = if e.parent.eq(&from) { to } else { e.parent };
+            let f = DerivationEntry { child: e.child, parent: moved };
+            let ghost before = handed@;
+            proof {
+                // The child was not seen before, as keys are unique
+                if (prefix_keys(derived_from@, i as int).contains(e.child@)) {
+                    let j = choose |j: int| 0 <= j < i && #[trigger] derived_from[j]@.0 == e.child@;
+                    assert(derived_from[j]@.0 == derived_from[i as int]@.0);
+                }
+                assert(!prefix_keys(before, before.len() as int).contains(e.child@));
+            }
+            handed.push(f);
+            proof {
+                assert(handed@[before.len() as int] == f);
+                assert forall |j: int| 0 <= j < before.len() implies #[trigger] handed@[j] == before[j] by {}
+                assert(prefix_keys(handed@, handed.len() as int) =~= prefix_keys(before, before.len() as int).insert(f@.0));
+                assert forall |j: int, k: int| 0 <= j < handed.len() && 0 <= k < handed.len() && #[trigger] handed@[j]@.0 == #[trigger] handed@[k]@.0 implies j == k by {
+                    if (j < before.len() && k == before.len()) {
+                        assert(prefix_keys(before, before.len() as int).contains(before[j]@.0));
+                    }
+                    if (k < before.len() && j == before.len()) {
+                        assert(prefix_keys(before, before.len() as int).contains(before[k]@.0));
+                    }
+                }
+            }
+        }
+        i = i + 1;
+        proof {
+            assert(prefix_keys(derived_from@, i as int) =~= prefix_keys(derived_from@, i - 1).insert(e.child@));
+        }
+    }
+    proof {
+        lemma_prefix_keys_all(derived_from@, m);
+    }
+    match parent_of_from {
+        Some(p) => {
+            if !p.eq(&to) {
+                let ghost before = handed@;
+                let f = DerivationEntry { child: to, parent: p };
+                proof {
+                    lemma_prefix_keys_finite(handed@, handed.len() as int);
+                    assert(!prefix_keys(before, before.len() as int).contains(to@));
+                }
+                handed.push(f);
+                proof {
+                    assert(handed@[before.len() as int] == f);
+                    assert forall |j: int| 0 <= j < before.len() implies #[trigger] handed@[j] == before[j] by {}
+                    lemma_prefix_keys_finite(handed@, handed.len() as int);
+                    assert forall |j: int, k: int| 0 <= j < handed.len() && 0 <= k < handed.len() && #[trigger] handed@[j]@.0 == #[trigger] handed@[k]@.0 implies j == k by {
+                        if (j < before.len() && k == before.len()) {
+                            assert(prefix_keys(before, before.len() as int).contains(before[j]@.0));
+                        }
+                        if (k < before.len() && j == before.len()) {
+                            assert(prefix_keys(before, before.len() as int).contains(before[k]@.0));
+                        }
+                    }
+                }
+            }
+        },
+        None => {},
+    }
+    proof {
+        lemma_prefix_keys_finite(handed@, handed.len() as int);
+        assert forall |k: HoldEdge| #[trigger] target.contains_key(k) <==> exists |j: int| 0 <= j < handed.len() && #[trigger] handed@[j]@.0 == k by {
+            assert(prefix_keys(handed@, handed.len() as int).contains(k) <==> exists |j: int| 0 <= j < handed.len() && #[trigger] handed@[j]@.0 == k);
+        }
+    }
+    handed
+}
+
+/// Holders through a mirror
+///
+/// Every holder of [end] among the flagged entries of [v] holds [n] through them exactly when
+/// every holder of [end] among the elements of [holds] which satisfy [stays] holds [n] through
+/// them, when [v] mirrors [holds] and the flags are [stays]
+proof fn lemma_holders_keep_mirrored(v: Seq<ExecHoldEdge>, keep: Seq<bool>, holds: Set<HoldEdge>,
+    stays: spec_fn(HoldEdge) -> bool, end: ResourceLike, n: ResourceLike)
+    requires
+        mirrors(v, holds),
+        keep.len() == v.len(),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] keep[i] == stays(v[i]@),
+    ensures
+        (forall |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i]@.dst() == end ==>
+            exists |j: int| 0 <= j < v.len() && keep[j] && #[trigger] v[j]@.src() == v[i]@.src() && v[j]@.dst() == n)
+        <==> (forall |he: HoldEdge| #[trigger] holds.filter(stays).contains(he) && he.dst() == end ==>
+            exists |k: HoldEdge| #[trigger] holds.filter(stays).contains(k) && k.src() == he.src() && k.dst() == n),
+{
+    let kept_holds = holds.filter(stays);
+    if (forall |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i]@.dst() == end ==>
+        exists |j: int| 0 <= j < v.len() && keep[j] && #[trigger] v[j]@.src() == v[i]@.src() && v[j]@.dst() == n) {
+        assert forall |he: HoldEdge| #[trigger] kept_holds.contains(he) && he.dst() == end implies
+            exists |k: HoldEdge| #[trigger] kept_holds.contains(k) && k.src() == he.src() && k.dst() == n by {
+            let i = choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@ == he;
+            assert(keep[i] && v[i]@.dst() == end);
+            let j = choose |j: int| 0 <= j < v.len() && keep[j] && #[trigger] v[j]@.src() == v[i]@.src() && v[j]@.dst() == n;
+            lemma_mirrored(v, holds, j);
+            assert(kept_holds.contains(v[j]@));
+        }
+    }
+    if (forall |he: HoldEdge| #[trigger] kept_holds.contains(he) && he.dst() == end ==>
+        exists |k: HoldEdge| #[trigger] kept_holds.contains(k) && k.src() == he.src() && k.dst() == n) {
+        assert forall |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i]@.dst() == end implies
+            exists |j: int| 0 <= j < v.len() && keep[j] && #[trigger] v[j]@.src() == v[i]@.src() && v[j]@.dst() == n by {
+            lemma_mirrored(v, holds, i);
+            assert(kept_holds.contains(v[i]@));
+            let k = choose |k: HoldEdge| #[trigger] kept_holds.contains(k) && k.src() == v[i]@.src() && k.dst() == n;
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@ == k;
+            assert(keep[j] && v[j]@.src() == v[i]@.src());
+        }
+    }
+}
+
+/// Chains of derivations
+///
+/// A [chain] whose every hold edge but the last was derived from the next one leads from each of
+/// its hold edges to the last one
+proof fn lemma_chain_derived_through(s: OsmosisDAG::State<StandardKind>, chain: Seq<HoldEdge>, i: int)
+    requires
+        0 <= i < chain.len(),
+        forall |j: int| 0 <= j < chain.len() - 1 ==> #[trigger] s.derived_from.contains_key(chain[j]) && s.derived_from[chain[j]] == chain[j + 1],
+    ensures
+        s.derived_through(chain[i], chain.last(), (chain.len() - 1 - i) as nat),
+    decreases chain.len() - i
+{
+    if (i < chain.len() - 1) {
+        lemma_chain_derived_through(s, chain, i + 1);
+        assert(s.derived_from.contains_key(chain[i]) && s.derived_from[chain[i]] == chain[i + 1]);
+    }
+}
+
+/// A chain of derivations which ends in a hold edge without derivation, and never passes through
+/// [h], never leads to [h]
+proof fn lemma_chain_not_derived_through(s: OsmosisDAG::State<StandardKind>, chain: Seq<HoldEdge>, h: HoldEdge, i: int, fuel: nat)
+    requires
+        0 <= i < chain.len(),
+        forall |j: int| 0 <= j < chain.len() - 1 ==> #[trigger] s.derived_from.contains_key(chain[j]) && s.derived_from[chain[j]] == chain[j + 1],
+        !s.derived_from.contains_key(chain.last()),
+        forall |j: int| 0 <= j < chain.len() ==> #[trigger] chain[j] != h,
+    ensures
+        !s.derived_through(chain[i], h, fuel),
+    decreases chain.len() - i
+{
+    if (i < chain.len() - 1 && fuel > 0) {
+        assert(s.derived_from.contains_key(chain[i]) && s.derived_from[chain[i]] == chain[i + 1]);
+        lemma_chain_not_derived_through(s, chain, h, i + 1, (fuel - 1) as nat);
+    }
+}
+
+// Indexes:
+
+/// The vals taken by the Resources of a type only depend on the Resources
+proof fn lemma_used_frame(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>)
+    requires
+        post.resources == pre.resources,
+    ensures
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    assert forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t) by {
+        assert(used_vals(post, t) =~= used_vals(pre, t));
+    }
+}
+
+/// The indexes of a state only depend on its spaces, its Resources and the edges between them
+proof fn lemma_indexes_frame(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>)
+    requires
+        post.spaces == pre.spaces,
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions,
+    ensures
+        allocation_index(post) == allocation_index(pre),
+        carving_index(post) == carving_index(pre),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    lemma_used_frame(pre, post);
+    assert forall |k: SpaceId| allocated_vals(post, k) == allocated_vals(pre, k) && carved_vals(post, k) == carved_vals(pre, k) by {
+        assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+        assert(carved_vals(post, k) =~= carved_vals(pre, k));
+    }
+    assert(allocation_index(post) =~= allocation_index(pre));
+    assert(carving_index(post) =~= carving_index(pre));
+}
+
+/// A new space has nothing allocated from nor carved out of it, and leaves the others as they were
+proof fn lemma_indexes_new_space(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, space: SpaceId)
+    requires
+        pre.invariant(),
+        !pre.spaces.contains_key(space),
+        post.spaces == pre.spaces.insert(space, post.spaces[space]),
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions,
+    ensures
+        allocation_index(post) == allocation_index(pre).insert(space, Set::empty()),
+        carving_index(post) == carving_index(pre).insert(space, Set::empty()),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    lemma_used_frame(pre, post);
+    assert forall |k: SpaceId| #[trigger] post.spaces.contains_key(k) implies
+        allocated_vals(post, k) == (if k == space { Set::empty() } else { allocated_vals(pre, k) })
+        && carved_vals(post, k) == (if k == space { Set::empty() } else { carved_vals(pre, k) }) by {
+        // Invariant: subset_nodes_in_graph, partition_nodes_in_graph
+        assert forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) implies post.spaces[pe.src()] == pre.spaces[pe.src()] by {}
+        if (k == space) {
+            assert(allocated_vals(post, k) =~= Set::empty());
+            assert(carved_vals(post, k) =~= Set::empty());
+        } else {
+            assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+            assert(carved_vals(post, k) =~= carved_vals(pre, k));
+        }
+    }
+    assert(allocation_index(post) =~= allocation_index(pre).insert(space, Set::empty()));
+    assert(carving_index(post) =~= carving_index(pre).insert(space, Set::empty()));
+}
+
+/// Splitting [child] out of [parent] carves the vals of the child out of the parent
+proof fn lemma_indexes_split(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, parent: SpaceId, child: SpaceId)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(parent),
+        !pre.spaces.contains_key(child),
+        post.spaces == pre.spaces.insert(child, post.spaces[child]),
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions.insert(PartitionEdge { src: child, dst: parent }),
+    ensures
+        allocation_index(post) == allocation_index(pre).insert(child, Set::empty()),
+        carving_index(post) == carving_index(pre).insert(child, Set::empty())
+            .insert(parent, carved_vals(pre, parent).union(post.spaces[child].vals())),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    let mid = OsmosisDAG::State { partitions: pre.partitions, ..post };
+    lemma_indexes_new_space(pre, mid, child);
+    lemma_used_frame(pre, post);
+    let pe = PartitionEdge { src: child, dst: parent };
+    assert(allocation_index(post) =~= allocation_index(mid)) by {
+        assert forall |k: SpaceId| allocated_vals(post, k) == allocated_vals(mid, k) by {
+            assert(allocated_vals(post, k) =~= allocated_vals(mid, k));
+        }
+    }
+    assert forall |k: SpaceId| #[trigger] post.spaces.contains_key(k) implies
+        carved_vals(post, k) == (if k == parent { carved_vals(mid, k).union(post.spaces[child].vals()) } else { carved_vals(mid, k) }) by {
+        if (k == parent) {
+            assert forall |v: nat| carved_vals(post, k).contains(v) == #[trigger] carved_vals(mid, k).union(post.spaces[child].vals()).contains(v) by {
+                if (post.spaces[child].vals().contains(v)) {
+                    assert(post.partitions.contains(pe));
+                }
+            }
+            assert(carved_vals(post, k) =~= carved_vals(mid, k).union(post.spaces[child].vals()));
+        } else {
+            assert(carved_vals(post, k) =~= carved_vals(mid, k));
+        }
+    }
+    assert(carving_index(post) =~= carving_index(mid).insert(parent, carved_vals(pre, parent).union(post.spaces[child].vals())));
+}
+
+/// A new Resource allocated from [space] takes its range there and among the Resources of its type
+proof fn lemma_indexes_create_resource(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, id: ResourceId, space: SpaceId)
+    requires
+        pre.invariant(),
+        !pre.resources.contains_key(id),
+        pre.spaces.contains_key(space),
+        post.spaces == pre.spaces,
+        post.resources == pre.resources.insert(id, post.resources[id]),
+        post.subsets == pre.subsets.insert(SubsetEdge { src: id, dst: space }),
+        post.partitions == pre.partitions,
+    ensures
+        allocation_index(post) == allocation_index(pre).insert(space, allocated_vals(pre, space).union(post.resources[id].range())),
+        carving_index(post) == carving_index(pre),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) ==
+            if t == post.resources[id].rtype() { used_vals(pre, t).union(post.resources[id].range()) } else { used_vals(pre, t) },
+{
+    let se = SubsetEdge { src: id, dst: space };
+    let range = post.resources[id].range();
+    // Invariant: subset_nodes_in_graph
+    assert forall |e: SubsetEdge| #[trigger] pre.subsets.contains(e) implies post.resources[e.src()] == pre.resources[e.src()] by {}
+    assert forall |k: SpaceId| #[trigger] pre.spaces.contains_key(k) implies
+        allocated_vals(post, k) == (if k == space { allocated_vals(pre, k).union(range) } else { allocated_vals(pre, k) }) by {
+        if (k == space) {
+            assert forall |v: nat| allocated_vals(post, k).contains(v) == #[trigger] allocated_vals(pre, k).union(range).contains(v) by {
+                if (range.contains(v)) {
+                    assert(post.subsets.contains(se));
+                }
+            }
+            assert(allocated_vals(post, k) =~= allocated_vals(pre, k).union(range));
+        } else {
+            assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+        }
+    }
+    assert(allocation_index(post) =~= allocation_index(pre).insert(space, allocated_vals(pre, space).union(range)));
+    assert forall |k: SpaceId| carved_vals(post, k) == carved_vals(pre, k) by {
+        assert(carved_vals(post, k) =~= carved_vals(pre, k));
+    }
+    assert(carving_index(post) =~= carving_index(pre));
+    assert forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) ==
+        if t == post.resources[id].rtype() { used_vals(pre, t).union(range) } else { used_vals(pre, t) } by {
+        assert forall |v: nat| #[trigger] used_vals(post, t).contains(v) ==
+            (if t == post.resources[id].rtype() { used_vals(pre, t).union(range) } else { used_vals(pre, t) }).contains(v) by {
+            if (used_vals(pre, t).contains(v)) {
+                let r = choose |r: ResourceId| #[trigger] pre.resources.contains_key(r) && pre.resources[r].rtype() == t
+                    && pre.resources[r].range().contains(v);
+                assert(post.resources.contains_key(r) && post.resources[r] == pre.resources[r]);
+            }
+            if (t == post.resources[id].rtype() && range.contains(v)) {
+                assert(post.resources.contains_key(id));
+            }
+        }
+        if (t == post.resources[id].rtype()) {
+            assert(used_vals(post, t) =~= used_vals(pre, t).union(range));
+        } else {
+            assert(used_vals(post, t) =~= used_vals(pre, t));
+        }
+    }
+}
+
+/// Destroying the Resource [id] allocated from [space] frees its range there and among the
+/// physical Resources of its type, since no other Resource there overlaps it
+proof fn lemma_indexes_destroy_resource(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, id: ResourceId, space: SpaceId)
+    requires
+        pre.invariant(),
+        pre.subsets.contains(SubsetEdge { src: id, dst: space }),
+        post.spaces == pre.spaces,
+        post.resources == pre.resources.remove(id),
+        post.subsets == pre.subsets.remove(SubsetEdge { src: id, dst: space }),
+        post.partitions == pre.partitions,
+    ensures
+        allocation_index(post) == allocation_index(pre).insert(space, allocated_vals(pre, space).difference(pre.resources[id].range())),
+        carving_index(post) == carving_index(pre),
+        forall |t: ResourceType<StandardKind>| t is Physical ==> #[trigger] used_vals(post, t) ==
+            if t == pre.resources[id].rtype() { used_vals(pre, t).difference(pre.resources[id].range()) } else { used_vals(pre, t) },
+{
+    let se = SubsetEdge { src: id, dst: space };
+    let range = pre.resources[id].range();
+    // Invariant: subset_nodes_in_graph
+    assert(pre.spaces.contains_key(space) && pre.resources.contains_key(id));
+    assert forall |k: SpaceId| #[trigger] pre.spaces.contains_key(k) implies
+        allocated_vals(post, k) == (if k == space { allocated_vals(pre, k).difference(range) } else { allocated_vals(pre, k) }) by {
+        assert forall |v: nat| #[trigger] allocated_vals(post, k).contains(v) ==
+            (if k == space { allocated_vals(pre, k).difference(range) } else { allocated_vals(pre, k) }).contains(v) by {
+            if (allocated_vals(pre, k).contains(v)) {
+                let e = choose |e: SubsetEdge| #[trigger] pre.subsets.contains(e) && e.dst() == k
+                    && pre.resources[e.src()].range().contains(v);
+                if (e.src() != id) {
+                    assert(post.subsets.contains(e) && post.resources[e.src()] == pre.resources[e.src()]);
+                } else {
+                    // Invariant: subset_src_are_unique
+                    assert(e.dst() == se.dst());
+                }
+            }
+            if (allocated_vals(post, k).contains(v)) {
+                let e = choose |e: SubsetEdge| #[trigger] post.subsets.contains(e) && e.dst() == k
+                    && post.resources[e.src()].range().contains(v);
+                assert(pre.subsets.contains(e) && e.src() != id);
+                if (k == space) {
+                    // Invariant: subset_ranges_disjoint
+                    assert(pre.resources[e.src()].range().disjoint(range));
+                }
+            }
+        }
+        if (k == space) {
+            assert(allocated_vals(post, k) =~= allocated_vals(pre, k).difference(range));
+        } else {
+            assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+        }
+    }
+    assert(allocation_index(post) =~= allocation_index(pre).insert(space, allocated_vals(pre, space).difference(range)));
+    assert forall |k: SpaceId| carved_vals(post, k) == carved_vals(pre, k) by {
+        assert(carved_vals(post, k) =~= carved_vals(pre, k));
+    }
+    assert(carving_index(post) =~= carving_index(pre));
+    assert forall |t: ResourceType<StandardKind>| t is Physical implies #[trigger] used_vals(post, t) ==
+        if t == pre.resources[id].rtype() { used_vals(pre, t).difference(range) } else { used_vals(pre, t) } by {
+        assert forall |v: nat| #[trigger] used_vals(post, t).contains(v) ==
+            (if t == pre.resources[id].rtype() { used_vals(pre, t).difference(range) } else { used_vals(pre, t) }).contains(v) by {
+            if (used_vals(pre, t).contains(v)) {
+                let r = choose |r: ResourceId| #[trigger] pre.resources.contains_key(r) && pre.resources[r].rtype() == t
+                    && pre.resources[r].range().contains(v);
+                if (r != id) {
+                    assert(post.resources.contains_key(r) && post.resources[r] == pre.resources[r]);
+                }
+            }
+            if (used_vals(post, t).contains(v)) {
+                let r = choose |r: ResourceId| #[trigger] post.resources.contains_key(r) && post.resources[r].rtype() == t
+                    && post.resources[r].range().contains(v);
+                assert(pre.resources.contains_key(r) && r != id);
+                if (t == pre.resources[id].rtype()) {
+                    // Invariant: physical_ranges_disjoint
+                    assert(pre.resources[r].range().disjoint(range));
+                }
+            }
+        }
+        if (t == pre.resources[id].rtype()) {
+            assert(used_vals(post, t) =~= used_vals(pre, t).difference(range));
+        } else {
+            assert(used_vals(post, t) =~= used_vals(pre, t));
+        }
+    }
+}
+
+/// Resizing [space] changes what is carved out of its parent, if it has one, by the vals it gains
+/// and loses, since none of its siblings shares them
+proof fn lemma_indexes_resize(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, space: SpaceId, parent: Option<SpaceId>)
+    requires
+        pre.invariant(),
+        pre.spaces.contains_key(space),
+        post.spaces == pre.spaces.insert(space, post.spaces[space]),
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions,
+        match parent {
+            Some(p) => pre.partitions.contains(PartitionEdge { src: space, dst: p }),
+            None => forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space,
+        },
+    ensures
+        allocation_index(post) == allocation_index(pre),
+        carving_index(post) == match parent {
+            Some(p) => carving_index(pre).insert(p, carved_vals(pre, p).difference(pre.spaces[space].vals()).union(post.spaces[space].vals())),
+            None => carving_index(pre),
+        },
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    lemma_used_frame(pre, post);
+    assert forall |k: SpaceId| allocated_vals(post, k) == allocated_vals(pre, k) by {
+        assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+    }
+    assert(allocation_index(post) =~= allocation_index(pre));
+    assert forall |k: SpaceId| #[trigger] pre.spaces.contains_key(k) implies carved_vals(post, k) == (if parent == Some(k) {
+        carved_vals(pre, k).difference(pre.spaces[space].vals()).union(post.spaces[space].vals())
+    } else {
+        carved_vals(pre, k)
+    }) by {
+        let target = if parent == Some(k) {
+            carved_vals(pre, k).difference(pre.spaces[space].vals()).union(post.spaces[space].vals())
+        } else {
+            carved_vals(pre, k)
+        };
+        assert forall |v: nat| #[trigger] carved_vals(post, k).contains(v) == target.contains(v) by {
+            if (carved_vals(post, k).contains(v)) {
+                let pe = choose |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) && pe.dst() == k
+                    && post.spaces[pe.src()].vals().contains(v);
+                if (pe.src() == space) {
+                    // Invariant: partition_src_are_unique
+                    assert(parent == Some(k));
+                } else if (parent == Some(k)) {
+                    // Invariant: partition_siblings_disjoint
+                    assert(pre.spaces[pe.src()].vals().disjoint(pre.spaces[space].vals()));
+                }
+            }
+            if (target.contains(v)) {
+                if (parent == Some(k) && post.spaces[space].vals().contains(v)) {
+                    assert(post.partitions.contains(PartitionEdge { src: space, dst: k }));
+                } else {
+                    let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == k
+                        && pre.spaces[pe.src()].vals().contains(v);
+                    if (pe.src() == space) {
+                        // Invariant: partition_src_are_unique
+                        assert(parent == Some(k));
+                    }
+                }
+            }
+        }
+        assert(carved_vals(post, k) =~= target);
+    }
+    match parent {
+        Some(p) => {
+            assert(carving_index(post) =~= carving_index(pre).insert(p,
+                carved_vals(pre, p).difference(pre.spaces[space].vals()).union(post.spaces[space].vals())));
+        },
+        None => {
+            assert(carving_index(post) =~= carving_index(pre));
+        },
+    }
+}
+
+/// Merging [other] into its sibling [keep] moves no val in or out of their parent, and drops the
+/// indexes of [other]
+proof fn lemma_indexes_merge(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, parent: SpaceId, keep: SpaceId, other: SpaceId)
+    requires
+        pre.invariant(),
+        keep != other,
+        pre.partitions.contains(PartitionEdge { src: keep, dst: parent }),
+        pre.partitions.contains(PartitionEdge { src: other, dst: parent }),
+        post.spaces == pre.spaces.remove(other).insert(keep, post.spaces[keep]),
+        post.spaces[keep].vals() == pre.spaces[keep].vals().union(pre.spaces[other].vals()),
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions.remove(PartitionEdge { src: other, dst: parent }),
+    ensures
+        allocation_index(post) == allocation_index(pre).remove(other),
+        carving_index(post) == carving_index(pre).remove(other),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    lemma_used_frame(pre, post);
+    assert forall |k: SpaceId| allocated_vals(post, k) == allocated_vals(pre, k) by {
+        assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+    }
+    assert(allocation_index(post) =~= allocation_index(pre).remove(other));
+    assert forall |k: SpaceId| #[trigger] pre.spaces.contains_key(k) && k != other implies carved_vals(post, k) == carved_vals(pre, k) by {
+        assert forall |v: nat| #[trigger] carved_vals(post, k).contains(v) == carved_vals(pre, k).contains(v) by {
+            if (carved_vals(post, k).contains(v)) {
+                let pe = choose |pe: PartitionEdge| #[trigger] post.partitions.contains(pe) && pe.dst() == k
+                    && post.spaces[pe.src()].vals().contains(v);
+                // Invariant: partition_src_are_unique
+                assert(pe.src() != other);
+                if (pe.src() == keep && pre.spaces[other].vals().contains(v)) {
+                    assert(k == parent);
+                    assert(pre.partitions.contains(PartitionEdge { src: other, dst: parent }));
+                }
+            }
+            if (carved_vals(pre, k).contains(v)) {
+                let pe = choose |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) && pe.dst() == k
+                    && pre.spaces[pe.src()].vals().contains(v);
+                if (pe.src() == other) {
+                    // Invariant: partition_src_are_unique
+                    assert(k == parent);
+                    assert(post.partitions.contains(PartitionEdge { src: keep, dst: parent }));
+                } else {
+                    assert(post.partitions.contains(pe));
+                }
+            }
+        }
+        assert(carved_vals(post, k) =~= carved_vals(pre, k));
+    }
+    assert(carving_index(post) =~= carving_index(pre).remove(other));
+}
+
+/// Destroying [space], which was not carved out of another, only drops its indexes
+proof fn lemma_indexes_destroy_space(pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>, space: SpaceId)
+    requires
+        forall |pe: PartitionEdge| #[trigger] pre.partitions.contains(pe) ==> pe.src() != space,
+        post.spaces == pre.spaces.remove(space),
+        post.resources == pre.resources,
+        post.subsets == pre.subsets,
+        post.partitions == pre.partitions,
+    ensures
+        allocation_index(post) == allocation_index(pre).remove(space),
+        carving_index(post) == carving_index(pre).remove(space),
+        forall |t: ResourceType<StandardKind>| #[trigger] used_vals(post, t) == used_vals(pre, t),
+{
+    lemma_used_frame(pre, post);
+    assert forall |k: SpaceId| allocated_vals(post, k) == allocated_vals(pre, k) && carved_vals(post, k) == carved_vals(pre, k) by {
+        assert(allocated_vals(post, k) =~= allocated_vals(pre, k));
+        assert(carved_vals(post, k) =~= carved_vals(pre, k));
+    }
+    assert(allocation_index(post) =~= allocation_index(pre).remove(space));
+    assert(carving_index(post) =~= carving_index(pre).remove(space));
+}
+
+/// An index of the vals taken by physical Resources still indexes them when no type of them
+/// changed
+proof fn lemma_usage_kept(v: Seq<UsageEntry>, pre: OsmosisDAG::State<StandardKind>, post: OsmosisDAG::State<StandardKind>)
+    requires
+        indexes_usage(v, pre),
+        forall |t: ResourceType<StandardKind>| t is Physical ==> #[trigger] used_vals(post, t) == used_vals(pre, t),
+    ensures
+        indexes_usage(v, post),
+{
+    assert forall |i: int| 0 <= i < v.len() implies #[trigger] v[i]@.1 == used_vals(post, v[i]@.0) by {
+        assert(used_vals(post, v[i]@.0) == used_vals(pre, v[i]@.0));
+    }
+}
+
+/// Setting the entry [i] of an index of the vals taken by physical Resources, which is for the
+/// type of [e], keeps it indexing them when only those of that type changed
+proof fn lemma_usage_updated(v: Seq<UsageEntry>, i: int, e: UsageEntry, pre: OsmosisDAG::State<StandardKind>,
+    post: OsmosisDAG::State<StandardKind>)
+    requires
+        indexes_usage(v, pre),
+        0 <= i < v.len(),
+        v[i]@.0 == e@.0,
+        e.vals.wf(),
+        e@.1 == used_vals(post, e@.0),
+        forall |t: ResourceType<StandardKind>| t is Physical && t != e@.0 ==> #[trigger] used_vals(post, t) == used_vals(pre, t),
+    ensures
+        indexes_usage(v.update(i, e), post),
+{
+    let w = v.update(i, e);
+    assert forall |j: int| 0 <= j < w.len() implies {
+        &&& #[trigger] w[j].vals.wf()
+        &&& w[j]@.0 is Physical
+        &&& kind_rank(w[j]@.0->Physical_0) == j
+        &&& w[j]@.1 == used_vals(post, w[j]@.0)
+    } by {
+        if (j != i) {
+            assert(w[j] == v[j]);
+            // Entries at different ranks are for different kinds
+            assert(v[j]@.0 != e@.0);
+            assert(used_vals(post, v[j]@.0) == used_vals(pre, v[j]@.0));
+        }
+    }
+}
+
+// Queries:
+
+impl OsmosisGraph {
+    fn has_domain(&self, p: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.domains.contains(pd_of(p)),
+    {
+        let mut i: usize = 0;
+        while i < self.domains.len()
+            invariant
+                self.wf(),
+                i <= self.domains.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.domains[j] != p,
+            decreases self.domains.len() - i
+        {
+            if self.domains[i] == p {
+                assert(pd_of(self.domains[i as int]) == pd_of(p));
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.domains.contains(pd_of(p))) {
+                let j = choose |j: int| 0 <= j < self.domains.len() && #[trigger] pd_of(self.domains[j]) == pd_of(p);
+                assert(self.domains[j] == p);
+            }
+        }
+        false
+    }
+
+    /// The Resource [id], if it exists
+    fn resource(&self, id: u64) -> (r: Option<ExecResource>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(res) => self@.resources.contains_key(id as nat) && self@.resources[id as nat] == res@,
+                None => !self@.resources.contains_key(id as nat),
+            },
+    {
+        match sorted_index(&self.resources, id, Ghost(self@.resources)) {
+            Some(i) => Some(self.resources[i].res),
+            None => None,
+        }
+    }
+
+    /// The index of the ResourceSpace [id] in the spaces, if it exists
+    fn space_index(&self, id: u64) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => {
+                    &&& i < self.spaces.len()
+                    &&& self.spaces[i as int].id == id
+                    &&& self.spaces[i as int].space.wf()
+                    &&& self@.spaces.contains_key(id as nat)
+                    &&& self@.spaces[id as nat] == self.spaces[i as int].space@
+                },
+                None => !self@.spaces.contains_key(id as nat),
+            },
+    {
+        sorted_index(&self.spaces, id, Ghost(self@.spaces))
+    }
+
+    fn node_in_graph(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.node_in_graph(n@),
+    {
+        match n {
+            ExecResourceLike::Resource { res } => self.resource(res).is_some(),
+            ExecResourceLike::Space { space } => self.space_index(space).is_some(),
+        }
+    }
+
+    /// The type of the node [n], if it is in the graph
+    fn node_type(&self, n: ExecResourceLike) -> (r: Option<ExecResourceType>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(rtype) => self@.node_in_graph(n@) && rtype@ == n@.rtype(self@.resources, self@.spaces),
+                None => !self@.node_in_graph(n@),
+            },
+    {
+        match n {
+            ExecResourceLike::Resource { res } => match self.resource(res) {
+                Some(r) => Some(r.rtype),
+                None => None,
+            },
+            ExecResourceLike::Space { space } => match self.space_index(space) {
+                Some(i) => Some(self.spaces[i].space.rtype),
+                None => None,
+            },
+        }
+    }
+
+    fn holds_with_rights(&self, p: u64, n: ExecResourceLike, rights: &Rights) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.holds_with_rights(pd_of(p), n@, rights@),
+    {
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                i <= self.holds.len(),
+                forall |j: int| 0 <= j < i ==> !(#[trigger] self.holds[j]@.src() == pd_of(p)
+                    && self.holds[j]@.dst() == n@ && rights@.subset_of(self.holds[j]@.rights())),
+            decreases self.holds.len() - i
+        {
+            let he = self.holds[i];
+            if he.src == p && he.dst.eq(n) && rights.subset_of(&he.rights) {
+                proof {
+                    lemma_mirrored(self.holds@, self@.holds, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.holds_with_rights(pd_of(p), n@, rights@)) {
+                let he = choose |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p) && he.dst() == n@
+                    && rights@.subset_of(he.rights());
+                let j = choose |j: int| 0 <= j < self.holds.len() && #[trigger] self.holds@[j]@ == he;
+                assert(self.holds[j]@ == he);
+            }
+        }
+        false
+    }
+
+    /// The only hold edge of [p] on [n] with at least [rights], which is the one
+    /// hold_edge_with_rights chooses
+    fn hold_edge_with_rights(&self, p: u64, n: ExecResourceLike, rights: &Rights) -> (r: Result<ExecHoldEdge, StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> self@.holds_with_rights(pd_of(p), n@, rights@)
+                && self@.holds.contains(r->Ok_0@)
+                && r->Ok_0@ == self@.hold_edge_with_rights(pd_of(p), n@, rights@),
+    {
+        let mut found: Option<ExecHoldEdge> = None;
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                i <= self.holds.len(),
+                match found {
+                    Some(he) => self@.holds.contains(he@) && he@.src() == pd_of(p) && he@.dst() == n@ && rights@.subset_of(he@.rights())
+                        && forall |j: int| 0 <= j < i && #[trigger] self.holds[j]@.src() == pd_of(p) && self.holds[j]@.dst() == n@
+                            && rights@.subset_of(self.holds[j]@.rights()) ==> self.holds[j]@ == he@,
+                    None => forall |j: int| 0 <= j < i ==> !(#[trigger] self.holds[j]@.src() == pd_of(p)
+                        && self.holds[j]@.dst() == n@ && rights@.subset_of(self.holds[j]@.rights())),
+                },
+            decreases self.holds.len() - i
+        {
+            let he = self.holds[i];
+            if he.src == p && he.dst.eq(n) && rights.subset_of(&he.rights) {
+                proof {
+                    lemma_mirrored(self.holds@, self@.holds, i as int);
+                }
+                match found {
+                    Some(first) => {
+                        if !first.eq(&he) {
+                            return Err(StepError::Unchecked);
+                        }
+                    },
+                    None => {
+                        found = Some(he);
+                    },
+                }
+            }
+            i = i + 1;
+        }
+        match found {
+            Some(he) => {
+                proof {
+                    let s = self@;
+                    let chosen = s.hold_edge_with_rights(pd_of(p), n@, rights@);
+                    assert(s.holds.contains(he@) && he@.src() == pd_of(p) && he@.dst() == n@ && rights@.subset_of(he@.rights()));
+                    let j = choose |j: int| 0 <= j < self.holds.len() && #[trigger] self.holds@[j]@ == chosen;
+                    assert(self.holds[j]@ == chosen);
+                }
+                Ok(he)
+            },
+            None => Err(StepError::Disabled),
+        }
+    }
+
+    fn holds_resource(&self, p: u64, r: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == holds_resource(self@.holds, pd_of(p), r as nat),
+    {
+        let b = self.holds_with_rights(p, ExecResourceLike::Resource { res: r }, &Rights::empty());
+        proof {
+            if (holds_resource(self@.holds, pd_of(p), r as nat)) {
+                let he = choose |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p)
+                    && he.dst() == (ResourceLike::Resource { res: r as nat });
+                assert(Set::<Right>::empty().subset_of(he.rights()));
+            }
+        }
+        b
+    }
+
+    fn holds_space(&self, p: u64, space: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == holds_space(self@.holds, pd_of(p), space as nat),
+    {
+        let b = self.holds_with_rights(p, ExecResourceLike::Space { space }, &Rights::empty());
+        proof {
+            if (holds_space(self@.holds, pd_of(p), space as nat)) {
+                let he = choose |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p)
+                    && he.dst() == (ResourceLike::Space { space: space as nat });
+                assert(Set::<Right>::empty().subset_of(he.rights()));
+            }
+        }
+        b
+    }
+
+    /// Whether every hold on [n] is one of [p]
+    fn held_only_by(&self, p: u64, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == forall |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.dst() == n@ ==> he.src() == pd_of(p),
+    {
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                i <= self.holds.len(),
+                forall |j: int| 0 <= j < i && #[trigger] self.holds[j]@.dst() == n@ ==> self.holds[j]@.src() == pd_of(p),
+            decreases self.holds.len() - i
+        {
+            if self.holds[i].dst.eq(n) && self.holds[i].src != p {
+                proof {
+                    lemma_mirrored(self.holds@, self@.holds, i as int);
+                    assert(self.holds[i as int]@.src() != pd_of(p));
+                }
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.dst() == n@ implies he.src() == pd_of(p) by {
+                let j = choose |j: int| 0 <= j < self.holds.len() && #[trigger] self.holds@[j]@ == he;
+                assert(self.holds[j]@ == he);
+            }
+        }
+        true
+    }
+
+    fn is_lent(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_lent(n@),
+    {
+        let mut i: usize = 0;
+        while i < self.leases.len()
+            invariant
+                self.wf(),
+                i <= self.leases.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.leases[j]@.0.dst() != n@,
+            decreases self.leases.len() - i
+        {
+            if self.leases[i].he.dst.eq(n) {
+                proof {
+                    lemma_mirrored_key(self.leases@, self@.leases, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.is_lent(n@)) {
+                let he = choose |he: HoldEdge| #[trigger] self@.leases.contains_key(he) && he.dst() == n@;
+                let j = choose |j: int| 0 <= j < self.leases.len() && #[trigger] self.leases@[j]@.0 == he;
+                assert(self.leases[j]@.0 == he);
+            }
+        }
+        false
+    }
+
+    fn is_mapped(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_mapped(n@),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !#[trigger] self.maps[j]@.touches(n@),
+            decreases self.maps.len() - i
+        {
+            if self.maps[i].touches(n) {
+                proof {
+                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.is_mapped(n@)) {
+                let me = choose |me: MapEdge| #[trigger] self@.maps.contains(me) && me.touches(n@);
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        false
+    }
+
+    /// Whether no mapping maps [n]
+    fn unmapped_src(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == forall |m: MapEdge| #[trigger] self@.maps.contains(m) ==> m.src_node() != n@,
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.maps[j]@.src_node() != n@,
+            decreases self.maps.len() - i
+        {
+            if self.maps[i].src_node().eq(n) {
+                proof {
+                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                }
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |m: MapEdge| #[trigger] self@.maps.contains(m) implies m.src_node() != n@ by {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == m;
+                assert(self.maps[j]@ == m);
+            }
+        }
+        true
+    }
+
+    /// Whether no mapping is mapped onto [n]
+    fn unmapped_dst(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == forall |m: MapEdge| #[trigger] self@.maps.contains(m) ==> !m.maps_onto(n@),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !#[trigger] self.maps[j]@.maps_onto(n@),
+            decreases self.maps.len() - i
+        {
+            if self.maps[i].maps_onto(n) {
+                proof {
+                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                }
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |m: MapEdge| #[trigger] self@.maps.contains(m) implies !m.maps_onto(n@) by {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == m;
+                assert(self.maps[j]@ == m);
+            }
+        }
+        true
+    }
+
+    /// Whether [me] is the only mapping which maps [n], or is mapped onto it when [dst_too] is set
+    fn only_mapping(&self, n: ExecResourceLike, me: &ExecMapEdge, dst_too: bool) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+        ensures
+            b ==> forall |m: MapEdge| #[trigger] self@.maps.contains(m) && (if dst_too { m.touches(n@) } else { m.src_node() == n@ })
+                ==> m == me@,
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                me.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i && (if dst_too { self.maps[j]@.touches(n@) } else { self.maps[j]@.src_node() == n@ })
+                    ==> #[trigger] self.maps[j]@ == me@,
+            decreases self.maps.len() - i
+        {
+            let touches = if dst_too { self.maps[i].touches(n) } else { self.maps[i].src_node().eq(n) };
+            if touches && !self.maps[i].eq(me) {
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |m: MapEdge| #[trigger] self@.maps.contains(m) && (if dst_too { m.touches(n@) } else { m.src_node() == n@ })
+                implies m == me@ by {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == m;
+                assert(self.maps[j]@ == m);
+            }
+        }
+        true
+    }
+
+    fn has_map(&self, me: &ExecMapEdge) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+        ensures
+            b == self@.maps.contains(me@),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                me.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.maps[j]@ != me@,
+            decreases self.maps.len() - i
+        {
+            if self.maps[i].eq(me) {
+                proof {
+                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.maps.contains(me@)) {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me@;
+                assert(self.maps[j]@ == me@);
+            }
+        }
+        false
+    }
+
+    fn is_metadata(&self, r: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_metadata(r as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.metadata.len()
+            invariant
+                self.wf(),
+                i <= self.metadata.len(),
+                forall |j: int| 0 <= j < i ==> !(#[trigger] self.metadata[j]@.1).contains(r as nat),
+            decreases self.metadata.len() - i
+        {
+            if self.metadata[i].meta.contains(r) {
+                proof {
+                    lemma_mirrored_key(self.metadata@, self@.metadata, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.is_metadata(r as nat)) {
+                let s = choose |s: SpaceId| #[trigger] self@.metadata.contains_key(s) && self@.metadata[s].contains(r as nat);
+                let j = choose |j: int| 0 <= j < self.metadata.len() && #[trigger] self.metadata@[j]@.0 == s;
+                lemma_mirrored_key(self.metadata@, self@.metadata, j);
+                assert(self.metadata[j]@.1 == self@.metadata[s]);
+            }
+        }
+        false
+    }
+
+    fn is_metadata_node(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_metadata_node(n@),
+    {
+        match n {
+            ExecResourceLike::Resource { res } => self.is_metadata(res),
+            ExecResourceLike::Space { .. } => false,
+        }
+    }
+
+    /// The space the Resource [r] was retyped into, if it was
+    fn retyped_into(&self, r: u64) -> (s: Option<u64>)
+        requires
+            self.wf(),
+        ensures
+            match s {
+                Some(space) => self@.retyped.contains_key(r as nat) && self@.retyped[r as nat] == space as nat,
+                None => !self@.retyped.contains_key(r as nat),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.retyped.len()
+            invariant
+                self.wf(),
+                i <= self.retyped.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.retyped[j].key != r,
+            decreases self.retyped.len() - i
+        {
+            if self.retyped[i].key == r {
+                proof {
+                    lemma_mirrored_key(self.retyped@, self@.retyped, i as int);
+                }
+                return Some(self.retyped[i].val);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.retyped.contains_key(r as nat)) {
+                let j = choose |j: int| 0 <= j < self.retyped.len() && #[trigger] self.retyped@[j]@.0 == r as nat;
+                assert(self.retyped[j].key == r);
+            }
+        }
+        None
+    }
+
+    fn is_retyped(&self, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_retyped(n@),
+    {
+        match n {
+            ExecResourceLike::Resource { res } => self.retyped_into(res).is_some(),
+            ExecResourceLike::Space { .. } => false,
+        }
+    }
+
+    /// The Device of the ProtectionDomain [p], if it is one
+    fn device(&self, p: u64) -> (d: Option<ExecDevice>)
+        requires
+            self.wf(),
+        ensures
+            match d {
+                Some(dev) => self@.devices.contains_key(pd_of(p)) && self@.devices[pd_of(p)] == dev@,
+                None => !self@.devices.contains_key(pd_of(p)),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.devices.len()
+            invariant
+                self.wf(),
+                i <= self.devices.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.devices[j].pd != p,
+            decreases self.devices.len() - i
+        {
+            if self.devices[i].pd == p {
+                proof {
+                    lemma_mirrored_key(self.devices@, self@.devices, i as int);
+                }
+                return Some(self.devices[i].device);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.devices.contains_key(pd_of(p))) {
+                let j = choose |j: int| 0 <= j < self.devices.len() && #[trigger] self.devices@[j]@.0 == pd_of(p);
+                assert(self.devices[j].pd == p);
+            }
+        }
+        None
+    }
+
+    fn is_device(&self, p: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.devices.contains_key(pd_of(p)),
+    {
+        self.device(p).is_some()
+    }
+
+    fn is_iommu(&self, space: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_iommu(space as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.devices.len()
+            invariant
+                self.wf(),
+                i <= self.devices.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.devices[j].device.iommu != space,
+            decreases self.devices.len() - i
+        {
+            if self.devices[i].device.iommu == space {
+                proof {
+                    lemma_mirrored_key(self.devices@, self@.devices, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.is_iommu(space as nat)) {
+                let dev = choose |dev: ProtectionDomain| #[trigger] self@.devices.contains_key(dev) && self@.devices[dev].iommu() == space as nat;
+                let j = choose |j: int| 0 <= j < self.devices.len() && #[trigger] self.devices@[j]@.0 == dev;
+                lemma_mirrored_key(self.devices@, self@.devices, j);
+            }
+        }
+        false
+    }
+
+    /// Whether [n] is the IOMMU space [iommu], or a node it is mapped onto
+    fn reached_through(&self, iommu: u64, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == ({
+                let space = ResourceLike::Space { space: iommu as nat };
+                ||| n@ == space
+                ||| exists |me: MapEdge| #[trigger] self@.maps.contains(me) && me.src_node() == space && me.dst_node() == n@
+            }),
+    {
+        let space = ExecResourceLike::Space { space: iommu };
+        if n.eq(space) {
+            return true;
+        }
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !(#[trigger] self.maps[j]@.src_node() == space@ && self.maps[j]@.dst_node() == n@),
+            decreases self.maps.len() - i
+        {
+            if self.maps[i].src_node().eq(space) && self.maps[i].dst_node().eq(n) {
+                proof {
+                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (exists |me: MapEdge| #[trigger] self@.maps.contains(me) && me.src_node() == space@ && me.dst_node() == n@) {
+                let me = choose |me: MapEdge| #[trigger] self@.maps.contains(me) && me.src_node() == space@ && me.dst_node() == n@;
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        false
+    }
+
+    fn pins(&self, p: u64, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.pins(pd_of(p), n@),
+    {
+        let mut i: usize = 0;
+        while i < self.devices.len()
+            invariant
+                self.wf(),
+                i <= self.devices.len(),
+                forall |j: int| 0 <= j < i && #[trigger] self.devices[j].device.driver == p ==>
+                    !self@.pinned_by(self.devices[j]@.0, n@),
+            decreases self.devices.len() - i
+        {
+            let e = self.devices[i];
+            proof {
+                lemma_mirrored_key(self.devices@, self@.devices, i as int);
+            }
+            if e.device.driver == p && self.reached_through(e.device.iommu, n) {
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.pins(pd_of(p), n@)) {
+                let dev = choose |dev: ProtectionDomain| #[trigger] self@.devices.contains_key(dev)
+                    && self@.devices[dev].driver() == pd_of(p) && self@.pinned_by(dev, n@);
+                let j = choose |j: int| 0 <= j < self.devices.len() && #[trigger] self.devices@[j]@.0 == dev;
+                lemma_mirrored_key(self.devices@, self@.devices, j);
+                assert(self.devices[j].device.driver == p);
+            }
+        }
+        false
+    }
+
+    fn handles(&self, p: u64, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.handles(pd_of(p), n@),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !((#[trigger] self.maps[j]@) is Delivery && self.maps[j]@->dl_dst == pd_of(p)
+                    && n@ == self.maps[j]@.src_node()),
+            decreases self.maps.len() - i
+        {
+            if let ExecMapEdge::Delivery { dl_src, dl_dst } = &self.maps[i] {
+                if *dl_dst == p && n.eq(ExecResourceLike::Resource { res: *dl_src }) {
+                    proof {
+                        lemma_mirrored(self.maps@, self@.maps, i as int);
+                    }
+                    return true;
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.handles(pd_of(p), n@)) {
+                let me = choose |me: MapEdge| #[trigger] self@.maps.contains(me) && me is Delivery && me->dl_dst == pd_of(p)
+                    && n@ == me.src_node();
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        false
+    }
+
+    /// Whether the interrupt line [irq] is routed to a handler
+    fn is_routed(&self, irq: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.is_routed(irq as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !((#[trigger] self.maps[j]@) is Delivery && self.maps[j]@->dl_src == irq as nat),
+            decreases self.maps.len() - i
+        {
+            if let ExecMapEdge::Delivery { dl_src, .. } = &self.maps[i] {
+                if *dl_src == irq {
+                    proof {
+                        lemma_mirrored(self.maps@, self@.maps, i as int);
+                    }
+                    return true;
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.is_routed(irq as nat)) {
+                let me = choose |me: MapEdge| #[trigger] self@.maps.contains(me) && me is Delivery && me->dl_src == irq as nat;
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        false
+    }
+
+    /// The index of the Request [id] in the issued Requests, if it was issued and not closed yet
+    fn request_index(&self, id: u64) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => {
+                    &&& i < self.issued.len()
+                    &&& self.issued[i as int].id == id
+                    &&& self.issued[i as int].req.wf()
+                    &&& self@.issued.contains_key(id as nat)
+                    &&& self@.issued[id as nat] == self.issued[i as int].req@
+                },
+                None => !self@.issued.contains_key(id as nat),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.issued.len()
+            invariant
+                self.wf(),
+                i <= self.issued.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.issued[j].id != id,
+            decreases self.issued.len() - i
+        {
+            if self.issued[i].id == id {
+                proof {
+                    lemma_mirrored_key(self.issued@, self@.issued, i as int);
+                }
+                return Some(i);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.issued.contains_key(id as nat)) {
+                let j = choose |j: int| 0 <= j < self.issued.len() && #[trigger] self.issued@[j]@.0 == id as nat;
+                assert(self.issued[j].id == id);
+            }
+        }
+        None
+    }
+
+    /// Whether the Resource [r] was granted by a Request which is not closed yet, to [client] if
+    /// given
+    fn granted(&self, r: u64, client: Option<u64>) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == exists |id: RequestId| #[trigger] self@.granted_by(id, r as nat)
+                && (client is Some ==> self@.issued[id].client() == pd_of(client->Some_0)),
+    {
+        let mut i: usize = 0;
+        while i < self.issued.len()
+            invariant
+                self.wf(),
+                i <= self.issued.len(),
+                forall |j: int| 0 <= j < i ==> !(#[trigger] self.issued[j]@.1.granted().contains(r as nat)
+                    && (client is Some ==> self.issued[j]@.1.client() == pd_of(client->Some_0))),
+            decreases self.issued.len() - i
+        {
+            let e = &self.issued[i];
+            proof {
+                lemma_mirrored_key(self.issued@, self@.issued, i as int);
+            }
+            let to_client = match client {
+                Some(c) => e.req.client == c,
+                None => true,
+            };
+            if to_client && e.req.handed(r) {
+                assert(self@.granted_by(e.id as nat, r as nat));
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (exists |id: RequestId| #[trigger] self@.granted_by(id, r as nat)
+                && (client is Some ==> self@.issued[id].client() == pd_of(client->Some_0))) {
+                let id = choose |id: RequestId| #[trigger] self@.granted_by(id, r as nat)
+                    && (client is Some ==> self@.issued[id].client() == pd_of(client->Some_0));
+                let j = choose |j: int| 0 <= j < self.issued.len() && #[trigger] self.issued@[j]@.0 == id;
+                lemma_mirrored_key(self.issued@, self@.issued, j);
+                assert(self.issued[j]@.1 == self@.issued[id]);
+            }
+        }
+        false
+    }
+
+    fn granted_to(&self, p: u64, n: ExecResourceLike) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.granted_to(pd_of(p), n@),
+    {
+        match n {
+            ExecResourceLike::Resource { res } => self.granted(res, Some(p)),
+            ExecResourceLike::Space { .. } => false,
+        }
+    }
+
+    /// Whether a Request over [re] is still waiting for an answer
+    fn pending_over(&self, re: &ExecRequestEdge) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == exists |id: RequestId| #[trigger] self@.is_pending(id) && self@.issued[id].edge() == re@,
+    {
+        let mut i: usize = 0;
+        while i < self.issued.len()
+            invariant
+                self.wf(),
+                i <= self.issued.len(),
+                forall |j: int| 0 <= j < i ==> !(#[trigger] self.issued[j]@.1.status() is Pending
+                    && self.issued[j]@.1.edge() == re@),
+            decreases self.issued.len() - i
+        {
+            let e = &self.issued[i];
+            proof {
+                lemma_mirrored_key(self.issued@, self@.issued, i as int);
+            }
+            if e.req.is_pending() && e.req.edge().eq(re) {
+                assert(self@.is_pending(e.id as nat));
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (exists |id: RequestId| #[trigger] self@.is_pending(id) && self@.issued[id].edge() == re@) {
+                let id = choose |id: RequestId| #[trigger] self@.is_pending(id) && self@.issued[id].edge() == re@;
+                let j = choose |j: int| 0 <= j < self.issued.len() && #[trigger] self.issued@[j]@.0 == id;
+                lemma_mirrored_key(self.issued@, self@.issued, j);
+                assert(self.issued[j]@.1 == self@.issued[id]);
+            }
+        }
+        false
+    }
+
+    fn has_request_edge(&self, re: &ExecRequestEdge) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.requests.contains(re@),
+    {
+        let mut i: usize = 0;
+        while i < self.requests.len()
+            invariant
+                self.wf(),
+                i <= self.requests.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.requests[j]@ != re@,
+            decreases self.requests.len() - i
+        {
+            if self.requests[i].eq(re) {
+                proof {
+                    lemma_mirrored(self.requests@, self@.requests, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.requests.contains(re@)) {
+                let j = choose |j: int| 0 <= j < self.requests.len() && #[trigger] self.requests@[j]@ == re@;
+                assert(self.requests[j]@ == re@);
+            }
+        }
+        false
+    }
+
+    fn has_partition(&self, pe: ExecPartitionEdge) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.partitions.contains(pe@),
+    {
+        let mut i: usize = 0;
+        while i < self.partitions.len()
+            invariant
+                self.wf(),
+                i <= self.partitions.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.partitions[j]@ != pe@,
+            decreases self.partitions.len() - i
+        {
+            if self.partitions[i].src == pe.src && self.partitions[i].dst == pe.dst {
+                proof {
+                    lemma_mirrored(self.partitions@, self@.partitions, i as int);
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.partitions.contains(pe@)) {
+                let j = choose |j: int| 0 <= j < self.partitions.len() && #[trigger] self.partitions@[j]@ == pe@;
+                assert(self.partitions[j]@ == pe@);
+            }
+        }
+        false
+    }
+
+    /// The quota [p] has for [rtype]
+    fn quota(&self, p: u64, rtype: ExecResourceType) -> (q: u64)
+        requires
+            self.wf(),
+        ensures
+            q as nat == self@.quota(pd_of(p), rtype@),
+    {
+        let mut i: usize = 0;
+        while i < self.quotas.len()
+            invariant
+                self.wf(),
+                i <= self.quotas.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.quotas[j]@.0 != (pd_of(p), rtype@),
+            decreases self.quotas.len() - i
+        {
+            let e = self.quotas[i];
+            if e.pd == p && e.rtype.eq(rtype) {
+                proof {
+                    lemma_mirrored_key(self.quotas@, self@.quotas, i as int);
+                }
+                return e.amount;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.quotas.contains_key((pd_of(p), rtype@))) {
+                let j = choose |j: int| 0 <= j < self.quotas.len() && #[trigger] self.quotas@[j]@.0 == (pd_of(p), rtype@);
+                assert(self.quotas[j]@.0 == (pd_of(p), rtype@));
+            }
+        }
+        0
+    }
+
+    /// The usage of [p] for [rtype], if it fits in a machine word
+    fn usage(&self, p: u64, rtype: ExecResourceType) -> (u: Option<u64>)
+        requires
+            self.wf(),
+        ensures
+            u is Some ==> u->Some_0 as nat == usage(self@.resources, self@.holds, pd_of(p), rtype@),
+    {
+        let ghost s = self@;
+        let ghost held = held_resources(s.resources, s.holds, pd_of(p), rtype@);
+        let ghost size = resource_size(s.resources);
+        let mut total: u64 = 0;
+        let mut i: usize = 0;
+        while i < self.resources.len()
+            invariant
+                self.wf(),
+                s == self@,
+                i <= self.resources.len(),
+                prefix_keys(self.resources@, i as int).finite(),
+                total as nat == set_sum(held.intersect(prefix_keys(self.resources@, i as int)), size),
+            decreases self.resources.len() - i
+        {
+            let e = self.resources[i];
+            let ghost seen = prefix_keys(self.resources@, i as int);
+            proof {
+                lemma_mirrored_key(self.resources@, s.resources, i as int);
+                lemma_prefix_keys_finite(self.resources@, i + 1);
+                lemma_len_subset(held.intersect(seen), seen);
+                // The id of the entry was not seen before, as ids are unique
+                if (seen.contains(e.id as nat)) {
+                    let j = choose |j: int| 0 <= j < i && #[trigger] self.resources@[j]@.0 == e.id as nat;
+                    assert(self.resources@[j]@.0 == self.resources@[i as int]@.0);
+                }
+            }
+            if e.res.rtype.eq(rtype) && self.holds_resource(p, e.id) {
+                proof {
+                    assert(held.contains(e.id as nat));
+                    assert(held.intersect(prefix_keys(self.resources@, i + 1)) =~= held.intersect(seen).insert(e.id as nat));
+                    lemma_set_sum_insert(held.intersect(seen), e.id as nat, size);
+                }
+                if total as u128 + e.res.size as u128 > u64::MAX as u128 {
+                    return None;
+                }
+                total = total + e.res.size;
+            } else {
+                proof {
+                    assert(!held.contains(e.id as nat));
+                    assert(held.intersect(prefix_keys(self.resources@, i + 1)) =~= held.intersect(seen));
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            lemma_prefix_keys_all(self.resources@, s.resources);
+            assert(held.intersect(s.resources.dom()) =~= held);
+        }
+        Some(total)
+    }
+
+    /// The total budget of the reservations of [rtype] which [p] holds, if it fits in a machine word
+    fn reserved(&self, p: u64, rtype: ExecResourceType) -> (u: Option<u64>)
+        requires
+            self.wf(),
+        ensures
+            u is Some ==> u->Some_0 as nat == reserved(self@.spaces, self@.partitions, self@.holds, pd_of(p), rtype@),
+    {
+        let ghost s = self@;
+        let ghost held = held_reservations(s.spaces, s.partitions, s.holds, pd_of(p), rtype@);
+        let ghost budget = space_budget(s.spaces);
+        let mut total: u64 = 0;
+        let mut i: usize = 0;
+        while i < self.spaces.len()
+            invariant
+                self.wf(),
+                s == self@,
+                i <= self.spaces.len(),
+                prefix_keys(self.spaces@, i as int).finite(),
+                total as nat == set_sum(held.intersect(prefix_keys(self.spaces@, i as int)), budget),
+            decreases self.spaces.len() - i
+        {
+            let e = &self.spaces[i];
+            let ghost seen = prefix_keys(self.spaces@, i as int);
+            proof {
+                lemma_mirrored_key(self.spaces@, s.spaces, i as int);
+                lemma_prefix_keys_finite(self.spaces@, i + 1);
+                lemma_len_subset(held.intersect(seen), seen);
+                // The id of the entry was not seen before, as ids are unique
+                if (seen.contains(e.id as nat)) {
+                    let j = choose |j: int| 0 <= j < i && #[trigger] self.spaces@[j]@.0 == e.id as nat;
+                    assert(self.spaces@[j]@.0 == self.spaces@[i as int]@.0);
+                }
+            }
+            let carved = match self.parent_space(e.id) {
+                Some(parent) => {
+                    proof {
+                        assert(s.partitions.contains(PartitionEdge { src: e.id as nat, dst: parent as nat }));
+                    }
+                    true
+                },
+                None => false,
+            };
+            if e.space.rtype.eq(rtype) && carved && self.holds_space(p, e.id) {
+                let n = e.space.vals.len();
+                proof {
+                    assert(held.contains(e.id as nat));
+                    assert(held.intersect(prefix_keys(self.spaces@, i + 1)) =~= held.intersect(seen).insert(e.id as nat));
+                    lemma_set_sum_insert(held.intersect(seen), e.id as nat, budget);
+                }
+                if total as u128 + n > u64::MAX as u128 {
+                    return None;
+                }
+                total = total + n as u64;
+            } else {
+                proof {
+                    assert(!held.contains(e.id as nat));
+                    assert(held.intersect(prefix_keys(self.spaces@, i + 1)) =~= held.intersect(seen));
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            lemma_prefix_keys_all(self.spaces@, s.spaces);
+            assert(held.intersect(s.spaces.dom()) =~= held);
+        }
+        Some(total)
+    }
+
+    /// What [p] is charged for [rtype], if it fits in a machine word
+    fn charged(&self, p: u64, rtype: ExecResourceType) -> (c: Option<u64>)
+        requires
+            self.wf(),
+        ensures
+            c is Some ==> c->Some_0 as nat == self@.charged(pd_of(p), rtype@),
+    {
+        match (self.usage(p, rtype), self.reserved(p, rtype)) {
+            (Some(u), Some(v)) => {
+                if u as u128 + v as u128 > u64::MAX as u128 {
+                    None
+                } else {
+                    Some(u + v)
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether [p] has enough quota left to be charged [size] more for [rtype], on top of [credit]
+    /// it is released from
+    fn fits_quota(&self, p: u64, rtype: ExecResourceType, size: u64, credit: u64) -> (r: Result<(), StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> self@.charged(pd_of(p), rtype@) + size <= self@.quota(pd_of(p), rtype@) + credit,
+    {
+        match self.charged(p, rtype) {
+            Some(c) => {
+                if c as u128 + size as u128 <= self.quota(p, rtype) as u128 + credit as u128 {
+                    Ok(())
+                } else {
+                    Err(StepError::Disabled)
+                }
+            },
+            None => Err(StepError::Unchecked),
+        }
+    }
+
+    /// The parent of the hold edge [k] in the derivations, if it was derived
+    fn parent_of(&self, k: &ExecHoldEdge) -> (r: Option<ExecHoldEdge>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(h) => self@.derived_from.contains_key(k@) && self@.derived_from[k@] == h@,
+                None => !self@.derived_from.contains_key(k@),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.derived_from.len()
+            invariant
+                self.wf(),
+                i <= self.derived_from.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.derived_from[j]@.0 != k@,
+            decreases self.derived_from.len() - i
+        {
+            if self.derived_from[i].child.eq(k) {
+                proof {
+                    lemma_mirrored_key(self.derived_from@, self@.derived_from, i as int);
+                }
+                return Some(self.derived_from[i].parent);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.derived_from.contains_key(k@)) {
+                let j = choose |j: int| 0 <= j < self.derived_from.len() && #[trigger] self.derived_from@[j]@.0 == k@;
+                assert(self.derived_from[j]@.0 == k@);
+            }
+        }
+        None
+    }
+
+    /// Whether [k] descends from [h], if following the derivations from [k] either reaches [h] or
+    /// ends within as many steps as there are derivations
+    fn descends_from(&self, k: &ExecHoldEdge, h: &ExecHoldEdge) -> (d: Option<bool>)
+        requires
+            self.wf(),
+        ensures
+            d is Some ==> d->Some_0 == self@.descends_from(k@, h@),
+    {
+        let ghost s = self@;
+        let mut cur = *k;
+        let ghost mut chain: Seq<HoldEdge> = seq![k@];
+        let mut steps: usize = 0;
+        while steps <= self.derived_from.len()
+            invariant
+                self.wf(),
+                s == self@,
+                chain.len() == steps + 1,
+                chain[0] == k@,
+                chain.last() == cur@,
+                forall |j: int| 0 <= j < chain.len() - 1 ==> #[trigger] s.derived_from.contains_key(chain[j]) && s.derived_from[chain[j]] == chain[j + 1],
+                forall |j: int| 0 <= j < chain.len() - 1 ==> #[trigger] chain[j] != h@,
+            decreases self.derived_from.len() + 1 - steps
+        {
+            if cur.eq(h) {
+                proof {
+                    lemma_chain_derived_through(s, chain, 0);
+                    assert(s.derived_through(k@, h@, steps as nat));
+                }
+                return Some(true);
+            }
+            match self.parent_of(&cur) {
+                Some(parent) => {
+                    proof {
+                        chain = chain.push(parent@);
+                    }
+                    cur = parent;
+                    steps = steps + 1;
+                },
+                None => {
+                    proof {
+                        assert forall |fuel: nat| !#[trigger] s.derived_through(k@, h@, fuel) by {
+                            lemma_chain_not_derived_through(s, chain, h@, 0, fuel);
+                        }
+                    }
+                    return Some(false);
+                },
+            }
+        }
+        None
+    }
+
+    /// Whether every Resource allocated from [space] lies outside the [n] vals from [lo]
+    fn allocations_avoid_range(&self, space: u64, lo: u64, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b ==> forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == space as nat ==>
+                self@.resources[se.src()].range().disjoint(nat_interval(lo as nat, n as nat)),
+    {
+        let ai = match vals_entry_index(&self.allocated, space, Ghost(allocation_index(self@))) {
+            Some(ai) => ai,
+            None => return true,
+        };
+        let b = self.allocated[ai].vals.disjoint_from_range(lo, n);
+        proof {
+            if (b) {
+                assert forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == space as nat implies
+                    self@.resources[se.src()].range().disjoint(nat_interval(lo as nat, n as nat)) by {
+                    assert forall |v: nat| #[trigger] self@.resources[se.src()].range().contains(v) implies
+                        allocated_vals(self@, space as nat).contains(v) by {}
+                }
+            }
+        }
+        b
+    }
+
+    /// Whether every Resource allocated from [space] lies outside of [vals], or within them when
+    /// [within] is set
+    fn allocations_against(&self, space: u64, vals: &ValSet, within: bool) -> (b: bool)
+        requires
+            self.wf(),
+            vals.wf(),
+        ensures
+            b ==> forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == space as nat ==>
+                if within { self@.resources[se.src()].range().subset_of(vals@) } else { vals@.disjoint(self@.resources[se.src()].range()) },
+    {
+        let ai = match vals_entry_index(&self.allocated, space, Ghost(allocation_index(self@))) {
+            Some(ai) => ai,
+            None => return true,
+        };
+        let allocated = &self.allocated[ai].vals;
+        let b = if within { allocated.subset_of_probing(vals) } else { allocated.disjoint_probing(vals) };
+        proof {
+            if (b) {
+                assert forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == space as nat implies
+                    if within { self@.resources[se.src()].range().subset_of(vals@) } else { vals@.disjoint(self@.resources[se.src()].range()) } by {
+                    assert forall |v: nat| #[trigger] self@.resources[se.src()].range().contains(v) implies
+                        allocated_vals(self@, space as nat).contains(v) by {}
+                }
+            }
+        }
+        b
+    }
+
+    /// Whether no Resource is allocated from [space]
+    fn unallocated(&self, space: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) ==> se.dst() != space as nat,
+    {
+        let mut i: usize = 0;
+        while i < self.subsets.len()
+            invariant
+                self.wf(),
+                i <= self.subsets.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.subsets[j].dst != space,
+            decreases self.subsets.len() - i
+        {
+            if self.subsets[i].dst == space {
+                proof {
+                    lemma_mirrored(self.subsets@, self@.subsets, i as int);
+                }
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) implies se.dst() != space as nat by {
+                let j = choose |j: int| 0 <= j < self.subsets.len() && #[trigger] self.subsets@[j]@ == se;
+                assert(self.subsets[j]@ == se);
+            }
+        }
+        true
+    }
+
+    /// Whether the spaces carved out of [space], but [except], lie outside of the [n] vals from
+    /// [lo], or of [vals] if given, which they must be when [except] is
+    fn children_avoid(&self, space: u64, lo: u64, n: u64, vals: Option<&ValSet>, except: Option<u64>) -> (b: bool)
+        requires
+            self.wf(),
+            vals is Some ==> vals->Some_0.wf(),
+            except is Some ==> vals is Some,
+            except is Some ==> self@.partitions.contains(PartitionEdge { src: except->Some_0 as nat, dst: space as nat }),
+        ensures
+            b ==> forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) && pe.dst() == space as nat
+                && (except is Some ==> pe.src() != except->Some_0 as nat) ==>
+                self@.spaces[pe.src()].vals().disjoint(match vals {
+                    Some(v) => v@,
+                    None => nat_interval(lo as nat, n as nat),
+                }),
+    {
+        let ghost s = self@;
+        let ghost avoided = match vals {
+            Some(v) => v@,
+            None => nat_interval(lo as nat, n as nat),
+        };
+        let ci = match vals_entry_index(&self.carved, space, Ghost(carving_index(s))) {
+            Some(ci) => ci,
+            None => return true,
+        };
+        let carved = &self.carved[ci].vals;
+        // The siblings of [except] never share its vals, so its own may be left out of the check
+        let ghost others = match except {
+            Some(e) => carved@.difference(s.spaces[e as nat].vals()),
+            None => carved@,
+        };
+        let b = match (except, vals) {
+            // The carved vals may only meet [vals] within those of [except]
+            (Some(e), Some(v)) => {
+                let ei = match self.space_index(e) {
+                    Some(ei) => ei,
+                    None => return false,
+                };
+                carved.disjoint_outside(v, &self.spaces[ei].space.vals)
+            },
+            (None, Some(v)) => carved.disjoint_probing(v),
+            (None, None) => carved.disjoint_from_range(lo, n),
+            (Some(_), None) => vstd::pervasive::unreached(),
+        };
+        proof {
+            if (b) {
+                assert forall |v: nat| #[trigger] others.contains(v) implies !avoided.contains(v) by {
+                    if (except is Some && avoided.contains(v)) {
+                        assert(avoided.difference(s.spaces[except->Some_0 as nat].vals()).contains(v));
+                    }
+                }
+                assert forall |pe: PartitionEdge| #[trigger] s.partitions.contains(pe) && pe.dst() == space as nat
+                    && (except is Some ==> pe.src() != except->Some_0 as nat) implies
+                    s.spaces[pe.src()].vals().disjoint(avoided) by {
+                    assert forall |v: nat| #[trigger] s.spaces[pe.src()].vals().contains(v) implies others.contains(v) by {
+                        assert(carved_vals(s, space as nat).contains(v));
+                        if (except is Some) {
+                            let sib = PartitionEdge { src: except->Some_0 as nat, dst: space as nat };
+                            // Invariant: partition_siblings_disjoint
+                            assert(s.partitions.contains(sib));
+                            assert(s.spaces[pe.src()].vals().disjoint(s.spaces[sib.src()].vals()));
+                        }
+                    }
+                }
+            }
+        }
+        b
+    }
+
+    /// Whether the spaces carved out of [space] lie within [vals]
+    fn children_within(&self, space: u64, vals: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            vals.wf(),
+        ensures
+            b ==> forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) && pe.dst() == space as nat ==>
+                self@.spaces[pe.src()].vals().subset_of(vals@),
+    {
+        let ci = match vals_entry_index(&self.carved, space, Ghost(carving_index(self@))) {
+            Some(ci) => ci,
+            None => return true,
+        };
+        let b = self.carved[ci].vals.subset_of_probing(vals);
+        proof {
+            if (b) {
+                assert forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) && pe.dst() == space as nat implies
+                    self@.spaces[pe.src()].vals().subset_of(vals@) by {
+                    assert forall |v: nat| #[trigger] self@.spaces[pe.src()].vals().contains(v) implies
+                        carved_vals(self@, space as nat).contains(v) by {}
+                }
+            }
+        }
+        b
+    }
+
+    /// The space [space] was carved out of, if it was carved out of one
+    fn parent_space(&self, space: u64) -> (r: Option<u64>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(parent) => self@.partitions.contains(PartitionEdge { src: space as nat, dst: parent as nat }),
+                None => forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) ==> pe.src() != space as nat,
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.partitions.len()
+            invariant
+                self.wf(),
+                i <= self.partitions.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.partitions[j].src != space,
+            decreases self.partitions.len() - i
+        {
+            if self.partitions[i].src == space {
+                proof {
+                    lemma_mirrored(self.partitions@, self@.partitions, i as int);
+                }
+                return Some(self.partitions[i].dst);
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) implies pe.src() != space as nat by {
+                let j = choose |j: int| 0 <= j < self.partitions.len() && #[trigger] self.partitions@[j]@ == pe;
+                assert(self.partitions[j]@ == pe);
+            }
+        }
+        None
+    }
+
+    /// Whether [vals] avoid the slots admitted to every reservation on [core], but [except]
+    fn admits(&self, core: u64, vals: &ValSet, except: Option<u64>) -> (b: bool)
+        requires
+            self.wf(),
+            vals.wf(),
+        ensures
+            b ==> forall |s: SpaceId| #[trigger] self@.admitted(core as nat).contains(s)
+                && (except is Some ==> s != except->Some_0 as nat) ==> self@.spaces[s].vals().disjoint(vals@),
+    {
+        let mut i: usize = 0;
+        while i < self.spaces.len()
+            invariant
+                self.wf(),
+                vals.wf(),
+                i <= self.spaces.len(),
+                forall |j: int| 0 <= j < i && #[trigger] self@.admitted(core as nat).contains(self.spaces@[j]@.0)
+                    && (except is Some ==> self.spaces@[j]@.0 != except->Some_0 as nat) ==> self.spaces@[j]@.1.vals().disjoint(vals@),
+            decreases self.spaces.len() - i
+        {
+            let e = &self.spaces[i];
+            let skipped = match except {
+                Some(x) => e.id == x,
+                None => false,
+            };
+            proof {
+                lemma_mirrored_key(self.spaces@, self@.spaces, i as int);
+            }
+            if !skipped && e.space.rtype.eq(ExecResourceType::Time(core)) && self.parent_space(e.id).is_none()
+                && !e.space.vals.disjoint_probing(vals) {
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |s: SpaceId| #[trigger] self@.admitted(core as nat).contains(s)
+                && (except is Some ==> s != except->Some_0 as nat) implies self@.spaces[s].vals().disjoint(vals@) by {
+                let j = choose |j: int| 0 <= j < self.spaces.len() && #[trigger] self.spaces@[j]@.0 == s;
+                lemma_mirrored_key(self.spaces@, self@.spaces, j);
+            }
+        }
+        true
+    }
+
+    /// Whether [space] is neither carved out of another space nor has children
+    fn unpartitioned(&self, space: u64, as_parent_only: bool) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) ==>
+                pe.dst() != space as nat && (as_parent_only || pe.src() != space as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.partitions.len()
+            invariant
+                self.wf(),
+                i <= self.partitions.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.partitions[j].dst != space && (as_parent_only || self.partitions[j].src != space),
+            decreases self.partitions.len() - i
+        {
+            let pe = self.partitions[i];
+            if pe.dst == space || (!as_parent_only && pe.src == space) {
+                proof {
+                    lemma_mirrored(self.partitions@, self@.partitions, i as int);
+                }
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) implies
+                pe.dst() != space as nat && (as_parent_only || pe.src() != space as nat) by {
+                let j = choose |j: int| 0 <= j < self.partitions.len() && #[trigger] self.partitions@[j]@ == pe;
+                assert(self.partitions[j]@ == pe);
+            }
+        }
+        true
+    }
+
+    /// The index of the entry of the vals taken by the Resources of the physical [rtype], which is
+    /// the rank of its kind
+    fn used_index(&self, rtype: ExecResourceType) -> (r: usize)
+        requires
+            self.wf(),
+            rtype@ is Physical,
+        ensures
+            r < self.used.len() && self.used[r as int]@.0 == rtype@,
+    {
+        match rtype {
+            ExecResourceType::Physical(k) => {
+                let i = k.rank();
+                proof {
+                    lemma_kind_rank_injective(self.used[i as int]@.0->Physical_0, k@);
+                }
+                i
+            },
+            _ => vstd::pervasive::unreached(),
+        }
+    }
+
+    /// Whether the physical Resources of [rtype] all lie outside the [n] vals from [lo]
+    fn physical_range_free(&self, rtype: ExecResourceType, lo: u64, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+            rtype@ is Physical,
+        ensures
+            b ==> forall |r: ResourceId| #[trigger] self@.resources.contains_key(r) && self@.resources[r].rtype() == rtype@ ==>
+                self@.resources[r].range().disjoint(nat_interval(lo as nat, n as nat)),
+    {
+        let ui = self.used_index(rtype);
+        let b = self.used[ui].vals.disjoint_from_range(lo, n);
+        proof {
+            if (b) {
+                assert forall |r: ResourceId| #[trigger] self@.resources.contains_key(r) && self@.resources[r].rtype() == rtype@ implies
+                    self@.resources[r].range().disjoint(nat_interval(lo as nat, n as nat)) by {
+                    assert forall |v: nat| #[trigger] self@.resources[r].range().contains(v) implies
+                        used_vals(self@, rtype@).contains(v) by {}
+                }
+            }
+        }
+        b
+    }
+
+    /// Whether the SpaceMap [me] translates its vals into the space it is mapped onto, with the
+    /// vals of [over] replaced by [vals] if given
+    fn space_map_fits(&self, me: &ExecMapEdge, over: Option<(u64, &ExecSpace)>) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+            me@ is SpaceMap,
+            over is Some ==> over->Some_0.1.wf(),
+        ensures
+            b ==> ({
+                let spaces = match over {
+                    Some((id, sp)) => self@.spaces.insert(id as nat, sp@),
+                    None => self@.spaces,
+                };
+                me@.well_formed(self@.resources, spaces)
+            }),
+    {
+        match me {
+            ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } => {
+                let src = match over {
+                    Some((id, sp)) if id == *sm_src => Some(sp),
+                    _ => match self.space_index(*sm_src) {
+                        Some(i) => Some(&self.spaces[i].space),
+                        None => None,
+                    },
+                };
+                let dst = match over {
+                    Some((id, sp)) if id == *sm_dst => Some(sp),
+                    _ => match self.space_index(*sm_dst) {
+                        Some(i) => Some(&self.spaces[i].space),
+                        None => None,
+                    },
+                };
+                match (src, dst) {
+                    (Some(src), Some(dst)) => {
+                        let b = src.rtype.may_map_onto(dst.rtype) && sm_vals.subset_of(&src.vals)
+                            && sm_vals.translates_into(sm_attrs.offset, &dst.vals);
+                        b
+                    },
+                    _ => false,
+                }
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether [me] is well formed, with the ResourceSpace [over] replaced if given
+    fn map_well_formed(&self, me: &ExecMapEdge, over: Option<(u64, &ExecSpace)>) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+            over is Some ==> over->Some_0.1.wf(),
+        ensures
+            b ==> ({
+                let spaces = match over {
+                    Some((id, sp)) => self@.spaces.insert(id as nat, sp@),
+                    None => self@.spaces,
+                };
+                me@.well_formed(self@.resources, spaces)
+            }),
+    {
+        match me {
+            ExecMapEdge::SpaceBacking { sb_src, sb_dst } => {
+                let src = match over {
+                    Some((id, sp)) if id == *sb_src => Some(sp.rtype),
+                    _ => match self.space_index(*sb_src) {
+                        Some(i) => Some(self.spaces[i].space.rtype),
+                        None => None,
+                    },
+                };
+                match (src, self.resource(*sb_dst)) {
+                    (Some(rtype), Some(r)) => rtype.may_map_onto(r.rtype),
+                    _ => false,
+                }
+            },
+            ExecMapEdge::SpaceMap { .. } => self.space_map_fits(me, over),
+            ExecMapEdge::ResourceMap { rm_src, rm_dst, rm_attrs } => {
+                match (self.resource(*rm_src), self.resource(*rm_dst)) {
+                    (Some(src), Some(dst)) => {
+                        src.rtype.may_map_onto(dst.rtype) && src.val as i128 + rm_attrs.offset as i128 == dst.val as i128
+                    },
+                    _ => false,
+                }
+            },
+            ExecMapEdge::Delivery { dl_src, .. } => {
+                match self.resource(*dl_src) {
+                    Some(line) => line.rtype.delivered(),
+                    None => false,
+                }
+            },
+        }
+    }
+
+    /// Whether every mapping is well formed once the ResourceSpace [id] is replaced by [sp]
+    fn maps_well_formed_over(&self, id: u64, sp: &ExecSpace) -> (b: bool)
+        requires
+            self.wf(),
+            sp.wf(),
+        ensures
+            b ==> forall |me: MapEdge| #[trigger] self@.maps.contains(me) ==>
+                me.well_formed(self@.resources, self@.spaces.insert(id as nat, sp@)),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                sp.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> (#[trigger] self.maps[j]@).well_formed(self@.resources, self@.spaces.insert(id as nat, sp@)),
+            decreases self.maps.len() - i
+        {
+            if !self.map_well_formed(&self.maps[i], Some((id, sp))) {
+                return false;
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |me: MapEdge| #[trigger] self@.maps.contains(me) implies
+                me.well_formed(self@.resources, self@.spaces.insert(id as nat, sp@)) by {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        true
+    }
+
+    /// Whether no other SpaceMap translates any of the vals the SpaceMap [me] translates
+    fn translates_disjointly(&self, me: &ExecMapEdge) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+            me@ is SpaceMap,
+        ensures
+            b ==> self@.translates_disjointly(me@),
+    {
+        match me {
+            ExecMapEdge::SpaceMap { sm_src, sm_vals, .. } => {
+                let mut i: usize = 0;
+                while i < self.maps.len()
+                    invariant
+                        self.wf(),
+                        me.wf(),
+                        me@ is SpaceMap,
+                        me@->sm_src == *sm_src as nat,
+                        me@->sm_vals == sm_vals@,
+                        sm_vals.wf(),
+                        i <= self.maps.len(),
+                        forall |j: int| 0 <= j < i && (#[trigger] self.maps[j]@) is SpaceMap && self.maps[j]@->sm_src == me@->sm_src
+                            && self.maps[j]@ != me@ ==> self.maps[j]@->sm_vals.disjoint(me@->sm_vals),
+                    decreases self.maps.len() - i
+                {
+                    match &self.maps[i] {
+                        ExecMapEdge::SpaceMap { sm_src: src, sm_vals: vals, .. } => {
+                            if *src == *sm_src && !self.maps[i].eq(me) && !vals.disjoint(sm_vals) {
+                                return false;
+                            }
+                        },
+                        _ => {},
+                    }
+                    i = i + 1;
+                }
+                proof {
+                    assert forall |m: MapEdge| #[trigger] self@.maps.contains(m) && m is SpaceMap && m->sm_src == me@->sm_src && m != me@
+                        implies m->sm_vals.disjoint(me@->sm_vals) by {
+                        let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == m;
+                        assert(self.maps[j]@ == m);
+                    }
+                }
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether SpaceMaps fan out of [space]
+    fn fans_out(&self, space: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.fans_out(space as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int| 0 <= j < i ==> !((#[trigger] self.maps[j]@) is SpaceMap && self.maps[j]@->sm_src == space as nat),
+            decreases self.maps.len() - i
+        {
+            match &self.maps[i] {
+                ExecMapEdge::SpaceMap { sm_src, .. } => {
+                    if *sm_src == space {
+                        proof {
+                            lemma_mirrored(self.maps@, self@.maps, i as int);
+                        }
+                        return true;
+                    }
+                },
+                _ => {},
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.fans_out(space as nat)) {
+                let me = choose |me: MapEdge| #[trigger] self@.maps.contains(me) && me is SpaceMap && me->sm_src == space as nat;
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == me;
+                assert(self.maps[j]@ == me);
+            }
+        }
+        false
+    }
+
+    /// Whether the SpaceMap [me] translates every val of its src which no SpaceMap fanning out of
+    /// it translates yet
+    fn completes_fan_out(&self, me: &ExecMapEdge) -> (b: bool)
+        requires
+            self.wf(),
+            me.wf(),
+            me@ is SpaceMap,
+        ensures
+            b ==> self@.completes_fan_out(me@),
+    {
+        match me {
+            ExecMapEdge::SpaceMap { sm_src, sm_vals, .. } => {
+                let si = match self.space_index(*sm_src) {
+                    Some(si) => si,
+                    None => return false,
+                };
+                // The vals translated between me and the SpaceMaps fanning out of its src already
+                let mut covered = sm_vals.copy();
+                let mut i: usize = 0;
+                while i < self.maps.len()
+                    invariant
+                        self.wf(),
+                        me.wf(),
+                        me@ is SpaceMap,
+                        me@->sm_src == *sm_src as nat,
+                        me@->sm_vals == sm_vals@,
+                        covered.wf(),
+                        i <= self.maps.len(),
+                        forall |w: nat| #[trigger] covered@.contains(w) ==> me@->sm_vals.contains(w)
+                            || exists |m: MapEdge| #[trigger] self@.maps.contains(m) && m.covers(me@->sm_src, w),
+                    decreases self.maps.len() - i
+                {
+                    match &self.maps[i] {
+                        ExecMapEdge::SpaceMap { sm_src: src, sm_vals: vals, .. } => {
+                            if *src == *sm_src {
+                                proof {
+                                    assert(self.maps[i as int].wf());
+                                    lemma_mirrored(self.maps@, self@.maps, i as int);
+                                }
+                                let ghost prev = covered@;
+                                covered = covered.union(vals);
+                                proof {
+                                    assert forall |w: nat| #[trigger] covered@.contains(w) implies me@->sm_vals.contains(w)
+                                        || exists |m: MapEdge| #[trigger] self@.maps.contains(m) && m.covers(me@->sm_src, w) by {
+                                        if (!prev.contains(w)) {
+                                            assert(self.maps[i as int]@.covers(me@->sm_src, w));
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        _ => {},
+                    }
+                    i = i + 1;
+                }
+                self.spaces[si].space.vals.subset_of(&covered)
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether no SpaceMap onto [space] translates any val into the [n] vals from [lo]
+    fn translates_clear_of(&self, space: u64, lo: u64, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b ==> forall |m: MapEdge, w: nat| #[trigger] self@.maps.contains(m) && m is SpaceMap && m->sm_dst == space as nat
+                && #[trigger] m->sm_vals.contains(w) && 0 <= m->sm_attrs.translation.apply(w)
+                ==> !nat_interval(lo as nat, n as nat).contains(m->sm_attrs.translation.apply(w) as nat),
+    {
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                i <= self.maps.len(),
+                forall |j: int, w: nat| 0 <= j < i && (#[trigger] self.maps[j]@) is SpaceMap && self.maps[j]@->sm_dst == space as nat
+                    && #[trigger] self.maps[j]@->sm_vals.contains(w) && 0 <= self.maps[j]@->sm_attrs.translation.apply(w)
+                    ==> !nat_interval(lo as nat, n as nat).contains(self.maps[j]@->sm_attrs.translation.apply(w) as nat),
+            decreases self.maps.len() - i
+        {
+            match &self.maps[i] {
+                ExecMapEdge::SpaceMap { sm_dst, sm_vals, sm_attrs, .. } => {
+                    if *sm_dst == space {
+                        // The vals translated into the range are those of the range moved back by
+                        // the offset, which must fit in a machine word to be looked up
+                        let start = lo as i128 - sm_attrs.offset as i128;
+                        if start < 0 || start > u64::MAX as i128 {
+                            return false;
+                        }
+                        if !sm_vals.disjoint_from_range(start as u64, n) {
+                            return false;
+                        }
+                        proof {
+                            assert(self.maps[i as int].wf());
+                            assert forall |w: nat| #[trigger] sm_vals@.contains(w) && 0 <= w + sm_attrs.offset implies
+                                !nat_interval(lo as nat, n as nat).contains((w + sm_attrs.offset) as nat) by {
+                                if (nat_interval(lo as nat, n as nat).contains((w + sm_attrs.offset) as nat)) {
+                                    assert(nat_interval(start as nat, n as nat).contains(w));
+                                }
+                            }
+                        }
+                    }
+                },
+                _ => {},
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |m: MapEdge, w: nat| #[trigger] self@.maps.contains(m) && m is SpaceMap && m->sm_dst == space as nat
+                && #[trigger] m->sm_vals.contains(w) && 0 <= m->sm_attrs.translation.apply(w)
+                implies !nat_interval(lo as nat, n as nat).contains(m->sm_attrs.translation.apply(w) as nat) by {
+                let j = choose |j: int| 0 <= j < self.maps.len() && #[trigger] self.maps@[j]@ == m;
+                assert(self.maps[j]@ == m);
+            }
+        }
+        true
+    }
+
+    /// The cost of the metadata needed to manage [n] vals of [rtype], if it fits in a machine
+    /// word
+    fn metadata_cost(&self, rtype: ExecResourceType, n: u128, Ghost(vals): Ghost<Set<nat>>) -> (c: Option<u64>)
+        requires
+            self.wf(),
+            vals.finite(),
+            vals.len() == n,
+        ensures
+            c is Some ==> c->Some_0 as nat == self@.metadata_cost(rtype@, vals),
+    {
+        let mut i: usize = 0;
+        while i < self.metadata_costs.len()
+            invariant
+                self.wf(),
+                i <= self.metadata_costs.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.metadata_costs[j]@.0 != rtype@,
+            decreases self.metadata_costs.len() - i
+        {
+            let e = self.metadata_costs[i];
+            if e.rtype.eq(rtype) {
+                proof {
+                    lemma_mirrored_key(self.metadata_costs@, self@.metadata_costs, i as int);
+                }
+                if n > u64::MAX as u128 || e.cost as u128 * n > u64::MAX as u128 {
+                    return None;
+                }
+                return Some(e.cost * n as u64);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.metadata_costs.contains_key(rtype@)) {
+                let j = choose |j: int| 0 <= j < self.metadata_costs.len() && #[trigger] self.metadata_costs@[j]@.0 == rtype@;
+                assert(self.metadata_costs[j]@.0 == rtype@);
+            }
+        }
+        Some(0)
+    }
+
+    /// The total size of the Resources [meta], if they all exist and it fits in a machine word
+    fn metadata_size(&self, meta: &ValSet) -> (c: Option<u64>)
+        requires
+            self.wf(),
+            meta.wf(),
+        ensures
+            c is Some ==> c->Some_0 as nat == set_sum(meta@, resource_size(self@.resources)),
+    {
+        let ghost size = resource_size(self@.resources);
+        let ids = meta.to_vec();
+        let mut total: u64 = 0;
+        let mut i: usize = 0;
+        while i < ids.len()
+            invariant
+                self.wf(),
+                ids@.no_duplicates(),
+                i <= ids.len(),
+                size == resource_size(self@.resources),
+                total as nat == set_sum(prefix_vals(ids@, i as int), size),
+            decreases ids.len() - i
+        {
+            proof {
+                lemma_prefix_vals_len(ids@, i as int);
+                assert(prefix_vals(ids@, i + 1) =~= prefix_vals(ids@, i as int).insert(ids[i as int] as nat));
+                assert(!prefix_vals(ids@, i as int).contains(ids[i as int] as nat));
+                lemma_set_sum_insert(prefix_vals(ids@, i as int), ids[i as int] as nat, size);
+            }
+            match self.resource(ids[i]) {
+                Some(r) => {
+                    if total as u128 + r.size as u128 > u64::MAX as u128 {
+                        return None;
+                    }
+                    total = total + r.size;
+                },
+                None => return None,
+            }
+            i = i + 1;
+        }
+        proof {
+            assert(prefix_vals(ids@, ids.len() as int) =~= meta@);
+        }
+        Some(total)
+    }
+}
+
+/// The index of the entry for [key] in [v], if there is one
+fn nat_entry_index(v: &Vec<NatEntry>, key: u64, Ghost(m): Ghost<Map<nat, nat>>) -> (r: Option<usize>)
+    requires
+        mirrors_map(v@, m),
+    ensures
+        match r {
+            Some(i) => i < v.len() && v[i as int].key == key && m.contains_key(key as nat) && m[key as nat] == v[i as int].val as nat,
+            None => !m.contains_key(key as nat),
+        },
+{
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            mirrors_map(v@, m),
+            i <= v.len(),
+            forall |j: int| 0 <= j < i ==> #[trigger] v[j].key != key,
+        decreases v.len() - i
+    {
+        if v[i].key == key {
+            proof {
+                lemma_mirrored_key(v@, m, i as int);
+            }
+            return Some(i);
+        }
+        i = i + 1;
+    }
+    proof {
+        if (m.contains_key(key as nat)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v@[j]@.0 == key as nat;
+            assert(v[j].key == key);
+        }
+    }
+    None
+}
+
+/// The index of the entry for [space] in [v], if there is one
+fn vals_entry_index(v: &Vec<ValsEntry>, space: u64, Ghost(m): Ghost<Map<SpaceId, Set<nat>>>) -> (r: Option<usize>)
+    requires
+        mirrors_map(v@, m),
+        keys_sorted(v@),
+    ensures
+        match r {
+            Some(i) => i < v.len() && v[i as int].space == space && m.contains_key(space as nat) && m[space as nat] == v[i as int].vals@,
+            None => !m.contains_key(space as nat),
+        },
+{
+    sorted_index(v, space, Ghost(m))
+}
+
+/// Sets the entry of the index [v] for the space of [e], which is at [index] if there is one
+/// already, or else where the id of the space keeps the index sorted
+fn upsert_vals(v: &mut Vec<ValsEntry>, index: Option<usize>, e: ValsEntry, Ghost(m): Ghost<Map<SpaceId, Set<nat>>>)
+    requires
+        mirrors_map(old(v)@, m),
+        keys_sorted(old(v)@),
+        forall |i: int| 0 <= i < old(v).len() ==> #[trigger] old(v)[i].vals.wf(),
+        e.vals.wf(),
+        match index {
+            Some(i) => i < old(v).len() && old(v)[i as int]@.0 == e@.0,
+            None => !m.contains_key(e@.0),
+        },
+    ensures
+        mirrors_map(v@, m.insert(e@.0, e@.1)),
+        keys_sorted(v@),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] v[i].vals.wf(),
+{
+    let ghost before = v@;
+    match index {
+        Some(i) => {
+            proof {
+                lemma_keys_sorted_update(before, i as int, e);
+            }
+            upsert(v, index, e, Ghost(m));
+            proof {
+                assert forall |j: int| 0 <= j < v.len() implies #[trigger] v[j].vals.wf() by {
+                    if (j != i) {
+                        assert(v[j] == before[j]);
+                    }
+                }
+            }
+        },
+        None => {
+            let i = insert_sorted(v, e, Ghost(m));
+            proof {
+                assert forall |j: int| 0 <= j < v.len() implies #[trigger] v[j].vals.wf() by {
+                    if (j < i) {
+                        assert(v[j] == before[j]);
+                    } else if (j > i) {
+                        assert(v[j] == before[j - 1]);
+                    }
+                }
+            }
+        },
+    }
+}
+
+/// Removes the entry of the index [v] for [space], which is at [index]
+fn remove_vals(v: &mut Vec<ValsEntry>, index: usize, Ghost(m): Ghost<Map<SpaceId, Set<nat>>>, Ghost(space): Ghost<SpaceId>)
+    requires
+        mirrors_map(old(v)@, m),
+        keys_sorted(old(v)@),
+        forall |i: int| 0 <= i < old(v).len() ==> #[trigger] old(v)[i].vals.wf(),
+        index < old(v).len() && old(v)[index as int]@.0 == space,
+    ensures
+        mirrors_map(v@, m.remove(space)),
+        keys_sorted(v@),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] v[i].vals.wf(),
+{
+    let ghost before = v@;
+    remove_entry(v, Some(index), Ghost(m), Ghost(space));
+    proof {
+        lemma_keys_sorted_remove(before, index as int);
+        assert forall |i: int| 0 <= i < v.len() implies #[trigger] v[i].vals.wf() by {
+            if (i < index) {
+                assert(v[i] == before[i]);
+            } else {
+                assert(v[i] == before[i + 1]);
+            }
+        }
+    }
+}
+
+// Lookups:
+
+impl OsmosisGraph {
+    /// Whether [p] holds anything
+    fn holds_anything(&self, p: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == exists |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p),
+    {
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                i <= self.holds.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.holds[j].src != p,
+            decreases self.holds.len() - i
+        {
+            if self.holds[i].src == p {
+                proof {
+                    lemma_mirrored(self.holds@, self@.holds, i as int);
+                    assert(self.holds[i as int]@.src() == pd_of(p));
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (exists |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p)) {
+                let he = choose |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == pd_of(p);
+                let j = choose |j: int| 0 <= j < self.holds.len() && #[trigger] self.holds@[j]@ == he;
+                assert(self.holds[j]@ == he);
+            }
+        }
+        false
+    }
+
+    /// Whether a RequestEdge is from or towards [p]
+    fn in_requests(&self, p: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == exists |re: RequestEdge<StandardKind>| #[trigger] self@.requests.contains(re) && (re.src() == pd_of(p) || re.dst() == pd_of(p)),
+    {
+        let mut i: usize = 0;
+        while i < self.requests.len()
+            invariant
+                self.wf(),
+                i <= self.requests.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.requests[j].src != p && self.requests[j].dst != p,
+            decreases self.requests.len() - i
+        {
+            if self.requests[i].src == p || self.requests[i].dst == p {
+                proof {
+                    lemma_mirrored(self.requests@, self@.requests, i as int);
+                    assert(self.requests[i as int]@.src() == pd_of(p) || self.requests[i as int]@.dst() == pd_of(p));
+                }
+                return true;
+            }
+            i = i + 1;
+        }
+        proof {
+            if (exists |re: RequestEdge<StandardKind>| #[trigger] self@.requests.contains(re) && (re.src() == pd_of(p) || re.dst() == pd_of(p))) {
+                let re = choose |re: RequestEdge<StandardKind>| #[trigger] self@.requests.contains(re) && (re.src() == pd_of(p) || re.dst() == pd_of(p));
+                let j = choose |j: int| 0 <= j < self.requests.len() && #[trigger] self.requests@[j]@ == re;
+                assert(self.requests[j]@ == re);
+            }
+        }
+        false
+    }
+
+    /// The only SubsetEdge the Resource [res] was allocated through, which is the one
+    /// destroy_resource chooses
+    fn subset_edge_of(&self, res: u64) -> (r: Result<ExecSubsetEdge, StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> self@.subsets.contains(r->Ok_0@) && r->Ok_0@.src() == res as nat
+                && r->Ok_0@ == choose |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.src() == res as nat,
+    {
+        let mut found: Option<ExecSubsetEdge> = None;
+        let mut i: usize = 0;
+        while i < self.subsets.len()
+            invariant
+                self.wf(),
+                i <= self.subsets.len(),
+                match found {
+                    Some(se) => self@.subsets.contains(se@) && se.src == res
+                        && forall |j: int| 0 <= j < i && #[trigger] self.subsets[j].src == res ==> self.subsets[j]@ == se@,
+                    None => forall |j: int| 0 <= j < i ==> #[trigger] self.subsets[j].src != res,
+                },
+            decreases self.subsets.len() - i
+        {
+            let se = self.subsets[i];
+            if se.src == res {
+                proof {
+                    lemma_mirrored(self.subsets@, self@.subsets, i as int);
+                }
+                match found {
+                    Some(first) => {
+                        if first.dst != se.dst {
+                            return Err(StepError::Unchecked);
+                        }
+                    },
+                    None => {
+                        found = Some(se);
+                    },
+                }
+            }
+            i = i + 1;
+        }
+        match found {
+            Some(se) => {
+                proof {
+                    let chosen = choose |c: SubsetEdge| #[trigger] self@.subsets.contains(c) && c.src() == res as nat;
+                    assert(self@.subsets.contains(se@) && se@.src() == res as nat);
+                    let j = choose |j: int| 0 <= j < self.subsets.len() && #[trigger] self.subsets@[j]@ == chosen;
+                    assert(self.subsets[j]@ == chosen);
+                }
+                Ok(se)
+            },
+            None => Err(StepError::Unchecked),
+        }
+    }
+
+    /// The index of the Resource [id] in the resources, if it exists
+    fn resource_index(&self, id: u64) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => i < self.resources.len() && self.resources[i as int].id == id,
+                None => !self@.resources.contains_key(id as nat),
+            },
+    {
+        sorted_index(&self.resources, id, Ghost(self@.resources))
+    }
+
+    /// The index of the lease of [he], if it was lent
+    fn lease_index(&self, he: &ExecHoldEdge) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => i < self.leases.len() && self.leases[i as int]@.0 == he@
+                    && self@.leases.contains_key(he@) && self@.leases[he@] == self.leases[i as int]@.1,
+                None => !self@.leases.contains_key(he@),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.leases.len()
+            invariant
+                self.wf(),
+                i <= self.leases.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.leases[j]@.0 != he@,
+            decreases self.leases.len() - i
+        {
+            if self.leases[i].he.eq(he) {
+                proof {
+                    lemma_mirrored_key(self.leases@, self@.leases, i as int);
+                }
+                return Some(i);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.leases.contains_key(he@)) {
+                let j = choose |j: int| 0 <= j < self.leases.len() && #[trigger] self.leases@[j]@.0 == he@;
+                assert(self.leases[j]@.0 == he@);
+            }
+        }
+        None
+    }
+
+    /// The index of the metadata charged for [space], if there is any
+    fn metadata_index(&self, space: u64) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => i < self.metadata.len() && self.metadata[i as int].space == space
+                    && self.metadata[i as int].meta.wf()
+                    && self@.metadata.contains_key(space as nat) && self@.metadata[space as nat] == self.metadata[i as int].meta@,
+                None => !self@.metadata.contains_key(space as nat),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.metadata.len()
+            invariant
+                self.wf(),
+                i <= self.metadata.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.metadata[j].space != space,
+            decreases self.metadata.len() - i
+        {
+            if self.metadata[i].space == space {
+                proof {
+                    lemma_mirrored_key(self.metadata@, self@.metadata, i as int);
+                }
+                return Some(i);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.metadata.contains_key(space as nat)) {
+                let j = choose |j: int| 0 <= j < self.metadata.len() && #[trigger] self.metadata@[j]@.0 == space as nat;
+                assert(self.metadata[j].space == space);
+            }
+        }
+        None
+    }
+
+    /// The index of the quota of [p] for [rtype], if it was ever set
+    fn quota_index(&self, p: u64, rtype: ExecResourceType) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => i < self.quotas.len() && self.quotas[i as int]@.0 == (pd_of(p), rtype@),
+                None => !self@.quotas.contains_key((pd_of(p), rtype@)),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.quotas.len()
+            invariant
+                self.wf(),
+                i <= self.quotas.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.quotas[j]@.0 != (pd_of(p), rtype@),
+            decreases self.quotas.len() - i
+        {
+            if self.quotas[i].pd == p && self.quotas[i].rtype.eq(rtype) {
+                return Some(i);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.quotas.contains_key((pd_of(p), rtype@))) {
+                let j = choose |j: int| 0 <= j < self.quotas.len() && #[trigger] self.quotas@[j]@.0 == (pd_of(p), rtype@);
+                assert(self.quotas[j]@.0 == (pd_of(p), rtype@));
+            }
+        }
+        None
+    }
+
+    /// The index of the entry recording the parent of [p], if it has one
+    fn parent_index(&self, p: u64) -> (r: Option<usize>)
+        requires
+            self.wf(),
+        ensures
+            match r {
+                Some(i) => i < self.parents.len() && self.parents[i as int]@.0 == pd_of(p),
+                None => !self@.parents.contains_key(pd_of(p)),
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.parents.len()
+            invariant
+                self.wf(),
+                i <= self.parents.len(),
+                forall |j: int| 0 <= j < i ==> #[trigger] self.parents[j]@.0 != pd_of(p),
+            decreases self.parents.len() - i
+        {
+            if self.parents[i].pd == p {
+                return Some(i);
+            }
+            i = i + 1;
+        }
+        proof {
+            if (self@.parents.contains_key(pd_of(p))) {
+                let j = choose |j: int| 0 <= j < self.parents.len() && #[trigger] self.parents@[j]@.0 == pd_of(p);
+                assert(self.parents[j]@.0 == pd_of(p));
+            }
+        }
+        None
+    }
+
+    /// The ResourceMap of the Resource [src] onto the Resource [dst], if both exist and the
+    /// offset between them fits in a machine word
+    fn resource_mapping(&self, src: u64, dst: u64, perms: Rights, cache: ExecCache, cow: bool) -> (me: Option<ExecMapEdge>)
+        requires
+            self.wf(),
+        ensures
+            me is Some ==> {
+                &&& me->Some_0.wf()
+                &&& self@.resources.contains_key(src as nat)
+                &&& self@.resources.contains_key(dst as nat)
+                &&& me->Some_0@ == self@.resource_mapping(src as nat, dst as nat, perms@, cache@, cow)
+            },
+    {
+        match (self.resource(src), self.resource(dst)) {
+            (Some(s), Some(d)) => {
+                let offset = d.val as i128 - s.val as i128;
+                if offset < i64::MIN as i128 || offset > i64::MAX as i128 {
+                    return None;
+                }
+                let rm_attrs = ExecMapAttributes { perms, cache, offset: offset as i64, cow, lazy: false };
+                Some(ExecMapEdge::ResourceMap { rm_src: src, rm_dst: dst, rm_attrs })
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether [vals] respects the partitioning of every space [space] was carved out of
+    fn fits_parents(&self, space: u64, vals: &ValSet) -> (r: Result<(), StepError>)
+        requires
+            self.wf(),
+            vals.wf(),
+        ensures
+            r is Ok ==> forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) && pe.src() == space as nat ==> {
+                &&& vals@.subset_of(self@.spaces[pe.dst()].vals())
+                &&& forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == pe.dst() ==>
+                    vals@.disjoint(self@.resources[se.src()].range())
+                &&& forall |sib: PartitionEdge| #[trigger] self@.partitions.contains(sib) && sib.dst() == pe.dst() && sib.src() != space as nat ==>
+                    self@.spaces[sib.src()].vals().disjoint(vals@)
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.partitions.len()
+            invariant
+                self.wf(),
+                vals.wf(),
+                i <= self.partitions.len(),
+                forall |j: int| 0 <= j < i && #[trigger] self.partitions[j].src == space ==> {
+                    let parent = self.partitions[j]@.dst();
+                    &&& vals@.subset_of(self@.spaces[parent].vals())
+                    &&& forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == parent ==>
+                        vals@.disjoint(self@.resources[se.src()].range())
+                    &&& forall |sib: PartitionEdge| #[trigger] self@.partitions.contains(sib) && sib.dst() == parent && sib.src() != space as nat ==>
+                        self@.spaces[sib.src()].vals().disjoint(vals@)
+                },
+            decreases self.partitions.len() - i
+        {
+            let pe = self.partitions[i];
+            if pe.src == space {
+                let pi = match self.space_index(pe.dst) {
+                    Some(pi) => pi,
+                    None => return Err(StepError::Unchecked),
+                };
+                if !vals.subset_of(&self.spaces[pi].space.vals) {
+                    return Err(StepError::Disabled);
+                }
+                if !self.allocations_against(pe.dst, vals, false) {
+                    return Err(StepError::Disabled);
+                }
+                proof {
+                    lemma_mirrored(self.partitions@, self@.partitions, i as int);
+                }
+                if !self.children_avoid(pe.dst, 0, 0, Some(vals), Some(space)) {
+                    return Err(StepError::Disabled);
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |pe: PartitionEdge| #[trigger] self@.partitions.contains(pe) && pe.src() == space as nat implies {
+                &&& vals@.subset_of(self@.spaces[pe.dst()].vals())
+                &&& forall |se: SubsetEdge| #[trigger] self@.subsets.contains(se) && se.dst() == pe.dst() ==>
+                    vals@.disjoint(self@.resources[se.src()].range())
+                &&& forall |sib: PartitionEdge| #[trigger] self@.partitions.contains(sib) && sib.dst() == pe.dst() && sib.src() != space as nat ==>
+                    self@.spaces[sib.src()].vals().disjoint(vals@)
+            } by {
+                let j = choose |j: int| 0 <= j < self.partitions.len() && #[trigger] self.partitions@[j]@ == pe;
+                assert(self.partitions[j]@ == pe);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Construction:
+
+/// Whether no two of [v] are keyed by the same ResourceSpace
+fn space_ids_unique(v: &Vec<SpaceEntry>) -> (b: bool)
+    ensures
+        b ==> keys_unique(v@),
+{
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            i <= v.len(),
+            forall |j: int, k: int| 0 <= j < i && 0 <= k < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[k]@.0 ==> j == k,
+        decreases v.len() - i
+    {
+        let mut k: usize = 0;
+        while k < v.len()
+            invariant
+                i < v.len(),
+                k <= v.len(),
+                forall |j: int, l: int| 0 <= j < i && 0 <= l < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[l]@.0 ==> j == l,
+                forall |l: int| 0 <= l < k && #[trigger] v@[l]@.0 == v@[i as int]@.0 ==> l == i,
+            decreases v.len() - k
+        {
+            if k != i && v[k].id == v[i].id {
+                return false;
+            }
+            k = k + 1;
+        }
+        i = i + 1;
+    }
+    true
+}
+
+/// The entries of [v], which mirror [m], sorted by id
+fn sorted_spaces(v: &Vec<SpaceEntry>, Ghost(m): Ghost<Map<SpaceId, ResourceSpace<StandardKind>>>) -> (r: Vec<SpaceEntry>)
+    requires
+        mirrors_map(v@, m),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] v[i].space.wf(),
+    ensures
+        mirrors_map(r@, m),
+        keys_sorted(r@),
+        forall |i: int| 0 <= i < r.len() ==> #[trigger] r[i].space.wf(),
+{
+    let mut r: Vec<SpaceEntry> = Vec::new();
+    assert(prefix_keys(v@, 0) =~= Set::<SpaceId>::empty());
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            i <= v.len(),
+            mirrors_map(v@, m),
+            forall |j: int| 0 <= j < v.len() ==> #[trigger] v[j].space.wf(),
+            mirrors_map(r@, m.restrict(prefix_keys(v@, i as int))),
+            keys_sorted(r@),
+            forall |j: int| 0 <= j < r.len() ==> #[trigger] r[j].space.wf(),
+        decreases v.len() - i
+    {
+        let e = SpaceEntry { id: v[i].id, space: v[i].space.copy() };
+        let ghost before = r@;
+        let ghost done = m.restrict(prefix_keys(v@, i as int));
+        proof {
+            lemma_mirrored_key(v@, m, i as int);
+            lemma_prefix_keys_finite(v@, i + 1);
+            if (done.contains_key(e@.0)) {
+                let j = choose |j: int| 0 <= j < i && #[trigger] v@[j]@.0 == e@.0;
+                assert(v@[j]@.0 == v@[i as int]@.0);
+            }
+        }
+        let k = insert_sorted(&mut r, e, Ghost(done));
+        proof {
+            assert(done.insert(e@.0, e@.1) =~= m.restrict(prefix_keys(v@, i + 1)));
+            assert forall |j: int| 0 <= j < r.len() implies #[trigger] r[j].space.wf() by {
+                if (j < k) {
+                    assert(r[j] == before[j]);
+                } else if (j > k) {
+                    assert(r[j] == before[j - 1]);
+                }
+            }
+        }
+        i = i + 1;
+    }
+    proof {
+        lemma_prefix_keys_all(v@, m);
+        assert(m.restrict(prefix_keys(v@, v.len() as int)) =~= m);
+    }
+    r
+}
+
+/// Whether no two of [v] of the same type share a val
+fn initial_spaces_disjoint(v: &Vec<SpaceEntry>) -> (b: bool)
+    requires
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] v[i].space.wf(),
+    ensures
+        b ==> forall |j: int, k: int| 0 <= j < v.len() && 0 <= k < v.len() && j != k
+            && #[trigger] v@[j]@.1.rtype() == #[trigger] v@[k]@.1.rtype()
+            ==> v@[j]@.1.vals().disjoint(v@[k]@.1.vals()),
+{
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            i <= v.len(),
+            forall |m: int| 0 <= m < v.len() ==> #[trigger] v[m].space.wf(),
+            forall |j: int, k: int| 0 <= j < i && 0 <= k < v.len() && j != k
+                && #[trigger] v@[j]@.1.rtype() == #[trigger] v@[k]@.1.rtype()
+                ==> v@[j]@.1.vals().disjoint(v@[k]@.1.vals()),
+        decreases v.len() - i
+    {
+        let mut k: usize = 0;
+        while k < v.len()
+            invariant
+                i < v.len(),
+                k <= v.len(),
+                forall |m: int| 0 <= m < v.len() ==> #[trigger] v[m].space.wf(),
+                forall |j: int, l: int| 0 <= j < i && 0 <= l < v.len() && j != l
+                    && #[trigger] v@[j]@.1.rtype() == #[trigger] v@[l]@.1.rtype()
+                    ==> v@[j]@.1.vals().disjoint(v@[l]@.1.vals()),
+                forall |l: int| 0 <= l < k && l != i
+                    && v@[i as int]@.1.rtype() == #[trigger] v@[l]@.1.rtype() ==> v@[i as int]@.1.vals().disjoint(v@[l]@.1.vals()),
+            decreases v.len() - k
+        {
+            if k != i && v[i].space.rtype.eq(v[k].space.rtype)
+                && !v[i].space.vals.disjoint(&v[k].space.vals) {
+                return false;
+            }
+            k = k + 1;
+        }
+        i = i + 1;
+    }
+    true
+}
+
+/// Whether no two of [v] are keyed by the same ResourceType
+fn cost_types_unique(v: &Vec<CostEntry>) -> (b: bool)
+    ensures
+        b ==> keys_unique(v@),
+{
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            i <= v.len(),
+            forall |j: int, k: int| 0 <= j < i && 0 <= k < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[k]@.0 ==> j == k,
+        decreases v.len() - i
+    {
+        let mut k: usize = 0;
+        while k < v.len()
+            invariant
+                i < v.len(),
+                k <= v.len(),
+                forall |j: int, l: int| 0 <= j < i && 0 <= l < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[l]@.0 ==> j == l,
+                forall |l: int| 0 <= l < k && #[trigger] v@[l]@.0 == v@[i as int]@.0 ==> l == i,
+            decreases v.len() - k
+        {
+            if k != i && v[k].rtype.eq(v[i].rtype) {
+                return false;
+            }
+            k = k + 1;
+        }
+        i = i + 1;
+    }
+    true
+}
+
+/// Whether no two of [v] are keyed by the same nat
+fn nat_keys_unique(v: &Vec<NatEntry>) -> (b: bool)
+    ensures
+        b ==> keys_unique(v@),
+{
+    let mut i: usize = 0;
+    while i < v.len()
+        invariant
+            i <= v.len(),
+            forall |j: int, k: int| 0 <= j < i && 0 <= k < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[k]@.0 ==> j == k,
+        decreases v.len() - i
+    {
+        let mut k: usize = 0;
+        while k < v.len()
+            invariant
+                i < v.len(),
+                k <= v.len(),
+                forall |j: int, l: int| 0 <= j < i && 0 <= l < v.len() && #[trigger] v@[j]@.0 == #[trigger] v@[l]@.0 ==> j == l,
+                forall |l: int| 0 <= l < k && #[trigger] v@[l]@.0 == v@[i as int]@.0 ==> l == i,
+            decreases v.len() - k
+        {
+            if k != i && v[k].key == v[i].key {
+                return false;
+            }
+            k = k + 1;
+        }
+        i = i + 1;
+    }
+    true
+}
+
+impl OsmosisGraph {
+    /// Sets up the Osmosis DAG with the [physical_spaces], held by the initial ProtectionDomain 0
+    /// along with all of the [initial_quotas], see OsmosisDAG::initialize
+    pub fn new(physical_spaces: Vec<SpaceEntry>, initial_quotas: Vec<CostEntry>, core_periods: Vec<NatEntry>,
+        space_costs: Vec<CostEntry>) -> (r: Result<OsmosisGraph, StepError>)
+        requires
+            forall |i: int| 0 <= i < physical_spaces.len() ==> #[trigger] physical_spaces[i].space.wf(),
+        ensures
+            r is Ok ==> r->Ok_0.wf() && OsmosisDAG::State::initialize(r->Ok_0@, entries_map(physical_spaces@),
+                entries_map(initial_quotas@), entries_map(core_periods@), entries_map(space_costs@)),
+    {
+        if !space_ids_unique(&physical_spaces) || !cost_types_unique(&initial_quotas)
+            || !nat_keys_unique(&core_periods) || !cost_types_unique(&space_costs)
+            || !initial_spaces_disjoint(&physical_spaces) {
+            return Err(StepError::Disabled);
+        }
+        let ghost spaces = entries_map(physical_spaces@);
+        let ghost quotas = entries_map(initial_quotas@);
+        let ghost periods = entries_map(core_periods@);
+        let ghost costs = entries_map(space_costs@);
+        proof {
+            lemma_entries_map(physical_spaces@);
+            lemma_entries_map(initial_quotas@);
+            lemma_entries_map(core_periods@);
+            lemma_entries_map(space_costs@);
+            assert forall |s1: SpaceId, s2: SpaceId| #[trigger] spaces.contains_key(s1) && #[trigger] spaces.contains_key(s2) && s1 != s2
+                && spaces[s1].rtype() == spaces[s2].rtype()
+                implies spaces[s1].vals().disjoint(spaces[s2].vals()) by {
+                let j = choose |j: int| 0 <= j < physical_spaces.len() && #[trigger] physical_spaces@[j]@.0 == s1;
+                let k = choose |k: int| 0 <= k < physical_spaces.len() && #[trigger] physical_spaces@[k]@.0 == s2;
+                lemma_mirrored_key(physical_spaces@, spaces, j);
+                lemma_mirrored_key(physical_spaces@, spaces, k);
+            }
+        }
+        let initial_domain = ProtectionDomain { id: 0 };
+        let ghost holds = spaces.dom().map(|space: SpaceId| -> (HoldEdge) {
+            HoldEdge { src: initial_domain, dst: ResourceLike::Space { space }, rights: Set::full() }
+        });
+        let ghost quota_map = Map::new(
+            |k: (ProtectionDomain, ResourceType<StandardKind>)| k.0 == initial_domain && quotas.contains_key(k.1),
+            |k: (ProtectionDomain, ResourceType<StandardKind>)| quotas[k.1],
+        );
+
+        // The initial spaces, sorted by id however they were given, the hold edges on them, a copy
+        // of them to remember them by, and their empty indexes
+        let sorted = sorted_spaces(&physical_spaces, Ghost(spaces));
+        let mut hold_edges: Vec<ExecHoldEdge> = Vec::new();
+        let mut initial_spaces: Vec<SpaceEntry> = Vec::new();
+        let mut allocated: Vec<ValsEntry> = Vec::new();
+        let mut carved: Vec<ValsEntry> = Vec::new();
+        let mut i: usize = 0;
+        while i < sorted.len()
+            invariant
+                i <= sorted.len(),
+                forall |j: int| 0 <= j < sorted.len() ==> #[trigger] sorted[j].space.wf(),
+                mirrors_map(sorted@, spaces),
+                mirrors_map(core_periods@, periods),
+                hold_edges.len() == i,
+                initial_spaces.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] hold_edges[j]@ ==
+                    (HoldEdge { src: initial_domain, dst: ResourceLike::Space { space: sorted[j].id as nat }, rights: Set::full() }),
+                forall |j: int| 0 <= j < i ==> #[trigger] initial_spaces[j]@ == sorted[j]@ && initial_spaces[j].space.wf(),
+                allocated.len() == i,
+                carved.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] allocated[j]@ == (sorted[j]@.0, Set::<nat>::empty()) && allocated[j].vals.wf(),
+                forall |j: int| 0 <= j < i ==> #[trigger] carved[j]@ == (sorted[j]@.0, Set::<nat>::empty()) && carved[j].vals.wf(),
+                forall |j: int| 0 <= j < i ==> {
+                    let sp = #[trigger] sorted[j]@.1;
+                    &&& sp.rtype() is Physical || sp.rtype() is Time
+                    &&& sp.rtype().declared()
+                    &&& sp.rtype() is Time ==> periods.contains_key(sp.rtype()->Time_0)
+                        && sp.vals().subset_of(nat_range(periods[sp.rtype()->Time_0]))
+                },
+            decreases sorted.len() - i
+        {
+            let e = &sorted[i];
+            let rtype = e.space.rtype;
+            if !(rtype.is_physical() || rtype.is_time()) || !rtype.declared() {
+                return Err(StepError::Disabled);
+            }
+            match rtype {
+                ExecResourceType::Time(core) => {
+                    match nat_entry_index(&core_periods, core, Ghost(periods)) {
+                        Some(pi) => {
+                            if !e.space.vals.below(core_periods[pi].val) {
+                                return Err(StepError::Disabled);
+                            }
+                        },
+                        None => return Err(StepError::Disabled),
+                    }
+                },
+                _ => {},
+            }
+            hold_edges.push(ExecHoldEdge { src: 0, dst: ExecResourceLike::Space { space: e.id }, rights: Rights::full() });
+            initial_spaces.push(SpaceEntry { id: e.id, space: e.space.copy() });
+            allocated.push(ValsEntry { space: e.id, vals: ValSet::new() });
+            carved.push(ValsEntry { space: e.id, vals: ValSet::new() });
+            i = i + 1;
+        }
+
+        let mut quota_entries: Vec<QuotaEntry> = Vec::new();
+        let mut i: usize = 0;
+        while i < initial_quotas.len()
+            invariant
+                i <= initial_quotas.len(),
+                mirrors_map(initial_quotas@, quotas),
+                quota_entries.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] quota_entries[j]@ == ((initial_domain, initial_quotas[j]@.0), initial_quotas[j]@.1),
+            decreases initial_quotas.len() - i
+        {
+            let e = initial_quotas[i];
+            quota_entries.push(QuotaEntry { pd: 0, rtype: e.rtype, amount: e.cost });
+            i = i + 1;
+        }
+
+        // Nothing is taken of any kind yet, each kind having its entry at its rank
+        let mut used: Vec<UsageEntry> = Vec::new();
+        let mut i: usize = 0;
+        while i < KINDS
+            invariant
+                i <= KINDS,
+                used.len() == i,
+                forall |j: int| 0 <= j < i ==> {
+                    &&& #[trigger] used[j].vals.wf()
+                    &&& used[j]@.0 is Physical
+                    &&& kind_rank(used[j]@.0->Physical_0) == j
+                    &&& used[j]@.1 == Set::<nat>::empty()
+                },
+            decreases KINDS - i
+        {
+            used.push(UsageEntry { rtype: ExecResourceType::Physical(ExecKind::of_rank(i)), vals: ValSet::new() });
+            i = i + 1;
+        }
+
+        let ghost state = OsmosisDAG::State {
+            domains: Set::empty().insert(initial_domain),
+            resources: Map::empty(),
+            spaces,
+            holds,
+            maps: Set::empty(),
+            subsets: Set::empty(),
+            requests: Set::empty(),
+            partitions: Set::empty(),
+            quotas: quota_map,
+            parents: Map::empty(),
+            periods,
+            devices: Map::empty(),
+            retyped: Map::empty(),
+            initial_spaces: spaces,
+            metadata_costs: costs,
+            metadata: Map::empty(),
+            issued: Map::empty(),
+            epoch: 0,
+            leases: Map::empty(),
+            derived_from: Map::empty(),
+            depth: Map::empty(),
+            history: Seq::empty(),
+        };
+        let g = OsmosisGraph {
+            domains: vec![0],
+            resources: Vec::new(),
+            spaces: sorted,
+            holds: hold_edges,
+            maps: Vec::new(),
+            subsets: Vec::new(),
+            requests: Vec::new(),
+            partitions: Vec::new(),
+            quotas: quota_entries,
+            parents: Vec::new(),
+            periods: core_periods,
+            devices: Vec::new(),
+            retyped: Vec::new(),
+            initial_spaces,
+            metadata_costs: space_costs,
+            metadata: Vec::new(),
+            issued: Vec::new(),
+            epoch: 0,
+            leases: Vec::new(),
+            derived_from: Vec::new(),
+            allocated,
+            carved,
+            used,
+            state: Ghost(state),
+        };
+        proof {
+            assert(mirrors_domains(g.domains@, state.domains)) by {
+                assert forall |p: ProtectionDomain| #[trigger] state.domains.contains(p) <==>
+                    exists |i: int| 0 <= i < g.domains.len() && #[trigger] pd_of(g.domains[i]) == p by {
+                    if (state.domains.contains(p)) {
+                        assert(pd_of(g.domains[0]) == p);
+                    }
+                }
+            }
+            assert(mirrors(g.holds@, holds)) by {
+                assert forall |he: HoldEdge| #[trigger] holds.contains(he) <==> exists |i: int| 0 <= i < g.holds.len() && #[trigger] g.holds[i]@ == he by {
+                    if (holds.contains(he)) {
+                        let space = choose |space: SpaceId| spaces.contains_key(space) && he == (HoldEdge {
+                            src: initial_domain, dst: ResourceLike::Space { space }, rights: Set::full() });
+                        let j = choose |j: int| 0 <= j < g.spaces.len() && #[trigger] g.spaces@[j]@.0 == space;
+                        assert(g.holds[j]@ == he);
+                    }
+                    if (exists |i: int| 0 <= i < g.holds.len() && #[trigger] g.holds[i]@ == he) {
+                        let j = choose |j: int| 0 <= j < g.holds.len() && #[trigger] g.holds[j]@ == he;
+                        lemma_mirrored_key(g.spaces@, spaces, j);
+                        assert(spaces.dom().contains(g.spaces[j]@.0));
+                    }
+                }
+            }
+            assert(mirrors_map(g.quotas@, quota_map)) by {
+                assert forall |j: int, k: int| 0 <= j < g.quotas.len() && 0 <= k < g.quotas.len()
+                    && #[trigger] g.quotas@[j]@.0 == #[trigger] g.quotas@[k]@.0 implies j == k by {
+                    assert(initial_quotas@[j]@.0 == initial_quotas@[k]@.0);
+                }
+                assert forall |k: (ProtectionDomain, ResourceType<StandardKind>)| #[trigger] quota_map.contains_key(k) <==>
+                    exists |i: int| 0 <= i < g.quotas.len() && #[trigger] g.quotas@[i]@.0 == k by {
+                    if (quota_map.contains_key(k)) {
+                        let j = choose |j: int| 0 <= j < initial_quotas.len() && #[trigger] initial_quotas@[j]@.0 == k.1;
+                        assert(g.quotas@[j]@.0 == k);
+                    }
+                    if (exists |i: int| 0 <= i < g.quotas.len() && #[trigger] g.quotas@[i]@.0 == k) {
+                        let j = choose |j: int| 0 <= j < g.quotas.len() && #[trigger] g.quotas@[j]@.0 == k;
+                        lemma_mirrored_key(initial_quotas@, quotas, j);
+                    }
+                }
+                assert forall |j: int| 0 <= j < g.quotas.len() implies #[trigger] quota_map[g.quotas@[j]@.0] == g.quotas@[j]@.1 by {
+                    lemma_mirrored_key(initial_quotas@, quotas, j);
+                }
+            }
+            assert(mirrors_map(g.initial_spaces@, spaces)) by {
+                assert forall |j: int, k: int| 0 <= j < g.initial_spaces.len() && 0 <= k < g.initial_spaces.len()
+                    && #[trigger] g.initial_spaces@[j]@.0 == #[trigger] g.initial_spaces@[k]@.0 implies j == k by {
+                    assert(g.spaces@[j]@.0 == g.spaces@[k]@.0);
+                }
+                assert forall |k: SpaceId| #[trigger] spaces.contains_key(k) <==>
+                    exists |i: int| 0 <= i < g.initial_spaces.len() && #[trigger] g.initial_spaces@[i]@.0 == k by {
+                    if (spaces.contains_key(k)) {
+                        let j = choose |j: int| 0 <= j < g.spaces.len() && #[trigger] g.spaces@[j]@.0 == k;
+                        assert(g.initial_spaces@[j]@.0 == k);
+                    }
+                    if (exists |i: int| 0 <= i < g.initial_spaces.len() && #[trigger] g.initial_spaces@[i]@.0 == k) {
+                        let j = choose |j: int| 0 <= j < g.initial_spaces.len() && #[trigger] g.initial_spaces@[j]@.0 == k;
+                        assert(g.spaces@[j]@.0 == k);
+                    }
+                }
+                assert forall |j: int| 0 <= j < g.initial_spaces.len() implies #[trigger] spaces[g.initial_spaces@[j]@.0] == g.initial_spaces@[j]@.1 by {
+                    lemma_mirrored_key(g.spaces@, spaces, j);
+                }
+            }
+            assert(mirrors(g.maps@, Set::<MapEdge>::empty()));
+            assert(mirrors(g.subsets@, Set::<SubsetEdge>::empty()));
+            assert(mirrors(g.requests@, Set::<RequestEdge<StandardKind>>::empty()));
+            assert(mirrors(g.partitions@, Set::<PartitionEdge>::empty()));
+            assert forall |id: SpaceId| #[trigger] spaces.contains_key(id) implies {
+                &&& spaces[id].rtype() is Physical || spaces[id].rtype() is Time
+                &&& spaces[id].rtype().declared()
+                &&& spaces[id].rtype() is Time ==> periods.contains_key(spaces[id].rtype()->Time_0)
+                    && spaces[id].vals().subset_of(nat_range(periods[spaces[id].rtype()->Time_0]))
+                &&& spaces[id].vals().finite()
+            } by {
+                let j = choose |j: int| 0 <= j < g.spaces.len() && #[trigger] g.spaces@[j]@.0 == id;
+                lemma_mirrored_key(g.spaces@, spaces, j);
+                assert(g.spaces[j].space.wf());
+                g.spaces[j].space.vals.lemma_finite();
+            }
+            assert(OsmosisDAG::State::initialize(state, spaces, quotas, periods, costs));
+            OsmosisDAG::State::initialize_inductive(state, spaces, quotas, periods, costs);
+            // Nothing is allocated from, nor carved out of, the initial spaces
+            assert forall |k: SpaceId| #[trigger] allocation_index(state).contains_key(k) implies
+                allocated_vals(state, k) =~= Set::empty() && carved_vals(state, k) =~= Set::empty() by {}
+            assert forall |j: int, k: int| 0 <= j < g.allocated.len() && 0 <= k < g.allocated.len()
+                && #[trigger] g.allocated@[j]@.0 == #[trigger] g.allocated@[k]@.0 implies j == k by {
+                assert(g.spaces@[j]@.0 == g.spaces@[k]@.0);
+            }
+            assert forall |j: int, k: int| 0 <= j < g.carved.len() && 0 <= k < g.carved.len()
+                && #[trigger] g.carved@[j]@.0 == #[trigger] g.carved@[k]@.0 implies j == k by {
+                assert(g.spaces@[j]@.0 == g.spaces@[k]@.0);
+            }
+            assert forall |k: SpaceId| #[trigger] spaces.contains_key(k) implies
+                (exists |i: int| 0 <= i < g.allocated.len() && #[trigger] g.allocated@[i]@.0 == k)
+                && (exists |i: int| 0 <= i < g.carved.len() && #[trigger] g.carved@[i]@.0 == k) by {
+                let j = choose |j: int| 0 <= j < g.spaces.len() && #[trigger] g.spaces@[j]@.0 == k;
+                assert(g.allocated@[j]@.0 == k && g.carved@[j]@.0 == k);
+            }
+            assert forall |j: int| 0 <= j < g.allocated.len() implies #[trigger] spaces.contains_key(g.allocated@[j]@.0) by {
+                lemma_mirrored_key(g.spaces@, spaces, j);
+            }
+            assert forall |j: int| 0 <= j < g.carved.len() implies #[trigger] spaces.contains_key(g.carved@[j]@.0) by {
+                lemma_mirrored_key(g.spaces@, spaces, j);
+            }
+            assert(mirrors_map(g.allocated@, allocation_index(state)));
+            assert(mirrors_map(g.carved@, carving_index(state)));
+            assert forall |j: int| 0 <= j < g.used.len() implies #[trigger] g.used[j]@.1 == used_vals(state, g.used[j]@.0) by {
+                assert(used_vals(state, g.used[j]@.0) =~= Set::<nat>::empty());
+            }
+            // The indexes are laid out like the spaces, so they are sorted like them
+            assert forall |j: int, k: int| 0 <= j < k < g.allocated.len() implies
+                #[trigger] g.allocated@[j]@.0 < #[trigger] g.allocated@[k]@.0 by {
+                assert(g.spaces@[j]@.0 < g.spaces@[k]@.0);
+            }
+            assert forall |j: int, k: int| 0 <= j < k < g.carved.len() implies
+                #[trigger] g.carved@[j]@.0 < #[trigger] g.carved@[k]@.0 by {
+                assert(g.spaces@[j]@.0 < g.spaces@[k]@.0);
+            }
+        }
+        Ok(g)
+    }
+}
+
+// Transitions:
+
+impl OsmosisGraph {
+    /// Takes the create_resource step
+    pub fn create_resource(&mut self, pd: u64, id: u64, res: ExecResource, space: u64, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_resource(pd_of(pd), id as nat, res@, space as nat, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_resource(pd_of(pd), id as nat, res@, space as nat, rights@);
+        let node = ExecResourceLike::Space { space };
+        if !self.has_domain(pd) || self.resource(id).is_some() || !res.well_formed() || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        let si = match self.space_index(space) {
+            Some(si) => si,
+            None => return Err(StepError::Disabled),
+        };
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Resource { res: id }, rights };
+        let sp = &self.spaces[si].space;
+        if !res.rtype.eq(sp.rtype) || !sp.vals.covers_range(res.val, res.size)
+            || !self.allocations_avoid_range(space, res.val, res.size)
+            || !self.children_avoid(space, res.val, res.size, None, None)
+            || !self.holds_with_rights(pd, node, &rights)
+            || (res.rtype.is_physical() && !self.physical_range_free(res.rtype, res.val, res.size)) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_quota(pd, res.rtype, res.size, 0) {
+            return Err(e);
+        }
+        let ai = match vals_entry_index(&self.allocated, space, Ghost(allocation_index(pre))) {
+            Some(ai) => ai,
+            None => return Err(StepError::Unchecked),
+        };
+        let ui = if res.rtype.is_physical() { Some(self.used_index(res.rtype)) } else { None };
+        proof {
+            self.spaces[si as int].space.vals.lemma_words();
+            assert(nat_interval(res.val as nat, res.size as nat).contains((res.val + res.size - 1) as nat));
+        }
+        let range = ValSet::from_range(res.val, res.size);
+        let allocated = self.allocated[ai].vals.union(&range);
+
+        let entry = ResourceEntry { id, res };
+        let se = ExecSubsetEdge { src: id, dst: space };
+        proof {
+            lemma_mirrors_push(self.subsets@, pre.subsets, se);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        insert_sorted(&mut self.resources, entry, Ghost(pre.resources));
+        self.subsets.push(se);
+        self.holds.push(he);
+        self.state = Ghost(OsmosisDAG::State {
+            resources: pre.resources.insert(id as nat, res@),
+            subsets: pre.subsets.insert(se@),
+            holds: pre.holds.insert(he@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(res@.range() =~= nat_interval(res.val as nat, res.size as nat));
+            let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd_of(pd) && w.dst() == node@
+                && rights@.subset_of(w.rights());
+            assert(w.src() == pd_of(pd) && w.dst() is Space && w.dst()->space == space as nat);
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_create_resource(pre, self@, id as nat, space as nat);
+            assert(allocation_index(pre).insert(space as nat, allocated@) == allocation_index(self@));
+        }
+        upsert_vals(&mut self.allocated, Some(ai), ValsEntry { space, vals: allocated }, Ghost(allocation_index(pre)));
+        match ui {
+            Some(ui) => {
+                let e = UsageEntry { rtype: res.rtype, vals: self.used[ui].vals.union(&range) };
+                proof {
+                    lemma_usage_updated(self.used@, ui as int, e, pre, self@);
+                }
+                self.used.set(ui, e);
+            },
+            None => {
+                proof {
+                    lemma_usage_kept(self.used@, pre, self@);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Takes the destroy_resource step
+    pub fn destroy_resource(&mut self, pd: u64, res: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::destroy_resource(pd_of(pd), res as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::destroy_resource(pd_of(pd), res as nat);
+        let node = ExecResourceLike::Resource { res };
+        let ri = match self.resource_index(res) {
+            Some(ri) => ri,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !self.holds_with_rights(pd, node, &Rights::empty().with(ExecRight::Destroy))
+            || self.is_mapped(node) || self.is_routed(res) || self.is_metadata(res) || self.granted(res, None)
+            || self.is_lent(node) {
+            return Err(StepError::Disabled);
+        }
+        let se = match self.subset_edge_of(res) {
+            Ok(se) => se,
+            Err(e) => return Err(e),
+        };
+        let ai = match vals_entry_index(&self.allocated, se.dst, Ghost(allocation_index(pre))) {
+            Some(ai) => ai,
+            None => return Err(StepError::Unchecked),
+        };
+        let r = self.resources[ri].res;
+        let ui = if r.rtype.is_physical() {
+            Some(self.used_index(r.rtype))
+        } else {
+            None
+        };
+        proof {
+            lemma_mirrored_key(self.resources@, pre.resources, ri as int);
+            assert(r@.range() =~= nat_interval(r.val as nat, r.size as nat));
+            self.allocated[ai as int].vals.lemma_words();
+            if (r.size > 0) {
+                assert(pre.resources[res as nat].range().contains((r.val + r.size - 1) as nat));
+                assert(allocated_vals(pre, se.dst as nat).contains((r.val + r.size - 1) as nat));
+            }
+        }
+        let range = ValSet::from_range(r.val, r.size);
+        let allocated = self.allocated[ai].vals.difference(&range);
+
+        let ghost kept_holds = pre.holds.filter(|he: HoldEdge| he.dst() != node@);
+        let ghost resources = self.resources@;
+        let ghost subsets = self.subsets@;
+        let ghost holds = self.holds@;
+        let keep_subsets = subsets_other_than(&self.subsets, se);
+        let keep_holds = holds_not_on(&self.holds, node);
+        self.resources.remove(ri);
+        retain_flagged(&mut self.subsets, &keep_subsets);
+        retain_flagged(&mut self.holds, &keep_holds);
+        proof {
+            lemma_mirrors_map_remove(resources, pre.resources, ri as int);
+            lemma_keys_sorted_remove(resources, ri as int);
+            lemma_kept_mirrors(subsets, pre.subsets, keep_subsets@, |x: SubsetEdge| x != se@);
+            assert(pre.subsets.filter(|x: SubsetEdge| x != se@) =~= pre.subsets.remove(se@));
+            lemma_kept_mirrors(holds, pre.holds, keep_holds@, |he: HoldEdge| he.dst() != node@);
+        }
+        prune_derivations(&mut self.derived_from, &self.holds, Ghost(pre), Ghost(kept_holds));
+        self.state = Ghost(OsmosisDAG::State {
+            resources: pre.resources.remove(res as nat),
+            subsets: pre.subsets.remove(se@),
+            holds: kept_holds,
+            derived_from: pre.prune_derivations(kept_holds),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            let w = choose |w: HoldEdge| #[trigger] pre.holds.contains(w) && w.src() == pd_of(pd) && w.dst() == node@
+                && Set::<Right>::empty().insert(Right::Destroy).subset_of(w.rights());
+            assert(w.src() == pd_of(pd) && w.rights().contains(Right::Destroy));
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) implies ({
+                ||| me is SpaceBacking && me->sb_dst != res as nat
+                ||| me is SpaceMap
+                ||| me is ResourceMap && me->rm_src != res as nat && me->rm_dst != res as nat
+                ||| me is Delivery && me->dl_src != res as nat }) by {
+                if (!(me is Delivery)) {
+                    assert(!me.touches(node@));
+                }
+            }
+            assert(kept_holds =~= pre.holds.filter(|he: HoldEdge| he.dst() != ResourceLike::Resource { res: res as nat }));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_destroy_resource(pre, self@, res as nat, se.dst as nat);
+            assert(allocation_index(pre).insert(se.dst as nat, allocated@) == allocation_index(self@));
+        }
+        upsert_vals(&mut self.allocated, Some(ai), ValsEntry { space: se.dst, vals: allocated }, Ghost(allocation_index(pre)));
+        match ui {
+            Some(ui) => {
+                let e = UsageEntry { rtype: r.rtype, vals: self.used[ui].vals.difference(&range) };
+                proof {
+                    lemma_usage_updated(self.used@, ui as int, e, pre, self@);
+                }
+                self.used.set(ui, e);
+            },
+            None => {
+                proof {
+                    lemma_usage_kept(self.used@, pre, self@);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Takes the create_pd step
+    pub fn create_pd(&mut self, parent: u64, pd: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_pd(pd_of(parent), pd_of(pd)), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_pd(pd_of(parent), pd_of(pd));
+        if self.has_domain(pd) || !self.has_domain(parent) {
+            return Err(StepError::Disabled);
+        }
+        let pi = self.parent_index(pd);
+        self.domains.push(pd);
+        upsert(&mut self.parents, pi, ParentEntry { pd, parent }, Ghost(pre.parents));
+        self.state = Ghost(OsmosisDAG::State {
+            domains: pre.domains.insert(pd_of(pd)),
+            parents: pre.parents.insert(pd_of(pd), pd_of(parent)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            let v = self.domains@;
+            assert forall |p: ProtectionDomain| #[trigger] self@.domains.contains(p) <==>
+                exists |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p by {
+                if (pre.domains.contains(p)) {
+                    let i = choose |i: int| 0 <= i < old(self).domains.len() && #[trigger] pd_of(old(self).domains[i]) == p;
+                    assert(pd_of(v[i]) == p);
+                }
+                if (p == pd_of(pd)) {
+                    assert(pd_of(v[v.len() - 1]) == p);
+                }
+                if (exists |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p) {
+                    let i = choose |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p;
+                    if (i < v.len() - 1) {
+                        assert(pd_of(old(self).domains[i]) == p);
+                    }
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the destroy_pd step
+    pub fn destroy_pd(&mut self, pd: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::destroy_pd(pd_of(pd)), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::destroy_pd(pd_of(pd));
+        if !self.has_domain(pd) || self.holds_anything(pd) || self.in_requests(pd) || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        let mut keep: Vec<bool> = Vec::new();
+        let mut i: usize = 0;
+        while i < self.domains.len()
+            invariant
+                i <= self.domains.len(),
+                keep.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (self.domains[j] != pd),
+            decreases self.domains.len() - i
+        {
+            keep.push(self.domains[i] != pd);
+            i = i + 1;
+        }
+        let pi = self.parent_index(pd);
+        let ghost domains = self.domains@;
+        retain_flagged(&mut self.domains, &keep);
+        remove_entry(&mut self.parents, pi, Ghost(pre.parents), Ghost(pd_of(pd)));
+        self.state = Ghost(OsmosisDAG::State {
+            domains: pre.domains.remove(pd_of(pd)),
+            parents: pre.parents.remove(pd_of(pd)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            lemma_kept(domains, keep@);
+            let v = self.domains@;
+            assert forall |p: ProtectionDomain| #[trigger] self@.domains.contains(p) <==>
+                exists |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p by {
+                if (self@.domains.contains(p)) {
+                    let i = choose |i: int| 0 <= i < domains.len() && #[trigger] pd_of(domains[i]) == p;
+                    assert(keep[i]);
+                    assert(v.contains(domains[i]));
+                    let j = choose |j: int| 0 <= j < v.len() && v[j] == domains[i];
+                    assert(pd_of(v[j]) == p);
+                }
+                if (exists |i: int| 0 <= i < v.len() && #[trigger] pd_of(v[i]) == p) {
+                    let j = choose |j: int| 0 <= j < v.len() && #[trigger] pd_of(v[j]) == p;
+                    assert(v.contains(v[j]));
+                    let i = choose |i: int| 0 <= i < domains.len() && keep[i] && #[trigger] domains[i] == v[j];
+                    assert(pd_of(domains[i]) == p);
+                }
+            }
+            assert forall |he: HoldEdge| pre.holds.contains(he) implies #[trigger] he.src() != pd_of(pd) by {}
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the create_request_edge step
+    pub fn create_request_edge(&mut self, req: ExecRequestEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_request_edge(req@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_request_edge(req@);
+        if !self.has_domain(req.src) || !self.has_domain(req.dst) {
+            return Err(StepError::Disabled);
+        }
+        // A space of the requested type the server holds
+        let mut served = false;
+        let mut i: usize = 0;
+        while i < self.holds.len() && !served
+            invariant
+                self.wf(),
+                self@ == pre,
+                pre == old(self)@,
+                i <= self.holds.len(),
+                served ==> exists |he: HoldEdge| pre.holds.contains(he) && #[trigger] he.src() == req@.dst()
+                    && he.dst() is Space && pre.spaces[he.dst()->space].rtype() == req@.rtype(),
+            decreases self.holds.len() - i
+        {
+            let he = self.holds[i];
+            if he.src == req.dst {
+                match he.dst {
+                    ExecResourceLike::Space { space } => {
+                        match self.space_index(space) {
+                            Some(si) => {
+                                if self.spaces[si].space.rtype.eq(req.rtype) {
+                                    proof {
+                                        lemma_mirrored(self.holds@, pre.holds, i as int);
+                                        assert(he@.src() == req@.dst());
+                                    }
+                                    served = true;
+                                }
+                            },
+                            None => {},
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            i = i + 1;
+        }
+        if !served {
+            return Err(StepError::Disabled);
+        }
+        proof {
+            lemma_mirrors_push(self.requests@, pre.requests, req);
+        }
+        self.requests.push(req);
+        self.state = Ghost(OsmosisDAG::State {
+            requests: pre.requests.insert(req@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the destroy_request_edge step
+    pub fn destroy_request_edge(&mut self, req: ExecRequestEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::destroy_request_edge(req@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::destroy_request_edge(req@);
+        if !self.has_request_edge(&req) || self.pending_over(&req) {
+            return Err(StepError::Disabled);
+        }
+        let ghost requests = self.requests@;
+        let keep = requests_other_than(&self.requests, &req);
+        retain_flagged(&mut self.requests, &keep);
+        self.state = Ghost(OsmosisDAG::State {
+            requests: pre.requests.remove(req@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            lemma_kept_mirrors(requests, pre.requests, keep@, |x: RequestEdge<StandardKind>| x != req@);
+            assert(pre.requests.filter(|x: RequestEdge<StandardKind>| x != req@) =~= pre.requests.remove(req@));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the issue_request step
+    pub fn issue_request(&mut self, id: u64, req: ExecRequest) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            req.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::issue_request(id as nat, req@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::issue_request(id as nat, req@);
+        if self.request_index(id).is_some() || !self.has_request_edge(&req.edge()) || req.count == 0 || !req.fresh() {
+            return Err(StepError::Disabled);
+        }
+        let ghost issued = self.issued@;
+        let entry = RequestEntry { id, req };
+        proof {
+            lemma_mirrors_map_push(issued, pre.issued, entry);
+        }
+        self.issued.push(entry);
+        self.state = Ghost(OsmosisDAG::State {
+            issued: pre.issued.insert(id as nat, entry@.1),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.issued.len() implies #[trigger] self.issued[i].req.wf() by {
+                if (i < issued.len()) {
+                    assert(self.issued[i] == old(self).issued[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the fulfil_request step
+    pub fn fulfil_request(&mut self, id: u64, client: u64, he: ExecHoldEdge, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::fulfil_request(id as nat, pd_of(client), he@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::fulfil_request(id as nat, pd_of(client), he@, rights@);
+        let ri = match self.request_index(id) {
+            Some(ri) => ri,
+            None => return Err(StepError::Disabled),
+        };
+        proof {
+            lemma_mirrored_key(self.issued@, pre.issued, ri as int);
+        }
+        let server = self.issued[ri].req.server;
+        let rtype = self.issued[ri].req.rtype;
+        let count = self.issued[ri].req.count;
+        let mut handed = match &self.issued[ri].req.status {
+            ExecRequestStatus::Pending { handed } => handed.copy(),
+            _ => return Err(StepError::Disabled),
+        };
+        if client != self.issued[ri].req.client || !self.has_domain(client) || !self.has_domain(server)
+            || !hold_in(&self.holds, &he) || he.src != server || !he.rights.contains(ExecRight::Grant)
+            || !rights.subset_of(&he.rights) || self.is_device(client) {
+            return Err(StepError::Disabled);
+        }
+        let res = match he.dst {
+            ExecResourceLike::Resource { res } => res,
+            ExecResourceLike::Space { .. } => return Err(StepError::Disabled),
+        };
+        let typed = match self.resource(res) {
+            Some(x) => x.rtype.eq(rtype),
+            None => return Err(StepError::Unchecked),
+        };
+        if !typed || self.holds_resource(client, res) || self.granted(res, None) || self.pins(server, he.dst)
+            || self.handles(server, he.dst) || self.is_lent(he.dst) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_receiver(client, he.dst) {
+            return Err(e);
+        }
+
+        let granted = ExecHoldEdge { src: client, dst: he.dst, rights };
+        let ghost holds = self.holds@;
+        let keep = holds_other_than(&self.holds, &he);
+        retain_flagged(&mut self.holds, &keep);
+        let ghost kept_holds = self.holds@;
+        proof {
+            let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == he@;
+            lemma_mirrored(holds, pre.holds, i);
+            lemma_kept_mirrors(holds, pre.holds, keep@, |k: HoldEdge| k != he@);
+            assert(pre.holds.filter(|k: HoldEdge| k != he@) =~= pre.holds.remove(he@));
+            if (pre.holds.contains(granted@)) {
+                assert(holds_resource(pre.holds, pd_of(client), res as nat));
+            }
+            lemma_mirrors_push(kept_holds, pre.holds.remove(he@), granted);
+        }
+        self.holds.push(granted);
+        self.derived_from = hand_over_derivations(&self.derived_from, he, granted, Ghost(pre));
+
+        // The Request accounts for the handed over Resource, and is fulfilled once it has all of them
+        let ghost req = pre.issued[id as nat];
+        handed.insert(res);
+        let status = if handed.len() == count as u128 {
+            ExecRequestStatus::Granted { granted: handed }
+        } else {
+            ExecRequestStatus::Pending { handed }
+        };
+        let ghost issued = self.issued@;
+        let entry = RequestEntry { id, req: ExecRequest { client, server, rtype, count, status } };
+        proof {
+            lemma_mirrors_map_update(issued, pre.issued, ri as int, entry);
+        }
+        self.issued.set(ri, entry);
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.remove(he@).insert(granted@),
+            derived_from: pre.hand_over_derivations(he@, granted@),
+            depth: pre.depth.insert(granted@, pre.rank(he@)),
+            issued: pre.issued.insert(id as nat, entry@.1),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(entry@.1 == req.with_status(entry@.1.status()));
+            assert forall |i: int| 0 <= i < self.issued.len() implies #[trigger] self.issued[i].req.wf() by {
+                if (i != ri) {
+                    assert(self.issued[i] == old(self).issued[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the deny_request step
+    pub fn deny_request(&mut self, id: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::deny_request(id as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::deny_request(id as nat);
+        let ri = match self.request_index(id) {
+            Some(ri) => ri,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.issued[ri].req.is_pending() {
+            return Err(StepError::Disabled);
+        }
+        let ghost issued = self.issued@;
+        let ghost req = pre.issued[id as nat];
+        let denied = ExecRequest {
+            client: self.issued[ri].req.client,
+            server: self.issued[ri].req.server,
+            rtype: self.issued[ri].req.rtype,
+            count: self.issued[ri].req.count,
+            status: ExecRequestStatus::Denied,
+        };
+        let entry = RequestEntry { id, req: denied };
+        proof {
+            lemma_mirrors_map_update(issued, pre.issued, ri as int, entry);
+        }
+        self.issued.set(ri, entry);
+        self.state = Ghost(OsmosisDAG::State {
+            issued: pre.issued.insert(id as nat, req.with_status(RequestStatus::Denied)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(entry@.1 == req.with_status(RequestStatus::Denied));
+            assert forall |i: int| 0 <= i < self.issued.len() implies #[trigger] self.issued[i].req.wf() by {
+                if (i != ri) {
+                    assert(self.issued[i] == old(self).issued[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the close_request step
+    pub fn close_request(&mut self, id: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::close_request(id as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::close_request(id as nat);
+        let ri = match self.request_index(id) {
+            Some(ri) => ri,
+            None => return Err(StepError::Disabled),
+        };
+        if self.issued[ri].req.is_pending() {
+            return Err(StepError::Disabled);
+        }
+        let ghost issued = self.issued@;
+        remove_entry(&mut self.issued, Some(ri), Ghost(pre.issued), Ghost(id as nat));
+        self.state = Ghost(OsmosisDAG::State {
+            issued: pre.issued.remove(id as nat),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.issued.len() implies #[trigger] self.issued[i].req.wf() by {
+                if (i < ri) {
+                    assert(self.issued[i] == issued[i]);
+                } else {
+                    assert(self.issued[i] == issued[i + 1]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the create_map_edge step
+    pub fn create_map_edge(&mut self, pd: u64, me: ExecMapEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_map_edge(pd_of(pd), me@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_map_edge(pd_of(pd), me@);
+        // Interrupt lines are only delivered by routing them
+        if me.is_delivery() {
+            return Err(StepError::Disabled);
+        }
+        let src = me.src_node();
+        let dst = me.dst_node();
+        if !self.has_domain(pd) || !self.node_in_graph(src) || !self.node_in_graph(dst)
+            || !self.map_well_formed(&me, None)
+            || !self.holds_with_rights(pd, src, &Rights::empty().with(ExecRight::Map))
+            || !self.holds_with_rights(pd, dst, &me.perms().with(ExecRight::Map))
+            || self.is_retyped(src) || self.is_retyped(dst) || self.is_metadata_node(src) || self.is_metadata_node(dst) {
+            return Err(StepError::Disabled);
+        }
+        let disabled = match &me {
+            ExecMapEdge::SpaceMap { .. } => !self.translates_disjointly(&me) || !self.completes_fan_out(&me),
+            ExecMapEdge::SpaceBacking { .. } => self.is_lent(dst),
+            ExecMapEdge::ResourceMap { rm_attrs, .. } => rm_attrs.lazy,
+            ExecMapEdge::Delivery { .. } => true,
+        };
+        if disabled {
+            return Err(StepError::Disabled);
+        }
+        if let ExecResourceLike::Space { space } = src {
+            if self.is_iommu(space) {
+                return Err(StepError::Disabled);
+            }
+        }
+
+        let ghost maps = self.maps@;
+        proof {
+            lemma_mirrors_push(maps, pre.maps, me);
+        }
+        self.maps.push(me);
+        self.state = Ghost(OsmosisDAG::State {
+            maps: pre.maps.insert(me@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < maps.len()) {
+                    assert(self.maps[i] == maps[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Replaces the mapping [me] by [by] in the maps, which [me] is one of
+    fn replace_map(&mut self, me: &ExecMapEdge, by: ExecMapEdge, Ghost(post): Ghost<OsmosisDAG::State<StandardKind>>)
+        requires
+            old(self).wf(),
+            me.wf(),
+            by.wf(),
+            post == (OsmosisDAG::State { maps: old(self)@.maps.remove(me@).insert(by@), ..old(self)@ }),
+            post.invariant(),
+        ensures
+            self.wf(),
+            self@ == post,
+    {
+        let ghost pre = self@;
+        let ghost maps = self.maps@;
+        let keep = maps_other_than(&self.maps, me);
+        retain_flagged(&mut self.maps, &keep);
+        let ghost kept_maps = self.maps@;
+        proof {
+            lemma_kept_mirrors(maps, pre.maps, keep@, |m: MapEdge| m != me@);
+            assert(pre.maps.filter(|m: MapEdge| m != me@) =~= pre.maps.remove(me@));
+            lemma_kept_all(maps, keep@, |m: ExecMapEdge| m.wf());
+            lemma_mirrors_push(kept_maps, pre.maps.remove(me@), by);
+        }
+        self.maps.push(by);
+        self.state = Ghost(post);
+        proof {
+            lemma_indexes_frame(pre, post);
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < kept_maps.len()) {
+                    assert(self.maps[i] == kept_maps[i]);
+                }
+            }
+        }
+    }
+
+    /// Splits the mapping [me] into [narrowed] and [single] in the maps, which [me] is one of
+    fn split_map(&mut self, me: &ExecMapEdge, narrowed: ExecMapEdge, single: ExecMapEdge, Ghost(post): Ghost<OsmosisDAG::State<StandardKind>>)
+        requires
+            old(self).wf(),
+            me.wf(),
+            narrowed.wf(),
+            single.wf(),
+            post == (OsmosisDAG::State { maps: old(self)@.maps.remove(me@).insert(narrowed@).insert(single@), ..old(self)@ }),
+            post.invariant(),
+        ensures
+            self.wf(),
+            self@ == post,
+    {
+        let ghost pre = self@;
+        let ghost maps = self.maps@;
+        let keep = maps_other_than(&self.maps, me);
+        retain_flagged(&mut self.maps, &keep);
+        let ghost kept_maps = self.maps@;
+        proof {
+            lemma_kept_mirrors(maps, pre.maps, keep@, |m: MapEdge| m != me@);
+            assert(pre.maps.filter(|m: MapEdge| m != me@) =~= pre.maps.remove(me@));
+            lemma_kept_all(maps, keep@, |m: ExecMapEdge| m.wf());
+            lemma_mirrors_push(kept_maps, pre.maps.remove(me@), narrowed);
+            lemma_mirrors_push(kept_maps.push(narrowed), pre.maps.remove(me@).insert(narrowed@), single);
+        }
+        self.maps.push(narrowed);
+        self.maps.push(single);
+        self.state = Ghost(post);
+        proof {
+            lemma_indexes_frame(pre, post);
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < kept_maps.len()) {
+                    assert(self.maps[i] == kept_maps[i]);
+                }
+            }
+        }
+    }
+
+    /// Takes the split_map_edge step
+    pub fn split_map_edge(&mut self, pd: u64, me: ExecMapEdge, part: ExecMapEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+            part.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::split_map_edge(pd_of(pd), me@, part@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::split_map_edge(pd_of(pd), me@, part@);
+        let (narrowed, space) = match (&me, &part) {
+            (ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs }, ExecMapEdge::SpaceMap { sm_src: src, sm_vals: vals, .. }) => {
+                if *src != *sm_src || !vals.subset_of(sm_vals) {
+                    return Err(StepError::Disabled);
+                }
+                (ExecMapEdge::SpaceMap { sm_src: *sm_src, sm_dst: *sm_dst, sm_vals: sm_vals.difference(vals), sm_attrs: *sm_attrs }, *sm_src)
+            },
+            _ => return Err(StepError::Disabled),
+        };
+        let dst = part.dst_node();
+        if !self.has_domain(pd) || !self.has_map(&me) || !self.node_in_graph(dst) || !self.map_well_formed(&part, None)
+            || !self.holds_with_rights(pd, me.src_node(), &Rights::empty().with(ExecRight::Map))
+            || !self.holds_with_rights(pd, dst, &part.perms().with(ExecRight::Map))
+            || self.is_iommu(space) {
+            return Err(StepError::Disabled);
+        }
+        let ghost post = OsmosisDAG::State {
+            maps: pre.maps.remove(me@).insert(me@.without_vals(part@->sm_vals)).insert(part@),
+            history: pre.history.push(step),
+            ..pre
+        };
+        proof {
+            assert(narrowed@ == me@.without_vals(part@->sm_vals));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, post, step);
+        }
+        self.split_map(&me, narrowed, part, Ghost(post));
+        Ok(())
+    }
+
+    /// Takes the cow_write step
+    pub fn cow_write(&mut self, pd: u64, me: ExecMapEdge, v: u64, copy: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::cow_write(pd_of(pd), me@, v as nat, copy as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::cow_write(pd_of(pd), me@, v as nat, copy as nat);
+        let (rtype, size) = match &me {
+            ExecMapEdge::ResourceMap { rm_src, rm_dst, rm_attrs } => {
+                if !rm_attrs.cow {
+                    return Err(StepError::Disabled);
+                }
+                match (self.resource(*rm_src), self.resource(*rm_dst)) {
+                    (Some(s), Some(d)) => (s.rtype, d.size),
+                    _ => return Err(StepError::Disabled),
+                }
+            },
+            ExecMapEdge::SpaceMap { sm_src, sm_vals, sm_attrs, .. } => {
+                if !sm_attrs.cow || !sm_vals.contains(v) {
+                    return Err(StepError::Disabled);
+                }
+                match self.space_index(*sm_src) {
+                    Some(si) => (self.spaces[si].space.rtype, 1),
+                    None => return Err(StepError::Disabled),
+                }
+            },
+            ExecMapEdge::SpaceBacking { .. } | ExecMapEdge::Delivery { .. } => return Err(StepError::Disabled),
+        };
+        let src = me.src_node();
+        let node = ExecResourceLike::Resource { res: copy };
+        let whole = match &me {
+            ExecMapEdge::ResourceMap { .. } => true,
+            _ => false,
+        };
+        if !self.has_domain(pd) || !self.has_map(&me) || !rtype.is_virtual()
+            || !self.holds_with_rights(pd, src, &Rights::empty().with(ExecRight::Write))
+            || !self.held_only_by(pd, src) || !self.unmapped_dst(src)
+            || (whole && !self.only_mapping(src, &me, false))
+            || self.is_mapped(node) || self.is_metadata(copy) || !self.held_only_by(pd, node)
+            || !self.holds_with_rights(pd, node, &me.perms().with(ExecRight::Map)) {
+            return Err(StepError::Disabled);
+        }
+        let c = match self.resource(copy) {
+            Some(c) => c,
+            None => return Err(StepError::Disabled),
+        };
+        if !c.rtype.is_physical() || !rtype.may_map_onto(c.rtype) || c.size != size {
+            return Err(StepError::Disabled);
+        }
+        let se = match self.subset_edge_of(copy) {
+            Ok(se) => se,
+            Err(e) => return Err(e),
+        };
+        if !self.translates_clear_of(se.dst, c.val, c.size) {
+            return Err(StepError::Disabled);
+        }
+        proof {
+            assert(c@.range() =~= nat_interval(c.val as nat, c.size as nat));
+            assert(se.dst as nat == pre.allocated_from(copy as nat));
+            assert forall |s: SpaceId, w: nat| !#[trigger] pre.lands_in(s, w, copy as nat) by {
+                if (pre.lands_in(s, w, copy as nat)) {
+                    let m = choose |m: MapEdge| #[trigger] pre.maps.contains(m) && m.covers(s, w)
+                        && pre.subsets.contains(SubsetEdge { src: copy as nat, dst: m->sm_dst })
+                        && pre.resources[copy as nat].range().contains(m->sm_attrs.translation.apply(w) as nat);
+                    // The copy is only allocated from the space se is the edge onto
+                    assert(pre.subsets.contains(se@));
+                    assert(m->sm_dst == se.dst as nat);
+                    assert(m.well_formed(pre.resources, pre.spaces));
+                    assert(m->sm_vals.contains(w));
+                }
+            }
+        }
+        match &me {
+            ExecMapEdge::ResourceMap { rm_src, rm_attrs, .. } => {
+                let by = match self.resource_mapping(*rm_src, copy, rm_attrs.perms, rm_attrs.cache, false) {
+                    Some(by) => by,
+                    None => return Err(StepError::Unchecked),
+                };
+                let ghost post = OsmosisDAG::State {
+                    maps: pre.cow_split(me@, v as nat, copy as nat),
+                    history: pre.history.push(step),
+                    ..pre
+                };
+                proof {
+                    reveal(OsmosisDAG::State::next_by);
+                    lemma_step_keeps_invariant(pre, post, step);
+                }
+                self.replace_map(&me, by, Ghost(post));
+            },
+            ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } => {
+                if !self.holds_with_rights(pd, ExecResourceLike::Space { space: se.dst }, &sm_attrs.perms.with(ExecRight::Map)) {
+                    return Err(StepError::Disabled);
+                }
+                let offset = c.val as i128 - v as i128;
+                if offset < i64::MIN as i128 || offset > i64::MAX as i128 {
+                    return Err(StepError::Unchecked);
+                }
+                let written = ValSet::from_range(v, 1);
+                let narrowed = ExecMapEdge::SpaceMap { sm_src: *sm_src, sm_dst: *sm_dst, sm_vals: sm_vals.difference(&written), sm_attrs: *sm_attrs };
+                let single = ExecMapEdge::SpaceMap {
+                    sm_src: *sm_src,
+                    sm_dst: se.dst,
+                    sm_vals: written,
+                    sm_attrs: ExecMapAttributes { perms: sm_attrs.perms, cache: sm_attrs.cache, offset: offset as i64, cow: false, lazy: false },
+                };
+                let ghost post = OsmosisDAG::State {
+                    maps: pre.cow_split(me@, v as nat, copy as nat),
+                    history: pre.history.push(step),
+                    ..pre
+                };
+                proof {
+                    assert(nat_interval(v as nat, 1) =~= Set::<nat>::empty().insert(v as nat));
+                    assert(sm_vals@.difference(nat_interval(v as nat, 1)) =~= sm_vals@.remove(v as nat));
+                    assert(narrowed@ == me@.without(v as nat));
+                    assert(single@ == me@.single(v as nat, se.dst as nat, c.val as nat));
+                    reveal(OsmosisDAG::State::next_by);
+                    lemma_step_keeps_invariant(pre, post, step);
+                }
+                self.split_map(&me, narrowed, single, Ghost(post));
+            },
+            ExecMapEdge::SpaceBacking { .. } | ExecMapEdge::Delivery { .. } => return Err(StepError::Disabled),
+        }
+        Ok(())
+    }
+
+    /// Takes the demand_fault step
+    pub fn demand_fault(&mut self, pd: u64, me: ExecMapEdge, v: u64, backing: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::demand_fault(pd_of(pd), me@, v as nat, backing as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::demand_fault(pd_of(pd), me@, v as nat, backing as nat);
+        let (sm_src, sm_dst, sm_vals, sm_attrs) = match &me {
+            ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals, sm_attrs } => (*sm_src, *sm_dst, sm_vals, *sm_attrs),
+            _ => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !self.has_map(&me) || !sm_attrs.lazy || !sm_vals.contains(v)
+            || self.resource(backing).is_some() || self.is_device(pd)
+            || !self.holds_with_rights(pd, me.src_node(), &Rights::empty())
+            || !self.holds_with_rights(pd, me.dst_node(), &sm_attrs.perms.with(ExecRight::Map)) {
+            return Err(StepError::Disabled);
+        }
+        let di = match self.space_index(sm_dst) {
+            Some(di) => di,
+            None => return Err(StepError::Disabled),
+        };
+        let rtype = self.spaces[di].space.rtype;
+        if !rtype.is_physical() {
+            return Err(StepError::Disabled);
+        }
+        // The val landed on, which the backing covers
+        let w = v as i128 + sm_attrs.offset as i128;
+        if w < 0 || w > u64::MAX as i128 {
+            return Err(StepError::Unchecked);
+        }
+        let w = w as u64;
+        let res = ExecResource { rtype, val: w, size: 1, align: 1 };
+        if !self.allocations_avoid_range(sm_dst, w, 1) || !self.children_avoid(sm_dst, w, 1, None, None)
+            || !self.physical_range_free(rtype, w, 1) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_quota(pd, rtype, 1, 0) {
+            return Err(e);
+        }
+        let ai = match vals_entry_index(&self.allocated, sm_dst, Ghost(allocation_index(pre))) {
+            Some(ai) => ai,
+            None => return Err(StepError::Unchecked),
+        };
+        let ui = self.used_index(rtype);
+        let range = ValSet::from_range(w, 1);
+        let allocated = self.allocated[ai].vals.union(&range);
+        let touched = ValSet::from_range(v, 1);
+        let narrowed = ExecMapEdge::SpaceMap { sm_src, sm_dst, sm_vals: sm_vals.difference(&touched), sm_attrs };
+        let single = ExecMapEdge::SpaceMap {
+            sm_src,
+            sm_dst,
+            sm_vals: touched,
+            sm_attrs: ExecMapAttributes { perms: sm_attrs.perms, cache: sm_attrs.cache, offset: sm_attrs.offset, cow: false, lazy: false },
+        };
+        proof {
+            assert(res@ == pre.faulted(me@, v as nat));
+            assert(nat_interval(v as nat, 1) =~= Set::<nat>::empty().insert(v as nat));
+            assert(sm_vals@.difference(nat_interval(v as nat, 1)) =~= sm_vals@.remove(v as nat));
+            assert(narrowed@ == me@.without(v as nat));
+            assert(single@ == me@.single(v as nat, sm_dst as nat, me@->sm_attrs.translation.apply(v as nat) as nat));
+        }
+
+        let entry = ResourceEntry { id: backing, res };
+        let se = ExecSubsetEdge { src: backing, dst: sm_dst };
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Resource { res: backing }, rights: sm_attrs.perms.with(ExecRight::Map) };
+        proof {
+            lemma_mirrors_push(self.subsets@, pre.subsets, se);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        insert_sorted(&mut self.resources, entry, Ghost(pre.resources));
+        self.subsets.push(se);
+        self.holds.push(he);
+        let ghost maps = self.maps@;
+        let keep = maps_other_than(&self.maps, &me);
+        retain_flagged(&mut self.maps, &keep);
+        let ghost kept_maps = self.maps@;
+        proof {
+            lemma_kept_mirrors(maps, pre.maps, keep@, |m: MapEdge| m != me@);
+            assert(pre.maps.filter(|m: MapEdge| m != me@) =~= pre.maps.remove(me@));
+            lemma_kept_all(maps, keep@, |m: ExecMapEdge| m.wf());
+            lemma_mirrors_push(kept_maps, pre.maps.remove(me@), narrowed);
+            lemma_mirrors_push(kept_maps.push(narrowed), pre.maps.remove(me@).insert(narrowed@), single);
+        }
+        self.maps.push(narrowed);
+        self.maps.push(single);
+        self.state = Ghost(OsmosisDAG::State {
+            resources: pre.resources.insert(backing as nat, res@),
+            subsets: pre.subsets.insert(se@),
+            holds: pre.holds.insert(he@),
+            maps: pre.maps.remove(me@).insert(narrowed@).insert(single@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(res@.range() =~= nat_interval(w as nat, 1));
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < kept_maps.len()) {
+                    assert(self.maps[i] == kept_maps[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_create_resource(pre, self@, backing as nat, sm_dst as nat);
+            assert(allocation_index(pre).insert(sm_dst as nat, allocated@) == allocation_index(self@));
+        }
+        upsert_vals(&mut self.allocated, Some(ai), ValsEntry { space: sm_dst, vals: allocated }, Ghost(allocation_index(pre)));
+        let e = UsageEntry { rtype, vals: self.used[ui].vals.union(&range) };
+        proof {
+            lemma_usage_updated(self.used@, ui as int, e, pre, self@);
+        }
+        self.used.set(ui, e);
+        Ok(())
+    }
+
+    /// Takes the remap step
+    pub fn remap(&mut self, pd: u64, me: ExecMapEdge, dst: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::remap(pd_of(pd), me@, dst as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::remap(pd_of(pd), me@, dst as nat);
+        let backed = match &me {
+            ExecMapEdge::ResourceMap { rm_dst, .. } => *rm_dst,
+            ExecMapEdge::SpaceBacking { sb_dst, .. } => *sb_dst,
+            ExecMapEdge::SpaceMap { .. } | ExecMapEdge::Delivery { .. } => return Err(StepError::Disabled),
+        };
+        let src = me.src_node();
+        let old_node = me.dst_node();
+        let node = ExecResourceLike::Resource { res: dst };
+        if !self.has_domain(pd) || !self.has_map(&me) || !self.only_mapping(old_node, &me, true)
+            || self.is_retyped(old_node) || self.is_mapped(node) || self.is_metadata(dst)
+            || !self.holds_with_rights(pd, src, &Rights::empty().with(ExecRight::Map))
+            || !self.holds_with_rights(pd, node, &me.perms().with(ExecRight::Map)) {
+            return Err(StepError::Disabled);
+        }
+        if let ExecResourceLike::Space { space } = src {
+            if self.is_iommu(space) {
+                return Err(StepError::Disabled);
+            }
+        }
+        let rtype = match self.node_type(src) {
+            Some(rtype) => rtype,
+            None => return Err(StepError::Unchecked),
+        };
+        let o = match self.resource(backed) {
+            Some(o) => o,
+            None => return Err(StepError::Unchecked),
+        };
+        let d = match self.resource(dst) {
+            Some(d) => d,
+            None => return Err(StepError::Disabled),
+        };
+        if !rtype.is_virtual() || !o.rtype.is_physical() || !d.rtype.is_physical() || !rtype.may_map_onto(d.rtype)
+            || d.size != o.size {
+            return Err(StepError::Disabled);
+        }
+        let by = match &me {
+            ExecMapEdge::SpaceBacking { sb_src, .. } => {
+                if self.is_lent(node) {
+                    return Err(StepError::Disabled);
+                }
+                ExecMapEdge::SpaceBacking { sb_src: *sb_src, sb_dst: dst }
+            },
+            ExecMapEdge::ResourceMap { rm_src, rm_attrs, .. } => {
+                match self.resource_mapping(*rm_src, dst, rm_attrs.perms, rm_attrs.cache, rm_attrs.cow) {
+                    Some(by) => by,
+                    None => return Err(StepError::Unchecked),
+                }
+            },
+            ExecMapEdge::SpaceMap { .. } | ExecMapEdge::Delivery { .. } => return Err(StepError::Disabled),
+        };
+        proof {
+            assert(by@ == pre.retarget(me@, dst as nat));
+        }
+        let ghost post = OsmosisDAG::State {
+            maps: pre.maps.remove(me@).insert(pre.retarget(me@, dst as nat)),
+            history: pre.history.push(step),
+            ..pre
+        };
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, post, step);
+        }
+        self.replace_map(&me, by, Ghost(post));
+        Ok(())
+    }
+
+    /// Takes the retype step
+    pub fn retype(&mut self, pd: u64, res: u64, space: u64, rtype: ExecResourceType, vals: ValSet, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            vals.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::retype(pd_of(pd), res as nat, space as nat, rtype@, vals@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::retype(pd_of(pd), res as nat, space as nat, rtype@, vals@, rights@);
+        let node = ExecResourceLike::Resource { res };
+        let retyped = match self.resource(res) {
+            Some(retyped) => retyped,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !retyped.rtype.can_retype_into(rtype) || self.is_mapped(node) || self.is_metadata(res)
+            || self.is_lent(node) || self.space_index(space).is_some() || vals.len() > retyped.size as u128
+            || !self.holds_with_rights(pd, node, &rights.with(ExecRight::Map)) || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        let ri = nat_entry_index(&self.retyped, res, Ghost(pre.retyped));
+
+        let entry = SpaceEntry { id: space, space: ExecSpace { rtype, vals } };
+        let me = ExecMapEdge::SpaceBacking { sb_src: space, sb_dst: res };
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Space { space }, rights };
+        let ghost spaces = self.spaces@;
+        let ghost maps = self.maps@;
+        proof {
+            lemma_mirrors_push(maps, pre.maps, me);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        let k = insert_sorted(&mut self.spaces, entry, Ghost(pre.spaces));
+        self.maps.push(me);
+        self.holds.push(he);
+        upsert(&mut self.retyped, ri, NatEntry { key: res, val: space }, Ghost(pre.retyped));
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.insert(space as nat, entry@.1),
+            maps: pre.maps.insert(me@),
+            holds: pre.holds.insert(he@),
+            retyped: pre.retyped.insert(res as nat, space as nat),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                if (i < k) {
+                    assert(self.spaces[i] == spaces[i]);
+                } else if (i > k) {
+                    assert(self.spaces[i] == spaces[i - 1]);
+                }
+            }
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < maps.len()) {
+                    assert(self.maps[i] == maps[i]);
+                }
+            }
+            assert(entry@.1 == (ResourceSpace { rtype: rtype@, vals: vals@ }));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_new_space(pre, self@, space as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        upsert_vals(&mut self.allocated, None, ValsEntry { space, vals: ValSet::new() }, Ghost(allocation_index(pre)));
+        upsert_vals(&mut self.carved, None, ValsEntry { space, vals: ValSet::new() }, Ghost(carving_index(pre)));
+        Ok(())
+    }
+
+    /// Whether [to] has enough quota left to come to hold [n], unless it holds it already or [n]
+    /// is a space which is not a reservation
+    fn fits_receiver(&self, to: u64, n: ExecResourceLike) -> (r: Result<(), StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> (n@ is Resource && !holds_resource(self@.holds, pd_of(to), n@->res) ==> ({
+                let res = self@.resources[n@->res];
+                self@.charged(pd_of(to), res.rtype()) + res.size() <= self@.quota(pd_of(to), res.rtype())
+            })),
+            r is Ok ==> (n@ is Space && carved_out(self@.partitions, n@->space) && !holds_space(self@.holds, pd_of(to), n@->space) ==> ({
+                let sp = self@.spaces[n@->space];
+                self@.charged(pd_of(to), sp.rtype()) + sp.vals().len() <= self@.quota(pd_of(to), sp.rtype())
+            })),
+    {
+        match n {
+            ExecResourceLike::Resource { res } => {
+                if !self.holds_resource(to, res) {
+                    return match self.resource(res) {
+                        Some(x) => self.fits_quota(to, x.rtype, x.size, 0),
+                        None => Err(StepError::Unchecked),
+                    };
+                }
+            },
+            ExecResourceLike::Space { space } => {
+                if !self.holds_space(to, space) {
+                    return self.fits_reservation(to, space, 0);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Whether [to] has enough quota left to hold [space] when it is a reservation, on top of
+    /// [credit] it is released from
+    fn fits_reservation(&self, to: u64, space: u64, credit: u64) -> (r: Result<(), StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> (carved_out(self@.partitions, space as nat) ==> ({
+                let sp = self@.spaces[space as nat];
+                self@.charged(pd_of(to), sp.rtype()) + sp.vals().len() <= self@.quota(pd_of(to), sp.rtype()) + credit
+            })),
+    {
+        if self.parent_space(space).is_none() {
+            return Ok(());
+        }
+        match self.space_index(space) {
+            Some(i) => {
+                let n = self.spaces[i].space.vals.len();
+                if n > u64::MAX as u128 {
+                    return Err(StepError::Unchecked);
+                }
+                self.fits_quota(to, self.spaces[i].space.rtype, n as u64, credit)
+            },
+            None => Err(StepError::Unchecked),
+        }
+    }
+
+    /// Whether every holder of [space] has enough quota left to be charged [size] for it, on top of
+    /// [credit] it is released from, and of the vals of [also] for holders which hold it too
+    fn fits_holders(&self, space: u64, rtype: ExecResourceType, size: u64, credit: u64, also: Option<(u64, u64)>) -> (r: Result<(), StepError>)
+        requires
+            self.wf(),
+        ensures
+            r is Ok ==> forall |p: ProtectionDomain| #[trigger] holds_space(self@.holds, p, space as nat) ==>
+                self@.charged(p, rtype@) + size <= self@.quota(p, rtype@) + credit + match also {
+                    Some((other, n)) => if holds_space(self@.holds, p, other as nat) { n as nat } else { 0 },
+                    None => 0,
+                },
+    {
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                i <= self.holds.len(),
+                forall |j: int| 0 <= j < i && #[trigger] self.holds[j]@.dst() == (ResourceLike::Space { space: space as nat }) ==>
+                    self@.charged(self.holds[j]@.src(), rtype@) + size <= self@.quota(self.holds[j]@.src(), rtype@) + credit + match also {
+                        Some((other, n)) => if holds_space(self@.holds, self.holds[j]@.src(), other as nat) { n as nat } else { 0 },
+                        None => 0,
+                    },
+            decreases self.holds.len() - i
+        {
+            if self.holds[i].dst.eq(ExecResourceLike::Space { space }) {
+                let p = self.holds[i].src;
+                let extra = match also {
+                    Some((other, n)) => if self.holds_space(p, other) { n } else { 0 },
+                    None => 0,
+                };
+                if credit as u128 + extra as u128 > u64::MAX as u128 {
+                    return Err(StepError::Unchecked);
+                }
+                if let Err(e) = self.fits_quota(p, rtype, size, credit + extra) {
+                    return Err(e);
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |p: ProtectionDomain| #[trigger] holds_space(self@.holds, p, space as nat) implies
+                self@.charged(p, rtype@) + size <= self@.quota(p, rtype@) + credit + match also {
+                    Some((other, n)) => if holds_space(self@.holds, p, other as nat) { n as nat } else { 0 },
+                    None => 0,
+                } by {
+                let he = choose |he: HoldEdge| #[trigger] self@.holds.contains(he) && he.src() == p
+                    && he.dst() == (ResourceLike::Space { space: space as nat });
+                let j = choose |j: int| 0 <= j < self.holds.len() && #[trigger] self.holds@[j]@ == he;
+                assert(self.holds[j]@ == he);
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the share_hold step
+    pub fn share_hold(&mut self, pd: u64, to: u64, he: ExecHoldEdge, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::share_hold(pd_of(pd), pd_of(to), he@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::share_hold(pd_of(pd), pd_of(to), he@, rights@);
+        if !self.has_domain(pd) || !self.has_domain(to) || !hold_in(&self.holds, &he) || he.src != pd
+            || !he.rights.contains(ExecRight::Grant) || !rights.subset_of(&he.rights)
+            || self.is_device(to) || self.is_lent(he.dst) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_receiver(to, he.dst) {
+            return Err(e);
+        }
+
+        let shared = ExecHoldEdge { src: to, dst: he.dst, rights };
+        let held = hold_in(&self.holds, &shared);
+        let ghost holds = self.holds@;
+        proof {
+            if (held) {
+                let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == shared@;
+                lemma_mirrored(holds, pre.holds, i);
+            }
+            let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == he@;
+            lemma_mirrored(holds, pre.holds, i);
+        }
+        derive(&mut self.derived_from, shared, he, held, Ghost(pre));
+        if !held {
+            proof {
+                lemma_mirrors_push(holds, pre.holds, shared);
+            }
+            self.holds.push(shared);
+        }
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.insert(shared@),
+            derived_from: pre.derive(shared@, he@),
+            depth: pre.deepen(shared@, he@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            if (held) {
+                assert(pre.holds.insert(shared@) =~= pre.holds);
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the grant_hold step
+    pub fn grant_hold(&mut self, pd: u64, to: u64, he: ExecHoldEdge, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::grant_hold(pd_of(pd), pd_of(to), he@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::grant_hold(pd_of(pd), pd_of(to), he@, rights@);
+        if !self.has_domain(pd) || !self.has_domain(to) || !hold_in(&self.holds, &he) || he.src != pd
+            || !he.rights.contains(ExecRight::Grant) || !rights.subset_of(&he.rights)
+            || self.is_device(to) || self.is_lent(he.dst) {
+            return Err(StepError::Disabled);
+        }
+        if to != pd && (self.pins(pd, he.dst) || self.handles(pd, he.dst) || self.granted_to(pd, he.dst)) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_receiver(to, he.dst) {
+            return Err(e);
+        }
+
+        let granted = ExecHoldEdge { src: to, dst: he.dst, rights };
+        if hold_in(&self.holds, &granted) {
+            return Err(StepError::Disabled);
+        }
+        let ghost holds = self.holds@;
+        let keep = holds_other_than(&self.holds, &he);
+        retain_flagged(&mut self.holds, &keep);
+        let ghost kept_holds = self.holds@;
+        proof {
+            let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == he@;
+            lemma_mirrored(holds, pre.holds, i);
+            lemma_kept_mirrors(holds, pre.holds, keep@, |k: HoldEdge| k != he@);
+            assert(pre.holds.filter(|k: HoldEdge| k != he@) =~= pre.holds.remove(he@));
+            if (pre.holds.contains(granted@)) {
+                let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == granted@;
+                assert(holds[i]@ == granted@);
+            }
+            lemma_mirrors_push(kept_holds, pre.holds.remove(he@), granted);
+        }
+        self.holds.push(granted);
+        self.derived_from = hand_over_derivations(&self.derived_from, he, granted, Ghost(pre));
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.remove(he@).insert(granted@),
+            derived_from: pre.hand_over_derivations(he@, granted@),
+            depth: pre.depth.insert(granted@, pre.rank(he@)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the revoke_hold step
+    pub fn revoke_hold(&mut self, pd: u64, h: ExecHoldEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::revoke_hold(pd_of(pd), h@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::revoke_hold(pd_of(pd), h@);
+        if !hold_in(&self.holds, &h) || h.src != pd || self.is_lent(h.dst) {
+            return Err(StepError::Disabled);
+        }
+        // Flag the hold edges which stay, checking the revoked ones on the way
+        let mut keep: Vec<bool> = Vec::new();
+        let mut i: usize = 0;
+        while i < self.holds.len()
+            invariant
+                self.wf(),
+                self@ == pre,
+                pre == old(self)@,
+                i <= self.holds.len(),
+                keep.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] keep[j] == (self.holds[j]@ == h@ || !pre.descends_from(self.holds[j]@, h@)),
+                forall |j: int| 0 <= j < i && !#[trigger] keep[j] ==> {
+                    let k = self.holds[j]@;
+                    &&& !pre.pins(k.src(), k.dst())
+                    &&& !pre.handles(k.src(), k.dst())
+                    &&& !pre.granted_to(k.src(), k.dst())
+                },
+            decreases self.holds.len() - i
+        {
+            let k = self.holds[i];
+            let stays = if k.eq(&h) {
+                true
+            } else {
+                match self.descends_from(&k, &h) {
+                    Some(d) => !d,
+                    None => return Err(StepError::Unchecked),
+                }
+            };
+            if !stays && (self.pins(k.src, k.dst) || self.handles(k.src, k.dst) || self.granted_to(k.src, k.dst)) {
+                return Err(StepError::Disabled);
+            }
+            keep.push(stays);
+            i = i + 1;
+        }
+
+        let ghost holds = self.holds@;
+        let ghost stays = |k: HoldEdge| k == h@ || !pre.descends_from(k, h@);
+        let ghost kept_holds = pre.holds.filter(stays);
+        proof {
+            assert forall |k: HoldEdge| #[trigger] pre.descends_from(k, h@) && pre.holds.contains(k) && k != h@ implies {
+                &&& !pre.pins(k.src(), k.dst())
+                &&& !pre.handles(k.src(), k.dst())
+                &&& !pre.granted_to(k.src(), k.dst())
+            } by {
+                let j = choose |j: int| 0 <= j < holds.len() && #[trigger] holds[j]@ == k;
+                assert(!keep[j]);
+            }
+            let j = choose |j: int| 0 <= j < holds.len() && #[trigger] holds[j]@ == h@;
+            lemma_mirrored(holds, pre.holds, j);
+        }
+
+        // Flag the mappings which survive, of which only Resource mappings may go
+        let ghost maps = self.maps@;
+        let ghost survives = |me: MapEdge| pre.map_survives(me, h@.dst(), kept_holds);
+        let keep_maps = maps_surviving(&self.maps, h.dst, &self.holds, &keep, Ghost(pre), Ghost(stays));
+        let mut i: usize = 0;
+        while i < self.maps.len()
+            invariant
+                self.wf(),
+                self@ == pre,
+                pre == old(self)@,
+                keep.len() == self.holds.len(),
+                forall |j: int| 0 <= j < self.holds.len() ==> #[trigger] keep[j] == stays(self.holds[j]@),
+                forall |j: int| 0 <= j < self.maps.len() ==> #[trigger] keep_maps[j] == survives(self.maps[j]@),
+                i <= self.maps.len(),
+                keep_maps.len() == self.maps.len(),
+                forall |j: int| 0 <= j < i && !#[trigger] keep_maps[j] ==> self.maps[j]@ is ResourceMap,
+            decreases self.maps.len() - i
+        {
+            if !keep_maps[i] {
+                match self.maps[i] {
+                    ExecMapEdge::ResourceMap { .. } => {},
+                    _ => return Err(StepError::Disabled),
+                }
+            }
+            i = i + 1;
+        }
+        proof {
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) && !survives(me) implies me is ResourceMap by {
+                let j = choose |j: int| 0 <= j < maps.len() && #[trigger] maps[j]@ == me;
+                assert(!keep_maps[j]);
+            }
+        }
+
+        retain_flagged(&mut self.holds, &keep);
+        retain_flagged(&mut self.maps, &keep_maps);
+        proof {
+            lemma_kept_mirrors(holds, pre.holds, keep@, stays);
+            lemma_kept_mirrors(maps, pre.maps, keep_maps@, survives);
+            lemma_kept_all(maps, keep_maps@, |m: ExecMapEdge| m.wf());
+        }
+        prune_derivations(&mut self.derived_from, &self.holds, Ghost(pre), Ghost(kept_holds));
+        self.state = Ghost(OsmosisDAG::State {
+            holds: kept_holds,
+            maps: pre.maps.filter(survives),
+            derived_from: pre.prune_derivations(kept_holds),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(kept_holds =~= pre.holds.filter(|k: HoldEdge| k == h@ || !pre.descends_from(k, h@)));
+            assert(pre.maps.filter(survives) =~= pre.maps.filter(
+                |me: MapEdge| pre.map_survives(me, h@.dst(), pre.holds.filter(|k: HoldEdge| k == h@ || !pre.descends_from(k, h@)))));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the advance_epoch step
+    pub fn advance_epoch(&mut self) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::advance_epoch(), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::advance_epoch();
+        if self.epoch == u64::MAX {
+            return Err(StepError::Unchecked);
+        }
+        self.epoch = self.epoch + 1;
+        self.state = Ghost(OsmosisDAG::State {
+            epoch: pre.epoch + 1,
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the lend_hold step
+    pub fn lend_hold(&mut self, lender: u64, borrower: u64, he: ExecHoldEdge, rights: Rights, expiry: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::lend_hold(pd_of(lender), pd_of(borrower), he@, rights@, expiry as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::lend_hold(pd_of(lender), pd_of(borrower), he@, rights@, expiry as nat);
+        let res = match he.dst {
+            ExecResourceLike::Resource { res } => res,
+            ExecResourceLike::Space { .. } => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(lender) || !self.has_domain(borrower) || lender == borrower || !hold_in(&self.holds, &he)
+            || he.src != lender || !he.rights.contains(ExecRight::Grant) || !rights.subset_of(&he.rights)
+            || self.epoch >= expiry || self.is_lent(he.dst) || self.is_mapped(he.dst) || self.granted(res, None)
+            || self.holds_with_rights(borrower, he.dst, &Rights::empty()) || self.is_device(borrower) {
+            return Err(StepError::Disabled);
+        }
+        let x = match self.resource(res) {
+            Some(x) => x,
+            None => return Err(StepError::Unchecked),
+        };
+        if let Err(e) = self.fits_quota(borrower, x.rtype, x.size, 0) {
+            return Err(e);
+        }
+
+        let lease = ExecHoldEdge { src: borrower, dst: he.dst, rights };
+        let li = self.lease_index(&lease);
+        let entry = LeaseEntry { he: lease, lease: ExecLease { lender, expiry } };
+        proof {
+            if (pre.holds.contains(lease@)) {
+                assert(Set::<Right>::empty().subset_of(lease@.rights()));
+            }
+            lemma_mirrors_push(self.holds@, pre.holds, lease);
+        }
+        derive(&mut self.derived_from, lease, he, false, Ghost(pre));
+        self.holds.push(lease);
+        upsert(&mut self.leases, li, entry, Ghost(pre.leases));
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.insert(lease@),
+            leases: pre.leases.insert(lease@, entry@.1),
+            derived_from: pre.derive(lease@, he@),
+            depth: pre.deepen(lease@, he@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the reclaim_lease step
+    pub fn reclaim_lease(&mut self, lender: u64, he: ExecHoldEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::reclaim_lease(pd_of(lender), he@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::reclaim_lease(pd_of(lender), he@);
+        let li = match self.lease_index(&he) {
+            Some(li) => li,
+            None => return Err(StepError::Disabled),
+        };
+        let lease = self.leases[li].lease;
+        if lease.lender != lender || lease.expiry > self.epoch {
+            return Err(StepError::Disabled);
+        }
+
+        let ghost holds = self.holds@;
+        let ghost maps = self.maps@;
+        let keep_holds = holds_other_than(&self.holds, &he);
+        let keep_maps = maps_not_touching(&self.maps, he.dst, false);
+        retain_flagged(&mut self.holds, &keep_holds);
+        retain_flagged(&mut self.maps, &keep_maps);
+        let ghost untouched = |me: MapEdge| !me.touches(he@.dst());
+        proof {
+            lemma_kept_mirrors(holds, pre.holds, keep_holds@, |k: HoldEdge| k != he@);
+            assert(pre.holds.filter(|k: HoldEdge| k != he@) =~= pre.holds.remove(he@));
+            lemma_kept_mirrors(maps, pre.maps, keep_maps@, untouched);
+            lemma_kept_all(maps, keep_maps@, |m: ExecMapEdge| m.wf());
+        }
+        remove_entry(&mut self.leases, Some(li), Ghost(pre.leases), Ghost(he@));
+        prune_derivations(&mut self.derived_from, &self.holds, Ghost(pre), Ghost(pre.holds.remove(he@)));
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.remove(he@),
+            maps: pre.maps.filter(untouched),
+            leases: pre.leases.remove(he@),
+            derived_from: pre.prune_derivations(pre.holds.remove(he@)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert(pre.maps.filter(untouched) =~= pre.maps.filter(|me: MapEdge| !me.touches(he@.dst())));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the delegate_quota step
+    pub fn delegate_quota(&mut self, from: u64, to: u64, rtype: ExecResourceType, amount: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::delegate_quota(pd_of(from), pd_of(to), rtype@, amount as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::delegate_quota(pd_of(from), pd_of(to), rtype@, amount as nat);
+        if !self.has_domain(from) || !self.has_domain(to) || from == to {
+            return Err(StepError::Disabled);
+        }
+        // Quota is only handed down to a ProtectionDomain from the one which created it
+        match self.parent_index(to) {
+            Some(i) => {
+                if self.parents[i].parent != from {
+                    return Err(StepError::Disabled);
+                }
+            },
+            None => return Err(StepError::Disabled),
+        }
+        if let Err(e) = self.fits_quota(from, rtype, amount, 0) {
+            return Err(e);
+        }
+        let kept = self.quota(from, rtype);
+        let received = self.quota(to, rtype);
+        if received > u64::MAX - amount {
+            return Err(StepError::Unchecked);
+        }
+        let fi = self.quota_index(from, rtype);
+        let ti = self.quota_index(to, rtype);
+
+        let ghost from_key = (pd_of(from), rtype@);
+        let ghost to_key = (pd_of(to), rtype@);
+        let ghost quotas = pre.quotas.insert(from_key, (pre.quota(pd_of(from), rtype@) - amount) as nat);
+        let ghost before = self.quotas@;
+        upsert(&mut self.quotas, fi, QuotaEntry { pd: from, rtype, amount: kept - amount }, Ghost(pre.quotas));
+        proof {
+            assert(from_key != to_key);
+            if (ti is Some) {
+                let i = ti->Some_0 as int;
+                if (fi is Some) {
+                    assert(fi->Some_0 as int != i);
+                }
+                assert(self.quotas[i] == before[i]);
+            }
+        }
+        upsert(&mut self.quotas, ti, QuotaEntry { pd: to, rtype, amount: received + amount }, Ghost(quotas));
+        self.state = Ghost(OsmosisDAG::State {
+            quotas: quotas.insert(to_key, pre.quota(pd_of(to), rtype@) + amount),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the assign_device step
+    pub fn assign_device(&mut self, driver: u64, device: u64, iommu: u64, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::assign_device(pd_of(driver), pd_of(device), iommu as nat, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::assign_device(pd_of(driver), pd_of(device), iommu as nat, rights@);
+        let node = ExecResourceLike::Space { space: iommu };
+        if !self.has_domain(driver) || !self.has_domain(device) || driver == device || self.is_device(device)
+            || self.holds_anything(device) || self.space_index(iommu).is_none() || self.is_iommu(iommu)
+            || !self.unmapped_src(node) {
+            return Err(StepError::Disabled);
+        }
+        let parent = match self.hold_edge_with_rights(driver, node, &rights.with(ExecRight::Map)) {
+            Ok(parent) => parent,
+            Err(e) => return Err(e),
+        };
+        if let Err(e) = self.fits_reservation(device, iommu, 0) {
+            return Err(e);
+        }
+
+        let he = ExecHoldEdge { src: device, dst: node, rights };
+        let entry = DeviceEntry { pd: device, device: ExecDevice { driver, iommu } };
+        proof {
+            lemma_mirrors_map_push(self.devices@, pre.devices, entry);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        derive(&mut self.derived_from, he, parent, false, Ghost(pre));
+        self.devices.push(entry);
+        self.holds.push(he);
+        self.state = Ghost(OsmosisDAG::State {
+            devices: pre.devices.insert(pd_of(device), entry@.1),
+            holds: pre.holds.insert(he@),
+            derived_from: pre.derive(he@, parent@),
+            depth: pre.deepen(he@, parent@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the program_iommu step
+    pub fn program_iommu(&mut self, driver: u64, device: u64, me: ExecMapEdge) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            me.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::program_iommu(pd_of(driver), pd_of(device), me@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::program_iommu(pd_of(driver), pd_of(device), me@);
+        let dev = match self.device(device) {
+            Some(dev) => dev,
+            None => return Err(StepError::Disabled),
+        };
+        if me.is_delivery() {
+            return Err(StepError::Disabled);
+        }
+        let src = me.src_node();
+        let dst = me.dst_node();
+        if dev.driver != driver || !src.eq(ExecResourceLike::Space { space: dev.iommu })
+            || !self.node_in_graph(src) || !self.node_in_graph(dst) || !self.map_well_formed(&me, None)
+            || !self.holds_with_rights(driver, dst, &me.perms().with(ExecRight::Map))
+            || self.is_retyped(dst) || self.is_metadata_node(dst) {
+            return Err(StepError::Disabled);
+        }
+        let disabled = match &me {
+            ExecMapEdge::SpaceMap { .. } => !self.translates_disjointly(&me) || !self.completes_fan_out(&me),
+            ExecMapEdge::SpaceBacking { .. } => self.is_lent(dst),
+            ExecMapEdge::ResourceMap { .. } => false,
+            ExecMapEdge::Delivery { .. } => true,
+        };
+        if disabled {
+            return Err(StepError::Disabled);
+        }
+
+        let ghost maps = self.maps@;
+        proof {
+            lemma_mirrors_push(maps, pre.maps, me);
+        }
+        self.maps.push(me);
+        self.state = Ghost(OsmosisDAG::State {
+            maps: pre.maps.insert(me@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < maps.len()) {
+                    assert(self.maps[i] == maps[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the route_interrupt step
+    pub fn route_interrupt(&mut self, pd: u64, irq: u64, handler: u64, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::route_interrupt(pd_of(pd), irq as nat, pd_of(handler), rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::route_interrupt(pd_of(pd), irq as nat, pd_of(handler), rights@);
+        let node = ExecResourceLike::Resource { res: irq };
+        let line = match self.resource(irq) {
+            Some(line) => line,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !self.has_domain(handler) || !line.rtype.delivered() || self.is_routed(irq)
+            || self.is_lent(node) || self.is_device(handler) {
+            return Err(StepError::Disabled);
+        }
+        let parent = match self.hold_edge_with_rights(pd, node, &rights.with(ExecRight::Grant)) {
+            Ok(parent) => parent,
+            Err(e) => return Err(e),
+        };
+        if let Err(e) = self.fits_receiver(handler, node) {
+            return Err(e);
+        }
+
+        let he = ExecHoldEdge { src: handler, dst: node, rights };
+        let me = ExecMapEdge::Delivery { dl_src: irq, dl_dst: handler };
+        let held = hold_in(&self.holds, &he);
+        let ghost holds = self.holds@;
+        let ghost maps = self.maps@;
+        proof {
+            if (held) {
+                let i = choose |i: int| 0 <= i < holds.len() && #[trigger] holds[i]@ == he@;
+                lemma_mirrored(holds, pre.holds, i);
+                assert(pre.holds.insert(he@) =~= pre.holds);
+            }
+            lemma_mirrors_push(maps, pre.maps, me);
+        }
+        derive(&mut self.derived_from, he, parent, held, Ghost(pre));
+        if !held {
+            proof {
+                lemma_mirrors_push(holds, pre.holds, he);
+            }
+            self.holds.push(he);
+        }
+        self.maps.push(me);
+        self.state = Ghost(OsmosisDAG::State {
+            holds: pre.holds.insert(he@),
+            derived_from: pre.derive(he@, parent@),
+            depth: pre.deepen(he@, parent@),
+            maps: pre.maps.insert(me@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < maps.len()) {
+                    assert(self.maps[i] == maps[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the unroute_interrupt step
+    pub fn unroute_interrupt(&mut self, pd: u64, irq: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::unroute_interrupt(pd_of(pd), irq as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::unroute_interrupt(pd_of(pd), irq as nat);
+        if !self.has_domain(pd)
+            || !self.holds_with_rights(pd, ExecResourceLike::Resource { res: irq }, &Rights::empty().with(ExecRight::Grant)) {
+            return Err(StepError::Disabled);
+        }
+        let ghost maps = self.maps@;
+        let ghost undelivered = |me: MapEdge| !(me is Delivery && me->dl_src == irq as nat);
+        let keep = maps_not_delivering(&self.maps, irq);
+        retain_flagged(&mut self.maps, &keep);
+        self.state = Ghost(OsmosisDAG::State {
+            maps: pre.maps.filter(undelivered),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            lemma_kept_mirrors(maps, pre.maps, keep@, undelivered);
+            lemma_kept_all(maps, keep@, |m: ExecMapEdge| m.wf());
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_frame(pre, self@);
+        }
+        Ok(())
+    }
+
+    /// Takes the create_resource_space step
+    pub fn create_resource_space(&mut self, pd: u64, space: u64, res: ExecSpace, me: ExecMapEdge, meta: ValSet, rights: Rights)
+        -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            res.wf(),
+            me.wf(),
+            meta.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_resource_space(pd_of(pd), space as nat, res@, me@, meta@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_resource_space(pd_of(pd), space as nat, res@, me@, meta@, rights@);
+        if me.is_delivery() {
+            return Err(StepError::Disabled);
+        }
+        let dst = me.dst_node();
+        if !self.has_domain(pd) || self.space_index(space).is_some() || !res.rtype.is_virtual()
+            || !me.src_node().eq(ExecResourceLike::Space { space }) || !self.node_in_graph(dst)
+            || !self.map_well_formed(&me, Some((space, &res)))
+            || !self.holds_with_rights(pd, dst, &me.perms().with(ExecRight::Map)) || !self.holds_with_rights(pd, dst, &rights)
+            || self.is_retyped(dst) || self.is_metadata_node(dst) || self.is_lent(dst) || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        if let ExecResourceLike::Resource { res: backing } = dst {
+            if meta.contains(backing) {
+                return Err(StepError::Disabled);
+            }
+        }
+        if let ExecMapEdge::SpaceMap { sm_vals, .. } = &me {
+            if !sm_vals.eq(&res.vals) {
+                return Err(StepError::Disabled);
+            }
+        }
+        // The metadata must be fresh physical Resources held by pd
+        let ids = meta.to_vec();
+        let mut i: usize = 0;
+        while i < ids.len()
+            invariant
+                self.wf(),
+                self@ == pre,
+                pre == old(self)@,
+                i <= ids.len(),
+                forall |j: int| 0 <= j < i ==> {
+                    let m = #[trigger] ids[j] as nat;
+                    &&& pre.resources.contains_key(m)
+                    &&& pre.resources[m].rtype() is Physical
+                    &&& pre.holds_with_rights(pd_of(pd), ResourceLike::Resource { res: m }, Set::empty())
+                    &&& !pre.is_mapped(ResourceLike::Resource { res: m })
+                    &&& !pre.is_metadata(m)
+                    &&& !pre.is_lent(ResourceLike::Resource { res: m })
+                },
+            decreases ids.len() - i
+        {
+            let m = ids[i];
+            let node = ExecResourceLike::Resource { res: m };
+            let ok = match self.resource(m) {
+                Some(x) => x.rtype.is_physical() && self.holds_with_rights(pd, node, &Rights::empty())
+                    && !self.is_mapped(node) && !self.is_metadata(m) && !self.is_lent(node),
+                None => false,
+            };
+            if !ok {
+                return Err(StepError::Disabled);
+            }
+            i = i + 1;
+        }
+        let cost = match self.metadata_cost(res.rtype, res.vals.len(), Ghost(res.vals@)) {
+            Some(cost) => cost,
+            None => return Err(StepError::Unchecked),
+        };
+        let size = match self.metadata_size(&meta) {
+            Some(size) => size,
+            None => return Err(StepError::Unchecked),
+        };
+        if cost > size {
+            return Err(StepError::Disabled);
+        }
+        let mi = self.metadata_index(space);
+
+        let ghost view = res@;
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Space { space }, rights };
+        let entry = SpaceEntry { id: space, space: res };
+        let ghost spaces = self.spaces@;
+        let ghost maps = self.maps@;
+        let ghost metadata = self.metadata@;
+        proof {
+            meta.lemma_finite();
+            lemma_mirrors_push(maps, pre.maps, me);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        let k = insert_sorted(&mut self.spaces, entry, Ghost(pre.spaces));
+        self.maps.push(me);
+        self.holds.push(he);
+        let charged = MetadataEntry { space, meta };
+        upsert(&mut self.metadata, mi, charged, Ghost(pre.metadata));
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.insert(space as nat, view),
+            maps: pre.maps.insert(me@),
+            holds: pre.holds.insert(he@),
+            metadata: pre.metadata.insert(space as nat, charged@.1),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                if (i < k) {
+                    assert(self.spaces[i] == spaces[i]);
+                } else if (i > k) {
+                    assert(self.spaces[i] == spaces[i - 1]);
+                }
+            }
+            assert forall |i: int| 0 <= i < self.maps.len() implies #[trigger] self.maps[i].wf() by {
+                if (i < maps.len()) {
+                    assert(self.maps[i] == maps[i]);
+                }
+            }
+            assert forall |i: int| 0 <= i < self.metadata.len() implies #[trigger] self.metadata[i].meta.wf() by {
+                if (mi is None && i < metadata.len()) || (mi is Some && i != mi->Some_0) {
+                    assert(self.metadata[i] == metadata[i]);
+                }
+            }
+            assert forall |g: ResourceId| #[trigger] charged@.1.contains(g) implies {
+                &&& pre.resources.contains_key(g)
+                &&& pre.resources[g].rtype() is Physical
+                &&& pre.holds_with_rights(pd_of(pd), ResourceLike::Resource { res: g }, Set::empty())
+                &&& !pre.is_mapped(ResourceLike::Resource { res: g })
+                &&& !pre.is_metadata(g)
+                &&& !pre.is_lent(ResourceLike::Resource { res: g })
+            } by {
+                let j = choose |j: int| 0 <= j < ids.len() && ids[j] as nat == g;
+                assert(ids[j] as nat == g);
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_new_space(pre, self@, space as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        upsert_vals(&mut self.allocated, None, ValsEntry { space, vals: ValSet::new() }, Ghost(allocation_index(pre)));
+        upsert_vals(&mut self.carved, None, ValsEntry { space, vals: ValSet::new() }, Ghost(carving_index(pre)));
+        Ok(())
+    }
+
+    /// Takes the split_space step
+    pub fn split_space(&mut self, pd: u64, parent: u64, child: u64, vals: ValSet, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            vals.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::split_space(pd_of(pd), parent as nat, child as nat, vals@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::split_space(pd_of(pd), parent as nat, child as nat, vals@, rights@);
+        let pi = match self.space_index(parent) {
+            Some(pi) => pi,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || self.space_index(child).is_some()
+            || !self.holds_with_rights(pd, ExecResourceLike::Space { space: parent }, &rights)
+            || !vals.subset_of(&self.spaces[pi].space.vals) || !self.allocations_against(parent, &vals, false)
+            || !self.children_avoid(parent, 0, 0, Some(&vals), None) || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        // The child is a reservation, charged to pd
+        let n = vals.len();
+        if n > u64::MAX as u128 {
+            return Err(StepError::Unchecked);
+        }
+        if let Err(e) = self.fits_quota(pd, self.spaces[pi].space.rtype, n as u64, 0) {
+            return Err(e);
+        }
+        let ci = match vals_entry_index(&self.carved, parent, Ghost(carving_index(pre))) {
+            Some(ci) => ci,
+            None => return Err(StepError::Unchecked),
+        };
+        let carved = self.carved[ci].vals.union(&vals);
+        let ghost carved_view = carved@;
+
+        let entry = SpaceEntry { id: child, space: ExecSpace { rtype: self.spaces[pi].space.rtype, vals } };
+        let pe = ExecPartitionEdge { src: child, dst: parent };
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Space { space: child }, rights };
+        let ghost spaces = self.spaces@;
+        proof {
+            lemma_mirrors_push(self.partitions@, pre.partitions, pe);
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        let k = insert_sorted(&mut self.spaces, entry, Ghost(pre.spaces));
+        self.partitions.push(pe);
+        self.holds.push(he);
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.insert(child as nat, ResourceSpace { rtype: pre.spaces[parent as nat].rtype(), vals: entry@.1.vals() }),
+            partitions: pre.partitions.insert(pe@),
+            holds: pre.holds.insert(he@),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                if (i < k) {
+                    assert(self.spaces[i] == spaces[i]);
+                } else if (i > k) {
+                    assert(self.spaces[i] == spaces[i - 1]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_split(pre, self@, parent as nat, child as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+            assert(carving_index(pre).insert(parent as nat, carved_view).insert(child as nat, Set::empty()) =~= carving_index(self@));
+        }
+        upsert_vals(&mut self.allocated, None, ValsEntry { space: child, vals: ValSet::new() }, Ghost(allocation_index(pre)));
+        upsert_vals(&mut self.carved, Some(ci), ValsEntry { space: parent, vals: carved }, Ghost(carving_index(pre)));
+        upsert_vals(&mut self.carved, None, ValsEntry { space: child, vals: ValSet::new() },
+            Ghost(carving_index(pre).insert(parent as nat, carved_view)));
+        Ok(())
+    }
+
+    /// Takes the create_budget step
+    pub fn create_budget(&mut self, pd: u64, source: u64, budget: u64, vals: ValSet, rights: Rights) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            vals.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::create_budget(pd_of(pd), source as nat, budget as nat, vals@, rights@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::create_budget(pd_of(pd), source as nat, budget as nat, vals@, rights@);
+        let si = match self.space_index(source) {
+            Some(si) => si,
+            None => return Err(StepError::Disabled),
+        };
+        let rtype = self.spaces[si].space.rtype;
+        let core = match rtype {
+            ExecResourceType::Time(core) => core,
+            _ => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || self.space_index(budget).is_some()
+            || !self.holds_with_rights(pd, ExecResourceLike::Space { space: source }, &rights) || self.is_device(pd) {
+            return Err(StepError::Disabled);
+        }
+        match nat_entry_index(&self.periods, core, Ghost(pre.periods)) {
+            Some(ci) => {
+                if !vals.below(self.periods[ci].val) {
+                    return Err(StepError::Disabled);
+                }
+            },
+            None => return Err(StepError::Unchecked),
+        }
+        // Admission control: none of the slots may be admitted to another reservation on the core
+        if !self.admits(core, &vals, None) {
+            return Err(StepError::Disabled);
+        }
+        // The budget is spent out of the quota of pd
+        let n = vals.len();
+        if n > u64::MAX as u128 {
+            return Err(StepError::Unchecked);
+        }
+        if let Err(e) = self.fits_quota(pd, rtype, n as u64, 0) {
+            return Err(e);
+        }
+        let kept = self.quota(pd, rtype);
+        let qi = self.quota_index(pd, rtype);
+
+        let entry = SpaceEntry { id: budget, space: ExecSpace { rtype, vals } };
+        let he = ExecHoldEdge { src: pd, dst: ExecResourceLike::Space { space: budget }, rights };
+        let ghost spaces = self.spaces@;
+        proof {
+            lemma_mirrors_push(self.holds@, pre.holds, he);
+        }
+        let k = insert_sorted(&mut self.spaces, entry, Ghost(pre.spaces));
+        self.holds.push(he);
+        upsert(&mut self.quotas, qi, QuotaEntry { pd, rtype, amount: kept - n as u64 }, Ghost(pre.quotas));
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.insert(budget as nat, entry@.1),
+            holds: pre.holds.insert(he@),
+            quotas: pre.quotas.insert((pd_of(pd), rtype@), (pre.quota(pd_of(pd), rtype@) - n) as nat),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                if (i < k) {
+                    assert(self.spaces[i] == spaces[i]);
+                } else if (i > k) {
+                    assert(self.spaces[i] == spaces[i - 1]);
+                }
+            }
+            assert(entry@.1 == (ResourceSpace { rtype: rtype@, vals: vals@ }));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_new_space(pre, self@, budget as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        upsert_vals(&mut self.allocated, None, ValsEntry { space: budget, vals: ValSet::new() }, Ghost(allocation_index(pre)));
+        upsert_vals(&mut self.carved, None, ValsEntry { space: budget, vals: ValSet::new() }, Ghost(carving_index(pre)));
+        Ok(())
+    }
+
+    /// Takes the resize_space step
+    pub fn resize_space(&mut self, pd: u64, space: u64, vals: ValSet) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+            vals.wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::resize_space(pd_of(pd), space as nat, vals@), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::resize_space(pd_of(pd), space as nat, vals@);
+        let si = match self.space_index(space) {
+            Some(si) => si,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !self.holds_with_rights(pd, ExecResourceLike::Space { space }, &Rights::empty().with(ExecRight::Write))
+            || !self.allocations_against(space, &vals, true) || !self.children_within(space, &vals) {
+            return Err(StepError::Disabled);
+        }
+        if let Err(e) = self.fits_parents(space, &vals) {
+            return Err(e);
+        }
+        let rtype = self.spaces[si].space.rtype;
+        let parent = self.parent_space(space);
+        if rtype.is_physical() && parent.is_none() && !vals.subset_of(&self.spaces[si].space.vals) {
+            return Err(StepError::Disabled);
+        }
+        if self.fans_out(space) && !vals.subset_of(&self.spaces[si].space.vals) {
+            return Err(StepError::Disabled);
+        }
+        if let ExecResourceType::Time(core) = rtype {
+            match nat_entry_index(&self.periods, core, Ghost(pre.periods)) {
+                Some(ci) => {
+                    if !vals.below(self.periods[ci].val) {
+                        return Err(StepError::Disabled);
+                    }
+                },
+                None => return Err(StepError::Unchecked),
+            }
+            if parent.is_none() && !self.admits(core, &vals, Some(space)) {
+                return Err(StepError::Disabled);
+            }
+        }
+        if let Some(mi) = self.metadata_index(space) {
+            let cost = match self.metadata_cost(rtype, vals.len(), Ghost(vals@)) {
+                Some(cost) => cost,
+                None => return Err(StepError::Unchecked),
+            };
+            let size = match self.metadata_size(&self.metadata[mi].meta) {
+                Some(size) => size,
+                None => return Err(StepError::Unchecked),
+            };
+            if cost > size {
+                return Err(StepError::Disabled);
+            }
+        }
+        // What is carved out of the parent loses the old vals and gains the new ones
+        let carved = match parent {
+            Some(p) => match vals_entry_index(&self.carved, p, Ghost(carving_index(pre))) {
+                Some(ci) => Some((ci, ValsEntry { space: p, vals: self.carved[ci].vals.difference(&self.spaces[si].space.vals).union(&vals) })),
+                None => return Err(StepError::Unchecked),
+            },
+            None => None,
+        };
+        // A reservation is charged anew to each of its holders
+        if parent.is_some() {
+            let (n, old_n) = (vals.len(), self.spaces[si].space.vals.len());
+            if n > u64::MAX as u128 || old_n > u64::MAX as u128 {
+                return Err(StepError::Unchecked);
+            }
+            if let Err(e) = self.fits_holders(space, rtype, n as u64, old_n as u64, None) {
+                return Err(e);
+            }
+        }
+        let resized = ExecSpace { rtype, vals };
+        if !self.maps_well_formed_over(space, &resized) {
+            return Err(StepError::Disabled);
+        }
+
+        let entry = SpaceEntry { id: space, space: resized };
+        let ghost spaces = self.spaces@;
+        proof {
+            lemma_mirrored_key(spaces, pre.spaces, si as int);
+            lemma_mirrors_map_update(spaces, pre.spaces, si as int, entry);
+            lemma_keys_sorted_update(spaces, si as int, entry);
+        }
+        self.spaces.set(si, entry);
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.insert(space as nat, ResourceSpace { rtype: pre.spaces[space as nat].rtype(), vals: entry@.1.vals() }),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                if (i != si) {
+                    assert(self.spaces[i] == spaces[i]);
+                }
+            }
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_resize(pre, self@, space as nat, match parent {
+                Some(p) => Some(p as nat),
+                None => None,
+            });
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        if let Some((ci, e)) = carved {
+            proof {
+                assert(carving_index(pre).insert(e@.0, e@.1) == carving_index(self@));
+            }
+            upsert_vals(&mut self.carved, Some(ci), e, Ghost(carving_index(pre)));
+        }
+        Ok(())
+    }
+
+    /// Takes the merge_spaces step
+    pub fn merge_spaces(&mut self, pd: u64, parent: u64, keep: u64, other: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::merge_spaces(pd_of(pd), parent as nat, keep as nat, other as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::merge_spaces(pd_of(pd), parent as nat, keep as nat, other as nat);
+        let node = ExecResourceLike::Space { space: other };
+        let gone = ExecPartitionEdge { src: other, dst: parent };
+        if !self.has_domain(pd) || keep == other || !self.has_partition(ExecPartitionEdge { src: keep, dst: parent })
+            || !self.has_partition(gone)
+            || !self.holds_with_rights(pd, ExecResourceLike::Space { space: keep }, &Rights::empty().with(ExecRight::Write))
+            || !self.holds_with_rights(pd, node, &Rights::empty().with(ExecRight::Destroy)) || !self.held_only_by(pd, node)
+            || !self.unallocated(other) || self.is_iommu(other) || !self.unpartitioned(other, true) || self.is_mapped(node)
+            || self.fans_out(keep) {
+            return Err(StepError::Disabled);
+        }
+        let (ki, oi) = match (self.space_index(keep), self.space_index(other)) {
+            (Some(ki), Some(oi)) => (ki, oi),
+            _ => return Err(StepError::Unchecked),
+        };
+        let merged = ExecSpace { rtype: self.spaces[ki].space.rtype, vals: self.spaces[ki].space.vals.union(&self.spaces[oi].space.vals) };
+        if !self.maps_well_formed_over(keep, &merged) {
+            return Err(StepError::Disabled);
+        }
+        // The holders of keep are charged for the merged vals, and released from other if they held it
+        let (n, keep_n, other_n) = (merged.vals.len(), self.spaces[ki].space.vals.len(), self.spaces[oi].space.vals.len());
+        if n > u64::MAX as u128 || keep_n > u64::MAX as u128 || other_n > u64::MAX as u128 {
+            return Err(StepError::Unchecked);
+        }
+        if let Err(e) = self.fits_holders(keep, merged.rtype, n as u64, keep_n as u64, Some((other, other_n as u64))) {
+            return Err(e);
+        }
+        let (ai, ci) = match (vals_entry_index(&self.allocated, other, Ghost(allocation_index(pre))),
+            vals_entry_index(&self.carved, other, Ghost(carving_index(pre)))) {
+            (Some(ai), Some(ci)) => (ai, ci),
+            _ => return Err(StepError::Unchecked),
+        };
+
+        let ghost merged_spaces = pre.spaces.remove(other as nat).insert(keep as nat, merged@);
+        proof {
+            let over = pre.spaces.insert(keep as nat, merged@);
+            assert forall |me: MapEdge| #[trigger] pre.maps.contains(me) implies ({
+                ||| me is SpaceBacking && me->sb_src != other as nat
+                ||| me is SpaceMap && me->sm_src != other as nat && me->sm_dst != other as nat
+                ||| me is ResourceMap
+                ||| me is Delivery
+            }) && me.well_formed(pre.resources, merged_spaces) by {
+                assert(!me.touches(node@));
+                assert(me.well_formed(pre.resources, over));
+                match me {
+                    MapEdge::SpaceBacking { sb_src, .. } => {
+                        assert(merged_spaces[sb_src] == over[sb_src]);
+                    },
+                    MapEdge::SpaceMap { sm_src, sm_dst, .. } => {
+                        assert(merged_spaces[sm_src] == over[sm_src]);
+                        assert(merged_spaces[sm_dst] == over[sm_dst]);
+                    },
+                    MapEdge::ResourceMap { .. } | MapEdge::Delivery { .. } => {},
+                }
+            }
+        }
+
+        // Merge first, the indices of the spaces shift once the other is removed
+        let ghost spaces = self.spaces@;
+        let entry = SpaceEntry { id: keep, space: merged };
+        proof {
+            lemma_mirrors_map_update(spaces, pre.spaces, ki as int, entry);
+            lemma_keys_sorted_update(spaces, ki as int, entry);
+        }
+        self.spaces.set(ki, entry);
+        let ghost updated = self.spaces@;
+        proof {
+            lemma_mirrors_map_remove(updated, pre.spaces.insert(keep as nat, entry@.1), oi as int);
+            lemma_keys_sorted_remove(updated, oi as int);
+            assert(pre.spaces.insert(keep as nat, entry@.1).remove(other as nat) =~= merged_spaces);
+        }
+        self.spaces.remove(oi);
+
+        let ghost partitions = self.partitions@;
+        let ghost holds = self.holds@;
+        let ghost kept_holds = pre.holds.filter(|he: HoldEdge| he.dst() != node@);
+        let keep_partitions = partitions_other_than(&self.partitions, gone);
+        let keep_holds = holds_not_on(&self.holds, node);
+        retain_flagged(&mut self.partitions, &keep_partitions);
+        retain_flagged(&mut self.holds, &keep_holds);
+        proof {
+            lemma_kept_mirrors(partitions, pre.partitions, keep_partitions@, |x: PartitionEdge| x != gone@);
+            assert(pre.partitions.filter(|x: PartitionEdge| x != gone@) =~= pre.partitions.remove(gone@));
+            lemma_kept_mirrors(holds, pre.holds, keep_holds@, |he: HoldEdge| he.dst() != node@);
+        }
+        prune_derivations(&mut self.derived_from, &self.holds, Ghost(pre), Ghost(kept_holds));
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: merged_spaces,
+            partitions: pre.partitions.remove(gone@),
+            holds: kept_holds,
+            derived_from: pre.prune_derivations(kept_holds),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                let j = if i < oi { i } else { i + 1 };
+                assert(self.spaces[i] == updated[j]);
+                if (j != ki) {
+                    assert(updated[j] == spaces[j]);
+                }
+            }
+            assert(merged@ == (ResourceSpace {
+                rtype: pre.spaces[keep as nat].rtype(),
+                vals: pre.spaces[keep as nat].vals().union(pre.spaces[other as nat].vals()),
+            }));
+            assert(kept_holds =~= pre.holds.filter(|he: HoldEdge| he.dst() != ResourceLike::Space { space: other as nat }));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_merge(pre, self@, parent as nat, keep as nat, other as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        remove_vals(&mut self.allocated, ai, Ghost(allocation_index(pre)), Ghost(other as nat));
+        remove_vals(&mut self.carved, ci, Ghost(carving_index(pre)), Ghost(other as nat));
+        Ok(())
+    }
+
+    /// Takes the destroy_resource_space step
+    pub fn destroy_resource_space(&mut self, pd: u64, space: u64) -> (r: Result<(), StepError>)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            stepped(old(self)@, self@, OsmosisDAG::Step::destroy_resource_space(pd_of(pd), space as nat), r),
+    {
+        let ghost pre = self@;
+        let ghost step = OsmosisDAG::Step::destroy_resource_space(pd_of(pd), space as nat);
+        let node = ExecResourceLike::Space { space };
+        let si = match self.space_index(space) {
+            Some(si) => si,
+            None => return Err(StepError::Disabled),
+        };
+        if !self.has_domain(pd) || !self.spaces[si].space.rtype.is_virtual()
+            || !self.holds_with_rights(pd, node, &Rights::empty().with(ExecRight::Destroy))
+            || !self.unallocated(space) || !self.unpartitioned(space, false) || !self.unmapped_dst(node)
+            || self.is_iommu(space) {
+            return Err(StepError::Disabled);
+        }
+        let mi = self.metadata_index(space);
+        let (ai, ci) = match (vals_entry_index(&self.allocated, space, Ghost(allocation_index(pre))),
+            vals_entry_index(&self.carved, space, Ghost(carving_index(pre)))) {
+            (Some(ai), Some(ci)) => (ai, ci),
+            _ => return Err(StepError::Unchecked),
+        };
+        let mut keep_retyped: Vec<bool> = Vec::new();
+        let mut i: usize = 0;
+        while i < self.retyped.len()
+            invariant
+                i <= self.retyped.len(),
+                keep_retyped.len() == i,
+                forall |j: int| 0 <= j < i ==> #[trigger] keep_retyped[j] == (self.retyped[j].val != space),
+            decreases self.retyped.len() - i
+        {
+            keep_retyped.push(self.retyped[i].val != space);
+            i = i + 1;
+        }
+
+        let ghost spaces = self.spaces@;
+        let ghost holds = self.holds@;
+        let ghost maps = self.maps@;
+        let ghost metadata = self.metadata@;
+        let ghost retyped = self.retyped@;
+        let ghost kept_holds = pre.holds.filter(|he: HoldEdge| he.dst() != node@);
+        let ghost released = |r: ResourceId| pre.retyped[r] != space as nat;
+        let keep_holds = holds_not_on(&self.holds, node);
+        let keep_maps = maps_not_touching(&self.maps, node, true);
+        self.spaces.remove(si);
+        retain_flagged(&mut self.holds, &keep_holds);
+        retain_flagged(&mut self.maps, &keep_maps);
+        retain_flagged(&mut self.retyped, &keep_retyped);
+        proof {
+            lemma_mirrors_map_remove(spaces, pre.spaces, si as int);
+            lemma_keys_sorted_remove(spaces, si as int);
+            lemma_kept_mirrors(holds, pre.holds, keep_holds@, |he: HoldEdge| he.dst() != node@);
+            lemma_kept_mirrors(maps, pre.maps, keep_maps@, |me: MapEdge| me.src_node() != node@);
+            lemma_kept_all(maps, keep_maps@, |m: ExecMapEdge| m.wf());
+            assert forall |j: int| 0 <= j < retyped.len() implies #[trigger] keep_retyped[j] == released(retyped[j]@.0) by {
+                lemma_mirrored_key(retyped, pre.retyped, j);
+            }
+            lemma_kept_mirrors_map(retyped, pre.retyped, keep_retyped@, released);
+        }
+        prune_derivations(&mut self.derived_from, &self.holds, Ghost(pre), Ghost(kept_holds));
+        remove_entry(&mut self.metadata, mi, Ghost(pre.metadata), Ghost(space as nat));
+        self.state = Ghost(OsmosisDAG::State {
+            spaces: pre.spaces.remove(space as nat),
+            holds: kept_holds,
+            derived_from: pre.prune_derivations(kept_holds),
+            maps: pre.maps.filter(|me: MapEdge| me.src_node() != node@),
+            metadata: pre.metadata.remove(space as nat),
+            retyped: pre.retyped.restrict(pre.retyped.dom().filter(released)),
+            history: pre.history.push(step),
+            ..pre
+        });
+        proof {
+            assert forall |i: int| 0 <= i < self.spaces.len() implies #[trigger] self.spaces[i].space.wf() by {
+                let j = if i < si { i } else { i + 1 };
+                assert(self.spaces[i] == spaces[j]);
+            }
+            assert forall |i: int| 0 <= i < self.metadata.len() implies #[trigger] self.metadata[i].meta.wf() by {
+                let j = if mi is Some && i >= mi->Some_0 { i + 1 } else { i };
+                assert(self.metadata[i] == metadata[j]);
+            }
+            let spacelike = ResourceLike::Space { space: space as nat };
+            assert(kept_holds =~= pre.holds.filter(|he: HoldEdge| he.dst() != spacelike));
+            assert(pre.maps.filter(|me: MapEdge| me.src_node() != node@) =~= pre.maps.filter(|me: MapEdge| me.src_node() != spacelike));
+            assert(pre.retyped.dom().filter(released) =~= pre.retyped.dom().filter(|r: ResourceId| pre.retyped[r] != space as nat));
+            reveal(OsmosisDAG::State::next_by);
+            lemma_step_keeps_invariant(pre, self@, step);
+            lemma_indexes_destroy_space(pre, self@, space as nat);
+            lemma_usage_kept(self.used@, pre, self@);
+        }
+        remove_vals(&mut self.allocated, ai, Ghost(allocation_index(pre)), Ghost(space as nat));
+        remove_vals(&mut self.carved, ci, Ghost(carving_index(pre)), Ghost(space as nat));
+        Ok(())
+    }
+}
+
+} // verus!
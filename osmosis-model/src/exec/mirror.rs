@@ -0,0 +1,760 @@
+/// MIT License
+///
+/// Copyright (c) 2025 Ilias Karimalis
+
+use vstd::prelude::*;
+
+verus!
+{
+
+// Mirrors:
+
+/// Whether the entries of [v] are seen as exactly the elements of [s]
+pub open spec fn mirrors<E: View>(v: Seq<E>, s: Set<E::V>) -> bool {
+    forall |x: E::V| #[trigger] s.contains(x) <==> exists |i: int| 0 <= i < v.len() && #[trigger] v[i]@ == x
+}
+
+/// Whether the entries of [v] are seen as exactly the entries of [m], each key appearing once
+pub open spec fn mirrors_map<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>) -> bool {
+    &&& keys_unique(v)
+    &&& forall |k: K| #[trigger] m.contains_key(k) <==> exists |i: int| 0 <= i < v.len() && #[trigger] v[i]@.0 == k
+    &&& forall |i: int| 0 <= i < v.len() ==> #[trigger] m[v[i]@.0] == v[i]@.1
+}
+
+/// Whether no two entries of [v] are seen with the same key
+pub open spec fn keys_unique<E: View<V = (K, T)>, K, T>(v: Seq<E>) -> bool {
+    forall |i: int, j: int| 0 <= i < v.len() && 0 <= j < v.len() && #[trigger] v[i]@.0 == #[trigger] v[j]@.0 ==> i == j
+}
+
+/// The map the entries of [v] are seen as, the first entry of a key giving its value
+pub open spec fn entries_map<E: View<V = (K, T)>, K, T>(v: Seq<E>) -> Map<K, T> {
+    Map::new(
+        |k: K| exists |i: int| 0 <= i < v.len() && #[trigger] v[i]@.0 == k,
+        |k: K| v[choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@.0 == k]@.1,
+    )
+}
+
+/// The keys of the first [n] entries of [v]
+pub open spec fn prefix_keys<E: View<V = (K, T)>, K, T>(v: Seq<E>, n: int) -> Set<K> {
+    Set::new(|k: K| exists |i: int| 0 <= i < n && #[trigger] v[i]@.0 == k)
+}
+
+/// The entry [i] of a mirror is an element of the set it mirrors
+pub proof fn lemma_mirrored<E: View>(v: Seq<E>, s: Set<E::V>, i: int)
+    requires
+        mirrors(v, s),
+        0 <= i < v.len(),
+    ensures
+        s.contains(v[i]@),
+{
+    assert(v[i]@ == v[i]@);
+}
+
+/// The key of the entry [i] of a mirror is a key of the map it mirrors
+pub proof fn lemma_mirrored_key<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>, i: int)
+    requires
+        mirrors_map(v, m),
+        0 <= i < v.len(),
+    ensures
+        m.contains_key(v[i]@.0),
+        m[v[i]@.0] == v[i]@.1,
+{
+    assert(v[i]@.0 == v[i]@.0);
+}
+
+/// Entries with unique keys mirror the map they are seen as, whose keys are finitely many
+pub proof fn lemma_entries_map<E: View<V = (K, T)>, K, T>(v: Seq<E>)
+    requires
+        keys_unique(v),
+    ensures
+        mirrors_map(v, entries_map(v)),
+        entries_map(v).dom().finite(),
+{
+    let m = entries_map(v);
+    assert forall |i: int| 0 <= i < v.len() implies #[trigger] m[v[i]@.0] == v[i]@.1 by {
+        let k = v[i]@.0;
+        assert(m.contains_key(k));
+        let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@.0 == k;
+        assert(i == j);
+    }
+    lemma_prefix_keys_finite(v, v.len() as int);
+    assert(m.dom() =~= prefix_keys(v, v.len() as int));
+}
+
+/// The keys of a prefix of the entries are finitely many, and each entry adds its own
+pub proof fn lemma_prefix_keys_finite<E: View<V = (K, T)>, K, T>(v: Seq<E>, n: int)
+    requires
+        0 <= n <= v.len(),
+    ensures
+        prefix_keys(v, n).finite(),
+        n > 0 ==> prefix_keys(v, n) == prefix_keys(v, n - 1).insert(v[n - 1]@.0),
+    decreases n
+{
+    if (n == 0) {
+        assert(prefix_keys(v, n) =~= Set::empty());
+    } else {
+        lemma_prefix_keys_finite(v, n - 1);
+        assert(prefix_keys(v, n) =~= prefix_keys(v, n - 1).insert(v[n - 1]@.0));
+    }
+}
+
+/// The keys of all the entries of a mirror are the keys of the map it mirrors
+pub proof fn lemma_prefix_keys_all<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>)
+    requires
+        mirrors_map(v, m),
+    ensures
+        prefix_keys(v, v.len() as int) == m.dom(),
+{
+    assert(prefix_keys(v, v.len() as int) =~= m.dom());
+}
+
+/// Pushing [e] onto a mirror of [s] mirrors [s] with [e] inserted
+pub proof fn lemma_mirrors_push<E: View>(v: Seq<E>, s: Set<E::V>, e: E)
+    requires
+        mirrors(v, s),
+    ensures
+        mirrors(v.push(e), s.insert(e@)),
+{
+    let w = v.push(e);
+    assert forall |x: E::V| #[trigger] s.insert(e@).contains(x) <==> exists |i: int| 0 <= i < w.len() && #[trigger] w[i]@ == x by {
+        if (s.contains(x)) {
+            let i = choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@ == x;
+            assert(w[i] == v[i]);
+        }
+        if (x == e@) {
+            assert(w[v.len() as int] == e);
+        }
+        if (exists |i: int| 0 <= i < w.len() && #[trigger] w[i]@ == x) {
+            let i = choose |i: int| 0 <= i < w.len() && #[trigger] w[i]@ == x;
+            if (i < v.len()) {
+                assert(w[i] == v[i]);
+            }
+        }
+    }
+}
+
+/// Replacing the entry [i] of a mirror of [s], which no other entry is seen as, by [e] mirrors
+/// [s] with the element [i] was seen as replaced by [e]
+pub proof fn lemma_mirrors_update<E: View>(v: Seq<E>, s: Set<E::V>, i: int, e: E)
+    requires
+        mirrors(v, s),
+        0 <= i < v.len(),
+        forall |j: int| 0 <= j < v.len() && j != i ==> #[trigger] v[j]@ != v[i]@,
+    ensures
+        mirrors(v.update(i, e), s.remove(v[i]@).insert(e@)),
+{
+    let w = v.update(i, e);
+    assert forall |x: E::V| #[trigger] s.remove(v[i]@).insert(e@).contains(x) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x by {
+        if (s.remove(v[i]@).contains(x)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@ == x;
+            assert(j != i);
+            assert(w[j] == v[j]);
+        }
+        if (x == e@) {
+            assert(w[i] == e);
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x;
+            if (j != i) {
+                assert(w[j] == v[j]);
+                lemma_mirrored(v, s, j);
+            }
+        }
+    }
+}
+
+/// Pushing an entry with a fresh key onto a mirror of [m] mirrors [m] with the entry inserted
+pub proof fn lemma_mirrors_map_push<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>, e: E)
+    requires
+        mirrors_map(v, m),
+        !m.contains_key(e@.0),
+    ensures
+        mirrors_map(v.push(e), m.insert(e@.0, e@.1)),
+{
+    let w = v.push(e);
+    let n = m.insert(e@.0, e@.1);
+    assert forall |k: K| #[trigger] n.contains_key(k) <==> exists |i: int| 0 <= i < w.len() && #[trigger] w[i]@.0 == k by {
+        if (m.contains_key(k)) {
+            let i = choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@.0 == k;
+            assert(w[i] == v[i]);
+        }
+        if (k == e@.0) {
+            assert(w[v.len() as int] == e);
+        }
+        if (exists |i: int| 0 <= i < w.len() && #[trigger] w[i]@.0 == k) {
+            let i = choose |i: int| 0 <= i < w.len() && #[trigger] w[i]@.0 == k;
+            if (i < v.len()) {
+                assert(w[i] == v[i]);
+                lemma_mirrored_key(v, m, i);
+            }
+        }
+    }
+    assert forall |i: int, j: int| 0 <= i < w.len() && 0 <= j < w.len() && #[trigger] w[i]@.0 == #[trigger] w[j]@.0 implies i == j by {
+        if (i < v.len()) {
+            assert(w[i] == v[i]);
+            lemma_mirrored_key(v, m, i);
+        }
+        if (j < v.len()) {
+            assert(w[j] == v[j]);
+            lemma_mirrored_key(v, m, j);
+        }
+    }
+    assert forall |i: int| 0 <= i < w.len() implies #[trigger] n[w[i]@.0] == w[i]@.1 by {
+        if (i < v.len()) {
+            assert(w[i] == v[i]);
+            lemma_mirrored_key(v, m, i);
+        }
+    }
+}
+
+/// Replacing the entry [i] of a mirror of [m] by an entry with the same key mirrors [m] with
+/// the value of that key replaced
+pub proof fn lemma_mirrors_map_update<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>, i: int, e: E)
+    requires
+        mirrors_map(v, m),
+        0 <= i < v.len(),
+        e@.0 == v[i]@.0,
+    ensures
+        mirrors_map(v.update(i, e), m.insert(e@.0, e@.1)),
+{
+    let w = v.update(i, e);
+    let n = m.insert(e@.0, e@.1);
+    lemma_mirrored_key(v, m, i);
+    assert forall |j: int| 0 <= j < w.len() && j != i implies #[trigger] w[j] == v[j] by {}
+    assert forall |k: K| #[trigger] n.contains_key(k) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k by {
+        if (m.contains_key(k)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@.0 == k;
+            if (j == i) {
+                assert(w[i]@.0 == k);
+            } else {
+                assert(w[j] == v[j]);
+            }
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k;
+            if (j != i) {
+                assert(w[j] == v[j]);
+                lemma_mirrored_key(v, m, j);
+            }
+        }
+    }
+    assert forall |j: int, k: int| 0 <= j < w.len() && 0 <= k < w.len() && #[trigger] w[j]@.0 == #[trigger] w[k]@.0 implies j == k by {
+        assert(w[j]@.0 == v[j]@.0);
+        assert(w[k]@.0 == v[k]@.0);
+    }
+    assert forall |j: int| 0 <= j < w.len() implies #[trigger] n[w[j]@.0] == w[j]@.1 by {
+        if (j != i) {
+            assert(w[j] == v[j]);
+            assert(v[j]@.0 != v[i]@.0);
+            lemma_mirrored_key(v, m, j);
+        }
+    }
+}
+
+/// Removing the entry [i] of a mirror of [m] mirrors [m] with its key removed
+pub proof fn lemma_mirrors_map_remove<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>, i: int)
+    requires
+        mirrors_map(v, m),
+        0 <= i < v.len(),
+    ensures
+        mirrors_map(v.remove(i), m.remove(v[i]@.0)),
+{
+    let w = v.remove(i);
+    let gone = v[i]@.0;
+    let n = m.remove(gone);
+    assert forall |j: int| 0 <= j < w.len() implies #[trigger] w[j] == v[if j < i { j } else { j + 1 }] by {}
+    assert forall |k: K| #[trigger] n.contains_key(k) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k by {
+        if (n.contains_key(k)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@.0 == k;
+            assert(j != i);
+            let l = if j < i { j } else { j - 1 };
+            assert(w[l] == v[j]);
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k;
+            let l = if j < i { j } else { j + 1 };
+            assert(w[j] == v[l]);
+            assert(l != i);
+            lemma_mirrored_key(v, m, l);
+        }
+    }
+    assert forall |j: int, k: int| 0 <= j < w.len() && 0 <= k < w.len() && #[trigger] w[j]@.0 == #[trigger] w[k]@.0 implies j == k by {
+        let j2 = if j < i { j } else { j + 1 };
+        let k2 = if k < i { k } else { k + 1 };
+        assert(w[j] == v[j2] && w[k] == v[k2]);
+    }
+    assert forall |j: int| 0 <= j < w.len() implies #[trigger] n[w[j]@.0] == w[j]@.1 by {
+        let j2 = if j < i { j } else { j + 1 };
+        assert(w[j] == v[j2]);
+        assert(v[j2]@.0 != gone);
+        lemma_mirrored_key(v, m, j2);
+    }
+}
+
+// Retaining:
+
+/// The entries of [s] whose flag in [keep] is set, in order
+pub open spec fn kept<E>(s: Seq<E>, keep: Seq<bool>) -> Seq<E>
+    decreases s.len()
+{
+    if (s.len() == 0) {
+        Seq::empty()
+    } else {
+        let rest = kept(s.drop_last(), keep.take(s.len() - 1));
+        if (keep[s.len() - 1]) { rest.push(s.last()) } else { rest }
+    }
+}
+
+/// Every entry of kept(s, keep) is an entry of [s] whose flag is set, and every such entry is
+/// kept, in the same order
+pub proof fn lemma_kept<E>(s: Seq<E>, keep: Seq<bool>)
+    requires
+        keep.len() == s.len(),
+    ensures
+        forall |e: E| kept(s, keep).contains(e) <==> exists |i: int| 0 <= i < s.len() && keep[i] && #[trigger] s[i] == e,
+        forall |j: int| 0 <= j < kept(s, keep).len() ==>
+            exists |i: int| 0 <= i < s.len() && keep[i] && s[i] == #[trigger] kept(s, keep)[j]
+                && forall |k: int| 0 <= k < j ==> exists |l: int| 0 <= l < i && keep[l] && s[l] == #[trigger] kept(s, keep)[k],
+    decreases s.len()
+{
+    if (s.len() > 0) {
+        let init = s.drop_last();
+        let flags = keep.take(s.len() - 1);
+        lemma_kept(init, flags);
+        let rest = kept(init, flags);
+        assert forall |e: E| kept(s, keep).contains(e) <==> exists |i: int| 0 <= i < s.len() && keep[i] && #[trigger] s[i] == e by {
+            if (kept(s, keep).contains(e) && !(keep[s.len() - 1] && e == s.last())) {
+                assert(rest.contains(e));
+                let i = choose |i: int| 0 <= i < init.len() && flags[i] && #[trigger] init[i] == e;
+                assert(s[i] == e);
+            }
+            if (exists |i: int| 0 <= i < s.len() && keep[i] && #[trigger] s[i] == e) {
+                let i = choose |i: int| 0 <= i < s.len() && keep[i] && #[trigger] s[i] == e;
+                if (i < s.len() - 1) {
+                    assert(init[i] == e && flags[i]);
+                    assert(rest.contains(e));
+                    if (keep[s.len() - 1]) {
+                        let j = choose |j: int| 0 <= j < rest.len() && rest[j] == e;
+                        assert(kept(s, keep)[j] == e);
+                    }
+                } else {
+                    assert(kept(s, keep)[rest.len() as int] == e);
+                }
+            }
+        }
+        assert forall |j: int| 0 <= j < kept(s, keep).len() implies
+            exists |i: int| 0 <= i < s.len() && keep[i] && s[i] == #[trigger] kept(s, keep)[j]
+                && forall |k: int| 0 <= k < j ==> exists |l: int| 0 <= l < i && keep[l] && s[l] == #[trigger] kept(s, keep)[k] by {
+            if (j < rest.len()) {
+                assert(kept(s, keep)[j] == rest[j]);
+                let i = choose |i: int| 0 <= i < init.len() && flags[i] && init[i] == #[trigger] rest[j]
+                    && forall |k: int| 0 <= k < j ==> exists |l: int| 0 <= l < i && flags[l] && init[l] == #[trigger] rest[k];
+                assert(s[i] == init[i]);
+                assert forall |k: int| 0 <= k < j implies exists |l: int| 0 <= l < i && keep[l] && s[l] == #[trigger] kept(s, keep)[k] by {
+                    assert(kept(s, keep)[k] == rest[k]);
+                    let l = choose |l: int| 0 <= l < i && flags[l] && init[l] == #[trigger] rest[k];
+                    assert(s[l] == init[l]);
+                }
+            } else {
+                let i = s.len() - 1;
+                assert(kept(s, keep)[j] == s[i]);
+                assert forall |k: int| 0 <= k < j implies exists |l: int| 0 <= l < i && keep[l] && s[l] == #[trigger] kept(s, keep)[k] by {
+                    assert(kept(s, keep)[k] == rest[k]);
+                    let l = choose |l: int| 0 <= l < init.len() && flags[l] && init[l] == #[trigger] rest[k]
+                        && forall |m: int| 0 <= m < k ==> exists |n: int| 0 <= n < l && flags[n] && init[n] == #[trigger] rest[m];
+                    assert(s[l] == init[l]);
+                }
+            }
+        }
+    }
+}
+
+/// Keeping the entries of a mirror of [s] whose flag is set, which is set exactly for those
+/// seen as an element satisfying [p], mirrors [s] filtered by [p]
+pub proof fn lemma_kept_mirrors<E: View>(v: Seq<E>, s: Set<E::V>, keep: Seq<bool>, p: spec_fn(E::V) -> bool)
+    requires
+        mirrors(v, s),
+        keep.len() == v.len(),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] keep[i] == p(v[i]@),
+    ensures
+        mirrors(kept(v, keep), s.filter(p)),
+{
+    let w = kept(v, keep);
+    lemma_kept(v, keep);
+    assert forall |x: E::V| #[trigger] s.filter(p).contains(x) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x by {
+        if (s.filter(p).contains(x)) {
+            let i = choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@ == x;
+            assert(keep[i]);
+            assert(w.contains(v[i]));
+            let j = choose |j: int| 0 <= j < w.len() && w[j] == v[i];
+            assert(w[j]@ == x);
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@ == x;
+            assert(w.contains(w[j]));
+            let i = choose |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i] == w[j];
+            lemma_mirrored(v, s, i);
+        }
+    }
+}
+
+/// Keeping the entries of a mirror of [m] whose flag is set, which is set exactly for those
+/// whose key satisfies [p], mirrors [m] restricted to the keys satisfying [p]
+pub proof fn lemma_kept_mirrors_map<E: View<V = (K, T)>, K, T>(v: Seq<E>, m: Map<K, T>, keep: Seq<bool>, p: spec_fn(K) -> bool)
+    requires
+        mirrors_map(v, m),
+        keep.len() == v.len(),
+        forall |i: int| 0 <= i < v.len() ==> #[trigger] keep[i] == p(v[i]@.0),
+    ensures
+        mirrors_map(kept(v, keep), m.restrict(m.dom().filter(p))),
+{
+    let w = kept(v, keep);
+    let r = m.restrict(m.dom().filter(p));
+    lemma_kept(v, keep);
+    assert forall |k: K| #[trigger] r.contains_key(k) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k by {
+        if (r.contains_key(k)) {
+            let i = choose |i: int| 0 <= i < v.len() && #[trigger] v[i]@.0 == k;
+            assert(keep[i]);
+            assert(w.contains(v[i]));
+            let j = choose |j: int| 0 <= j < w.len() && w[j] == v[i];
+            assert(w[j]@.0 == k);
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k;
+            assert(w.contains(w[j]));
+            let i = choose |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i] == w[j];
+            lemma_mirrored_key(v, m, i);
+        }
+    }
+    assert forall |j: int, k: int| 0 <= j < w.len() && 0 <= k < w.len() && #[trigger] w[j]@.0 == #[trigger] w[k]@.0 implies j == k by {
+        if (j != k) {
+            let (lo, hi) = if j < k { (j, k) } else { (k, j) };
+            let i = choose |i: int| 0 <= i < v.len() && keep[i] && v[i] == #[trigger] w[hi]
+                && forall |a: int| 0 <= a < hi ==> exists |l: int| 0 <= l < i && keep[l] && v[l] == #[trigger] w[a];
+            let l = choose |l: int| 0 <= l < i && keep[l] && v[l] == #[trigger] w[lo];
+            assert(v[l]@.0 == v[i]@.0);
+        }
+    }
+    assert forall |j: int| 0 <= j < w.len() implies #[trigger] r[w[j]@.0] == w[j]@.1 by {
+        assert(w.contains(w[j]));
+        let i = choose |i: int| 0 <= i < v.len() && keep[i] && #[trigger] v[i] == w[j];
+        lemma_mirrored_key(v, m, i);
+    }
+}
+
+/// Every entry kept out of [s] satisfies [p] when every entry of [s] does
+pub proof fn lemma_kept_all<E>(s: Seq<E>, keep: Seq<bool>, p: spec_fn(E) -> bool)
+    requires
+        keep.len() == s.len(),
+        forall |i: int| 0 <= i < s.len() ==> #[trigger] p(s[i]),
+    ensures
+        forall |j: int| 0 <= j < kept(s, keep).len() ==> #[trigger] p(kept(s, keep)[j]),
+{
+    lemma_kept(s, keep);
+    assert forall |j: int| 0 <= j < kept(s, keep).len() implies #[trigger] p(kept(s, keep)[j]) by {
+        let e = kept(s, keep)[j];
+        assert(kept(s, keep).contains(e));
+        let i = choose |i: int| 0 <= i < s.len() && keep[i] && #[trigger] s[i] == e;
+        assert(p(s[i]));
+    }
+}
+
+/// Removes the entries of [v] whose flag in [keep] is not set
+pub fn retain_flagged<E>(v: &mut Vec<E>, keep: &Vec<bool>)
+    requires
+        keep.len() == old(v).len(),
+    ensures
+        v@ == kept(old(v)@, keep@),
+{
+    let ghost original = v@;
+    let mut i: usize = 0;
+    let mut j: usize = 0;
+    while j < keep.len()
+        invariant
+            keep.len() == original.len(),
+            j <= keep.len(),
+            i <= j,
+            v@ == kept(original.take(j as int), keep@.take(j as int)) + original.skip(j as int),
+            i == kept(original.take(j as int), keep@.take(j as int)).len(),
+        decreases keep.len() - j
+    {
+        let ghost before = kept(original.take(j as int), keep@.take(j as int));
+        assert(original.take(j + 1).drop_last() =~= original.take(j as int));
+        assert(keep@.take(j + 1).take(j as int) =~= keep@.take(j as int));
+        if keep[j] {
+            assert(kept(original.take(j + 1), keep@.take(j + 1)) == before.push(original[j as int]));
+            i = i + 1;
+        } else {
+            assert(v[i as int] == original[j as int]);
+            v.remove(i);
+            assert(kept(original.take(j + 1), keep@.take(j + 1)) == before);
+        }
+        j = j + 1;
+        assert(v@ =~= kept(original.take(j as int), keep@.take(j as int)) + original.skip(j as int));
+    }
+    assert(original.take(keep.len() as int) =~= original);
+    assert(keep@.take(keep.len() as int) =~= keep@);
+    assert(v@ =~= kept(original, keep@));
+}
+
+// Entries:
+
+/// Sets the entry for the key of [e], which is at [index] if there is one already
+pub fn upsert<E: View<V = (K, T)>, K, T>(v: &mut Vec<E>, index: Option<usize>, e: E, Ghost(m): Ghost<Map<K, T>>)
+    requires
+        mirrors_map(old(v)@, m),
+        match index {
+            Some(i) => i < old(v).len() && old(v)[i as int]@.0 == e@.0,
+            None => !m.contains_key(e@.0),
+        },
+    ensures
+        mirrors_map(v@, m.insert(e@.0, e@.1)),
+        index is Some ==> v@ == old(v)@.update(index->Some_0 as int, e),
+        index is None ==> v@ == old(v)@.push(e),
+{
+    let ghost before = v@;
+    proof {
+        match index {
+            Some(i) => lemma_mirrors_map_update(before, m, i as int, e),
+            None => lemma_mirrors_map_push(before, m, e),
+        }
+    }
+    match index {
+        Some(i) => v.set(i, e),
+        None => v.push(e),
+    }
+}
+
+/// Removes the entry for [k], which is at [index] if there is one
+pub fn remove_entry<E: View<V = (K, T)>, K, T>(v: &mut Vec<E>, index: Option<usize>, Ghost(m): Ghost<Map<K, T>>, Ghost(k): Ghost<K>)
+    requires
+        mirrors_map(old(v)@, m),
+        match index {
+            Some(i) => i < old(v).len() && old(v)[i as int]@.0 == k,
+            None => !m.contains_key(k),
+        },
+    ensures
+        mirrors_map(v@, m.remove(k)),
+        index is Some ==> v@ == old(v)@.remove(index->Some_0 as int),
+        index is None ==> v@ == old(v)@,
+{
+    match index {
+        Some(i) => {
+            let ghost before = v@;
+            v.remove(i);
+            proof {
+                lemma_mirrors_map_remove(before, m, i as int);
+            }
+        },
+        None => {
+            assert(m.remove(k) =~= m);
+        },
+    }
+}
+
+// Sorted entries:
+
+/// Whether the entries of [v] are seen with strictly increasing keys
+pub open spec fn keys_sorted<E: View<V = (nat, T)>, T>(v: Seq<E>) -> bool {
+    forall |i: int, j: int| 0 <= i < j < v.len() ==> #[trigger] v[i]@.0 < #[trigger] v[j]@.0
+}
+
+/// An entry keyed by a machine word, which it is seen with
+pub trait KeyedEntry<T>: View<V = (nat, T)> {
+    fn key(&self) -> (k: u64)
+        ensures
+            k as nat == self@.0;
+}
+
+/// Replacing an entry of sorted entries by one with the same key keeps them sorted
+pub proof fn lemma_keys_sorted_update<E: View<V = (nat, T)>, T>(v: Seq<E>, i: int, e: E)
+    requires
+        keys_sorted(v),
+        0 <= i < v.len(),
+        e@.0 == v[i]@.0,
+    ensures
+        keys_sorted(v.update(i, e)),
+{
+    let w = v.update(i, e);
+    assert forall |j: int, k: int| 0 <= j < k < w.len() implies #[trigger] w[j]@.0 < #[trigger] w[k]@.0 by {
+        assert(w[j]@.0 == v[j]@.0 && w[k]@.0 == v[k]@.0);
+    }
+}
+
+/// Removing an entry of sorted entries keeps them sorted
+pub proof fn lemma_keys_sorted_remove<E: View<V = (nat, T)>, T>(v: Seq<E>, i: int)
+    requires
+        keys_sorted(v),
+        0 <= i < v.len(),
+    ensures
+        keys_sorted(v.remove(i)),
+{
+    let w = v.remove(i);
+    assert forall |j: int, k: int| 0 <= j < k < w.len() implies #[trigger] w[j]@.0 < #[trigger] w[k]@.0 by {
+        let j2 = if j < i { j } else { j + 1 };
+        let k2 = if k < i { k } else { k + 1 };
+        assert(w[j] == v[j2] && w[k] == v[k2]);
+    }
+}
+
+/// Inserting [e] at [i] into a sorted mirror of [m], after the entries with smaller keys and
+/// before those with larger ones, mirrors [m] with the entry inserted and keeps it sorted
+pub proof fn lemma_mirrors_map_insert<E: View<V = (nat, T)>, T>(v: Seq<E>, m: Map<nat, T>, i: int, e: E)
+    requires
+        mirrors_map(v, m),
+        keys_sorted(v),
+        0 <= i <= v.len(),
+        forall |j: int| 0 <= j < i ==> #[trigger] v[j]@.0 < e@.0,
+        forall |j: int| i <= j < v.len() ==> #[trigger] v[j]@.0 > e@.0,
+    ensures
+        mirrors_map(v.insert(i, e), m.insert(e@.0, e@.1)),
+        keys_sorted(v.insert(i, e)),
+{
+    let w = v.insert(i, e);
+    let n = m.insert(e@.0, e@.1);
+    assert forall |j: int| 0 <= j < w.len() implies
+        #[trigger] w[j] == if j < i { v[j] } else if j == i { e } else { v[j - 1] } by {}
+    assert forall |j: int, k: int| 0 <= j < k < w.len() implies #[trigger] w[j]@.0 < #[trigger] w[k]@.0 by {
+        if (j != i && k != i) {
+            let j2 = if j < i { j } else { j - 1 };
+            let k2 = if k < i { k } else { k - 1 };
+            assert(w[j] == v[j2] && w[k] == v[k2]);
+        } else if (j == i) {
+            assert(w[k] == v[k - 1]);
+        } else {
+            assert(w[j] == v[j]);
+        }
+    }
+    assert forall |k: nat| #[trigger] n.contains_key(k) <==> exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k by {
+        if (m.contains_key(k)) {
+            let j = choose |j: int| 0 <= j < v.len() && #[trigger] v[j]@.0 == k;
+            let l = if j < i { j } else { j + 1 };
+            assert(w[l] == v[j]);
+        }
+        if (k == e@.0) {
+            assert(w[i] == e);
+        }
+        if (exists |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k) {
+            let j = choose |j: int| 0 <= j < w.len() && #[trigger] w[j]@.0 == k;
+            if (j != i) {
+                let l = if j < i { j } else { j - 1 };
+                assert(w[j] == v[l]);
+                lemma_mirrored_key(v, m, l);
+            }
+        }
+    }
+    assert forall |j: int, k: int| 0 <= j < w.len() && 0 <= k < w.len() && #[trigger] w[j]@.0 == #[trigger] w[k]@.0 implies j == k by {
+        if (j < k) {
+            assert(w[j]@.0 < w[k]@.0);
+        } else if (k < j) {
+            assert(w[k]@.0 < w[j]@.0);
+        }
+    }
+    assert forall |j: int| 0 <= j < w.len() implies #[trigger] n[w[j]@.0] == w[j]@.1 by {
+        if (j != i) {
+            let l = if j < i { j } else { j - 1 };
+            assert(w[j] == v[l]);
+            assert(v[l]@.0 != e@.0);
+            lemma_mirrored_key(v, m, l);
+        }
+    }
+}
+
+/// The number of entries of the sorted [v] whose keys are below [key], found by binary search
+pub fn sorted_position<E: KeyedEntry<T>, T>(v: &Vec<E>, key: u64) -> (r: usize)
+    requires
+        keys_sorted(v@),
+    ensures
+        r <= v.len(),
+        forall |j: int| 0 <= j < r ==> #[trigger] v[j]@.0 < key as nat,
+        forall |j: int| r <= j < v.len() ==> #[trigger] v[j]@.0 >= key as nat,
+{
+    let mut lo: usize = 0;
+    let mut hi: usize = v.len();
+    while lo < hi
+        invariant
+            keys_sorted(v@),
+            lo <= hi <= v.len(),
+            forall |j: int| 0 <= j < lo ==> #[trigger] v[j]@.0 < key as nat,
+            forall |j: int| hi <= j < v.len() ==> #[trigger] v[j]@.0 >= key as nat,
+        decreases hi - lo
+    {
+        let mid = lo + (hi - lo) / 2;
+        if v[mid].key() < key {
+            proof {
+                assert forall |j: int| 0 <= j <= mid implies #[trigger] v[j]@.0 < key as nat by {
+                    if (j < mid) {
+                        assert(v[j]@.0 < v[mid as int]@.0);
+                    }
+                }
+            }
+            lo = mid + 1;
+        } else {
+            proof {
+                assert forall |j: int| mid <= j < v.len() implies #[trigger] v[j]@.0 >= key as nat by {
+                    if (j > mid) {
+                        assert(v[mid as int]@.0 < v[j]@.0);
+                    }
+                }
+            }
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// The index of the entry for [key] in the sorted mirror [v] of [m], if there is one
+pub fn sorted_index<E: KeyedEntry<T>, T>(v: &Vec<E>, key: u64, Ghost(m): Ghost<Map<nat, T>>) -> (r: Option<usize>)
+    requires
+        mirrors_map(v@, m),
+        keys_sorted(v@),
+    ensures
+        match r {
+            Some(i) => i < v.len() && v[i as int]@.0 == key as nat && m.contains_key(key as nat) && m[key as nat] == v[i as int]@.1,
+            None => !m.contains_key(key as nat),
+        },
+{
+    let i = sorted_position(v, key);
+    if i < v.len() && v[i].key() == key {
+        proof {
+            lemma_mirrored_key(v@, m, i as int);
+        }
+        Some(i)
+    } else {
+        proof {
+            if (m.contains_key(key as nat)) {
+                let j = choose |j: int| 0 <= j < v.len() && #[trigger] v@[j]@.0 == key as nat;
+                if (j > i) {
+                    assert(v[i as int]@.0 < v[j]@.0);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Inserts [e], whose key is fresh, into the sorted mirror [v] of [m] where its key keeps it
+/// sorted, returning where
+pub fn insert_sorted<E: KeyedEntry<T>, T>(v: &mut Vec<E>, e: E, Ghost(m): Ghost<Map<nat, T>>) -> (i: usize)
+    requires
+        mirrors_map(old(v)@, m),
+        keys_sorted(old(v)@),
+        !m.contains_key(e@.0),
+    ensures
+        mirrors_map(v@, m.insert(e@.0, e@.1)),
+        keys_sorted(v@),
+        i <= old(v).len(),
+        v@ == old(v)@.insert(i as int, e),
+{
+    let i = sorted_position(v, e.key());
+    proof {
+        assert forall |j: int| i <= j < v.len() implies #[trigger] v[j]@.0 > e@.0 by {
+            lemma_mirrored_key(v@, m, j);
+        }
+        lemma_mirrors_map_insert(v@, m, i as int, e);
+    }
+    v.insert(i, e);
+    i
+}
+
+} // verus!
@@ -0,0 +1,4 @@
+pub mod vals;
+pub mod mirror;
+pub mod component;
+pub mod graph;
@@ -0,0 +1,1413 @@
+/// MIT License
+///
+/// Copyright (c) 2025 Ilias Karimalis
+
+use vstd::prelude::*;
+use vstd::set_lib::*;
+
+use crate::utils::{nat_range, nat_interval, lemma_nat_interval_len};
+
+verus!
+{
+
+/// The vals among the first [n] entries of [s]
+pub open spec fn prefix_vals(s: Seq<u64>, n: int) -> Set<nat> {
+    Set::new(|v: nat| exists |i: int| 0 <= i < n && s[i] as nat == v)
+}
+
+/// The vals among the first [n] distinct entries of [s] are as many as the entries
+pub proof fn lemma_prefix_vals_len(s: Seq<u64>, n: int)
+    requires
+        0 <= n <= s.len(),
+        s.no_duplicates(),
+    ensures
+        prefix_vals(s, n).finite(),
+        prefix_vals(s, n).len() == n,
+    decreases n
+{
+    if (n == 0) {
+        assert(prefix_vals(s, n) =~= Set::empty());
+    } else {
+        lemma_prefix_vals_len(s, n - 1);
+        assert(prefix_vals(s, n) =~= prefix_vals(s, n - 1).insert(s[n - 1] as nat));
+        assert(!prefix_vals(s, n - 1).contains(s[n - 1] as nat));
+    }
+}
+
+/// Pushing [x] onto [s] adds it to the vals of [s]
+proof fn lemma_prefix_vals_push(s: Seq<u64>, x: u64)
+    ensures
+        prefix_vals(s.push(x), s.len() + 1) == prefix_vals(s, s.len() as int).insert(x as nat),
+{
+    let t = s.push(x);
+    assert forall |v: nat| #[trigger] prefix_vals(t, s.len() + 1).contains(v) == prefix_vals(s, s.len() as int).insert(x as nat).contains(v) by {
+        if (prefix_vals(s, s.len() as int).contains(v)) {
+            let i = choose |i: int| 0 <= i < s.len() && s[i] as nat == v;
+            assert(t[i] == s[i]);
+        }
+        if (prefix_vals(t, s.len() + 1).contains(v) && v != x as nat) {
+            let i = choose |i: int| 0 <= i < s.len() + 1 && t[i] as nat == v;
+            assert(t[i] == s[i]);
+        }
+        if (v == x as nat) {
+            assert(t[s.len() as int] == x);
+        }
+    }
+    assert(prefix_vals(t, s.len() + 1) =~= prefix_vals(s, s.len() as int).insert(x as nat));
+}
+
+// Section: Intervals
+//
+// A set of vals is stored as the sorted sequence of the inclusive intervals it is made of. The
+// intervals are kept canonical, each non-empty and separated from the next by a val outside of
+// the set, so that the single interval which can answer a query about a val is found by binary
+// search.
+
+/// Whether [v] lies in the inclusive interval [iv]
+pub open spec fn in_interval(iv: (u64, u64), v: nat) -> bool {
+    iv.0 <= v <= iv.1
+}
+
+/// The vals of the inclusive interval [iv]
+pub open spec fn interval_vals(iv: (u64, u64)) -> Set<nat> {
+    Set::new(|v: nat| in_interval(iv, v))
+}
+
+/// The vals of the first [n] intervals of [s]
+pub open spec fn intervals_vals(s: Seq<(u64, u64)>, n: int) -> Set<nat> {
+    Set::new(|v: nat| exists |k: int| 0 <= k < n && in_interval(s[k], v))
+}
+
+/// The number of vals in the first [n] intervals of [s], counting repeats
+pub open spec fn intervals_len(s: Seq<(u64, u64)>, n: int) -> nat
+    decreases n
+{
+    if n <= 0 {
+        0
+    } else {
+        intervals_len(s, n - 1) + (s[n - 1].1 - s[n - 1].0 + 1) as nat
+    }
+}
+
+/// The intervals of [s] are non-empty, sorted, and separated by a val outside of all of them
+pub open spec fn intervals_wf(s: Seq<(u64, u64)>) -> bool {
+    &&& forall |i: int| 0 <= i < s.len() ==> #[trigger] s[i].0 <= s[i].1
+    &&& forall |i: int, j: int| #![trigger s[i], s[j]] 0 <= i < j < s.len() ==> s[i].1 + 1 < s[j].0
+}
+
+/// Both the starts and ends of canonical intervals are strictly increasing
+proof fn lemma_intervals_sorted(s: Seq<(u64, u64)>)
+    requires
+        intervals_wf(s),
+    ensures
+        forall |i: int, j: int| #![trigger s[i], s[j]] 0 <= i < j < s.len() ==> s[i].0 < s[j].0 && s[i].1 < s[j].1,
+{
+    assert forall |i: int, j: int| #![trigger s[i], s[j]] 0 <= i < j < s.len() implies s[i].0 < s[j].0 && s[i].1 < s[j].1 by {
+        assert(s[i].0 <= s[i].1 && s[j].0 <= s[j].1);
+    }
+}
+
+/// Adding the [n]th interval to the first [n] adds its vals
+proof fn lemma_intervals_vals_step(s: Seq<(u64, u64)>, n: int)
+    requires
+        0 <= n < s.len(),
+    ensures
+        intervals_vals(s, n + 1) == intervals_vals(s, n).union(interval_vals(s[n])),
+{
+    assert forall |v: nat| #[trigger] intervals_vals(s, n + 1).contains(v) == intervals_vals(s, n).union(interval_vals(s[n])).contains(v) by {
+        if (intervals_vals(s, n + 1).contains(v)) {
+            let k = choose |k: int| 0 <= k < n + 1 && in_interval(s[k], v);
+            if (k < n) {
+                assert(intervals_vals(s, n).contains(v));
+            }
+        }
+        if (intervals_vals(s, n).contains(v)) {
+            let k = choose |k: int| 0 <= k < n && in_interval(s[k], v);
+            assert(0 <= k < n + 1 && in_interval(s[k], v));
+        }
+        if (interval_vals(s[n]).contains(v)) {
+            assert(in_interval(s[n], v));
+        }
+    }
+    assert(intervals_vals(s, n + 1) =~= intervals_vals(s, n).union(interval_vals(s[n])));
+}
+
+/// The vals of the first [n] intervals depend on those intervals alone
+proof fn lemma_intervals_vals_prefix(s: Seq<(u64, u64)>, t: Seq<(u64, u64)>, n: int)
+    requires
+        0 <= n <= s.len(),
+        n <= t.len(),
+        forall |k: int| 0 <= k < n ==> s[k] == t[k],
+    ensures
+        intervals_vals(s, n) == intervals_vals(t, n),
+{
+    assert forall |v: nat| #[trigger] intervals_vals(s, n).contains(v) == intervals_vals(t, n).contains(v) by {
+        if (intervals_vals(s, n).contains(v)) {
+            let k = choose |k: int| 0 <= k < n && in_interval(s[k], v);
+            assert(in_interval(t[k], v));
+        }
+        if (intervals_vals(t, n).contains(v)) {
+            let k = choose |k: int| 0 <= k < n && in_interval(t[k], v);
+            assert(in_interval(s[k], v));
+        }
+    }
+    assert(intervals_vals(s, n) =~= intervals_vals(t, n));
+}
+
+/// The vals of the first [n] canonical intervals lie before the gap preceding the [n]th
+proof fn lemma_intervals_vals_before(s: Seq<(u64, u64)>, n: int)
+    requires
+        intervals_wf(s),
+        0 <= n < s.len(),
+    ensures
+        forall |v: nat| #[trigger] intervals_vals(s, n).contains(v) ==> v + 1 < s[n].0,
+{
+    assert forall |v: nat| #[trigger] intervals_vals(s, n).contains(v) implies v + 1 < s[n].0 by {
+        let k = choose |k: int| 0 <= k < n && in_interval(s[k], v);
+        assert(s[k].1 + 1 < s[n].0);
+    }
+}
+
+/// The first [n] canonical intervals hold as many vals as their lengths add up to
+proof fn lemma_intervals_vals_len(s: Seq<(u64, u64)>, n: int)
+    requires
+        intervals_wf(s),
+        0 <= n <= s.len(),
+    ensures
+        intervals_vals(s, n).finite(),
+        intervals_vals(s, n).len() == intervals_len(s, n),
+    decreases n
+{
+    if (n == 0) {
+        assert(intervals_vals(s, n) =~= Set::empty());
+    } else {
+        let iv = s[n - 1];
+        lemma_intervals_vals_len(s, n - 1);
+        lemma_intervals_vals_step(s, n - 1);
+        lemma_intervals_vals_before(s, n - 1);
+        assert(iv.0 <= iv.1);
+        assert(interval_vals(iv) =~= nat_interval(iv.0 as nat, (iv.1 - iv.0 + 1) as nat));
+        lemma_nat_interval_len(iv.0 as nat, (iv.1 - iv.0 + 1) as nat);
+        assert(intervals_vals(s, n - 1).disjoint(interval_vals(iv)));
+        lemma_set_disjoint_lens(intervals_vals(s, n - 1), interval_vals(iv));
+    }
+}
+
+/// The val just past a canonical interval is in none of them
+proof fn lemma_intervals_gap(s: Seq<(u64, u64)>, k: int)
+    requires
+        intervals_wf(s),
+        0 <= k < s.len(),
+    ensures
+        !intervals_vals(s, s.len() as int).contains((s[k].1 + 1) as nat),
+{
+    let v = (s[k].1 + 1) as nat;
+    if (intervals_vals(s, s.len() as int).contains(v)) {
+        let j = choose |j: int| 0 <= j < s.len() && in_interval(s[j], v);
+        if (j < k) {
+            assert(s[j].1 + 1 < s[k].0);
+            assert(s[k].0 <= s[k].1);
+        } else if (j > k) {
+            assert(s[k].1 + 1 < s[j].0);
+        }
+    }
+}
+
+/// A val is in canonical intervals exactly when it lies in the last one starting at or before
+/// it, the [i - 1]th
+proof fn lemma_intervals_located(s: Seq<(u64, u64)>, i: int, v: nat)
+    requires
+        intervals_wf(s),
+        0 <= i <= s.len(),
+        forall |k: int| 0 <= k < i ==> s[k].0 <= v,
+        forall |k: int| i <= k < s.len() ==> s[k].0 > v,
+    ensures
+        intervals_vals(s, s.len() as int).contains(v) == (i > 0 && in_interval(s[i - 1], v)),
+{
+    if (intervals_vals(s, s.len() as int).contains(v)) {
+        let k = choose |k: int| 0 <= k < s.len() && in_interval(s[k], v);
+        if (k >= i) {
+            assert(s[k].0 > v);
+        } else if (k < i - 1) {
+            assert(s[k].1 + 1 < s[i - 1].0);
+            assert(s[i - 1].0 <= v);
+        }
+    }
+    if (i > 0 && in_interval(s[i - 1], v)) {
+        assert(intervals_vals(s, s.len() as int).contains(v));
+    }
+}
+
+/// The vals of the first [i] intervals of [s], along with those of the [i]th below [lo]
+pub open spec fn intervals_vals_upto(s: Seq<(u64, u64)>, i: int, lo: nat) -> Set<nat> {
+    intervals_vals(s, i).union(Set::new(|v: nat| i < s.len() && s[i].0 <= v < lo))
+}
+
+/// The vals from [lo] up to before the [j]th canonical interval are in none of them, when those
+/// before it end before [lo]
+proof fn lemma_intervals_skip(s: Seq<(u64, u64)>, j: int, lo: nat, hi: nat)
+    requires
+        intervals_wf(s),
+        0 <= j <= s.len(),
+        forall |k: int| 0 <= k < j ==> #[trigger] s[k].1 < lo,
+        j < s.len() ==> hi <= s[j].0,
+    ensures
+        Set::new(|v: nat| lo <= v < hi).disjoint(intervals_vals(s, s.len() as int)),
+{
+    lemma_intervals_sorted(s);
+    assert forall |v: nat| #[trigger] intervals_vals(s, s.len() as int).contains(v) implies !(lo <= v < hi) by {
+        let k = choose |k: int| 0 <= k < s.len() && in_interval(s[k], v);
+        if (k > j) {
+            assert(s[j].0 < s[k].0);
+        }
+    }
+}
+
+/// A val of canonical intervals past the end of the [i]th lies in a later one, from the
+/// [i + 1]th on
+proof fn lemma_intervals_past(s: Seq<(u64, u64)>, i: int, v: nat)
+    requires
+        intervals_wf(s),
+        0 <= i < s.len(),
+        intervals_vals(s, s.len() as int).contains(v),
+        v > s[i].1,
+    ensures
+        i + 1 < s.len(),
+        s[i + 1].0 <= v,
+{
+    lemma_intervals_sorted(s);
+    let k = choose |k: int| 0 <= k < s.len() && in_interval(s[k], v);
+    if (k < i) {
+        assert(s[k].1 < s[i].1);
+    } else if (k > i + 1) {
+        assert(s[i + 1].0 < s[k].0);
+    }
+}
+
+/// A val of the [i]th interval of [s] from [cur] on, before the [e]th interval of [t] and past
+/// the end of those before it, is in [s] but not in [t]
+proof fn lemma_intervals_outside(s: Seq<(u64, u64)>, t: Seq<(u64, u64)>, i: int, e: int, cur: nat, v: nat)
+    requires
+        intervals_wf(s),
+        intervals_wf(t),
+        0 <= i < s.len(),
+        0 <= e <= t.len(),
+        forall |k: int| 0 <= k < e ==> #[trigger] t[k].1 < cur,
+        s[i].0 <= cur <= v <= s[i].1,
+        e < t.len() ==> v < t[e].0,
+    ensures
+        intervals_vals(s, s.len() as int).contains(v),
+        !intervals_vals(t, t.len() as int).contains(v),
+{
+    lemma_intervals_sorted(t);
+    assert(in_interval(s[i], v));
+    if (intervals_vals(t, t.len() as int).contains(v)) {
+        let k = choose |k: int| 0 <= k < t.len() && in_interval(t[k], v);
+        if (k > e) {
+            assert(t[e].0 < t[k].0);
+        }
+    }
+}
+
+// Section: ValSet
+
+/// An executable set of vals, such as those a ResourceSpace manages, whose size is that of its
+/// intervals rather than of its vals
+pub struct ValSet {
+    /// The inclusive intervals of the set, in increasing order
+    ivs: Vec<(u64, u64)>,
+}
+
+impl View for ValSet {
+    type V = Set<nat>;
+
+    closed spec fn view(&self) -> Set<nat> {
+        intervals_vals(self.ivs@, self.ivs.len() as int)
+    }
+}
+
+impl ValSet {
+    /// The intervals are canonical
+    pub closed spec fn wf(&self) -> bool {
+        intervals_wf(self.ivs@)
+    }
+
+    /// A ValSet is always finite
+    pub proof fn lemma_finite(&self)
+        requires
+            self.wf(),
+        ensures
+            self@.finite(),
+    {
+        lemma_intervals_vals_len(self.ivs@, self.ivs.len() as int);
+    }
+
+    /// The vals of a ValSet all fit in a machine word
+    pub proof fn lemma_words(&self)
+        ensures
+            forall |v: nat| #[trigger] self@.contains(v) ==> v <= u64::MAX,
+    {
+        assert forall |v: nat| #[trigger] self@.contains(v) implies v <= u64::MAX by {
+            let k = choose |k: int| 0 <= k < self.ivs.len() && in_interval(self.ivs[k], v);
+        }
+    }
+
+    pub fn new() -> (s: ValSet)
+        ensures
+            s.wf(),
+            s@ == Set::<nat>::empty(),
+    {
+        let s = ValSet { ivs: Vec::new() };
+        assert(s@ =~= Set::<nat>::empty());
+        s
+    }
+
+    /// The set of the [n] vals from [lo] onwards
+    pub fn from_range(lo: u64, n: u64) -> (s: ValSet)
+        requires
+            lo + n <= u64::MAX + 1,
+        ensures
+            s.wf(),
+            s@ == nat_interval(lo as nat, n as nat),
+    {
+        let mut s = ValSet { ivs: Vec::new() };
+        if n > 0 {
+            let last = lo + (n - 1);
+            s.append(lo, last);
+            assert(interval_vals((lo, last)) =~= nat_interval(lo as nat, n as nat));
+        }
+        assert(s@ =~= nat_interval(lo as nat, n as nat));
+        s
+    }
+
+    /// The number of intervals that start at or before [v]
+    fn find(&self, v: u64) -> (i: usize)
+        requires
+            self.wf(),
+        ensures
+            i <= self.ivs.len(),
+            forall |k: int| 0 <= k < i ==> self.ivs[k].0 <= v,
+            forall |k: int| i <= k < self.ivs.len() ==> self.ivs[k].0 > v,
+    {
+        let mut lo: usize = 0;
+        let mut hi: usize = self.ivs.len();
+        while lo < hi
+            invariant
+                self.wf(),
+                lo <= hi <= self.ivs.len(),
+                forall |k: int| 0 <= k < lo ==> self.ivs[k].0 <= v,
+                forall |k: int| hi <= k < self.ivs.len() ==> self.ivs[k].0 > v,
+            decreases hi - lo
+        {
+            proof {
+                lemma_intervals_sorted(self.ivs@);
+            }
+            let mid = lo + (hi - lo) / 2;
+            if self.ivs[mid].0 <= v {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Adds the vals from [first] up to and including [last], which start no earlier than the
+    /// last interval of the set
+    fn append(&mut self, first: u64, last: u64)
+        requires
+            old(self).wf(),
+            first <= last,
+            old(self).ivs.len() > 0 ==> old(self).ivs@.last().0 <= first,
+        ensures
+            self.wf(),
+            self@ == old(self)@.union(interval_vals((first, last))),
+            self.ivs.len() > 0,
+            self.ivs@.last().0 <= first,
+            self.ivs@.last().1 == if old(self).ivs.len() > 0 && old(self).ivs@.last().1 > last { old(self).ivs@.last().1 } else { last },
+    {
+        let ghost pre = self.ivs@;
+        let len = self.ivs.len();
+        if len > 0 && first as u128 <= self.ivs[len - 1].1 as u128 + 1 {
+            let (start, end) = self.ivs[len - 1];
+            let end = if end < last { last } else { end };
+            self.ivs.set(len - 1, (start, end));
+            proof {
+                let s = self.ivs@;
+                lemma_intervals_sorted(pre);
+                assert forall |i: int| 0 <= i < s.len() implies #[trigger] s[i].0 <= s[i].1 by {
+                    if (i < len - 1) {
+                        assert(pre[i] == s[i]);
+                    }
+                }
+                assert forall |i: int, j: int| #![trigger s[i], s[j]] 0 <= i < j < s.len() implies s[i].1 + 1 < s[j].0 by {
+                    assert(pre[i] == s[i]);
+                    assert(pre[i].1 + 1 < pre[j].0);
+                    if (j < len - 1) {
+                        assert(pre[j] == s[j]);
+                    }
+                }
+                lemma_intervals_vals_prefix(pre, s, len - 1);
+                lemma_intervals_vals_step(pre, len - 1);
+                lemma_intervals_vals_step(s, len - 1);
+                assert(interval_vals(s[len - 1]) =~= interval_vals(pre[len - 1]).union(interval_vals((first, last))));
+                assert(self@ =~= old(self)@.union(interval_vals((first, last))));
+            }
+        } else {
+            self.ivs.push((first, last));
+            proof {
+                let s = self.ivs@;
+                lemma_intervals_sorted(pre);
+                assert forall |i: int| 0 <= i < s.len() implies #[trigger] s[i].0 <= s[i].1 by {
+                    if (i < len) {
+                        assert(pre[i] == s[i]);
+                    }
+                }
+                assert forall |i: int, j: int| #![trigger s[i], s[j]] 0 <= i < j < s.len() implies s[i].1 + 1 < s[j].0 by {
+                    assert(pre[i] == s[i]);
+                    if (j < len) {
+                        assert(pre[j] == s[j]);
+                    } else if (i < len - 1) {
+                        assert(pre[i].1 < pre[len - 1].1);
+                    }
+                }
+                lemma_intervals_vals_prefix(pre, s, len as int);
+                lemma_intervals_vals_step(s, len as int);
+            }
+        }
+    }
+
+    pub fn contains(&self, v: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.contains(v as nat),
+    {
+        let i = self.find(v);
+        proof {
+            lemma_intervals_located(self.ivs@, i as int, v as nat);
+        }
+        i > 0 && v <= self.ivs[i - 1].1
+    }
+
+    /// Whether the set covers the vals from [first] up to and including [last]
+    fn covers(&self, first: u64, last: u64) -> (b: bool)
+        requires
+            self.wf(),
+            first <= last,
+        ensures
+            b == interval_vals((first, last)).subset_of(self@),
+    {
+        let i = self.find(first);
+        proof {
+            lemma_intervals_located(self.ivs@, i as int, first as nat);
+            assert(interval_vals((first, last)).contains(first as nat));
+        }
+        if i == 0 {
+            return false;
+        }
+        let b = last <= self.ivs[i - 1].1;
+        proof {
+            let s = self.ivs@;
+            if (b) {
+                assert forall |v: nat| #[trigger] interval_vals((first, last)).contains(v) implies self@.contains(v) by {
+                    assert(in_interval(s[i - 1], v));
+                }
+            } else if (first <= s[i - 1].1) {
+                lemma_intervals_gap(s, i - 1);
+                assert(interval_vals((first, last)).contains((s[i - 1].1 + 1) as nat));
+            }
+        }
+        b
+    }
+
+    /// Whether none of the vals from [first] up to and including [last] are in the set
+    fn avoids(&self, first: u64, last: u64) -> (b: bool)
+        requires
+            self.wf(),
+            first <= last,
+        ensures
+            b == self@.disjoint(interval_vals((first, last))),
+    {
+        let i = self.find(last);
+        let b = i == 0 || self.ivs[i - 1].1 < first;
+        proof {
+            let s = self.ivs@;
+            lemma_intervals_sorted(s);
+            if (b) {
+                assert forall |v: nat| #[trigger] self@.contains(v) implies !interval_vals((first, last)).contains(v) by {
+                    let k = choose |k: int| 0 <= k < s.len() && in_interval(s[k], v);
+                    if (k >= i) {
+                        assert(s[k].0 > last);
+                    } else if (k < i - 1) {
+                        assert(s[k].1 < s[i - 1].1);
+                    }
+                }
+            } else {
+                let w = if first <= s[i - 1].0 { s[i - 1].0 } else { first };
+                assert(in_interval(s[i - 1], w as nat));
+                assert(self@.contains(w as nat));
+                assert(interval_vals((first, last)).contains(w as nat));
+            }
+        }
+        b
+    }
+
+    pub fn insert(&mut self, v: u64)
+        requires
+            old(self).wf(),
+        ensures
+            self.wf(),
+            self@ == old(self)@.insert(v as nat),
+    {
+        self.insert_range(v, 1);
+        assert(nat_interval(v as nat, 1) =~= set![v as nat]);
+        assert(self@ =~= old(self)@.insert(v as nat));
+    }
+
+    /// Adds the [n] vals from [lo] onwards
+    pub fn insert_range(&mut self, lo: u64, n: u64)
+        requires
+            old(self).wf(),
+            lo + n <= u64::MAX + 1,
+        ensures
+            self.wf(),
+            self@ == old(self)@.union(nat_interval(lo as nat, n as nat)),
+    {
+        *self = self.union(&ValSet::from_range(lo, n));
+    }
+
+    /// A copy of the set
+    pub fn copy(&self) -> (s: ValSet)
+        requires
+            self.wf(),
+        ensures
+            s.wf(),
+            s@ == self@,
+    {
+        let mut ivs: Vec<(u64, u64)> = Vec::new();
+        let mut i: usize = 0;
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                ivs@ == self.ivs@.take(i as int),
+            decreases self.ivs.len() - i
+        {
+            ivs.push(self.ivs[i]);
+            i = i + 1;
+            assert(ivs@ =~= self.ivs@.take(i as int));
+        }
+        assert(ivs@ =~= self.ivs@);
+        ValSet { ivs }
+    }
+
+    /// Builds the set of the vals in [vals], which may repeat
+    pub fn from_vec(vals: &Vec<u64>) -> (s: ValSet)
+        ensures
+            s.wf(),
+            forall |v: nat| #[trigger] s@.contains(v) <==> exists |i: int| 0 <= i < vals.len() && vals[i] as nat == v,
+    {
+        let mut s = ValSet::new();
+        let mut i: usize = 0;
+        while i < vals.len()
+            invariant
+                i <= vals.len(),
+                s.wf(),
+                forall |v: nat| #[trigger] s@.contains(v) <==> exists |j: int| 0 <= j < i && vals[j] as nat == v,
+            decreases vals.len() - i
+        {
+            s.insert(vals[i]);
+            i = i + 1;
+        }
+        s
+    }
+
+    /// The vals of the set, each once, in increasing order
+    pub fn to_vec(&self) -> (v: Vec<u64>)
+        requires
+            self.wf(),
+        ensures
+            v@.no_duplicates(),
+            forall |x: nat| #[trigger] self@.contains(x) <==> exists |i: int| 0 <= i < v.len() && v[i] as nat == x,
+    {
+        let mut v: Vec<u64> = Vec::new();
+        let mut i: usize = 0;
+        assert(prefix_vals(v@, 0) =~= intervals_vals(self.ivs@, 0));
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                self.wf(),
+                v@.no_duplicates(),
+                prefix_vals(v@, v.len() as int) == intervals_vals(self.ivs@, i as int),
+                forall |t: int| 0 <= t < v.len() && i < self.ivs.len() ==> v[t] < self.ivs[i as int].0,
+            decreases self.ivs.len() - i
+        {
+            let (first, last) = self.ivs[i];
+            let mut x: u128 = first as u128;
+            proof {
+                assert(intervals_vals(self.ivs@, i as int).union(Set::new(|u: nat| first <= u < x)) =~= intervals_vals(self.ivs@, i as int));
+            }
+            while x <= last as u128
+                invariant
+                    i < self.ivs.len(),
+                    self.wf(),
+                    (first, last) == self.ivs[i as int],
+                    first <= x <= last + 1,
+                    v@.no_duplicates(),
+                    prefix_vals(v@, v.len() as int) == intervals_vals(self.ivs@, i as int).union(Set::new(|u: nat| first <= u < x)),
+                    forall |t: int| 0 <= t < v.len() ==> v[t] < x,
+                decreases last + 1 - x
+            {
+                let ghost before = v@;
+                v.push(x as u64);
+                proof {
+                    lemma_prefix_vals_push(before, x as u64);
+                    assert(prefix_vals(v@, v.len() as int) =~= intervals_vals(self.ivs@, i as int).union(Set::new(|u: nat| first <= u < x + 1)));
+                    assert forall |a: int, b: int| 0 <= a < v.len() && 0 <= b < v.len() && a != b implies v[a] != v[b] by {
+                        if (a == v.len() - 1) {
+                            assert(v[b] == before[b]);
+                        } else if (b == v.len() - 1) {
+                            assert(v[a] == before[a]);
+                        } else {
+                            assert(v[a] == before[a] && v[b] == before[b]);
+                        }
+                    }
+                }
+                x = x + 1;
+            }
+            proof {
+                lemma_intervals_vals_step(self.ivs@, i as int);
+                assert(Set::new(|u: nat| first <= u < x) =~= interval_vals(self.ivs[i as int]));
+                lemma_intervals_sorted(self.ivs@);
+                if (i + 1 < self.ivs.len()) {
+                    assert(self.ivs[i as int].1 + 1 < self.ivs[i + 1].0);
+                }
+            }
+            i = i + 1;
+        }
+        v
+    }
+
+    /// The number of vals in the set, which may exceed a machine word
+    pub fn len(&self) -> (n: u128)
+        requires
+            self.wf(),
+        ensures
+            self@.finite(),
+            n == self@.len(),
+    {
+        let mut total: u128 = 0;
+        let mut i: usize = 0;
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                self.wf(),
+                total == intervals_len(self.ivs@, i as int),
+                i == 0 ==> total == 0,
+                i > 0 ==> total <= self.ivs[i - 1].1 + 1,
+            decreases self.ivs.len() - i
+        {
+            let (first, last) = self.ivs[i];
+            proof {
+                if (i > 0) {
+                    assert(self.ivs[i - 1].1 + 1 < self.ivs[i as int].0);
+                }
+            }
+            total = total + (last - first) as u128 + 1;
+            i = i + 1;
+        }
+        proof {
+            lemma_intervals_vals_len(self.ivs@, self.ivs.len() as int);
+        }
+        total
+    }
+
+    /// Walks both sets in order, in time linear in their number of intervals
+    pub fn subset_of(&self, other: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == self@.subset_of(other@),
+    {
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                j <= other.ivs.len(),
+                self.wf(),
+                other.wf(),
+                intervals_vals(self.ivs@, i as int).subset_of(other@),
+                i < self.ivs.len() ==> forall |k: int| 0 <= k < j ==> #[trigger] other.ivs[k].1 < self.ivs[i as int].0,
+            decreases self.ivs.len() - i + other.ivs.len() - j
+        {
+            proof {
+                lemma_intervals_sorted(self.ivs@);
+                lemma_intervals_sorted(other.ivs@);
+            }
+            let (first, last) = self.ivs[i];
+            if j < other.ivs.len() && other.ivs[j].1 < first {
+                j = j + 1;
+            } else {
+                if j == other.ivs.len() || first < other.ivs[j].0 {
+                    proof {
+                        // No interval of [other] holds [first]: those before [j] end before it,
+                        // and the others start after it
+                        assert(in_interval(self.ivs[i as int], first as nat));
+                        assert(self@.contains(first as nat));
+                        if (other@.contains(first as nat)) {
+                            let k = choose |k: int| 0 <= k < other.ivs.len() && in_interval(other.ivs[k], first as nat);
+                            if (k > j) {
+                                assert(other.ivs[j as int].0 < other.ivs[k].0);
+                            }
+                        }
+                    }
+                    return false;
+                }
+                if other.ivs[j].1 < last {
+                    proof {
+                        // The val just past the [j]th interval of [other] is in the [i]th of the set
+                        lemma_intervals_gap(other.ivs@, j as int);
+                        assert(in_interval(self.ivs[i as int], (other.ivs[j as int].1 + 1) as nat));
+                        assert(self@.contains((other.ivs[j as int].1 + 1) as nat));
+                    }
+                    return false;
+                }
+                proof {
+                    lemma_intervals_vals_step(self.ivs@, i as int);
+                    assert forall |v: nat| #[trigger] interval_vals(self.ivs[i as int]).contains(v) implies other@.contains(v) by {
+                        assert(in_interval(other.ivs[j as int], v));
+                    }
+                }
+                i = i + 1;
+            }
+        }
+        true
+    }
+
+    /// Walks both sets in order, in time linear in their number of intervals
+    pub fn disjoint(&self, other: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == self@.disjoint(other@),
+    {
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        while i < self.ivs.len() && j < other.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                j <= other.ivs.len(),
+                self.wf(),
+                other.wf(),
+                intervals_vals(self.ivs@, i as int).disjoint(other@),
+                intervals_vals(other.ivs@, j as int).disjoint(self@),
+            decreases self.ivs.len() - i + other.ivs.len() - j
+        {
+            proof {
+                lemma_intervals_sorted(self.ivs@);
+                lemma_intervals_sorted(other.ivs@);
+            }
+            let (a1, a2) = self.ivs[i];
+            let (b1, b2) = other.ivs[j];
+            if a2 < b1 {
+                proof {
+                    // The intervals of [other] before [j] avoid the set, and the others start
+                    // past the [i]th interval of the set
+                    lemma_intervals_vals_step(self.ivs@, i as int);
+                    assert forall |v: nat| #[trigger] interval_vals((a1, a2)).contains(v) implies !other@.contains(v) by {
+                        assert(self@.contains(v));
+                        if (other@.contains(v)) {
+                            let k = choose |k: int| 0 <= k < other.ivs.len() && in_interval(other.ivs[k], v);
+                            if (k < j) {
+                                assert(intervals_vals(other.ivs@, j as int).contains(v));
+                            } else if (k > j) {
+                                assert(other.ivs[j as int].0 < other.ivs[k].0);
+                            }
+                        }
+                    }
+                }
+                i = i + 1;
+            } else if b2 < a1 {
+                proof {
+                    lemma_intervals_vals_step(other.ivs@, j as int);
+                    assert forall |v: nat| #[trigger] interval_vals((b1, b2)).contains(v) implies !self@.contains(v) by {
+                        assert(other@.contains(v));
+                        if (self@.contains(v)) {
+                            let k = choose |k: int| 0 <= k < self.ivs.len() && in_interval(self.ivs[k], v);
+                            if (k < i) {
+                                assert(intervals_vals(self.ivs@, i as int).contains(v));
+                            } else if (k > i) {
+                                assert(self.ivs[i as int].0 < self.ivs[k].0);
+                            }
+                        }
+                    }
+                }
+                j = j + 1;
+            } else {
+                proof {
+                    // The intervals overlap from the later of their starts
+                    let w = if a1 <= b1 { b1 } else { a1 };
+                    assert(in_interval(self.ivs[i as int], w as nat));
+                    assert(in_interval(other.ivs[j as int], w as nat));
+                    assert(self@.contains(w as nat) && other@.contains(w as nat));
+                }
+                return false;
+            }
+        }
+        proof {
+            if (i == self.ivs.len()) {
+                assert(self@.disjoint(other@));
+            } else {
+                assert(other@.disjoint(self@));
+            }
+        }
+        true
+    }
+
+    /// Whether none of the vals of [other] outside of [except] are in the set. Rather than walking
+    /// the set, probes it by binary search for each piece of [other] between the intervals of
+    /// [except], in time O((k + e) log n) for the k intervals of [other], e of [except] and n of
+    /// the set
+    pub fn disjoint_outside(&self, other: &ValSet, except: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+            except.wf(),
+        ensures
+            b == self@.disjoint(other@.difference(except@)),
+    {
+        if other.ivs.len() == 0 {
+            assert(other@.difference(except@) =~= Set::<nat>::empty());
+            return true;
+        }
+        proof {
+            lemma_intervals_sorted(other.ivs@);
+            assert forall |v: nat| #[trigger] self@.contains(v) && other@.contains(v) && !except@.contains(v) implies
+                other.ivs[0].0 <= v by {
+                let k = choose |k: int| 0 <= k < other.ivs.len() && in_interval(other.ivs[k], v);
+                if (k > 0) {
+                    assert(other.ivs[0].0 < other.ivs[k].0);
+                }
+            }
+        }
+        // The vals of [other] outside of [except] which are left to probe are those from [cur] on,
+        // and the intervals of [except] before [e] all end before [cur]
+        let mut i: usize = 0;
+        let mut e: usize = 0;
+        let mut cur: u64 = other.ivs[0].0;
+        while i < other.ivs.len()
+            invariant
+                self.wf(),
+                other.wf(),
+                except.wf(),
+                i <= other.ivs.len(),
+                e <= except.ivs.len(),
+                i < other.ivs.len() ==> other.ivs[i as int].0 <= cur <= other.ivs[i as int].1,
+                i < other.ivs.len() ==> forall |k: int| 0 <= k < e ==> #[trigger] except.ivs[k].1 < cur,
+                forall |v: nat| #[trigger] self@.contains(v) && other@.contains(v) && !except@.contains(v) ==>
+                    i < other.ivs.len() && cur <= v,
+            decreases other.ivs.len() - i + except.ivs.len() - e
+        {
+            proof {
+                lemma_intervals_sorted(other.ivs@);
+                lemma_intervals_sorted(except.ivs@);
+            }
+            let (first, last) = other.ivs[i];
+            if e < except.ivs.len() && except.ivs[e].1 < cur {
+                e = e + 1;
+            } else if e == except.ivs.len() || except.ivs[e].0 > last {
+                // No val of [except] is left in the [i]th interval of [other]
+                if !self.avoids(cur, last) {
+                    proof {
+                        let w = choose |w: nat| self@.contains(w) && interval_vals((cur, last)).contains(w);
+                        lemma_intervals_outside(other.ivs@, except.ivs@, i as int, e as int, cur as nat, w);
+                        assert(other@.difference(except@).contains(w));
+                    }
+                    return false;
+                }
+                proof {
+                    assert forall |v: nat| #[trigger] self@.contains(v) && other@.contains(v) && !except@.contains(v) implies
+                        i + 1 < other.ivs.len() && other.ivs[i + 1].0 <= v by {
+                        if (v <= last) {
+                            assert(interval_vals((cur, last)).contains(v));
+                        }
+                        lemma_intervals_past(other.ivs@, i as int, v);
+                    }
+                    if (i + 1 < other.ivs.len()) {
+                        assert(last + 1 < other.ivs[i + 1].0);
+                    }
+                }
+                i = i + 1;
+                if i < other.ivs.len() {
+                    cur = other.ivs[i].0;
+                }
+            } else {
+                // The [e]th interval of [except] starts within the [i]th of [other], so only the
+                // vals before it are probed
+                let (efirst, elast) = except.ivs[e];
+                if cur < efirst && !self.avoids(cur, efirst - 1) {
+                    proof {
+                        let w = choose |w: nat| self@.contains(w) && interval_vals((cur, (efirst - 1) as u64)).contains(w);
+                        lemma_intervals_outside(other.ivs@, except.ivs@, i as int, e as int, cur as nat, w);
+                        assert(other@.difference(except@).contains(w));
+                    }
+                    return false;
+                }
+                proof {
+                    assert forall |v: nat| #[trigger] self@.contains(v) && other@.contains(v) && !except@.contains(v) implies
+                        v > elast by {
+                        if (v < efirst) {
+                            assert(interval_vals((cur, (efirst - 1) as u64)).contains(v));
+                        } else if (v <= elast) {
+                            assert(in_interval(except.ivs[e as int], v));
+                            assert(except@.contains(v));
+                        }
+                    }
+                }
+                if elast >= last {
+                    proof {
+                        assert forall |v: nat| #[trigger] self@.contains(v) && other@.contains(v) && !except@.contains(v) implies
+                            i + 1 < other.ivs.len() && other.ivs[i + 1].0 <= v by {
+                            lemma_intervals_past(other.ivs@, i as int, v);
+                        }
+                        if (i + 1 < other.ivs.len()) {
+                            assert(last + 1 < other.ivs[i + 1].0);
+                        }
+                    }
+                    i = i + 1;
+                    if i < other.ivs.len() {
+                        cur = other.ivs[i].0;
+                    }
+                } else {
+                    cur = elast + 1;
+                    e = e + 1;
+                }
+            }
+        }
+        proof {
+            assert forall |v: nat| #[trigger] self@.contains(v) implies !other@.difference(except@).contains(v) by {}
+        }
+        true
+    }
+
+    /// Whether the set and [other] share no val, probing the set by binary search for each
+    /// interval of [other], in time O(k log n) for the k intervals of [other] and n of the set
+    pub fn disjoint_probing(&self, other: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == self@.disjoint(other@),
+    {
+        let none = ValSet::new();
+        assert(other@.difference(none@) =~= other@);
+        self.disjoint_outside(other, &none)
+    }
+
+    /// Whether every val of the set is in [other], probing the set by binary search for each gap
+    /// between the intervals of [other], in time O(k log n) for the k intervals of [other] and n
+    /// of the set
+    pub fn subset_of_probing(&self, other: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == self@.subset_of(other@),
+    {
+        proof {
+            self.lemma_words();
+        }
+        // The vals of the set below [lo], the start of the gap before the [j]th interval of
+        // [other], are all in [other]
+        let mut j: usize = 0;
+        let mut lo: u64 = 0;
+        while j < other.ivs.len()
+            invariant
+                self.wf(),
+                other.wf(),
+                j <= other.ivs.len(),
+                j == 0 ==> lo == 0,
+                j > 0 ==> lo as nat == other.ivs[j - 1].1 + 1,
+                forall |v: nat| #[trigger] self@.contains(v) && v < lo ==> other@.contains(v),
+                forall |v: nat| #[trigger] self@.contains(v) ==> v <= u64::MAX,
+            decreases other.ivs.len() - j
+        {
+            proof {
+                lemma_intervals_sorted(other.ivs@);
+            }
+            let (first, last) = other.ivs[j];
+            if lo < first && !self.avoids(lo, first - 1) {
+                proof {
+                    // A val of the set lies in the gap, after the intervals of [other] before
+                    // the [j]th and before the others
+                    let w = choose |w: nat| self@.contains(w) && interval_vals((lo, (first - 1) as u64)).contains(w);
+                    if (other@.contains(w)) {
+                        let k = choose |k: int| 0 <= k < other.ivs.len() && in_interval(other.ivs[k], w);
+                        if (k < j - 1) {
+                            assert(other.ivs[k].1 < other.ivs[j - 1].1);
+                        } else if (k > j) {
+                            assert(other.ivs[j as int].0 < other.ivs[k].0);
+                        }
+                    }
+                }
+                return false;
+            }
+            proof {
+                assert forall |v: nat| #[trigger] self@.contains(v) && v <= last implies other@.contains(v) by {
+                    if (v >= lo) {
+                        if (v >= first) {
+                            assert(in_interval(other.ivs[j as int], v));
+                        } else {
+                            assert(interval_vals((lo, (first - 1) as u64)).contains(v));
+                        }
+                    }
+                }
+            }
+            if last == u64::MAX {
+                // The [j]th interval of [other] reaches the last of the machine words
+                return true;
+            }
+            lo = last + 1;
+            j = j + 1;
+        }
+        // No val of the set may lie past the last interval of [other]
+        let b = self.avoids(lo, u64::MAX);
+        proof {
+            if (b) {
+                assert forall |v: nat| #[trigger] self@.contains(v) implies other@.contains(v) by {
+                    if (v >= lo) {
+                        assert(interval_vals((lo, u64::MAX)).contains(v));
+                    }
+                }
+            } else {
+                let w = choose |w: nat| self@.contains(w) && interval_vals((lo, u64::MAX)).contains(w);
+                if (other@.contains(w)) {
+                    let k = choose |k: int| 0 <= k < other.ivs.len() && in_interval(other.ivs[k], w);
+                    lemma_intervals_sorted(other.ivs@);
+                    if (k < j - 1) {
+                        assert(other.ivs[k].1 < other.ivs[j - 1].1);
+                    }
+                }
+            }
+        }
+        b
+    }
+
+    /// Whether the set has the same vals as [other]
+    pub fn eq(&self, other: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            b == (self@ == other@),
+    {
+        let b = self.subset_of(other) && other.subset_of(self);
+        if b {
+            assert(self@ =~= other@);
+        }
+        b
+    }
+
+    pub fn union(&self, other: &ValSet) -> (u: ValSet)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            u.wf(),
+            u@ == self@.union(other@),
+    {
+        let mut u = ValSet { ivs: Vec::new() };
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        assert(u@ =~= intervals_vals(self.ivs@, 0).union(intervals_vals(other.ivs@, 0)));
+        while i < self.ivs.len() || j < other.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                j <= other.ivs.len(),
+                self.wf(),
+                other.wf(),
+                u.wf(),
+                u@ == intervals_vals(self.ivs@, i as int).union(intervals_vals(other.ivs@, j as int)),
+                u.ivs.len() > 0 && i < self.ivs.len() ==> u.ivs@.last().0 <= self.ivs[i as int].0,
+                u.ivs.len() > 0 && j < other.ivs.len() ==> u.ivs@.last().0 <= other.ivs[j as int].0,
+            decreases self.ivs.len() - i + other.ivs.len() - j
+        {
+            proof {
+                lemma_intervals_sorted(self.ivs@);
+                lemma_intervals_sorted(other.ivs@);
+            }
+            if j == other.ivs.len() || (i < self.ivs.len() && self.ivs[i].0 <= other.ivs[j].0) {
+                let (first, last) = self.ivs[i];
+                u.append(first, last);
+                proof {
+                    lemma_intervals_vals_step(self.ivs@, i as int);
+                    assert(u@ =~= intervals_vals(self.ivs@, i + 1).union(intervals_vals(other.ivs@, j as int)));
+                }
+                i = i + 1;
+            } else {
+                let (first, last) = other.ivs[j];
+                u.append(first, last);
+                proof {
+                    lemma_intervals_vals_step(other.ivs@, j as int);
+                    assert(u@ =~= intervals_vals(self.ivs@, i as int).union(intervals_vals(other.ivs@, j + 1)));
+                }
+                j = j + 1;
+            }
+        }
+        u
+    }
+
+    /// The vals of the set which are not in [other], walking both sets in order
+    pub fn difference(&self, other: &ValSet) -> (d: ValSet)
+        requires
+            self.wf(),
+            other.wf(),
+        ensures
+            d.wf(),
+            d@ == self@.difference(other@),
+    {
+        let mut d = ValSet { ivs: Vec::new() };
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        // The first val of the [i]th interval which is yet to be walked
+        let mut lo: u64 = if self.ivs.len() > 0 { self.ivs[0].0 } else { 0 };
+        assert(d@ =~= intervals_vals_upto(self.ivs@, 0, lo as nat).difference(other@));
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                j <= other.ivs.len(),
+                self.wf(),
+                other.wf(),
+                d.wf(),
+                i < self.ivs.len() ==> self.ivs[i as int].0 <= lo <= self.ivs[i as int].1,
+                d@ == intervals_vals_upto(self.ivs@, i as int, lo as nat).difference(other@),
+                i < self.ivs.len() && d.ivs.len() > 0 ==> d.ivs@.last().1 < lo,
+                i < self.ivs.len() ==> forall |k: int| 0 <= k < j ==> #[trigger] other.ivs[k].1 < lo,
+            decreases self.ivs.len() - i + other.ivs.len() - j
+        {
+            proof {
+                lemma_intervals_sorted(self.ivs@);
+                lemma_intervals_sorted(other.ivs@);
+            }
+            let ghost before = d@;
+            let (first, last) = self.ivs[i];
+            if j < other.ivs.len() && other.ivs[j].1 < lo {
+                j = j + 1;
+            } else if j == other.ivs.len() || last < other.ivs[j].0 {
+                // The rest of the [i]th interval is kept whole
+                d.append(lo, last);
+                proof {
+                    lemma_intervals_skip(other.ivs@, j as int, lo as nat, (last + 1) as nat);
+                    lemma_intervals_vals_step(self.ivs@, i as int);
+                    assert(interval_vals((lo, last)) =~= Set::new(|v: nat| lo <= v < last + 1));
+                    assert(intervals_vals_upto(self.ivs@, i + 1, 0) =~=
+                        intervals_vals_upto(self.ivs@, i as int, lo as nat).union(interval_vals((lo, last))));
+                }
+                i = i + 1;
+                if i < self.ivs.len() {
+                    lo = self.ivs[i].0;
+                    proof {
+                        assert(self.ivs[i - 1].1 + 1 < self.ivs[i as int].0);
+                    }
+                }
+                proof {
+                    assert(intervals_vals_upto(self.ivs@, i as int, lo as nat) =~= intervals_vals_upto(self.ivs@, i as int, 0));
+                    assert(d@ =~= intervals_vals_upto(self.ivs@, i as int, lo as nat).difference(other@));
+                }
+            } else {
+                // The [j]th interval of [other] cuts the rest of the [i]th, which is kept up to
+                // its start
+                let (b1, b2) = other.ivs[j];
+                let ghost kept = Set::new(|v: nat| lo <= v < b1);
+                if lo < b1 {
+                    d.append(lo, b1 - 1);
+                    proof {
+                        assert(interval_vals((lo, (b1 - 1) as u64)) =~= kept);
+                    }
+                } else {
+                    assert(kept =~= Set::<nat>::empty());
+                }
+                proof {
+                    lemma_intervals_skip(other.ivs@, j as int, lo as nat, b1 as nat);
+                }
+                if last <= b2 {
+                    proof {
+                        lemma_intervals_vals_step(self.ivs@, i as int);
+                        assert forall |v: nat| #[trigger] intervals_vals_upto(self.ivs@, i + 1, 0).difference(other@).contains(v) ==
+                            before.union(kept).contains(v) by {
+                            if (b1 <= v <= last) {
+                                assert(in_interval(other.ivs[j as int], v));
+                            }
+                        }
+                        assert(d@ =~= intervals_vals_upto(self.ivs@, i + 1, 0).difference(other@));
+                    }
+                    i = i + 1;
+                    if i < self.ivs.len() {
+                        lo = self.ivs[i].0;
+                        proof {
+                            assert(self.ivs[i - 1].1 + 1 < self.ivs[i as int].0);
+                        }
+                    }
+                    proof {
+                        assert(intervals_vals_upto(self.ivs@, i as int, lo as nat) =~= intervals_vals_upto(self.ivs@, i as int, 0));
+                    }
+                } else {
+                    lo = b2 + 1;
+                    j = j + 1;
+                    proof {
+                        assert forall |v: nat| #[trigger] intervals_vals_upto(self.ivs@, i as int, lo as nat).difference(other@).contains(v) ==
+                            before.union(kept).contains(v) by {
+                            if (b1 <= v <= b2) {
+                                assert(in_interval(other.ivs[j - 1], v));
+                            }
+                        }
+                        assert(d@ =~= intervals_vals_upto(self.ivs@, i as int, lo as nat).difference(other@));
+                    }
+                }
+            }
+        }
+        proof {
+            assert(intervals_vals_upto(self.ivs@, i as int, lo as nat) =~= self@);
+        }
+        d
+    }
+
+    /// Whether the set covers the [n] vals from [lo] onwards
+    pub fn covers_range(&self, lo: u64, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == nat_interval(lo as nat, n as nat).subset_of(self@),
+    {
+        if n == 0 {
+            assert(nat_interval(lo as nat, 0) =~= Set::<nat>::empty());
+            return true;
+        }
+        if n - 1 > u64::MAX - lo {
+            proof {
+                // The range runs past the machine words, which no interval reaches
+                let v = (u64::MAX + 1) as nat;
+                assert(nat_interval(lo as nat, n as nat).contains(v));
+                if (self@.contains(v)) {
+                    let k = choose |k: int| 0 <= k < self.ivs.len() && in_interval(self.ivs[k], v);
+                }
+            }
+            return false;
+        }
+        let last = lo + (n - 1);
+        assert(nat_interval(lo as nat, n as nat) =~= interval_vals((lo, last)));
+        self.covers(lo, last)
+    }
+
+    /// Whether none of the [n] vals from [lo] onwards are in the set
+    pub fn disjoint_from_range(&self, lo: u64, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.disjoint(nat_interval(lo as nat, n as nat)),
+    {
+        if n == 0 {
+            assert(nat_interval(lo as nat, 0) =~= Set::<nat>::empty());
+            return true;
+        }
+        // Vals past the machine words are in no interval, so the range may stop at the last one
+        let last = if n - 1 > u64::MAX - lo { u64::MAX } else { lo + (n - 1) };
+        let b = self.avoids(lo, last);
+        proof {
+            if (b) {
+                assert forall |v: nat| #[trigger] self@.contains(v) implies !nat_interval(lo as nat, n as nat).contains(v) by {
+                    let k = choose |k: int| 0 <= k < self.ivs.len() && in_interval(self.ivs[k], v);
+                    assert(!interval_vals((lo, last)).contains(v));
+                }
+            } else {
+                let v = choose |v: nat| self@.contains(v) && interval_vals((lo, last)).contains(v);
+                assert(nat_interval(lo as nat, n as nat).contains(v));
+            }
+        }
+        b
+    }
+
+    /// Whether every val of the set is below [n]
+    pub fn below(&self, n: u64) -> (b: bool)
+        requires
+            self.wf(),
+        ensures
+            b == self@.subset_of(nat_range(n as nat)),
+    {
+        let len = self.ivs.len();
+        if len == 0 {
+            assert(self@ =~= Set::<nat>::empty());
+            return true;
+        }
+        let b = self.ivs[len - 1].1 < n;
+        proof {
+            let s = self.ivs@;
+            lemma_intervals_sorted(s);
+            if (b) {
+                assert forall |v: nat| #[trigger] self@.contains(v) implies nat_range(n as nat).contains(v) by {
+                    let k = choose |k: int| 0 <= k < len && in_interval(s[k], v);
+                    if (k < len - 1) {
+                        assert(s[k].1 < s[len - 1].1);
+                    }
+                }
+            } else {
+                assert(in_interval(s[len - 1], s[len - 1].1 as nat));
+                assert(self@.contains(s[len - 1].1 as nat));
+            }
+        }
+        b
+    }
+
+    /// Whether every val of the set, moved by [offset], lands on a val of [dst]
+    pub fn translates_into(&self, offset: i64, dst: &ValSet) -> (b: bool)
+        requires
+            self.wf(),
+            dst.wf(),
+        ensures
+            b ==> forall |v: nat| #[trigger] self@.contains(v) ==> {
+                &&& 0 <= v + offset
+                &&& dst@.contains((v + offset) as nat)
+            },
+    {
+        let mut i: usize = 0;
+        while i < self.ivs.len()
+            invariant
+                i <= self.ivs.len(),
+                self.wf(),
+                dst.wf(),
+                forall |v: nat| #[trigger] intervals_vals(self.ivs@, i as int).contains(v) ==> {
+                    &&& 0 <= v + offset
+                    &&& dst@.contains((v + offset) as nat)
+                },
+            decreases self.ivs.len() - i
+        {
+            let (first, last) = self.ivs[i];
+            let lo = first as i128 + offset as i128;
+            let hi = last as i128 + offset as i128;
+            if lo < 0 || hi > u64::MAX as i128 {
+                return false;
+            }
+            if !dst.covers(lo as u64, hi as u64) {
+                return false;
+            }
+            proof {
+                lemma_intervals_vals_step(self.ivs@, i as int);
+                assert forall |v: nat| #[trigger] intervals_vals(self.ivs@, i + 1).contains(v) implies {
+                    &&& 0 <= v + offset
+                    &&& dst@.contains((v + offset) as nat)
+                } by {
+                    if (interval_vals(self.ivs[i as int]).contains(v)) {
+                        assert(interval_vals((lo as u64, hi as u64)).contains((v + offset) as nat));
+                    }
+                }
+            }
+            i = i + 1;
+        }
+        true
+    }
+}
+
+} // verus!
@@ -0,0 +1,83 @@
+/// MIT License
+///
+/// Copyright (c) 2025 Ilias Karimalis
+
+use vstd::prelude::*;
+
+verus!
+{
+
+/// Declaration of the kinds of Resources a system is made of: which kinds exist physically, and
+/// which kinds may be mapped onto which
+pub trait ResourceKind: Sized {
+    /// Whether Resources of this kind exist physically, rather than only as names
+    spec fn physical(self) -> bool;
+
+    /// Whether a node of this kind may be mapped onto a node of kind [other]
+    spec fn maps_onto(self, other: Self) -> bool;
+
+    /// Whether Resources of this kind are delivered to a ProtectionDomain which handles them,
+    /// rather than mapped
+    spec fn delivered(self) -> bool;
+
+    /// A kind which exists physically only ever maps onto kinds which do
+    proof fn lemma_physical_maps_onto_physical(self, other: Self)
+        requires
+            self.physical(),
+            self.maps_onto(other),
+        ensures
+            other.physical();
+
+    /// A kind which is delivered neither maps onto any kind nor is mapped onto by one
+    proof fn lemma_delivered_never_mapped(self, other: Self)
+        requires
+            self.delivered(),
+        ensures
+            !self.maps_onto(other),
+            !other.maps_onto(self);
+}
+
+/// The kinds of Resources of a commodity machine, which the executable graph is instantiated with
+pub ghost enum StandardKind {
+    Memory,
+    /// Blocks of a storage device
+    Storage,
+    /// Registers of a device, and the addresses a device accesses memory through
+    Device,
+    /// ProtectionDomain identifiers, which only ever exist as names
+    PdId,
+    /// Interrupt lines, whose vals are the IRQ numbers of the machine, global across its
+    /// interrupt controllers
+    Irq,
+}
+
+impl ResourceKind for StandardKind {
+    open spec fn physical(self) -> bool {
+        !(self is PdId)
+    }
+
+    /// Memory is backed by memory, by the registers of devices, and by storage, as memory mapped
+    /// files and swap are. Devices access memory through their IOMMU. Interrupt lines are never
+    /// mapped, and every other kind only maps onto itself.
+    open spec fn maps_onto(self, other: StandardKind) -> bool {
+        match (self, other) {
+            (StandardKind::Memory, StandardKind::Memory) => true,
+            (StandardKind::Memory, StandardKind::Storage) => true,
+            (StandardKind::Memory, StandardKind::Device) => true,
+            (StandardKind::Device, StandardKind::Memory) => true,
+            (StandardKind::Irq, _) | (_, StandardKind::Irq) => false,
+            _ => self == other,
+        }
+    }
+
+    /// Interrupt lines are delivered to their handler
+    open spec fn delivered(self) -> bool {
+        self is Irq
+    }
+
+    proof fn lemma_physical_maps_onto_physical(self, other: StandardKind) {}
+
+    proof fn lemma_delivered_never_mapped(self, other: StandardKind) {}
+}
+
+} // verus!
@@ -3,6 +3,8 @@
 /// Copyright (c) 2025 Ilias Karimalis
 
 mod component;
+mod kind;
 mod dag;
 mod utils;
 mod barrelfish;
+mod exec;
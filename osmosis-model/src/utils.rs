@@ -4,6 +4,7 @@
 
 use vstd::prelude::*;
 use vstd::set::Set;
+use vstd::set_lib::*;
 
 verus! {
 
@@ -22,6 +23,209 @@ pub proof fn set_map_finite_preserving<A, B>(x: Set<A>, map_fn: spec_fn(A) -> B)
     }
 }
 
+/// Sums [f] over the elements of a finite set, an infinite set sums to 0
+pub open spec fn set_sum<A>(s: Set<A>, f: spec_fn(A) -> nat) -> nat
+    decreases s.len()
+{
+    if (s.finite() && s.len() > 0) {
+        let x = s.choose();
+        f(x) + set_sum(s.remove(x), f)
+    } else {
+        0
+    }
+}
+
+pub proof fn lemma_set_sum_remove<A>(s: Set<A>, x: A, f: spec_fn(A) -> nat)
+    requires s.finite(), s.contains(x)
+    ensures set_sum(s, f) == f(x) + set_sum(s.remove(x), f)
+    decreases s.len()
+{
+    let y = s.choose();
+    if (y != x) {
+        lemma_set_sum_remove(s.remove(y), x, f);
+        lemma_set_sum_remove(s.remove(x), y, f);
+        assert(s.remove(y).remove(x) =~= s.remove(x).remove(y));
+    }
+}
+
+pub proof fn lemma_set_sum_insert<A>(s: Set<A>, x: A, f: spec_fn(A) -> nat)
+    requires s.finite(), !s.contains(x)
+    ensures set_sum(s.insert(x), f) == f(x) + set_sum(s, f)
+{
+    lemma_set_sum_remove(s.insert(x), x, f);
+    assert(s.insert(x).remove(x) =~= s);
+}
+
+pub proof fn lemma_set_sum_congruent<A>(s: Set<A>, f: spec_fn(A) -> nat, g: spec_fn(A) -> nat)
+    requires forall |x: A| #[trigger] s.contains(x) ==> f(x) == g(x)
+    ensures set_sum(s, f) == set_sum(s, g)
+    decreases s.len()
+{
+    if (s.finite() && s.len() > 0) {
+        let x = s.choose();
+        lemma_set_sum_congruent(s.remove(x), f, g);
+    }
+}
+
+pub proof fn lemma_set_sum_subset<A>(s: Set<A>, t: Set<A>, f: spec_fn(A) -> nat)
+    requires t.finite(), s.subset_of(t)
+    ensures set_sum(s, f) <= set_sum(t, f)
+    decreases t.len()
+{
+    assert(s =~= t.intersect(s));
+    if (t.len() > 0) {
+        let x = t.choose();
+        if (s.contains(x)) {
+            lemma_set_sum_remove(s, x, f);
+            lemma_set_sum_subset(s.remove(x), t.remove(x), f);
+        } else {
+            assert(s.subset_of(t.remove(x)));
+            lemma_set_sum_subset(s, t.remove(x), f);
+        }
+    } else {
+        assert(s =~= Set::empty());
+    }
+}
+
+/// The sizes of pairwise disjoint subsets of a finite set never add up to more than its size
+pub proof fn lemma_set_sum_disjoint_subsets<A, B>(ids: Set<A>, parts: spec_fn(A) -> Set<B>, f: spec_fn(A) -> nat, whole: Set<B>)
+    requires
+        ids.finite(),
+        whole.finite(),
+        forall |a: A| #[trigger] ids.contains(a) ==> parts(a).subset_of(whole) && f(a) == parts(a).len(),
+        forall |a: A, b: A| #[trigger] ids.contains(a) && #[trigger] ids.contains(b) && a != b ==> parts(a).disjoint(parts(b)),
+    ensures set_sum(ids, f) <= whole.len()
+    decreases ids.len()
+{
+    if (ids.len() > 0) {
+        let a = ids.choose();
+        let rest = whole.difference(parts(a));
+        assert forall |b: A| #[trigger] ids.remove(a).contains(b) implies parts(b).subset_of(rest) by {
+            assert(ids.contains(a) && ids.contains(b));
+        }
+        lemma_set_sum_disjoint_subsets(ids.remove(a), parts, f, rest);
+        lemma_set_sum_remove(ids, a, f);
+        lemma_len_subset(parts(a), whole);
+        assert(whole =~= parts(a).union(rest));
+        lemma_set_disjoint_lens(parts(a), rest);
+    }
+}
+
+/// The sum over two disjoint finite sets is the sum over their union
+pub proof fn lemma_set_sum_union<A>(s: Set<A>, t: Set<A>, f: spec_fn(A) -> nat)
+    requires s.finite(), t.finite(), s.disjoint(t)
+    ensures set_sum(s.union(t), f) == set_sum(s, f) + set_sum(t, f)
+    decreases s.len()
+{
+    if (s.len() > 0) {
+        let x = s.choose();
+        lemma_set_sum_union(s.remove(x), t, f);
+        assert(s.union(t).remove(x) =~= s.remove(x).union(t));
+        lemma_set_sum_remove(s.union(t), x, f);
+        lemma_set_sum_remove(s, x, f);
+    } else {
+        assert(s =~= Set::empty());
+        assert(s.union(t) =~= t);
+    }
+}
+
+/// The sums over pairwise disjoint finite parts, [g] giving the sum over each part, add up to the
+/// sum over their union
+pub proof fn lemma_set_sum_parts<A, B>(ids: Set<A>, parts: spec_fn(A) -> Set<B>, f: spec_fn(B) -> nat, g: spec_fn(A) -> nat)
+    requires
+        ids.finite(),
+        forall |a: A| #[trigger] ids.contains(a) ==> parts(a).finite() && g(a) == set_sum(parts(a), f),
+        forall |a: A, b: A| #[trigger] ids.contains(a) && #[trigger] ids.contains(b) && a != b ==> parts(a).disjoint(parts(b)),
+    ensures
+        set_sum(ids, g) == set_sum(Set::new(|b: B| exists |a: A| #[trigger] ids.contains(a) && parts(a).contains(b)), f),
+    decreases ids.len()
+{
+    let union = Set::new(|b: B| exists |a: A| #[trigger] ids.contains(a) && parts(a).contains(b));
+    if (ids.len() > 0) {
+        let a = ids.choose();
+        let rest = ids.remove(a);
+        lemma_set_sum_parts(rest, parts, f, g);
+        lemma_union_finite(rest, parts);
+        let rest_union = Set::new(|b: B| exists |x: A| #[trigger] rest.contains(x) && parts(x).contains(b));
+        assert forall |b: B| union.contains(b) implies #[trigger] rest_union.union(parts(a)).contains(b) by {
+            let x = choose |x: A| #[trigger] ids.contains(x) && parts(x).contains(b);
+            if (x != a) {
+                assert(rest.contains(x));
+            }
+        }
+        assert(union =~= rest_union.union(parts(a)));
+        assert forall |b: B| #[trigger] rest_union.contains(b) implies !parts(a).contains(b) by {
+            let x = choose |x: A| #[trigger] rest.contains(x) && parts(x).contains(b);
+            assert(ids.contains(x) && ids.contains(a));
+        }
+        lemma_set_sum_union(rest_union, parts(a), f);
+        lemma_set_sum_remove(ids, a, g);
+    } else {
+        assert(union =~= Set::empty());
+    }
+}
+
+/// The nats below [n]
+pub open spec fn nat_range(n: nat) -> Set<nat> {
+    Set::new(|v: nat| v < n)
+}
+
+pub proof fn lemma_nat_range_len(n: nat)
+    ensures nat_range(n).finite(), nat_range(n).len() == n
+    decreases n
+{
+    if (n == 0) {
+        assert(nat_range(n) =~= Set::empty());
+    } else {
+        lemma_nat_range_len((n - 1) as nat);
+        assert(nat_range(n) =~= nat_range((n - 1) as nat).insert((n - 1) as nat));
+    }
+}
+
+/// The nats in the interval from [lo] up to, but excluding, [lo] + [n]
+pub open spec fn nat_interval(lo: nat, n: nat) -> Set<nat> {
+    Set::new(|v: nat| lo <= v < lo + n)
+}
+
+pub proof fn lemma_nat_interval_len(lo: nat, n: nat)
+    ensures nat_interval(lo, n).finite(), nat_interval(lo, n).len() == n
+    decreases n
+{
+    if (n == 0) {
+        assert(nat_interval(lo, n) =~= Set::empty());
+    } else {
+        lemma_nat_interval_len(lo, (n - 1) as nat);
+        assert(nat_interval(lo, n) =~= nat_interval(lo, (n - 1) as nat).insert((lo + n - 1) as nat));
+    }
+}
+
+/// The union of finitely many finite sets is finite
+pub proof fn lemma_union_finite<A, B>(ids: Set<A>, parts: spec_fn(A) -> Set<B>)
+    requires
+        ids.finite(),
+        forall |a: A| #[trigger] ids.contains(a) ==> parts(a).finite(),
+    ensures
+        Set::new(|b: B| exists |a: A| #[trigger] ids.contains(a) && parts(a).contains(b)).finite(),
+    decreases ids.len()
+{
+    let union = Set::new(|b: B| exists |a: A| #[trigger] ids.contains(a) && parts(a).contains(b));
+    if (ids.len() > 0) {
+        let a = ids.choose();
+        let rest = ids.remove(a);
+        lemma_union_finite(rest, parts);
+        let rest_union = Set::new(|b: B| exists |x: A| #[trigger] rest.contains(x) && parts(x).contains(b));
+        assert forall |b: B| union.contains(b) implies #[trigger] rest_union.union(parts(a)).contains(b) by {
+            let x = choose |x: A| #[trigger] ids.contains(x) && parts(x).contains(b);
+            if (x != a) {
+                assert(rest.contains(x));
+            }
+        }
+        assert(union =~= rest_union.union(parts(a)));
+    } else {
+        assert(union =~= Set::empty());
+    }
+}
+
 pub ghost enum Optional<T> {
     Some { some: T },
     None,